@@ -0,0 +1,64 @@
+class Temperature {
+  static #count = 0;
+  static unit = "C";
+
+  static get count() {
+    return Temperature.#count;
+  }
+
+  static set label(value) {
+    this.unit = value.toUpperCase();
+  }
+
+  static {
+    this.freezing = 0;
+    Temperature.#count = 10;
+  }
+
+  constructor(degrees) {
+    this.degrees = degrees;
+  }
+
+  get fahrenheit() {
+    return this.degrees * 9 / 5 + 32;
+  }
+}
+
+console.log(Temperature.count, Temperature.unit, Temperature.freezing);
+Temperature.label = "f";
+console.log(Temperature.unit);
+console.log(new Temperature(100).fahrenheit);
+
+let log = [];
+const account = {
+  balance: 5,
+  get doubled() {
+    return this.balance * 2;
+  },
+  set deposit(amount) {
+    log.push(amount);
+    this.balance += amount;
+  },
+  get "a b"() {
+    return "an account";
+  },
+};
+account.deposit = 10;
+account["deposit"] = 1;
+console.log(account.balance, account.doubled, account["a b"], log);
+console.log(account);
+console.log(JSON.stringify(account));
+console.log(Object.keys(account), Object.values(account));
+
+const range = {
+  low: 1,
+  high: 3,
+  get size() {
+    return this.high - this.low;
+  },
+  set size(n) {
+    this.high = this.low + n;
+  },
+};
+range.size = 10;
+console.log(range.size, range.high);
@@ -0,0 +1,61 @@
+from js_runtime import JSON, JSObject, JSStatic, Object, console, js_accessor, js_add, js_function, js_method, js_number, js_static_property
+
+class Temperature(JSStatic):
+    def __init__(self, degrees):
+        self.degrees = degrees
+
+    __count = 0
+    unit = "C"
+
+    @js_static_property
+    def count(self):
+        return Temperature.__count
+
+    def _set_label(self, value):
+        self.unit = js_method(value, "toUpperCase")
+
+    label = js_static_property(None, _set_label)
+
+    def _static1(self):
+        self.freezing = 0
+        Temperature.__count = 10
+
+    @property
+    def fahrenheit(self):
+        return js_number(self.degrees) * 9 / 5 + 32
+
+Temperature._static1(Temperature)
+console.log(Temperature.count, Temperature.unit, Temperature.freezing)
+Temperature.label = "f"
+console.log(Temperature.unit)
+console.log(Temperature(100).fahrenheit)
+log = []
+
+@js_function
+def _fn2(self):
+    return js_number(self.balance) * 2
+
+@js_function
+def _fn3(self, amount):
+    log.append(amount)
+    self.balance = js_add(self.balance, amount)
+
+account = JSObject({"balance": 5, "doubled": js_accessor(_fn2, None), "deposit": js_accessor(None, _fn3), "a b": js_accessor(lambda: "an account", None)})
+account.deposit = 10
+account["deposit"] = 1
+console.log(account.balance, account.doubled, account["a b"], log)
+console.log(account)
+console.log(JSON.stringify(account))
+console.log(Object.keys(account), Object.values(account))
+
+@js_function
+def _fn4(self):
+    return js_number(self.high) - js_number(self.low)
+
+@js_function
+def _fn5(self, n):
+    self.high = js_add(self.low, n)
+
+range = JSObject({"low": 1, "high": 3, "size": js_accessor(_fn4, _fn5)})
+range.size = 10
+console.log(range.size, range.high)
@@ -0,0 +1,27 @@
+const wait = async (value) => value + 1;
+
+async function total(values) {
+  let sum = 0;
+  for (const value of values) {
+    sum += await wait(value);
+  }
+  return sum;
+}
+
+function describe({ name, ...details }) {
+  return name + ": " + Object.keys(details).join(",");
+}
+
+async function main() {
+  console.log(await total([1, 2, 3]));
+  const key = "b";
+  const { a, [key]: b, ...others } = { a: 1, b: 2, c: 3, d: 4 };
+  console.log(a, b, others);
+  let { ...copy } = others;
+  copy.c = 5;
+  console.log(others.c, copy.c);
+  console.log(describe({ name: "box", width: 2, height: 3 }));
+}
+
+main();
+wait(1);
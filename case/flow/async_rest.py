@@ -0,0 +1,31 @@
+from js_runtime import JSObject, Object, console, js_add, js_get, js_method, js_rest, js_run, js_str
+
+async def total(values):
+    sum = 0
+    for value in values:
+        sum = js_add(sum, await wait(value))
+    return sum
+
+def describe(_arg1):
+    name = js_get(_arg1, "name")
+    details = js_rest(_arg1, "name")
+    return js_str(name) + ": " + js_str(js_method(Object.keys(details), "join", ","))
+
+async def main():
+    console.log(await total([1, 2, 3]))
+    key = "b"
+    _tmp2 = JSObject({"a": 1, "b": 2, "c": 3, "d": 4})
+    a = js_get(_tmp2, "a")
+    b = js_get(_tmp2, key)
+    others = js_rest(_tmp2, "a", key)
+    console.log(a, b, others)
+    copy = js_rest(others)
+    copy.c = 5
+    console.log(others.c, copy.c)
+    console.log(describe(JSObject({"name": "box", "width": 2, "height": 3})))
+
+async def wait(value):
+    return js_add(value, 1)
+
+js_run(main())
+js_run(wait(1))
@@ -0,0 +1,32 @@
+const len = 5;
+const print = (value) => "printed " + value;
+const list = ["x"];
+const float = 1 / 0;
+let isinstance = "kept";
+const words = "hello";
+console.log(words.length + len, print(list.length), float, isinstance);
+
+function gather(...rest) {
+  const property = rest.length;
+  return [property, rest];
+}
+console.log(gather(1, 2));
+
+class Box {
+  static staticmethod = "static";
+  constructor(Exception) {
+    this.Exception = Exception;
+  }
+}
+const box = new Box(Infinity);
+console.log(box instanceof Box, box.Exception, Box.staticmethod);
+
+function super_(super$) {
+  return super$ + len;
+}
+console.log(super_(1));
+try {
+  throw new Error("boom");
+} catch (e) {
+  console.log(e.message, [1, 2, 3].length);
+}
@@ -0,0 +1,33 @@
+from js_runtime import Error, console, js_add, js_caught, js_div, js_get, js_str
+
+def gather(*rest):
+    rest = list(rest)
+    property_ = js_get(rest, "length")
+    return [property_, rest]
+
+def super_(super_dollar):
+    return js_add(super_dollar, len_)
+
+len_ = 5
+print_ = lambda value: "printed " + js_str(value)
+list_ = ["x"]
+float_ = js_div(1, 0)
+isinstance_ = "kept"
+words = "hello"
+console.log(len(words) + len_, print_(len(list_)), float_, isinstance_)
+console.log(gather(1, 2))
+
+class Box:
+    def __init__(self, Exception_):
+        self.Exception = Exception_
+
+    staticmethod = "static"
+
+box = Box(float("inf"))
+console.log(isinstance(box, Box), box.Exception, Box.staticmethod)
+console.log(super_(1))
+try:
+    raise Error("boom")
+except Exception as e:
+    e = js_caught(e)
+    console.log(e.message, len([1, 2, 3]))
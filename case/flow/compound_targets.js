@@ -0,0 +1,22 @@
+const arr = [1, 2, 3, 4];
+let cnt = 0;
+arr[cnt++] += 5;
+arr[cnt++] *= 10;
+console.log(arr, cnt);
+
+let calls = 0;
+function pick(o) {
+  calls += 1;
+  return o;
+}
+const box = { n: 1, name: null, flag: 0 };
+pick(box).n += 2;
+pick(box).name ??= "set";
+pick(box).flag ||= 7;
+pick(box).n &&= 40;
+console.log(box, calls);
+
+const grid = [[0, 0], [0, 0]];
+let row = 0;
+grid[row++][row] -= 3;
+console.log(grid, row);
@@ -0,0 +1,33 @@
+from js_runtime import JSObject, console, js_add, js_get, js_number, js_truthy, null
+
+def pick(o):
+    global calls
+    calls += 1
+    return o
+
+arr = [1, 2, 3, 4]
+cnt = 0
+_tmp1 = (cnt := cnt + 1) - 1
+arr[_tmp1] = js_add(arr[_tmp1], 5)
+_tmp2 = (cnt := cnt + 1) - 1
+arr[_tmp2] = js_number(arr[_tmp2]) * 10
+console.log(arr, cnt)
+calls = 0
+box = JSObject({"n": 1, "name": null, "flag": 0})
+_tmp3 = pick(box)
+_tmp3.n = js_add(_tmp3.n, 2)
+_tmp4 = pick(box)
+if _tmp4.name is None or _tmp4.name is null:
+    _tmp4.name = "set"
+_tmp5 = pick(box)
+if not js_truthy(_tmp5.flag):
+    _tmp5.flag = 7
+_tmp6 = pick(box)
+if js_truthy(_tmp6.n):
+    _tmp6.n = 40
+console.log(box, calls)
+grid = [[0, 0], [0, 0]]
+row = 0
+_tmp7 = js_get(grid, (row := row + 1) - 1)
+_tmp7[row] = js_number(_tmp7[row]) - 3
+console.log(grid, row)
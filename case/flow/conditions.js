@@ -0,0 +1,24 @@
+let i = 0;
+while ((i++, i < 4)) {
+  console.log("i " + i);
+}
+
+const box = { n: 3, last: 0 };
+while (box.n--) {
+  console.log("box " + box.n);
+}
+
+function next() {
+  box.last += 1;
+  return box.last;
+}
+if ((box.value = next())) {
+  console.log("value " + box.value);
+}
+let item;
+while ((item = next()) < 5) {
+  console.log("item " + item);
+}
+if ((box.count = 0, box.count += 2, box.count > 1)) {
+  console.log("count " + box.count);
+}
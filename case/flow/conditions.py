@@ -0,0 +1,30 @@
+from js_runtime import JSObject, js_add, js_gt, js_lt, js_number, js_str, js_truthy
+
+def next():
+    box.last = js_add(box.last, 1)
+    return box.last
+
+i = 0
+while True:
+    i += 1
+    if not i < 4:
+        break
+    print("i " + js_str(i))
+box = JSObject({"n": 3, "last": 0})
+while True:
+    _tmp1 = js_number(box.n)
+    box.n = _tmp1 - 1
+    if not js_truthy(_tmp1):
+        break
+    print("box " + js_str(box.n))
+_tmp2 = next()
+box.value = _tmp2
+if js_truthy(_tmp2):
+    print("value " + js_str(box.value))
+item = None
+while js_lt((item := next()), 5):
+    print("item " + js_str(item))
+box.count = 0
+box.count = js_add(box.count, 2)
+if js_gt(box.count, 1):
+    print("count " + js_str(box.count))
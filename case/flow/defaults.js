@@ -0,0 +1,15 @@
+const { n = 1, get = 2, m = 3 } = { m: 30, get: null };
+console.log(n, get, m);
+
+function area({ width = 1, height = width } = {}) {
+  return width * height;
+}
+console.log(area(), area({ width: 4 }), area({ width: 2, height: 5 }));
+
+class Box {
+  constructor() {
+    this.size = 7;
+  }
+}
+const { size = 0, label = "box" } = new Box();
+console.log(size, label);
@@ -0,0 +1,31 @@
+from js_runtime import JSObject, console, js_get, js_number, null
+
+def area(_arg1=None):
+    if _arg1 is None:
+        _arg1 = JSObject()
+    _tmp2 = js_get(_arg1, "width")
+    width = _tmp2 if _tmp2 is not None else 1
+    _tmp3 = js_get(_arg1, "height")
+    height = _tmp3 if _tmp3 is not None else width
+    return js_number(width) * js_number(height)
+
+_tmp4 = JSObject({"m": 30, "get": null})
+_tmp5 = js_get(_tmp4, "n")
+n = _tmp5 if _tmp5 is not None else 1
+_tmp6 = js_get(_tmp4, "get")
+get = _tmp6 if _tmp6 is not None else 2
+_tmp7 = js_get(_tmp4, "m")
+m = _tmp7 if _tmp7 is not None else 3
+console.log(n, get, m)
+console.log(area(), area(JSObject({"width": 4})), area(JSObject({"width": 2, "height": 5})))
+
+class Box:
+    def __init__(self):
+        self.size = 7
+
+_tmp8 = Box()
+_tmp9 = js_get(_tmp8, "size")
+size = _tmp9 if _tmp9 is not None else 0
+_tmp10 = js_get(_tmp8, "label")
+label = _tmp10 if _tmp10 is not None else "box"
+console.log(size, label)
@@ -0,0 +1,34 @@
+let [p, , q = 9] = [1, 2];
+console.log(p, q);
+
+const [first, ...others] = ["a", "b", "c"];
+console.log(first, others);
+
+const [x, y] = "hi";
+console.log(x, y);
+
+class Point {
+  constructor(x, y) {
+    this.x = x;
+    this.y = y;
+  }
+}
+const { x: px, y: py } = new Point(3, 4);
+console.log(px, py);
+
+try {
+  throw new TypeError("bad value");
+} catch ({ message }) {
+  console.log(message);
+}
+
+function* naturals() {
+  let n = 0;
+  while (true) yield n++;
+}
+const [zero, one] = naturals();
+console.log(zero, one);
+
+let a = 1, b = 2;
+[a, b] = [b, a];
+console.log(a, b);
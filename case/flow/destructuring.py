@@ -0,0 +1,44 @@
+from js_runtime import TypeError, console, js_caught, js_get, js_unpack
+
+def naturals():
+    n = 0
+    while True:
+        yield (n := n + 1) - 1
+
+_tmp1 = js_unpack([1, 2], 3)
+p = _tmp1[0]
+_tmp2 = _tmp1[2]
+q = _tmp2 if _tmp2 is not None else 9
+console.log(p, q)
+_tmp3 = js_unpack(["a", "b", "c"], 1, True)
+first = _tmp3[0]
+others = _tmp3[1]
+console.log(first, others)
+_tmp4 = js_unpack("hi", 2)
+x = _tmp4[0]
+y = _tmp4[1]
+console.log(x, y)
+
+class Point:
+    def __init__(self, x, y):
+        self.x = x
+        self.y = y
+
+_tmp5 = Point(3, 4)
+px = js_get(_tmp5, "x")
+py = js_get(_tmp5, "y")
+console.log(px, py)
+try:
+    raise TypeError("bad value")
+except Exception as _error6:
+    _tmp7 = js_caught(_error6)
+    message = js_get(_tmp7, "message")
+    console.log(message)
+_tmp8 = js_unpack(naturals(), 2)
+zero = _tmp8[0]
+one = _tmp8[1]
+console.log(zero, one)
+a = 1
+b = 2
+a, b = b, a
+console.log(a, b)
@@ -0,0 +1,18 @@
+let i = 0;
+do {
+  i++;
+  if (i === 2) continue;
+  console.log("do " + i);
+} while (i < 4);
+
+let n = 0;
+do {
+  n += 1;
+} while ((console.log("test " + n), n < 3));
+
+let k = 0;
+do {
+  k++;
+  if (k % 3) continue;
+  console.log("third " + k);
+} while ((k += 1, k < 9));
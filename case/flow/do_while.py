@@ -0,0 +1,27 @@
+from js_runtime import js_mod, js_str, js_truthy
+i = 0
+_first1 = True
+while _first1 or i < 4:
+    _first1 = False
+    i += 1
+    if i == 2:
+        continue
+    print("do " + js_str(i))
+n = 0
+while True:
+    n += 1
+    print("test " + js_str(n))
+    if not n < 3:
+        break
+k = 0
+_first2 = True
+while True:
+    if not _first2:
+        k += 1
+        if not k < 9:
+            break
+    _first2 = False
+    k += 1
+    if js_truthy(js_mod(k, 3)):
+        continue
+    print("third " + js_str(k))
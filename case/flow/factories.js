@@ -0,0 +1,26 @@
+function Point(x, y) {
+  return { x: x, y: y, sum() { return x + y; } };
+}
+const p = new Point(1, 2);
+console.log(p.x, p.y, p.sum());
+
+function Counter(start) {
+  this.count = start;
+  if (start < 0) {
+    return { count: 0, clamped: true };
+  }
+}
+const c = new Counter(5);
+const d = new Counter(-1);
+console.log(c.count, d.count, d.clamped);
+
+function Ignored() {
+  this.kept = true;
+  return 42;
+}
+console.log(new Ignored().kept);
+
+function Person(name) {
+  this.name = name;
+}
+console.log(new Person("ada").name);
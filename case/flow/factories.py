@@ -0,0 +1,27 @@
+from js_runtime import JSObject, console, js_add, js_function, js_lt, js_new
+
+def Point(x, y):
+    return JSObject({"x": x, "y": y, "sum": lambda: js_add(x, y)})
+
+@js_function
+def Counter(self, start):
+    self.count = start
+    if js_lt(start, 0):
+        return JSObject({"count": 0, "clamped": True})
+
+@js_function
+def Ignored(self):
+    self.kept = True
+    return 42
+
+class Person:
+    def __init__(self, name):
+        self.name = name
+
+p = js_new(Point, 1, 2)
+console.log(p.x, p.y, p.sum())
+c = js_new(Counter, 5)
+d = js_new(Counter, -1)
+console.log(c.count, d.count, d.clamped)
+console.log(js_new(Ignored).kept)
+console.log(Person("ada").name)
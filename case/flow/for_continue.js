@@ -0,0 +1,19 @@
+for (let i = 0; i < 6; i++) {
+  if (i % 3 === 0) continue;
+  console.log("i = " + i);
+}
+
+let total = 0;
+for (let j = 10; j > 0; j -= 2) {
+  if (j === 6) {
+    continue;
+  }
+  total += j;
+}
+console.log("total " + total);
+
+let n = 0;
+for (; n < 3; ) {
+  n++;
+}
+console.log("n " + n);
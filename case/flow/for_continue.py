@@ -0,0 +1,29 @@
+from js_runtime import js_mod, js_str
+i = 0
+_first1 = True
+while True:
+    if not _first1:
+        i += 1
+    _first1 = False
+    if not i < 6:
+        break
+    if js_mod(i, 3) == 0:
+        continue
+    print("i = " + js_str(i))
+total = 0
+j = 10
+_first2 = True
+while True:
+    if not _first2:
+        j -= 2
+    _first2 = False
+    if not j > 0:
+        break
+    if j == 6:
+        continue
+    total += j
+print("total " + js_str(total))
+n = 0
+while n < 3:
+    n += 1
+print("n " + js_str(n))
@@ -0,0 +1,44 @@
+console.log(Math.max(3, 7, 2), Math.min(), Math.floor(7 / 2), Math.abs(-4), Math.round(5 / 2));
+console.log(Math.sqrt(-1), Math.pow(2, 10), Math.sign(-3), Math.trunc(-7 / 2));
+console.log(parseInt("42px"), parseInt("ff", 16), parseInt("0x1A"), parseInt("px"));
+console.log(parseFloat("3e2x"), isNaN("abc"), isFinite("12"));
+console.log(Number("12"), Number(""), Number.isInteger(4), Number.isInteger("4"));
+console.log(String(12), String(null), String.fromCharCode(104, 105), Boolean(""), Boolean("x"));
+
+const point = { x: 1, y: 2 };
+console.log(Object.keys(point), Object.values(point), Object.entries(point));
+const merged = Object.assign({}, point, { z: 3 });
+console.log(merged, Object.fromEntries([["a", 1], ["b", 2]]));
+console.log(Array.isArray([]), Array.isArray(point), Array.from("abc"), Array.of(1, 2));
+
+const text = JSON.stringify({ name: "js", tags: ["a", "b"], nothing: undefined, n: null });
+console.log(text);
+console.log(JSON.stringify([1, "two", undefined]), JSON.stringify(7));
+console.log(JSON.stringify({ a: [1, 2] }, null, 2));
+const parsed = JSON.parse(text);
+console.log(parsed.name, parsed.tags.length, parsed.n);
+try {
+  JSON.parse("{bad");
+} catch (e) {
+  console.log(e instanceof SyntaxError);
+}
+
+const seen = new Set([1, 2, 2, 3]);
+seen.add(4);
+seen.delete(1);
+console.log(seen.size, seen.has(2), seen.has(1));
+const [first, second] = new Set(["x", "y", "x"]);
+console.log(first, second);
+for (const value of seen) {
+  console.log(value);
+}
+
+const counts = new Map();
+for (const word of ["a", "b", "a"]) {
+  counts.set(word, (counts.get(word) || 0) + 1);
+}
+console.log(counts.get("a"), counts.get("c"), counts.size);
+counts.forEach((count, word) => console.log(word, count));
+for (const [word, count] of counts) {
+  console.log(word + "=" + count);
+}
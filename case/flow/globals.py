@@ -0,0 +1,40 @@
+from js_runtime import Array, Boolean, JSON, JSObject, Map, Math, Number, Object, Set, String, SyntaxError, console, isFinite, isNaN, js_add, js_caught, js_get, js_str, js_truthy, js_unpack, null, parseFloat, parseInt
+console.log(Math.max(3, 7, 2), Math.min(), Math.floor(7 / 2), Math.abs(-4), Math.round(5 / 2))
+console.log(Math.sqrt(-1), Math.pow(2, 10), Math.sign(-3), Math.trunc(-7 / 2))
+console.log(parseInt("42px"), parseInt("ff", 16), parseInt("0x1A"), parseInt("px"))
+console.log(parseFloat("3e2x"), isNaN("abc"), isFinite("12"))
+console.log(Number("12"), Number(""), Number.isInteger(4), Number.isInteger("4"))
+console.log(String(12), String(null), String.fromCharCode(104, 105), Boolean(""), Boolean("x"))
+point = JSObject({"x": 1, "y": 2})
+console.log(Object.keys(point), Object.values(point), Object.entries(point))
+merged = Object.assign(JSObject(), point, JSObject({"z": 3}))
+console.log(merged, Object.fromEntries([["a", 1], ["b", 2]]))
+console.log(Array.isArray([]), Array.isArray(point), js_get(Array, "from")("abc"), Array.of(1, 2))
+text = JSON.stringify(JSObject({"name": "js", "tags": ["a", "b"], "nothing": None, "n": null}))
+console.log(text)
+console.log(JSON.stringify([1, "two", None]), JSON.stringify(7))
+console.log(JSON.stringify(JSObject({"a": [1, 2]}), null, 2))
+parsed = JSON.parse(text)
+console.log(parsed.name, js_get(parsed.tags, "length"), parsed.n)
+try:
+    JSON.parse("{bad")
+except Exception as e:
+    e = js_caught(e)
+    console.log(isinstance(e, SyntaxError))
+seen = Set([1, 2, 2, 3])
+seen.add(4)
+seen.delete(1)
+console.log(seen.size, seen.has(2), seen.has(1))
+_tmp1 = js_unpack(Set(["x", "y", "x"]), 2)
+first = _tmp1[0]
+second = _tmp1[1]
+console.log(first, second)
+for value in seen:
+    console.log(value)
+counts = Map()
+for word in ["a", "b", "a"]:
+    counts.set(word, js_add(_tmp2 if js_truthy((_tmp2 := counts.get(word))) else 0, 1))
+console.log(counts.get("a"), counts.get("c"), counts.size)
+counts.forEach(lambda count, word: console.log(word, count))
+for word, count in counts:
+    print(js_str(word) + "=" + js_str(count))
@@ -0,0 +1,31 @@
+outer: for (let i = 0; i < 4; i++) {
+  for (let j = 0; j < 4; j++) {
+    if (j === 2) continue outer;
+    if (i === 3) break outer;
+    console.log(i + "," + j);
+  }
+}
+
+let found = "none";
+search: {
+  for (const row of [[1, 2], [3, 4], [5, 6]]) {
+    for (const cell of row) {
+      if (cell === 4) {
+        found = "cell " + cell;
+        break search;
+      }
+    }
+  }
+  found = "not found";
+}
+console.log(found);
+
+let count = 0;
+a: b: while (count < 10) {
+  count++;
+  while (true) {
+    if (count < 5) continue a;
+    break b;
+  }
+}
+console.log("count " + count);
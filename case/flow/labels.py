@@ -0,0 +1,59 @@
+from js_runtime import js_str, js_strict_eq
+i = 0
+_first3 = True
+_break2 = False
+_continue1 = False
+while True:
+    if not _first3:
+        i += 1
+    _first3 = False
+    if not i < 4:
+        break
+    j = 0
+    while j < 4:
+        if j == 2:
+            _continue1 = True
+            break
+        if i == 3:
+            _break2 = True
+            break
+        print(js_str(i) + "," + js_str(j))
+        j += 1
+    if _continue1:
+        _continue1 = False
+        continue
+    if _break2:
+        break
+found = "none"
+_break4 = False
+while True:
+    for row in [[1, 2], [3, 4], [5, 6]]:
+        for cell in row:
+            if js_strict_eq(cell, 4):
+                found = "cell " + js_str(cell)
+                _break4 = True
+                break
+        if _break4:
+            break
+    if _break4:
+        break
+    found = "not found"
+    break
+print(found)
+count = 0
+_break6 = False
+_continue5 = False
+while count < 10:
+    count += 1
+    while True:
+        if count < 5:
+            _continue5 = True
+            break
+        _break6 = True
+        break
+    if _continue5:
+        _continue5 = False
+        continue
+    if _break6:
+        break
+print("count " + js_str(count))
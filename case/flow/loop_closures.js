@@ -0,0 +1,53 @@
+const fns = [];
+for (let i = 0; i < 3; i++) {
+  fns.push(() => i);
+}
+console.log(fns.map((f) => f()));
+
+const named = [];
+for (const v of ["a", "b", "c"]) {
+  named.push(function () {
+    const upper = v.toUpperCase();
+    return upper + v;
+  });
+}
+console.log(named.map((f) => f()));
+
+const handlers = {};
+for (const key in { x: 1, y: 2 }) {
+  let count = 0;
+  handlers[key] = () => {
+    count += 1;
+    return key + count;
+  };
+}
+console.log(handlers.x(), handlers.x(), handlers.y());
+
+const later = [];
+let n = 0;
+while (n < 3) {
+  const square = n * n;
+  function show() {
+    return "square " + square;
+  }
+  later.push(show);
+  n++;
+}
+console.log(later.map((f) => f()));
+
+function collect(items) {
+  const out = [];
+  for (const item of items) {
+    const label = "#" + item;
+    out.push([1, 2].map((k) => label + k));
+    out.push(() => item * 10);
+  }
+  return out.map((x) => (typeof x === "function" ? x() : x));
+}
+console.log(collect([1, 2]));
+
+var shared = [];
+for (var j = 0; j < 3; j++) {
+  shared.push(() => j);
+}
+console.log(shared.map((f) => f()));
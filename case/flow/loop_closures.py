@@ -0,0 +1,65 @@
+from js_runtime import JSObject, console, js_add, js_keys, js_method, js_number, js_str, js_typeof
+
+def collect(items):
+    out = []
+    for item in items:
+        label = "#" + js_str(item)
+        out.append(js_method([1, 2], "map", (lambda label: lambda k: label + js_str(k))(label)))
+        out.append((lambda item: lambda: js_number(item) * 10)(item))
+    return [x() if js_typeof(x) == "function" else x for x in out]
+
+fns = []
+i = 0
+while i < 3:
+    fns.append((lambda i: lambda: i)(i))
+    i += 1
+console.log([f() for f in fns])
+named = []
+for v in ["a", "b", "c"]:
+    def _make2(v):
+        def _fn1():
+            upper = js_method(v, "toUpperCase")
+            return js_add(upper, v)
+
+        return _fn1
+
+    _fn1 = _make2(v)
+    named.append(_fn1)
+console.log([f() for f in named])
+handlers = JSObject()
+for key in js_keys(JSObject({"x": 1, "y": 2})):
+    count = 0
+
+    def _make4(count, key):
+        def _fn3():
+            nonlocal count
+            count += 1
+            return js_add(key, count)
+
+        return _fn3
+
+    _fn3 = _make4(count, key)
+    handlers[key] = _fn3
+console.log(handlers.x(), handlers.x(), handlers.y())
+later = []
+n = 0
+while n < 3:
+    square = n * n
+
+    def _make5(square):
+        def show():
+            return "square " + js_str(square)
+
+        return show
+
+    show = _make5(square)
+    later.append(show)
+    n += 1
+console.log([f() for f in later])
+console.log(collect([1, 2]))
+shared = []
+j = 0
+while j < 3:
+    shared.append(lambda: j)
+    j += 1
+console.log([f() for f in shared])
@@ -0,0 +1,45 @@
+const c = { n: 0 };
+while (c.n++ < 3) {
+  console.log("up", c.n);
+}
+while (c.n-- > -2) {
+  console.log("down", c.n);
+}
+c.n = 0;
+if (c.n++ === 0) {
+  console.log("was zero", c.n);
+}
+const v = c.n++;
+const w = ++c.n;
+console.log(v, w, c.n);
+
+class Counter {
+  constructor() {
+    this.id = 10;
+  }
+  next() {
+    return this.id++;
+  }
+}
+const counter = new Counter();
+console.log(counter.next(), counter.next(), counter.id);
+
+function f() {
+  return "set";
+}
+const o = {};
+if ((o.x = f())) {
+  console.log("assigned", o.x);
+}
+
+const arr = [1, 2, 3];
+let i = 0;
+const total = arr[i++] + (arr[i] += 10) + (o.y ??= 7);
+console.log(total, arr, i, o.y);
+
+const log = [];
+const item = (k) => { log.push(k); return k; };
+const sum = item(1) + (c.n *= 2);
+console.log(sum, log, c.n);
+const bump = () => c.n++;
+console.log(bump(), bump(), c.n);
@@ -0,0 +1,77 @@
+from js_runtime import JSObject, console, js_add, js_get, js_number, js_truthy
+
+def f():
+    return "set"
+
+c = JSObject({"n": 0})
+while True:
+    _tmp1 = js_number(c.n)
+    c.n = _tmp1 + 1
+    if not _tmp1 < 3:
+        break
+    console.log("up", c.n)
+while True:
+    _tmp2 = js_number(c.n)
+    c.n = _tmp2 - 1
+    if not _tmp2 > -2:
+        break
+    console.log("down", c.n)
+c.n = 0
+_tmp3 = js_number(c.n)
+c.n = _tmp3 + 1
+if _tmp3 == 0:
+    console.log("was zero", c.n)
+_tmp4 = js_number(c.n)
+c.n = _tmp4 + 1
+v = _tmp4
+_tmp5 = js_number(c.n) + 1
+c.n = _tmp5
+w = _tmp5
+console.log(v, w, c.n)
+
+class Counter:
+    def __init__(self):
+        self.id = 10
+
+    def next(self):
+        _tmp6 = js_number(self.id)
+        self.id = _tmp6 + 1
+        return _tmp6
+
+counter = Counter()
+console.log(counter.next(), counter.next(), counter.id)
+o = JSObject()
+_tmp7 = f()
+o.x = _tmp7
+if js_truthy(_tmp7):
+    console.log("assigned", o.x)
+arr = [1, 2, 3]
+i = 0
+_tmp9 = js_get(arr, (i := i + 1) - 1)
+_tmp8 = js_add(arr[i], 10)
+arr[i] = _tmp8
+_tmp11 = js_add(_tmp9, _tmp8)
+_tmp10 = o.y
+if _tmp10 is None:
+    _tmp10 = 7
+    o.y = _tmp10
+total = js_add(_tmp11, _tmp10)
+console.log(total, arr, i, o.y)
+log = []
+
+def item(k):
+    log.append(k)
+    return k
+
+_tmp13 = item(1)
+_tmp12 = js_number(c.n) * 2
+c.n = _tmp12
+sum = js_add(_tmp13, _tmp12)
+console.log(sum, log, c.n)
+
+def bump():
+    _tmp14 = js_number(c.n)
+    c.n = _tmp14 + 1
+    return _tmp14
+
+console.log(bump(), bump(), c.n)
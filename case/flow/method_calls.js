@@ -0,0 +1,36 @@
+class Shelf {
+  constructor(items) {
+    this.items = items;
+  }
+  find(name) {
+    return this.items.indexOf(name);
+  }
+  toString() {
+    return "Shelf of " + this.items.length;
+  }
+}
+class Labelled extends Shelf {
+  find(name) {
+    return "at " + super.find(name);
+  }
+  toString() {
+    return "[" + super.toString() + "]";
+  }
+}
+function shelf(items) {
+  return new Labelled(items);
+}
+const s = shelf(["a", "b"]);
+console.log(s.find("b"), s.toString(), `${s}`);
+
+const nested = [1, [2, [3, [4]]]];
+console.log(nested.flat(), nested.flat(2), nested.flat(Infinity));
+console.log([1, 2, 3].flatMap((x) => [x, x * 10]));
+console.log(["a", "b", "c"].reduceRight((acc, x) => acc + x, ""));
+const price = 201 / 200;
+const count = 12;
+console.log(price.toFixed(2), count.toFixed(1), (5 / 2).toFixed(), (255).toString(16));
+function describe(value) {
+  return value.toString();
+}
+console.log(describe(3), describe([1, 2]), describe("x"), describe(s));
@@ -0,0 +1,39 @@
+from js_runtime import console, js_add, js_get, js_method, js_number, js_str, js_to_string
+
+def shelf(items):
+    return Labelled(items)
+
+def describe(value):
+    return js_method(value, "toString")
+
+class Shelf:
+    def __init__(self, items):
+        self.items = items
+
+    def find(self, name):
+        return js_method(self.items, "indexOf", name)
+
+    def toString(self):
+        return "Shelf of " + js_str(js_get(self.items, "length"))
+
+    __str__ = js_to_string
+
+class Labelled(Shelf):
+    def find(self, name):
+        return "at " + js_str(super().find(name))
+
+    def toString(self):
+        return "[" + js_str(super().toString()) + "]"
+
+    __str__ = js_to_string
+
+s = shelf(["a", "b"])
+console.log(s.find("b"), js_method(s, "toString"), f"{js_str(s)}")
+nested = [1, [2, [3, [4]]]]
+console.log(js_method(nested, "flat"), js_method(nested, "flat", 2), js_method(nested, "flat", float("inf")))
+console.log(js_method([1, 2, 3], "flatMap", lambda x: [x, js_number(x) * 10]))
+console.log(js_method(["a", "b", "c"], "reduceRight", lambda acc, x: js_add(acc, x), ""))
+price = 201 / 200
+count = 12
+print(js_method(price, "toFixed", 2), js_method(count, "toFixed", 1), js_method(5 / 2, "toFixed"), js_method(255, "toString", 16))
+console.log(describe(3), describe([1, 2]), describe("x"), describe(s))
@@ -0,0 +1,7 @@
+let nothing = null;
+let missing;
+console.log(null == 0, 0 == null, null != "", undefined == 0);
+console.log(null === 0, -1 !== null, undefined === "", null == undefined);
+console.log(nothing == 0, missing == null, nothing === null, missing === undefined);
+const limit = 3;
+console.log(limit === 3, limit == 3);
@@ -0,0 +1,8 @@
+from js_runtime import console, js_eq, js_strict_eq, null
+nothing = null
+missing = None
+console.log(js_eq(null, 0), js_eq(0, null), not js_eq(null, ""), js_eq(None, 0))
+console.log(js_strict_eq(null, 0), not js_strict_eq(-1, null), js_strict_eq(None, ""), None is None)
+console.log(js_eq(nothing, 0), null is null, nothing is null, missing is None)
+limit = 3
+console.log(limit == 3, limit == 3)
@@ -0,0 +1,18 @@
+const o = { from: 1, None: 2, $el: "el", class: "c", if: true, import: "i", return: 3 };
+console.log(o.from, o.None, o.$el, o.class, o.if, o.import, o.return);
+o.in = 4;
+o.with = 5;
+o.yield = o.in + o.with;
+o.__x__ = "dunder";
+console.log(o.in, o.with, o.yield, o.__x__, o.missing);
+o.return += 10;
+delete o.class;
+delete o.import;
+delete o.None;
+delete o.yield;
+delete o.with;
+delete o.__x__;
+console.log(o);
+const items = Array.from("ab");
+const p = { async: 1, self: 2 };
+console.log(items, p.async, p.self);
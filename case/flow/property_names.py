@@ -0,0 +1,19 @@
+from js_runtime import Array, JSObject, console, js_add, js_get
+o = JSObject({"from": 1, "None": 2, "$el": "el", "class": "c", "if": True, "import": "i", "return": 3})
+console.log(js_get(o, "from"), js_get(o, "None"), js_get(o, "$el"), js_get(o, "class"), js_get(o, "if"), js_get(o, "import"), js_get(o, "return"))
+o["in"] = 4
+o["with"] = 5
+o["yield"] = js_add(js_get(o, "in"), js_get(o, "with"))
+o["__x__"] = "dunder"
+console.log(js_get(o, "in"), js_get(o, "with"), js_get(o, "yield"), js_get(o, "__x__"), o.missing)
+o["return"] = js_add(o["return"], 10)
+del o["class"]
+del o["import"]
+del o["None"]
+del o["yield"]
+del o["with"]
+del o["__x__"]
+console.log(o)
+items = js_get(Array, "from")("ab")
+p = JSObject({"async": 1, "self": 2})
+console.log(items, js_get(p, "async"), p.self)
@@ -0,0 +1,52 @@
+function Animal(name) {
+  this.name = name;
+}
+Animal.prototype.legs = 4;
+Animal.prototype.sound = null;
+Animal.prototype.speak = function () {
+  return this.name + " makes " + (this.sound || "no sound");
+};
+Animal.prototype.toString = function () {
+  return "Animal(" + this.name + ")";
+};
+
+function Dog(name, breed) {
+  Animal.call(this, name);
+  this.breed = breed;
+}
+Dog.prototype = Object.create(Animal.prototype);
+Dog.prototype.constructor = Dog;
+Dog.prototype.sound = "woof";
+Dog.prototype.speak = function () {
+  return Animal.prototype.speak.call(this) + "!";
+};
+
+const rex = new Dog("Rex", "lab");
+const cat = new Animal("Tom");
+console.log(rex.speak(), rex.legs, rex.breed);
+console.log(cat.speak(), cat.legs);
+console.log(String(rex), `${cat}`, "" + rex);
+
+class Point {
+  constructor(x, y) {
+    this.x = x;
+    this.y = y;
+  }
+  toString() {
+    return `(${this.x}, ${this.y})`;
+  }
+  static count = 0;
+  static make(x, y) {
+    this.count++;
+    return new this(x, y);
+  }
+  static origin() {
+    return Point.make(0, 0);
+  }
+}
+const p = Point.make(1, 2);
+const q = Point.origin();
+console.log(`${p} and ${q}`, "at " + p, String(q), Point.count);
+console.log([p, q].join(" "));
+const plain = { toString() { return "plain"; } };
+console.log(`${plain}`, "" + {});
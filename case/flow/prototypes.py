@@ -0,0 +1,60 @@
+from js_runtime import JSObject, String, console, js_method, js_number, js_str, js_to_string, js_truthy, null
+
+class Animal:
+    def __init__(self, name):
+        self.name = name
+
+    legs = 4
+    sound = null
+
+    def speak(self):
+        return js_str(self.name) + " makes " + js_str(_tmp1 if js_truthy((_tmp1 := self.sound)) else "no sound")
+
+    def toString(self):
+        return "Animal(" + js_str(self.name) + ")"
+
+    __str__ = js_to_string
+
+class Dog(Animal):
+    def __init__(self, name, breed):
+        super().__init__(name)
+        self.breed = breed
+
+    sound = "woof"
+
+    def speak(self):
+        return js_str(super().speak()) + "!"
+
+rex = Dog("Rex", "lab")
+cat = Animal("Tom")
+console.log(rex.speak(), rex.legs, rex.breed)
+console.log(cat.speak(), cat.legs)
+console.log(String(rex), f"{js_str(cat)}", "" + js_str(rex))
+
+class Point:
+    def __init__(self, x, y):
+        self.x = x
+        self.y = y
+
+    def toString(self):
+        return f"({js_str(self.x)}, {js_str(self.y)})"
+
+    count = 0
+
+    @classmethod
+    def make(cls, x, y):
+        cls.count = js_number(cls.count) + 1
+        return cls(x, y)
+
+    @staticmethod
+    def origin():
+        return Point.make(0, 0)
+
+    __str__ = js_to_string
+
+p = Point.make(1, 2)
+q = Point.origin()
+console.log(f"{js_str(p)} and {js_str(q)}", "at " + js_str(p), String(q), Point.count)
+print(js_method([p, q], "join", " "))
+plain = JSObject({"toString": lambda: "plain"})
+print(f"{js_str(plain)}", "" + js_str(JSObject()))
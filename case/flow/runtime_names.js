@@ -0,0 +1,16 @@
+const JSObject = "mine";
+let js_add = (a, b) => a * b;
+function null_() {}
+const js_str = [1, 2];
+function js_get(key) {
+  return { key }[key];
+}
+const undefined_value = js_get("key");
+{
+  const console = { log: (x) => x };
+  console.log("hidden");
+}
+let Math = { max: 0 };
+const total = js_add(3, 4) + js_str.length;
+console.log(JSObject, total, undefined_value, Math.max, { a: 1 });
+console.log(`${total} and ${JSObject}`);
@@ -0,0 +1,18 @@
+from js_runtime import JSObject, console, js_add, js_get, js_number, js_str
+
+def null_():
+    pass
+
+def js_get_(key):
+    return js_get(JSObject({"key": key}), key)
+
+JSObject_ = "mine"
+js_add_ = lambda a, b: js_number(a) * js_number(b)
+js_str_ = [1, 2]
+undefined_value = js_get_("key")
+console_ = JSObject({"log": lambda x: x})
+console_.log("hidden")
+Math_ = JSObject({"max": 0})
+total = js_add(js_add_(3, 4), len(js_str_))
+console.log(JSObject_, total, undefined_value, Math_.max, JSObject({"a": 1}))
+print(f"{js_str(total)} and {JSObject_}")
@@ -0,0 +1,12 @@
+const quote = 'it\'s "quoted"';
+const other = "say \"hi\" and 'bye'";
+const accents = 'héllo wörld ☃ 😀';
+const tabbed = "a	b";
+const joined = 'line \
+continued';
+console.log(quote, quote.length);
+console.log(other);
+console.log(accents, accents.toUpperCase());
+console.log(tabbed.split("\t").length);
+console.log(joined);
+console.log('é\x41\\n' + "\n".length);
@@ -0,0 +1,12 @@
+from js_runtime import console, js_str
+quote = 'it\'s "quoted"'
+other = "say \"hi\" and 'bye'"
+accents = "héllo wörld ☃ 😀"
+tabbed = "a\tb"
+joined = "line continued"
+console.log(quote, len(quote))
+print(other)
+print(accents, accents.upper())
+console.log(len(tabbed.split("\t")))
+print(joined)
+print("éA\\n" + js_str(len("\n")))
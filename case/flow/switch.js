@@ -0,0 +1,48 @@
+function describe(n) {
+  let words = "";
+  switch (n) {
+    case 1:
+      words += "one ";
+    case 2:
+      words += "two ";
+      break;
+    default:
+      words += "many ";
+    case 3:
+      words += "three ";
+  }
+  return words;
+}
+console.log(describe(1));
+console.log(describe(2));
+console.log(describe(3));
+console.log(describe(4));
+
+function check(s, early) {
+  switch (s) {
+    case "a":
+      if (early) {
+        console.log("early");
+        break;
+      }
+      console.log("late");
+      break;
+    case "b":
+      return "b";
+  }
+  return "done";
+}
+console.log(check("a", true));
+console.log(check("a", false));
+console.log(check("b", true));
+
+for (let i = 0; i < 4; i++) {
+  switch (i) {
+    case 1:
+      continue;
+    case 2:
+      console.log("two");
+    default:
+      console.log("i " + i);
+  }
+}
@@ -0,0 +1,61 @@
+from js_runtime import console, js_str, js_strict_eq, js_truthy
+
+def describe(n):
+    words = ""
+    _case1 = 0 if js_strict_eq(n, 1) else 1 if js_strict_eq(n, 2) else 3 if js_strict_eq(n, 3) else 2
+    while True:
+        if _case1 <= 0:
+            words += "one "
+        if _case1 <= 1:
+            words += "two "
+            break
+        if _case1 <= 2:
+            words += "many "
+        if _case1 <= 3:
+            words += "three "
+        break
+    return words
+
+def check(s, early):
+    _case2 = 0 if s == "a" else 1 if s == "b" else 2
+    while True:
+        if _case2 <= 0:
+            if js_truthy(early):
+                print("early")
+                break
+            print("late")
+            break
+        if _case2 <= 1:
+            return "b"
+        break
+    return "done"
+
+console.log(describe(1))
+console.log(describe(2))
+console.log(describe(3))
+console.log(describe(4))
+console.log(check("a", True))
+console.log(check("a", False))
+console.log(check("b", True))
+i = 0
+_first5 = True
+_continue4 = False
+while True:
+    if not _first5:
+        i += 1
+    _first5 = False
+    if not i < 4:
+        break
+    _case3 = 0 if i == 1 else 1 if i == 2 else 2
+    while True:
+        if _case3 <= 0:
+            _continue4 = True
+            break
+        if _case3 <= 1:
+            print("two")
+        if _case3 <= 2:
+            print("i " + js_str(i))
+        break
+    if _continue4:
+        _continue4 = False
+        continue
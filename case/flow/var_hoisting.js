@@ -0,0 +1,19 @@
+x = 5;
+var x;
+console.log(x);
+
+function f(p) {
+  var p;
+  console.log(p, q);
+  if (p) {
+    var q;
+    q = p * 2;
+  }
+  var q;
+  console.log(q);
+}
+f(3);
+
+let y;
+console.log(y);
+for (var i; !i; i = 1) console.log(i);
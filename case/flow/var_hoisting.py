@@ -0,0 +1,19 @@
+from js_runtime import console, js_number, js_truthy
+x = None
+i = None
+
+def f(p):
+    q = None
+    console.log(p, q)
+    if js_truthy(p):
+        q = js_number(p) * 2
+    console.log(q)
+
+x = 5
+console.log(x)
+f(3)
+y = None
+console.log(y)
+while not js_truthy(i):
+    console.log(i)
+    i = 1
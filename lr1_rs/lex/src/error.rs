@@ -0,0 +1,39 @@
+use crate::token::Span;
+use std::fmt;
+
+/// What went wrong in a lexical error
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorKind {
+    UnexpectedCharacter(char),
+    UnterminatedString,
+    UnterminatedTemplate,
+    UnterminatedRegExp,
+    UnterminatedComment,
+    MalformedNumber,
+}
+
+/// A lexical error. The lexer reports it and carries on after the offending input
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LexError {
+    pub kind: ErrorKind,
+    pub span: Span,
+}
+
+impl fmt::Display for ErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ErrorKind::UnexpectedCharacter(c) => write!(f, "unexpected character: '{}'", c),
+            ErrorKind::UnterminatedString => write!(f, "unterminated string literal"),
+            ErrorKind::UnterminatedTemplate => write!(f, "unterminated template literal"),
+            ErrorKind::UnterminatedRegExp => write!(f, "unterminated regular expression literal"),
+            ErrorKind::UnterminatedComment => write!(f, "unterminated comment"),
+            ErrorKind::MalformedNumber => write!(f, "malformed numeric literal"),
+        }
+    }
+}
+
+impl fmt::Display for LexError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.kind)
+    }
+}
@@ -1,42 +1,99 @@
+use crate::error::{ErrorKind, LexError};
 use crate::statefn::StateFn;
-use crate::token::{Token, Type};
+use crate::token::{Span, Token, TokenData, Type};
+use std::collections::VecDeque;
 use std::sync::mpsc;
 
 /// Create a token stream from the input string
 ///
 /// * `input`: the input string to tokenize
+///
+/// A '/' is always lexed as a division here, use `Lexer::next_token` when regular expression
+/// literals have to be recognized.
 pub fn token_stream(input: &str) -> mpsc::Receiver<Token> {
     let (tx, rx) = mpsc::channel();
-    let mut l = Lexer {
-        input: input.to_string(),
-        start: 0,
-        pos: 0,
-        initial_state: StateFn::default(),
-        sender: tx,
-    };
-    std::thread::spawn(move || l.run());
+    let mut l = Lexer::new(input);
+    std::thread::spawn(move || {
+        for token in l.by_ref() {
+            let _ = tx.send(token);
+        }
+    });
     rx
 }
 
+/// Lex the whole input (with '/' as division), returning the tokens and, separately, all the
+/// lexical errors met on the way.
+pub fn tokenize(input: &str) -> (Vec<Token>, Vec<LexError>) {
+    let mut tokens = Vec::new();
+    let mut errors = Vec::new();
+    for token in Lexer::new(input) {
+        match token.data {
+            TokenData::Error(kind) => errors.push(LexError {
+                kind,
+                span: token.span,
+            }),
+            _ => tokens.push(token),
+        }
+    }
+    (tokens, errors)
+}
+
 /// Lexer context
 pub struct Lexer {
     input: String,
     start: usize,
     pos: usize,
-    initial_state: StateFn,
-    sender: mpsc::Sender<Token>,
+    state: Option<StateFn>,
+    modes: Vec<Mode>,
+    goal: Goal,
+    pending: VecDeque<Token>,
+}
+
+/// What an open `{` belongs to, so that the matching `}` knows whether to resume a template
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mode {
+    /// A plain brace: block, object literal, ...
+    Brace,
+    /// The `${` of a template substitution
+    Substitution,
+}
+
+/// The goal symbol of the lexical grammar, only matters for what a '/' starts.
+/// Which one applies depends on the syntactic context, so it is chosen by the caller.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Goal {
+    /// InputElementDiv: '/' is a division (or '/=')
+    Div,
+    /// InputElementRegExp: '/' starts a regular expression literal
+    RegExp,
 }
 
 impl Lexer {
-    pub fn run(&mut self) {
-        let mut f = self.initial_state.clone();
-
-        // Run the state functions until there are no more state functions to run
-        // This pattern can decouple the lexer from the state functions and make it easier to
-        // extend the lexer with new state functions.
-        while let Some(next_f) = f.call(self) {
-            f = next_f;
+    pub fn new(input: &str) -> Lexer {
+        Lexer {
+            input: input.to_string(),
+            start: 0,
+            pos: 0,
+            state: Some(StateFn::default()),
+            modes: Vec::new(),
+            goal: Goal::Div,
+            pending: VecDeque::new(),
+        }
+    }
+
+    /// Lex the next token with the given goal symbol, `None` once the stream has ended (after
+    /// the EOF token or an error).
+    pub fn next_token(&mut self, goal: Goal) -> Option<Token> {
+        self.goal = goal;
+
+        // Run the state functions until one of them emits a token or there are no more state
+        // functions to run. This pattern can decouple the lexer from the state functions and
+        // make it easier to extend the lexer with new state functions.
+        while self.pending.is_empty() {
+            let f = self.state.take()?;
+            self.state = f.call(self);
         }
+        self.pending.pop_front()
     }
 
     // The following methods are used by the state functions to interact with the lexer context:
@@ -44,6 +101,10 @@ impl Lexer {
     pub fn current(&self) -> String {
         self.input[self.start..self.pos].to_string()
     }
+    /// The input that is left to lex
+    pub fn rest(&self) -> &str {
+        &self.input[self.pos..]
+    }
     /// Emit a token with the current value
     pub fn emit(&mut self, typ: Type) {
         let val = self.input[self.start..self.pos].to_string();
@@ -52,7 +113,40 @@ impl Lexer {
     }
     /// Send a token (without updating the start position)
     pub fn send(&mut self, typ: Type, val: String) {
-        let _ = self.sender.send(Token { typ, val });
+        self.send_data(typ, val, TokenData::None);
+    }
+    /// Emit a token with the current value and extra data
+    pub fn emit_data(&mut self, typ: Type, data: TokenData) {
+        let val = self.input[self.start..self.pos].to_string();
+        self.send_data(typ, val, data);
+        self.start = self.pos;
+    }
+    pub fn send_data(&mut self, typ: Type, val: String, data: TokenData) {
+        let span = Span {
+            start: self.start,
+            end: self.pos,
+        };
+        self.pending.push_back(Token {
+            typ,
+            val,
+            data,
+            span,
+        });
+    }
+    /// Report a lexical error as a `LexerError` token, lexing goes on afterwards
+    pub fn error(&mut self, kind: ErrorKind, span: Span) {
+        self.pending.push_back(Token {
+            typ: Type::LexerError,
+            val: kind.to_string(),
+            data: TokenData::Error(kind),
+            span,
+        });
+    }
+    pub fn push_mode(&mut self, mode: Mode) {
+        self.modes.push(mode);
+    }
+    pub fn pop_mode(&mut self) -> Option<Mode> {
+        self.modes.pop()
     }
     pub fn ignore(&mut self) {
         self.start = self.pos;
@@ -80,7 +174,307 @@ impl Lexer {
         }
         accepted
     }
+    /// The span of the current value
+    pub fn span(&self) -> Span {
+        Span {
+            start: self.start,
+            end: self.pos,
+        }
+    }
+    pub fn pos(&self) -> usize {
+        self.pos
+    }
+    pub fn goal(&self) -> Goal {
+        self.goal
+    }
     pub fn eof(&self) -> bool {
         self.pos >= self.input.len()
     }
 }
+
+impl Iterator for Lexer {
+    type Item = Token;
+
+    fn next(&mut self) -> Option<Token> {
+        self.next_token(Goal::Div)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn lex(input: &str) -> Vec<Token> {
+        token_stream(input).iter().collect()
+    }
+
+    fn types(input: &str) -> Vec<Type> {
+        lex(input).iter().map(|t| t.typ).collect()
+    }
+
+    fn template(raw: &str, cooked: Option<&str>) -> TokenData {
+        TokenData::Template {
+            raw: raw.to_string(),
+            cooked: cooked.map(|s| s.to_string()),
+        }
+    }
+
+    #[test]
+    fn test_template_without_substitution() {
+        let tokens = lex("`a\\tb`");
+        assert_eq!(tokens[0].typ, Type::NoSubstitutionTemplate);
+        assert_eq!(tokens[0].val, "`a\\tb`");
+        assert_eq!(tokens[0].data, template("a\\tb", Some("a\tb")));
+        assert_eq!(tokens[1].typ, Type::EOF);
+    }
+
+    #[test]
+    fn test_template_substitutions() {
+        let tokens = lex("`x${a}y${ {b} }z`");
+        assert_eq!(
+            tokens.iter().map(|t| t.typ).collect::<Vec<_>>(),
+            vec![
+                Type::TemplateHead,
+                Type::Identifier,
+                Type::TemplateMiddle,
+                Type::LeftBrace,
+                Type::Identifier,
+                Type::RightBrace,
+                Type::TemplateTail,
+                Type::EOF,
+            ]
+        );
+        assert_eq!(tokens[0].data, template("x", Some("x")));
+        assert_eq!(tokens[2].val, "}y${");
+        assert_eq!(tokens[6].data, template("z", Some("z")));
+    }
+
+    #[test]
+    fn test_nested_templates() {
+        assert_eq!(
+            types("`a${`b${c}`}d`"),
+            vec![
+                Type::TemplateHead,
+                Type::TemplateHead,
+                Type::Identifier,
+                Type::TemplateTail,
+                Type::TemplateTail,
+                Type::EOF,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_template_cooked_strings() {
+        assert_eq!(
+            lex("`\\u{1F600}\\x41\\`$`")[0].data,
+            template("\\u{1F600}\\x41\\`$", Some("😀A`$"))
+        );
+        assert_eq!(
+            lex("`a\r\nb\\\nc`")[0].data,
+            template("a\nb\\\nc", Some("a\nbc"))
+        );
+        assert_eq!(lex("`\\unicode`")[0].data, template("\\unicode", None));
+    }
+
+    #[test]
+    fn test_unterminated_template() {
+        assert_eq!(
+            types("`abc${x}"),
+            vec![
+                Type::TemplateHead,
+                Type::Identifier,
+                Type::LexerError,
+                Type::EOF
+            ]
+        );
+    }
+
+    fn lex_with(input: &str, goals: &[Goal]) -> Vec<Token> {
+        let mut lexer = Lexer::new(input);
+        goals.iter().filter_map(|&g| lexer.next_token(g)).collect()
+    }
+
+    #[test]
+    fn test_slash_goal() {
+        let div = lex_with("/a/g", &[Goal::Div, Goal::Div, Goal::Div, Goal::Div]);
+        assert_eq!(
+            div.iter().map(|t| t.typ).collect::<Vec<_>>(),
+            vec![
+                Type::Divide,
+                Type::Identifier,
+                Type::Divide,
+                Type::Identifier
+            ]
+        );
+
+        let re = lex_with("/[/]\\//gi /= 2", &[Goal::RegExp, Goal::Div, Goal::Div]);
+        assert_eq!(re[0].typ, Type::RegularExpression);
+        assert_eq!(
+            re[0].data,
+            TokenData::RegExp {
+                pattern: "[/]\\/".to_string(),
+                flags: "gi".to_string()
+            }
+        );
+        assert_eq!(re[1].typ, Type::DivideAssign);
+        assert_eq!(re[2].typ, Type::Number);
+    }
+
+    #[test]
+    fn test_punctuators() {
+        let tokens = token_stream("a>>>=b?c:!d--===-1**'e'")
+            .iter()
+            .collect::<Vec<_>>();
+        assert_eq!(
+            tokens.iter().map(|t| t.val.as_str()).collect::<Vec<_>>(),
+            vec!["a", ">>>=", "b", "?", "c", ":", "!", "d", "--", "===", "-", "1", "**", "'e'", ""]
+        );
+        assert_eq!(tokens[10].typ, Type::Minus);
+        assert_eq!(tokens[13].typ, Type::StringLiteral);
+
+        let values = |input| {
+            token_stream(input)
+                .iter()
+                .map(|t| t.val)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(values("a?.b??c"), vec!["a", "?.", "b", "??", "c", ""]);
+        assert_eq!(values("a?.5:b"), vec!["a", "?", ".5", ":", "b", ""]);
+        assert_eq!(values("[...a.b]"), vec!["[", "...", "a", ".", "b", "]", ""]);
+        assert_eq!(
+            types("this.#_a$ #1"),
+            vec![
+                Type::Identifier,
+                Type::Dot,
+                Type::PrivateIdentifier,
+                Type::LexerError,
+                Type::Number,
+                Type::EOF
+            ]
+        );
+    }
+
+    #[test]
+    fn test_string_literals() {
+        let strings = [
+            r"'it\'s'",
+            r#""say \"hi\"""#,
+            r#""a'b""#,
+            r"'\\'",
+            "'héllo ☃ 😀'",
+            "\"tab\there\"",
+            "'a\\\nb'",
+            "'c\\\r\nd'",
+            "'\u{2028}'",
+        ];
+        for string in strings {
+            let tokens = lex(string);
+            assert_eq!(tokens[0].typ, Type::StringLiteral, "{}", string);
+            assert_eq!(tokens[0].val, string);
+            assert_eq!(tokens[1].typ, Type::EOF);
+        }
+        assert_eq!(
+            types("'a\nb' \"c\\"),
+            vec![
+                Type::LexerError,
+                Type::LineTerminator,
+                Type::Identifier,
+                Type::LexerError,
+                Type::EOF
+            ]
+        );
+    }
+
+    #[test]
+    fn test_numbers() {
+        let numbers = "1.5 .5 1. 1e3 2E-7 1.5e+10 0x1F 0O17 0b101 1_000 0xFF_FF 1_0.0_1e1_0";
+        let tokens = lex(numbers);
+        assert!(tokens[..tokens.len() - 1]
+            .iter()
+            .all(|t| t.typ == Type::Number));
+        assert_eq!(
+            tokens.iter().map(|t| t.val.as_str()).collect::<Vec<_>>(),
+            numbers.split(' ').chain([""]).collect::<Vec<_>>()
+        );
+        assert_eq!(
+            types("1..toString()"),
+            vec![
+                Type::Number,
+                Type::Dot,
+                Type::Identifier,
+                Type::LeftParen,
+                Type::RightParen,
+                Type::EOF
+            ]
+        );
+        let (tokens, errors) = tokenize("1_ 1__0 0x 1e 3in 0b2 1.toString");
+        assert_eq!(tokens.len(), 1);
+        assert!(errors.iter().all(|e| e.kind == ErrorKind::MalformedNumber));
+        assert_eq!(
+            errors
+                .iter()
+                .map(|e| &"1_ 1__0 0x 1e 3in 0b2 1.toString"[e.span.start..e.span.end])
+                .collect::<Vec<_>>(),
+            vec!["1_", "1__0", "0x", "1e", "3in", "0b2", "1.toString"]
+        );
+    }
+
+    #[test]
+    fn test_comments() {
+        assert_eq!(
+            types("a // b\n/* c */ d /*/ e */ /*"),
+            vec![
+                Type::Identifier,
+                Type::LineTerminator,
+                Type::Identifier,
+                Type::LexerError,
+                Type::EOF
+            ]
+        );
+        assert_eq!(
+            types("a /*\r\n*/ b\r\n"),
+            vec![
+                Type::Identifier,
+                Type::LineTerminator,
+                Type::Identifier,
+                Type::LineTerminator,
+                Type::EOF
+            ]
+        );
+    }
+
+    #[test]
+    fn test_error_recovery() {
+        let (tokens, errors) = tokenize("a # b \"x\nc `d");
+        assert_eq!(
+            tokens.iter().map(|t| t.typ).collect::<Vec<_>>(),
+            vec![
+                Type::Identifier,
+                Type::Identifier,
+                Type::LineTerminator,
+                Type::Identifier,
+                Type::EOF
+            ]
+        );
+        assert_eq!(tokens[3].span, Span { start: 9, end: 10 });
+        assert_eq!(
+            errors,
+            vec![
+                LexError {
+                    kind: ErrorKind::UnexpectedCharacter('#'),
+                    span: Span { start: 2, end: 3 }
+                },
+                LexError {
+                    kind: ErrorKind::UnterminatedString,
+                    span: Span { start: 6, end: 8 }
+                },
+                LexError {
+                    kind: ErrorKind::UnterminatedTemplate,
+                    span: Span { start: 11, end: 13 }
+                },
+            ]
+        );
+        assert_eq!(errors[2].span.line_col("a # b \"x\nc `d"), (2, 3));
+    }
+}
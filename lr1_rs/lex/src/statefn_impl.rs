@@ -1,8 +1,9 @@
 //! The implementation of the state functions that the lexer uses to parse the input string.
 
-use crate::lexer::Lexer;
+use crate::error::ErrorKind;
+use crate::lexer::{Goal, Lexer, Mode};
 use crate::statefn::StateFn;
-use crate::token::Type;
+use crate::token::{TokenData, Type};
 
 impl Default for StateFn {
     fn default() -> StateFn {
@@ -18,18 +19,23 @@ pub fn lex_start(lexer: &mut Lexer) -> Option<StateFn> {
 
     let c = lexer.peek();
 
+    if lexer.rest().starts_with("...") {
+        for _ in 0..3 {
+            lexer.step();
+        }
+        lexer.emit(Type::Punctuator);
+        return Some(StateFn::from(lex_start));
+    }
+
     // trivial cases:
     if let Some(next_f) = match c.unwrap() {
         ';' => Some(Type::SemiColon),
         '(' => Some(Type::LeftParen),
         ')' => Some(Type::RightParen),
-        '{' => Some(Type::LeftBrace),
-        '}' => Some(Type::RightBrace),
-        '+' => Some(Type::Plus),
-        '*' => Some(Type::Multiply),
-        '/' => Some(Type::Divide),
-        '>' => Some(Type::Greater),
-        '<' => Some(Type::Less),
+        '[' => Some(Type::LeftBracket),
+        ']' => Some(Type::RightBracket),
+        ',' => Some(Type::Comma),
+        '.' if !lexer.rest()[1..].starts_with(|c: char| c.is_ascii_digit()) => Some(Type::Dot),
         _ => None,
     } {
         lexer.step();
@@ -38,81 +44,398 @@ pub fn lex_start(lexer: &mut Lexer) -> Option<StateFn> {
     }
 
     match c.unwrap() {
-        'a'..='z' | 'A'..='Z' => Some(StateFn::from(lex_alpha)),
-        '0'..='9' => Some(StateFn::from(lex_number)),
-        '"' => Some(StateFn::from(lex_string_literal)),
-        '-' => Some(StateFn::from(lex_minus)), // '-' or number
-        '=' => Some(StateFn::from(lex_eq_op)), // '=' or '=='
+        'a'..='z' | 'A'..='Z' | '_' | '$' => Some(StateFn::from(lex_alpha)),
+        '#' => Some(StateFn::from(lex_private_identifier)),
+        '0'..='9' | '.' => Some(StateFn::from(lex_number)),
+        '"' | '\'' => Some(StateFn::from(lex_string_literal)),
+        c if PUNCTUATOR_CHARS.contains(c) => Some(StateFn::from(lex_punctuator)),
+        '{' => Some(StateFn::from(lex_left_brace)),
+        '}' => Some(StateFn::from(lex_right_brace)), // '}' or the rest of a template
+        '`' => Some(StateFn::from(lex_template)),
+        '/' => Some(StateFn::from(lex_slash)), // comment, division or regular expression
 
         // all blank characters
-        ' ' | '\t' | '\n' | '\r' => {
+        ' ' | '\t' => {
             lexer.step();
             lexer.ignore();
             Some(StateFn::from(lex_start))
         }
+        '\n' | '\r' | '\u{2028}' | '\u{2029}' => {
+            // "\r\n" is a single line terminator
+            if lexer.step() == Some('\n') && c == Some('\r') {
+                lexer.step();
+            }
+            lexer.emit(Type::LineTerminator);
+            Some(StateFn::from(lex_start))
+        }
 
-        _ => lex_error(
-            lexer,
-            format!("unexpected character: '{}'", c.unwrap()).as_str(),
-        ),
+        c => {
+            lexer.step();
+            lex_error(lexer, ErrorKind::UnexpectedCharacter(c))
+        }
     }
 }
 
-fn lex_eq_op(lexer: &mut Lexer) -> Option<StateFn> {
-    lexer.accept_run("=");
-    let s = lexer.current();
-    match s.as_str() {
-        "=" => lexer.emit(Type::Assign),
-        "==" => lexer.emit(Type::Equal),
-        _ => unreachable!(),
+static PUNCTUATOR_CHARS: &str = "=!<>+-*%&|^~?:";
+
+/// Operators, longest first so that the longest one matching the input wins
+static PUNCTUATORS: &[&str] = &[
+    ">>>=", "===", "!==", "**=", "<<=", ">>=", ">>>", "&&=", "||=", "??=", "==", "!=", "<=", ">=",
+    "+=", "-=", "*=", "%=", "&=", "|=", "^=", "++", "--", "**", "<<", ">>", "&&", "||", "??", "?.",
+    "=>", "=", "!", "<", ">", "+", "-", "*", "%", "&", "|", "^", "~", "?", ":",
+];
+
+fn lex_punctuator(lexer: &mut Lexer) -> Option<StateFn> {
+    let rest = lexer.rest();
+    // `a?.5:b` is a conditional expression
+    let punctuator = PUNCTUATORS
+        .iter()
+        .find(|&&p| {
+            rest.starts_with(p)
+                && !(p == "?." && rest[2..].starts_with(|c: char| c.is_ascii_digit()))
+        })
+        .expect("every punctuator character is a punctuator");
+    for _ in 0..punctuator.len() {
+        lexer.step();
     }
+    lexer.emit(match *punctuator {
+        "=" => Type::Assign,
+        "==" => Type::Equal,
+        "+" => Type::Plus,
+        "-" => Type::Minus,
+        "*" => Type::Multiply,
+        ">" => Type::Greater,
+        "<" => Type::Less,
+        _ => Type::Punctuator,
+    });
     Some(StateFn::from(lex_start))
 }
 
-fn lex_minus(lexer: &mut Lexer) -> Option<StateFn> {
-    lexer.step();
-    if let Some('0'..='9') = lexer.peek() {
-        Some(StateFn::from(lex_number))
-    } else {
-        lexer.emit(Type::Minus);
-        Some(StateFn::from(lex_start))
+static DECIMAL_DIGITS: &str = "0123456789";
+
+/// A numeric literal: a decimal one with a fraction and an exponent or not (`1.5`, `.5`, `1.`,
+/// `1e-3`), or a hexadecimal, octal or binary integer (`0x1F`, `0o17`, `0b101`), the digits of any
+/// part separated by single `_`s. Its value is left to the parser
+fn lex_number(lexer: &mut Lexer) -> Option<StateFn> {
+    let radix_digits = match lexer.rest().get(..2) {
+        Some("0x" | "0X") => Some("0123456789abcdefABCDEF"),
+        Some("0o" | "0O") => Some("01234567"),
+        Some("0b" | "0B") => Some("01"),
+        _ => None,
+    };
+    let valid = match radix_digits {
+        Some(digits) => {
+            lexer.step();
+            lexer.step();
+            accept_digits(lexer, digits)
+        }
+        None => {
+            let integer = accept_digits(lexer, DECIMAL_DIGITS);
+            let fraction = lexer.accept(".") && accept_digits(lexer, DECIMAL_DIGITS);
+            let exponent = match lexer.accept("eE") {
+                true => {
+                    lexer.accept("+-");
+                    accept_digits(lexer, DECIMAL_DIGITS)
+                }
+                false => true,
+            };
+            (integer || fraction) && exponent
+        }
+    };
+    // `3in` and `1_` are errors, the number runs into what follows
+    let rest = lexer.accept_run(IDENTIFIER_CHARS);
+    if !valid || rest {
+        return lex_error(lexer, ErrorKind::MalformedNumber);
     }
+    lexer.emit(Type::Number);
+    Some(StateFn::from(lex_start))
 }
 
-fn lex_number(lexer: &mut Lexer) -> Option<StateFn> {
-    if let Some('0'..='9') = lexer.peek() {
-        lexer.step();
-        Some(StateFn::from(lex_number))
-    } else {
-        lexer.emit(Type::Number);
-        Some(StateFn::from(lex_start))
+/// A run of `digits` with single `_`s between them, whether there was a digit
+fn accept_digits(lexer: &mut Lexer, digits: &str) -> bool {
+    let mut accepted = false;
+    loop {
+        if lexer.accept(digits) {
+            accepted = true;
+        } else if accepted
+            && lexer.rest().starts_with('_')
+            && lexer.rest()[1..].starts_with(|c| digits.contains(c))
+        {
+            lexer.step();
+        } else {
+            return accepted;
+        }
     }
 }
 
-static SPECIAL_CHARS: &str = " !#$%&'()*+,-./:;<=>?@[\\]^_{|}~";
-static DIGITS_AND_ALPHA: &str = "abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789";
+static IDENTIFIER_CHARS: &str = "abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789_$";
 
+/// A string literal: any characters but its quote and a line break, which a `\` before them
+/// escapes, as it does any other character (`\` and a line break is a line continuation). The
+/// escapes are the parser's to process. U+2028 and U+2029 may be in a string as they are
 fn lex_string_literal(lexer: &mut Lexer) -> Option<StateFn> {
+    let quote = lexer.peek();
     lexer.step();
     while let Some(c) = lexer.peek() {
-        if c == '"' {
+        match c {
+            c if Some(c) == quote => {
+                lexer.step();
+                lexer.emit(Type::StringLiteral);
+                return Some(StateFn::from(lex_start));
+            }
+            '\n' | '\r' => break,
+            '\\' => {
+                // "\r\n" is a single line terminator
+                if lexer.step() == Some('\r') {
+                    lexer.step();
+                    lexer.accept("\n");
+                } else {
+                    lexer.step();
+                }
+            }
+            _ => {
+                lexer.step();
+            }
+        }
+    }
+    lex_error(lexer, ErrorKind::UnterminatedString)
+}
+
+fn lex_slash(lexer: &mut Lexer) -> Option<StateFn> {
+    lexer.step();
+    match lexer.peek() {
+        Some('/') => {
+            while !matches!(
+                lexer.peek(),
+                None | Some('\n' | '\r' | '\u{2028}' | '\u{2029}')
+            ) {
+                lexer.step();
+            }
+            lexer.ignore();
+        }
+        Some('*') => {
             lexer.step();
-            lexer.emit(Type::StringLiteral);
-            return Some(StateFn::from(lex_start));
+            loop {
+                match lexer.peek() {
+                    None => return lex_error(lexer, ErrorKind::UnterminatedComment),
+                    Some('*') => {
+                        lexer.step();
+                        if lexer.peek() == Some('/') {
+                            lexer.step();
+                            break;
+                        }
+                    }
+                    _ => {
+                        lexer.step();
+                    }
+                }
+            }
+            // a comment spanning lines counts as a line break
+            if lexer
+                .current()
+                .contains(['\n', '\r', '\u{2028}', '\u{2029}'])
+            {
+                lexer.send(Type::LineTerminator, "\n".to_string());
+            }
+            lexer.ignore();
         }
-        if !SPECIAL_CHARS.contains(c) && !DIGITS_AND_ALPHA.contains(c) {
-            return lex_error(
-                lexer,
-                format!("unexpected character in string literal: '{}'", c).as_str(),
-            );
+        _ if lexer.goal() == Goal::RegExp => return Some(StateFn::from(lex_regex)),
+        Some('=') => {
+            lexer.step();
+            lexer.emit(Type::DivideAssign);
+        }
+        _ => lexer.emit(Type::Divide),
+    }
+    Some(StateFn::from(lex_start))
+}
+
+/// The rest of a regular expression literal after the opening '/'. The pattern itself is not
+/// validated, only scanned far enough to find the closing '/' (which may appear unescaped in
+/// a class like `[/]`).
+fn lex_regex(lexer: &mut Lexer) -> Option<StateFn> {
+    let mut in_class = false;
+    loop {
+        match lexer.peek() {
+            None | Some('\n' | '\r' | '\u{2028}' | '\u{2029}') => {
+                return lex_error(lexer, ErrorKind::UnterminatedRegExp);
+            }
+            Some('\\') => {
+                lexer.step();
+                if matches!(
+                    lexer.peek(),
+                    None | Some('\n' | '\r' | '\u{2028}' | '\u{2029}')
+                ) {
+                    return lex_error(lexer, ErrorKind::UnterminatedRegExp);
+                }
+            }
+            Some('[') => in_class = true,
+            Some(']') => in_class = false,
+            Some('/') if !in_class => {
+                lexer.step();
+                break;
+            }
+            _ => {}
         }
         lexer.step();
     }
-    lex_error(lexer, "unterminated string literal")
+
+    let body = lexer.current().len();
+    lexer.accept_run(IDENTIFIER_CHARS);
+    let s = lexer.current();
+    let data = TokenData::RegExp {
+        pattern: s[1..body - 1].to_string(),
+        flags: s[body..].to_string(),
+    };
+    lexer.emit_data(Type::RegularExpression, data);
+    Some(StateFn::from(lex_start))
+}
+
+fn lex_left_brace(lexer: &mut Lexer) -> Option<StateFn> {
+    lexer.step();
+    lexer.push_mode(Mode::Brace);
+    lexer.emit(Type::LeftBrace);
+    Some(StateFn::from(lex_start))
+}
+
+fn lex_right_brace(lexer: &mut Lexer) -> Option<StateFn> {
+    if lexer.pop_mode() == Some(Mode::Substitution) {
+        return Some(StateFn::from(lex_template_continue));
+    }
+    lexer.step();
+    lexer.emit(Type::RightBrace);
+    Some(StateFn::from(lex_start))
+}
+
+/// '`' starts a template: `abc` or `abc${
+fn lex_template(lexer: &mut Lexer) -> Option<StateFn> {
+    lexer.step();
+    lex_template_chars(lexer, Type::TemplateHead, Type::NoSubstitutionTemplate)
+}
+
+/// '}' closing a substitution resumes the template: }abc${ or }abc`
+fn lex_template_continue(lexer: &mut Lexer) -> Option<StateFn> {
+    lexer.step();
+    lex_template_chars(lexer, Type::TemplateMiddle, Type::TemplateTail)
+}
+
+/// Scan template characters up to the next `${` (emitting `open`) or closing '`' (emitting
+/// `close`). Inside `${ ... }` the lexer goes back to `lex_start`, the pushed
+/// `Mode::Substitution` tells `lex_right_brace` where the substitution ends, which also makes
+/// templates nested in substitutions work.
+fn lex_template_chars(lexer: &mut Lexer, open: Type, close: Type) -> Option<StateFn> {
+    while let Some(c) = lexer.peek() {
+        match c {
+            '`' => {
+                lexer.step();
+                let s = lexer.current();
+                let data = template_data(&s[1..s.len() - 1]);
+                lexer.emit_data(close, data);
+                return Some(StateFn::from(lex_start));
+            }
+            '$' => {
+                lexer.step();
+                if lexer.peek() == Some('{') {
+                    lexer.step();
+                    let s = lexer.current();
+                    let data = template_data(&s[1..s.len() - 2]);
+                    lexer.push_mode(Mode::Substitution);
+                    lexer.emit_data(open, data);
+                    return Some(StateFn::from(lex_start));
+                }
+            }
+            '\\' => {
+                lexer.step();
+                lexer.step();
+            }
+            _ => {
+                lexer.step();
+            }
+        }
+    }
+    lex_error(lexer, ErrorKind::UnterminatedTemplate)
+}
+
+fn template_data(source: &str) -> TokenData {
+    let raw = source.replace("\r\n", "\n").replace('\r', "\n");
+    let cooked = cook_template(&raw);
+    TokenData::Template { raw, cooked }
+}
+
+/// Process the escape sequences of a template chunk (or of the body of a string literal), `None`
+/// if one of them is invalid
+pub fn cook_template(raw: &str) -> Option<String> {
+    let mut cooked = String::new();
+    let mut chars = raw.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            cooked.push(c);
+            continue;
+        }
+        match chars.next()? {
+            'n' => cooked.push('\n'),
+            't' => cooked.push('\t'),
+            'r' => cooked.push('\r'),
+            'b' => cooked.push('\u{8}'),
+            'f' => cooked.push('\u{c}'),
+            'v' => cooked.push('\u{b}'),
+            '0' if !chars.peek().is_some_and(|c| c.is_ascii_digit()) => cooked.push('\0'),
+            '0'..='9' => return None,
+            'x' => {
+                let hex: String = (0..2).filter_map(|_| chars.next()).collect();
+                cooked.push(char::from_u32(parse_hex(&hex, 2)?)?);
+            }
+            'u' => {
+                let mut code = parse_unicode_escape(&mut chars)?;
+                // a surrogate pair written as two escapes
+                if (0xD800..0xDC00).contains(&code) {
+                    let mut rest = chars.clone();
+                    if rest.next() == Some('\\') && rest.next() == Some('u') {
+                        if let Some(low @ 0xDC00..=0xDFFF) = parse_unicode_escape(&mut rest) {
+                            code = 0x10000 + ((code - 0xD800) << 10) + (low - 0xDC00);
+                            chars = rest;
+                        }
+                    }
+                }
+                cooked.push(char::from_u32(code).unwrap_or(char::REPLACEMENT_CHARACTER));
+            }
+            // line continuation, "\r\n" in a string literal
+            '\r' => {
+                chars.next_if_eq(&'\n');
+            }
+            '\n' | '\u{2028}' | '\u{2029}' => {}
+            c => cooked.push(c),
+        }
+    }
+    Some(cooked)
+}
+
+/// The part of `\uXXXX` or `\u{X...}` after the 'u'
+fn parse_unicode_escape(chars: &mut std::iter::Peekable<std::str::Chars>) -> Option<u32> {
+    if chars.peek() == Some(&'{') {
+        chars.next();
+        let mut hex = String::new();
+        loop {
+            match chars.next()? {
+                '}' => break,
+                c => hex.push(c),
+            }
+        }
+        parse_hex(&hex, hex.len()).filter(|&code| code <= 0x10FFFF)
+    } else {
+        let hex: String = (0..4).filter_map(|_| chars.next()).collect();
+        parse_hex(&hex, 4)
+    }
+}
+
+fn parse_hex(hex: &str, len: usize) -> Option<u32> {
+    if hex.is_empty() || hex.len() != len || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    u32::from_str_radix(hex, 16).ok()
 }
 
 fn lex_alpha(lexer: &mut Lexer) -> Option<StateFn> {
-    lexer.accept_run(DIGITS_AND_ALPHA);
+    lexer.accept_run(IDENTIFIER_CHARS);
     let s = lexer.current();
     match s.as_str() {
         "var" => lexer.emit(Type::Var),
@@ -125,7 +448,19 @@ fn lex_alpha(lexer: &mut Lexer) -> Option<StateFn> {
     Some(StateFn::from(lex_start))
 }
 
-fn lex_error(lexer: &mut Lexer, msg: &str) -> Option<StateFn> {
-    lexer.send(Type::LexerError, msg.to_string());
-    None
+/// `#name`, a private class member
+fn lex_private_identifier(lexer: &mut Lexer) -> Option<StateFn> {
+    lexer.step();
+    if lexer.peek().is_some_and(|c| c.is_ascii_digit()) || !lexer.accept_run(IDENTIFIER_CHARS) {
+        return lex_error(lexer, ErrorKind::UnexpectedCharacter('#'));
+    }
+    lexer.emit(Type::PrivateIdentifier);
+    Some(StateFn::from(lex_start))
+}
+
+/// Report the current text as erroneous and skip it
+fn lex_error(lexer: &mut Lexer, kind: ErrorKind) -> Option<StateFn> {
+    lexer.error(kind, lexer.span());
+    lexer.ignore();
+    Some(StateFn::from(lex_start))
 }
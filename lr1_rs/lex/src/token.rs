@@ -1,7 +1,44 @@
+use crate::error::ErrorKind;
+
 #[derive(Debug, Clone)]
 pub struct Token {
     pub typ: Type,
     pub val: String,
+    pub data: TokenData,
+    pub span: Span,
+}
+
+/// Byte range of a token in the input
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+}
+
+impl Span {
+    /// 1-based line and column (in characters) of the start of the span
+    pub fn line_col(&self, input: &str) -> (usize, usize) {
+        let before = &input[..self.start.min(input.len())];
+        let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+        (
+            before.matches('\n').count() + 1,
+            before[line_start..].chars().count() + 1,
+        )
+    }
+}
+
+/// Extra information carried by tokens whose value is more than their source text
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TokenData {
+    None,
+    /// A template chunk. `raw` is the text between the delimiters (with line terminators
+    /// normalized to `\n`), `cooked` has its escape sequences processed and is `None` when it
+    /// contains an invalid escape, which is only allowed in tagged templates.
+    Template { raw: String, cooked: Option<String> },
+    /// A regular expression literal `/pattern/flags`
+    RegExp { pattern: String, flags: String },
+    /// What a `LexerError` token reports
+    Error(ErrorKind),
 }
 
 #[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
@@ -10,10 +47,15 @@ pub enum Type {
     RightBrace,
     LeftParen,
     RightParen,
+    LeftBracket,
+    RightBracket,
     SemiColon,
+    Comma,
+    Dot,
     Plus,
     Multiply,
     Divide,
+    DivideAssign,
     Greater,
     Less,
 
@@ -21,6 +63,8 @@ pub enum Type {
     Equal,
 
     Minus,
+    /// Any other operator, its value tells which
+    Punctuator,
 
     Input,
     Print,
@@ -31,7 +75,16 @@ pub enum Type {
     Identifier,
     Number,
     StringLiteral,
+    RegularExpression,
+    PrivateIdentifier,
+
+    // `abc` / `abc${ / }abc${ / }abc`
+    NoSubstitutionTemplate,
+    TemplateHead,
+    TemplateMiddle,
+    TemplateTail,
 
+    LineTerminator,
     EOF,
 
     LexerError
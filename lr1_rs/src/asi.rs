@@ -0,0 +1,157 @@
+use super::*;
+
+/// Automatic semicolon insertion, following the rules of ECMAScript:
+///
+/// * a terminal the grammar doesn't allow gets a semicolon inserted before it if it follows a
+///   line break, is the closing brace of a block or is the end of the input
+/// * a line break right after a restricted terminal (`return`, `break`, ...) or right before a
+///   terminal that can't start a new line (postfix `++`, `--`) ends the statement
+/// * a line break right before a terminal that never follows one (`=>`) is a syntax error
+/// * a terminal the grammar doesn't allow gets a semicolon inserted before it if that semicolon
+///   would end a `do`-`while` statement, line break or not
+///
+/// A semicolon is only inserted where it would be parsed as `terminator` or `do_while_terminator`,
+/// never as an empty statement or one of the semicolons in a `for` head.
+#[derive(Debug, Clone)]
+pub struct Asi {
+    /// The terminal to insert
+    pub semicolon: Symbol,
+    /// The nonterminal `semicolon` is reduced to when it ends a statement
+    pub terminator: Symbol,
+    /// The nonterminal `semicolon` is reduced to when it ends a `do`-`while` statement
+    pub do_while_terminator: Option<Symbol>,
+    pub close_brace: Symbol,
+    /// A line break after these ends the statement
+    pub restricted: Vec<Symbol>,
+    /// A line break before these ends the statement
+    pub no_line_break_before: Vec<Symbol>,
+    /// A line break before these is an error
+    pub never_after_line_break: Vec<Symbol>,
+}
+
+impl Parser {
+    pub fn with_asi(mut self, asi: Asi) -> Self {
+        self.asi = Some(asi);
+        self
+    }
+
+    /// The terminator a semicolon inserted before `next` with the parser in `stack` would be
+    /// parsed as, if it can be inserted at all
+    pub(crate) fn inserted_terminator<'a>(
+        &self,
+        asi: &'a Asi,
+        stack: &[usize],
+        next: &Symbol,
+    ) -> Option<&'a Symbol> {
+        let state = self.shift_target(stack, &asi.semicolon)?;
+        match self.action_table.get(&(state, next.clone())) {
+            Some(Action::Reduce(p)) if p.lhs == asi.terminator => Some(&asi.terminator),
+            Some(Action::Reduce(p)) if asi.do_while_terminator.as_ref() == Some(&p.lhs) => {
+                asi.do_while_terminator.as_ref()
+            }
+            _ => None,
+        }
+    }
+
+    /// The state `symbol` gets shifted into, after the reductions it causes
+    fn shift_target(&self, stack: &[usize], symbol: &Symbol) -> Option<usize> {
+        let mut stack = stack.to_vec();
+        loop {
+            match self.action_table.get(&(*stack.last()?, symbol.clone()))? {
+                Action::Shift(state) => return Some(*state),
+                Action::Reduce(production) => {
+                    stack.truncate(stack.len().checked_sub(production.rhs.len())?);
+                    let state = self
+                        .goto_table
+                        .get(&(*stack.last()?, production.lhs.clone()))?;
+                    stack.push(*state);
+                }
+                _ => return None,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn parse(input: &str) -> Result<ASTNode, ParseError> {
+        let parser = Parser::new(grammar!(
+            "P",
+            prod!("P" => "S", "P"),
+            prod!("P" => ),
+            prod!("S" => "E", "eos"),
+            prod!("S" => "return", "E_", "eos"),
+            prod!("S" => "if", "(", "E", ")", "S"),
+            prod!("S" => "do", "S", "while", "(", "E", ")", "do_eos"),
+            prod!("S" => "{", "P", "}"),
+            prod!("S" => ";"),
+            prod!("E_" => "E"),
+            prod!("E_" => ),
+            prod!("E" => "E", "+", "T"),
+            prod!("E" => "T"),
+            prod!("E" => "x", "=>", "E"),
+            prod!("T" => "T", "++"),
+            prod!("T" => "++", "T"),
+            prod!("T" => "x"),
+            prod!("eos" => ";"),
+            prod!("do_eos" => ";")
+        ))
+        .with_asi(Asi {
+            semicolon: Term!(";"),
+            terminator: NonTerm!("eos"),
+            do_while_terminator: Some(NonTerm!("do_eos")),
+            close_brace: Term!("}"),
+            restricted: vec![Term!("return")],
+            no_line_break_before: vec![Term!("++")],
+            never_after_line_break: vec![Term!("=>")],
+        });
+
+        // one terminal per word, `\n` marks a line break before the next one
+        let mut symbols = Vec::new();
+        let mut newline = false;
+        for word in input.split(' ') {
+            match word {
+                "\n" => newline = true,
+                _ => symbols.push((Term!(word), std::mem::take(&mut newline))),
+            }
+        }
+        parser.parse_source(&mut Symbols::new(symbols))
+    }
+
+    #[test]
+    fn test_asi() {
+        assert!(parse("x \n x + x").is_ok());
+        assert!(parse("{ x } x").is_ok());
+        assert!(parse("if ( x ) { return x }").is_ok());
+        assert!(parse("x x").is_err());
+
+        // `return \n x` is `return; x;`, not `return x;`
+        let tree = parse("return \n x").unwrap().to_string();
+        assert_eq!(tree.matches("E_").count(), 1);
+        assert!(tree.contains("\x1b[0;34mE_\x1b[0m()"));
+
+        // `x \n ++ x` is `x; ++x;`
+        assert!(parse("x \n ++ x").is_ok());
+        assert!(parse("x ++ \n x").is_ok());
+
+        assert!(parse("x => \n x").is_ok());
+        assert!(parse("x \n => x").is_err());
+    }
+
+    #[test]
+    fn test_no_empty_statement_inserted() {
+        assert!(parse("if ( x ) \n x").is_ok());
+        assert!(parse("if ( x )").is_err());
+        assert!(parse("{ if ( x ) }").is_err());
+    }
+
+    #[test]
+    fn test_do_while() {
+        // the `;` after a do-while's `)` is inserted without a line break, but not after any `)`
+        assert!(parse("do x ; while ( x ) x").is_ok());
+        assert!(parse("do x while ( x ) x").is_err());
+        assert!(parse("if ( x ) x x").is_err());
+    }
+}
@@ -0,0 +1,321 @@
+//! 以下标表示的LR(1)自动机.
+//!
+//! 规范LR(1)项集族对 JS 这样规模的文法过于庞大, 这里按 Pager 的弱相容判定在构造时合并同核心的
+//! 状态: 只要合并不会引入新的归约/归约冲突就合并, 否则像规范LR(1)一样保留为不同的状态.
+//! 没有冲突的文法得到与 LALR(1) 同样大小的表, 又不会出现 LALR(1) 特有的冲突.
+
+use super::*;
+use std::collections::{BTreeMap, HashMap, VecDeque};
+
+/// 终结符集合的位图
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Lookahead(Vec<u64>);
+
+impl Lookahead {
+    fn new(terminals: usize) -> Self {
+        Lookahead(vec![0; terminals.div_ceil(64)])
+    }
+
+    fn insert(&mut self, terminal: usize) {
+        self.0[terminal / 64] |= 1 << (terminal % 64);
+    }
+
+    /// 并入 `other`, 返回是否有变化
+    fn union(&mut self, other: &Lookahead) -> bool {
+        let mut changed = false;
+        for (a, b) in self.0.iter_mut().zip(&other.0) {
+            changed |= *b & !*a != 0;
+            *a |= b;
+        }
+        changed
+    }
+
+    fn intersects(&self, other: &Lookahead) -> bool {
+        self.0.iter().zip(&other.0).any(|(a, b)| a & b != 0)
+    }
+
+    pub(crate) fn iter(&self) -> impl Iterator<Item = usize> + '_ {
+        self.0.iter().enumerate().flat_map(|(i, &word)| {
+            (0..64)
+                .filter(move |bit| word & (1 << bit) != 0)
+                .map(move |bit| i * 64 + bit)
+        })
+    }
+}
+
+/// 项目的核心: (产生式下标, 点的位置)
+type Core = (usize, usize);
+
+/// 状态由内核项目决定, `lookaheads[i]` 属于 `core[i]`
+#[derive(Debug, Clone)]
+struct Kernel {
+    core: Vec<Core>,
+    lookaheads: Vec<Lookahead>,
+}
+
+impl Kernel {
+    /// Pager 的弱相容: 合并后任意两个项目的向前看符号若相交, 则合并前在某一方就已相交
+    fn compatible(&self, other: &Kernel) -> bool {
+        let (a, b) = (&self.lookaheads, &other.lookaheads);
+        for i in 0..a.len() {
+            for j in i + 1..a.len() {
+                if (a[i].intersects(&b[j]) || a[j].intersects(&b[i]))
+                    && !a[i].intersects(&a[j])
+                    && !b[i].intersects(&b[j])
+                {
+                    return false;
+                }
+            }
+        }
+        true
+    }
+
+    fn merge(&mut self, other: &Kernel) -> bool {
+        let mut changed = false;
+        for (a, b) in self.lookaheads.iter_mut().zip(&other.lookaheads) {
+            changed |= a.union(b);
+        }
+        changed
+    }
+}
+
+pub(crate) struct Automaton {
+    /// 终结符在前 (0 号是 EndMarker), 非终结符在后
+    pub symbols: Vec<Symbol>,
+    pub terminals: usize,
+    /// 与 `grammar.productions` 一一对应, 最后一条是增广产生式
+    pub productions: Vec<(usize, Vec<usize>)>,
+    /// 每个状态的转移: 符号 -> 状态
+    pub gotos: Vec<BTreeMap<usize, usize>>,
+    kernels: Vec<Kernel>,
+    by_nonterminal: Vec<Vec<usize>>,
+    /// `first_after[p][i]`: 产生式 p 右部从 i 开始的后缀的 First 集, 以及它能否推导出空串
+    first_after: Vec<Vec<(Lookahead, bool)>>,
+}
+
+impl Automaton {
+    pub(crate) fn new(grammar: &Grammar) -> Self {
+        // 按出现顺序编号, 让状态编号在多次运行间保持一致
+        let mut symbols = vec![Symbol::EndMarker];
+        let mut index = HashMap::new();
+        let in_order = grammar
+            .productions
+            .iter()
+            .flat_map(|p| std::iter::once(&p.lhs).chain(&p.rhs));
+        for symbol in in_order
+            .clone()
+            .filter(|s| matches!(s, Symbol::Terminal(_)))
+        {
+            if !index.contains_key(symbol) {
+                index.insert(symbol.clone(), symbols.len());
+                symbols.push(symbol.clone());
+            }
+        }
+        let terminals = symbols.len();
+        let start = NonTerm!(FAKE_START);
+        let nonterminals = std::iter::once(&grammar.start_symbol)
+            .chain(in_order.filter(|s| matches!(s, Symbol::NonTerminal(_))))
+            .chain([&start]);
+        for symbol in nonterminals {
+            if !index.contains_key(symbol) {
+                index.insert(symbol.clone(), symbols.len());
+                symbols.push(symbol.clone());
+            }
+        }
+
+        let mut productions: Vec<(usize, Vec<usize>)> = grammar
+            .productions
+            .iter()
+            .map(|p| {
+                let rhs = p.rhs.iter().filter(|&s| *s != Symbol::Epsilon);
+                (index[&p.lhs], rhs.map(|s| index[s]).collect())
+            })
+            .collect();
+        productions.push((index[&start], vec![index[&grammar.start_symbol]]));
+
+        let mut by_nonterminal = vec![Vec::new(); symbols.len()];
+        for (p, (lhs, _)) in productions.iter().enumerate() {
+            by_nonterminal[*lhs].push(p);
+        }
+
+        let mut automaton = Automaton {
+            symbols,
+            terminals,
+            productions,
+            gotos: Vec::new(),
+            kernels: Vec::new(),
+            by_nonterminal,
+            first_after: Vec::new(),
+        };
+        automaton.compute_first();
+        automaton.build();
+        automaton
+    }
+
+    fn compute_first(&mut self) {
+        let n = self.symbols.len();
+        let mut first = vec![Lookahead::new(self.terminals); n];
+        let mut nullable = vec![false; n];
+        for (t, set) in first.iter_mut().enumerate().take(self.terminals) {
+            set.insert(t);
+        }
+
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for (lhs, rhs) in &self.productions {
+                let mut all_nullable = true;
+                for &s in rhs {
+                    let set = first[s].clone();
+                    changed |= first[*lhs].union(&set);
+                    if !nullable[s] {
+                        all_nullable = false;
+                        break;
+                    }
+                }
+                if all_nullable && !nullable[*lhs] {
+                    nullable[*lhs] = true;
+                    changed = true;
+                }
+            }
+        }
+
+        self.first_after = self
+            .productions
+            .iter()
+            .map(|(_, rhs)| {
+                let mut suffixes = vec![(Lookahead::new(self.terminals), true)];
+                for &s in rhs.iter().rev() {
+                    let (next, next_nullable) = suffixes.last().unwrap().clone();
+                    let mut set = first[s].clone();
+                    if nullable[s] {
+                        set.union(&next);
+                    }
+                    suffixes.push((set, nullable[s] && next_nullable));
+                }
+                suffixes.reverse();
+                suffixes
+            })
+            .collect();
+    }
+
+    /// 状态 `state` 的闭包
+    pub(crate) fn closure(&self, state: usize) -> Vec<(Core, Lookahead)> {
+        let kernel = &self.kernels[state];
+        let mut items: Vec<(Core, Lookahead)> = kernel
+            .core
+            .iter()
+            .cloned()
+            .zip(kernel.lookaheads.iter().cloned())
+            .collect();
+        let mut position: HashMap<Core, usize> = kernel
+            .core
+            .iter()
+            .enumerate()
+            .map(|(i, &c)| (c, i))
+            .collect();
+        let mut work: VecDeque<usize> = (0..items.len()).collect();
+
+        while let Some(i) = work.pop_front() {
+            let ((p, dot), _) = items[i];
+            let rhs = &self.productions[p].1;
+            if dot >= rhs.len() || rhs[dot] < self.terminals {
+                continue;
+            }
+            let (first, nullable) = &self.first_after[p][dot + 1];
+            let mut lookahead = first.clone();
+            if *nullable {
+                lookahead.union(&items[i].1);
+            }
+            for &q in &self.by_nonterminal[rhs[dot]] {
+                match position.get(&(q, 0)) {
+                    Some(&j) => {
+                        if items[j].1.union(&lookahead) {
+                            work.push_back(j);
+                        }
+                    }
+                    None => {
+                        position.insert((q, 0), items.len());
+                        work.push_back(items.len());
+                        items.push(((q, 0), lookahead.clone()));
+                    }
+                }
+            }
+        }
+        items
+    }
+
+    fn build(&mut self) {
+        let mut lookahead = Lookahead::new(self.terminals);
+        lookahead.insert(0);
+        self.kernels.push(Kernel {
+            core: vec![(self.productions.len() - 1, 0)],
+            lookaheads: vec![lookahead],
+        });
+        self.gotos.push(BTreeMap::new());
+
+        let mut by_core: HashMap<Vec<Core>, Vec<usize>> = HashMap::new();
+        by_core.insert(self.kernels[0].core.clone(), vec![0]);
+        let mut queue = VecDeque::from([0]);
+        let mut queued = vec![true];
+
+        while let Some(state) = queue.pop_front() {
+            queued[state] = false;
+
+            let mut successors: BTreeMap<usize, BTreeMap<Core, Lookahead>> = BTreeMap::new();
+            for ((p, dot), lookahead) in self.closure(state) {
+                if let Some(&symbol) = self.productions[p].1.get(dot) {
+                    let kernel = successors.entry(symbol).or_default();
+                    match kernel.get_mut(&(p, dot + 1)) {
+                        Some(existing) => {
+                            existing.union(&lookahead);
+                        }
+                        None => {
+                            kernel.insert((p, dot + 1), lookahead);
+                        }
+                    }
+                }
+            }
+
+            for (symbol, kernel) in successors {
+                let kernel = Kernel {
+                    core: kernel.keys().cloned().collect(),
+                    lookaheads: kernel.into_values().collect(),
+                };
+                // 已有的转移: 向前看符号变多时只需合并进去
+                let target = match self.gotos[state].get(&symbol) {
+                    Some(&target) => Some(target),
+                    None => by_core.get(&kernel.core).and_then(|candidates| {
+                        candidates
+                            .iter()
+                            .copied()
+                            .find(|&c| self.kernels[c].compatible(&kernel))
+                    }),
+                };
+                let target = match target {
+                    Some(target) => {
+                        if self.kernels[target].merge(&kernel) && !queued[target] {
+                            queued[target] = true;
+                            queue.push_back(target);
+                        }
+                        target
+                    }
+                    None => {
+                        let target = self.kernels.len();
+                        by_core.entry(kernel.core.clone()).or_default().push(target);
+                        self.kernels.push(kernel);
+                        self.gotos.push(BTreeMap::new());
+                        queued.push(true);
+                        queue.push_back(target);
+                        target
+                    }
+                };
+                self.gotos[state].insert(symbol, target);
+            }
+        }
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.kernels.len()
+    }
+}
@@ -1,5 +1,4 @@
 use lr1_rs::*;
-use std::collections::HashSet;
 
 fn js_grammar() -> Grammar {
     grammar!(
@@ -81,7 +81,10 @@ fn process(text: &str) {
     let grammar = build_t2_grammar();
     let parser = Parser::new(grammar);
 
-    let tokens = token_stream(text).iter().collect::<Vec<_>>();
+    let tokens = token_stream(text)
+        .iter()
+        .filter(|token| token.typ != Type::LineTerminator)
+        .collect::<Vec<_>>();
     let preprocessed_tokens = tokens
         .iter()
         .filter(|token| token.typ != Type::EOF)
@@ -1,5 +1,4 @@
-use super::Production;
-use super::Symbol;
+use super::{ProdShortCut, Production, Symbol};
 use std::collections::{HashMap, HashSet};
 
 // 语法规则
@@ -21,6 +20,23 @@ impl Grammar {
         }
     }
 
+    /// 出现在左部的名字是非终结符, 其余的都是终结符
+    pub fn from_prods(start: &str, prods: Vec<ProdShortCut>) -> Self {
+        let mut grammar = Grammar::new(Symbol::NonTerminal(start.to_string()));
+        let non_terminals: HashSet<_> = prods.iter().map(|p| p.lhs.clone()).collect();
+        let symbol = |s: &String| {
+            if non_terminals.contains(s) {
+                Symbol::NonTerminal(s.clone())
+            } else {
+                Symbol::Terminal(s.clone())
+            }
+        };
+        for p in &prods {
+            grammar.add_production(symbol(&p.lhs), p.rhs.iter().map(symbol).collect());
+        }
+        grammar
+    }
+
     // 添加产生式
     pub fn add_production(&mut self, lhs: Symbol, rhs: Vec<Symbol>) {
         match &lhs {
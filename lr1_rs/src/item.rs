@@ -36,20 +36,26 @@ impl LR1Item {
                 let next_symbol = &item.production.rhs[item.dot_position];
                 if let Symbol::NonTerminal(_) = next_symbol {
                     let beta = &item.production.rhs[item.dot_position + 1..];
+                    // FIRST(beta lookahead)
                     let mut first_beta = HashSet::new();
-                    first_beta.insert(item.lookahead.clone());
+                    let mut beta_nullable = true;
 
                     for symbol in beta {
                         let first_symbol = &first[symbol];
-                        first_beta = first_symbol
-                            .iter()
-                            .filter(|&x| *x != Symbol::Epsilon)
-                            .cloned()
-                            .collect();
+                        first_beta.extend(
+                            first_symbol
+                                .iter()
+                                .filter(|&x| *x != Symbol::Epsilon)
+                                .cloned(),
+                        );
                         if !first_symbol.contains(&Symbol::Epsilon) {
+                            beta_nullable = false;
                             break;
                         }
                     }
+                    if beta_nullable {
+                        first_beta.insert(item.lookahead.clone());
+                    }
 
                     for production in &grammar.productions {
                         if &production.lhs == next_symbol {
@@ -72,3 +78,24 @@ impl LR1Item {
         closure
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+
+    /// The lookaheads of `A -> a` in `S -> A B c` are all of FIRST(B c): `b`, and `c` as `B`
+    /// can be empty
+    #[test]
+    fn test_closure_lookaheads() {
+        let parser = Parser::new(grammar!(
+            "S",
+            prod!("S" => "A", "B", "c"),
+            prod!("A" => "a"),
+            prod!("B" => "b"),
+            prod!("B" => )
+        ));
+        assert!(parser.parse(vec![Term!("a"), Term!("b"), Term!("c")]).is_ok());
+        assert!(parser.parse(vec![Term!("a"), Term!("c")]).is_ok());
+        assert!(parser.parse(vec![Term!("a"), Term!("b")]).is_err());
+    }
+}
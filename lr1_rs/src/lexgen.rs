@@ -0,0 +1,317 @@
+//! A lexer generator: tokens are declared as regular expressions, compiled into one minimized
+//! DFA (Thompson NFA, subset construction, then minimization) and scanned by longest match.
+
+mod dfa;
+mod nfa;
+mod regex;
+
+use super::{Grammar, Symbol, Symbols};
+use dfa::Dfa;
+use nfa::Nfa;
+pub use regex::{CharSet, Regex};
+use std::collections::{BTreeSet, HashMap};
+
+/// A token of a lexer specification
+#[derive(Debug, Clone)]
+pub struct TokenSpec {
+    /// The terminal it produces
+    pub name: String,
+    pub pattern: String,
+    /// Decides between tokens matching the same longest text, the highest one wins (then the
+    /// one declared first)
+    pub priority: i32,
+    /// Matched but never handed to the parser: blanks, comments...
+    pub skip: bool,
+}
+
+/// The tokens of a language
+#[derive(Debug, Clone, Default)]
+pub struct LexerSpec {
+    pub tokens: Vec<TokenSpec>,
+    /// Words that are their own terminal instead of the token matching them, e.g. `if` is not
+    /// an `identifier`
+    pub keywords: HashMap<String, Vec<String>>,
+}
+
+/// A scanned token
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Lexeme {
+    /// Terminal name: the token name, or the keyword itself
+    pub name: String,
+    pub text: String,
+    /// Byte range in the input
+    pub start: usize,
+    pub end: usize,
+    /// Whether a skipped token containing a line break came before it
+    pub newline_before: bool,
+}
+
+/// Input no token matches, one character is skipped and scanning goes on
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScanError {
+    pub pos: usize,
+    pub found: char,
+}
+
+/// The scanner generated from a `LexerSpec`
+#[derive(Debug, Clone)]
+pub struct Scanner {
+    spec: LexerSpec,
+    dfa: Dfa,
+}
+
+impl LexerSpec {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn token(self, name: &str, pattern: &str) -> Self {
+        self.token_with_priority(name, pattern, 0)
+    }
+
+    pub fn token_with_priority(mut self, name: &str, pattern: &str, priority: i32) -> Self {
+        self.tokens.push(TokenSpec {
+            name: name.to_string(),
+            pattern: pattern.to_string(),
+            priority,
+            skip: false,
+        });
+        self
+    }
+
+    pub fn skip(mut self, name: &str, pattern: &str) -> Self {
+        self.tokens.push(TokenSpec {
+            name: name.to_string(),
+            pattern: pattern.to_string(),
+            priority: 0,
+            skip: true,
+        });
+        self
+    }
+
+    /// Words matched by `token` that are terminals of their own
+    pub fn keywords(mut self, token: &str, words: &[&str]) -> Self {
+        self.keywords
+            .entry(token.to_string())
+            .or_default()
+            .extend(words.iter().map(|w| w.to_string()));
+        self
+    }
+
+    /// The terminals this specification can produce
+    pub fn terminals(&self) -> BTreeSet<String> {
+        self.tokens
+            .iter()
+            .filter(|t| !t.skip)
+            .map(|t| t.name.clone())
+            .chain(self.keywords.values().flatten().cloned())
+            .collect()
+    }
+
+    /// The terminals of `grammar` that no token or keyword produces
+    pub fn check_grammar(&self, grammar: &Grammar) -> Result<(), Vec<String>> {
+        let terminals = self.terminals();
+        let mut missing: Vec<String> = grammar
+            .terminals
+            .iter()
+            .filter_map(|t| match t {
+                Symbol::Terminal(name) if !terminals.contains(name) => Some(name.clone()),
+                _ => None,
+            })
+            .collect();
+        if missing.is_empty() {
+            return Ok(());
+        }
+        missing.sort();
+        Err(missing)
+    }
+
+    pub fn build(&self) -> Result<Scanner, String> {
+        let mut regexes = Vec::new();
+        for token in &self.tokens {
+            let regex = Regex::parse(&token.pattern)
+                .map_err(|e| format!("token '{}': {}", token.name, e))?;
+            if regex.nullable() {
+                return Err(format!("token '{}' matches the empty string", token.name));
+            }
+            regexes.push(regex);
+        }
+        if let Some(token) = self
+            .keywords
+            .keys()
+            .find(|&k| !self.tokens.iter().any(|t| &t.name == k && !t.skip))
+        {
+            return Err(format!("keywords for an unknown token '{}'", token));
+        }
+
+        let priorities: Vec<i32> = self.tokens.iter().map(|t| t.priority).collect();
+        let dfa = Dfa::new(&Nfa::new(&regexes), &priorities).minimize();
+        Ok(Scanner {
+            spec: self.clone(),
+            dfa,
+        })
+    }
+}
+
+impl Scanner {
+    /// Split `input` into lexemes, always taking the longest match
+    pub fn scan(&self, input: &str) -> (Vec<Lexeme>, Vec<ScanError>) {
+        let mut lexemes = Vec::new();
+        let mut errors = Vec::new();
+        let mut newline = false;
+        let mut pos = 0;
+
+        while pos < input.len() {
+            let mut state = self.dfa.start;
+            let mut longest = None;
+            for (i, c) in input[pos..].char_indices() {
+                match self.dfa.step(state, c) {
+                    Some(next) => state = next,
+                    None => break,
+                }
+                if let Some(token) = self.dfa.accept[state] {
+                    longest = Some((pos + i + c.len_utf8(), token));
+                }
+            }
+
+            let Some((end, token)) = longest else {
+                let found = input[pos..].chars().next().unwrap();
+                errors.push(ScanError { pos, found });
+                pos += found.len_utf8();
+                continue;
+            };
+            let spec = &self.spec.tokens[token];
+            let text = &input[pos..end];
+            if spec.skip {
+                newline |= text.contains(['\n', '\r', '\u{2028}', '\u{2029}']);
+            } else {
+                let is_keyword = self
+                    .spec
+                    .keywords
+                    .get(&spec.name)
+                    .is_some_and(|words| words.iter().any(|w| w == text));
+                lexemes.push(Lexeme {
+                    name: if is_keyword { text } else { &spec.name }.to_string(),
+                    text: text.to_string(),
+                    start: pos,
+                    end,
+                    newline_before: std::mem::take(&mut newline),
+                });
+            }
+            pos = end;
+        }
+        (lexemes, errors)
+    }
+
+    /// The scanned terminals, ready to be parsed
+    pub fn symbols(&self, input: &str) -> (Symbols, Vec<ScanError>) {
+        let (lexemes, errors) = self.scan(input);
+        let symbols = lexemes
+            .into_iter()
+            .map(|l| (Symbol::Terminal(l.name), l.newline_before))
+            .collect();
+        (Symbols::new(symbols), errors)
+    }
+
+    /// The number of states of the minimized automaton
+    pub fn states(&self) -> usize {
+        self.dfa.transitions.len()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::*;
+
+    fn spec() -> LexerSpec {
+        LexerSpec::new()
+            .skip("blank", r"[ \t\r\n]+")
+            .skip("comment", r"//[^\n]*|/\*([^*]|\*+[^*/])*\*+/")
+            .token("identifier", r"[a-zA-Z_$][a-zA-Z0-9_$]*")
+            .token("number", r"\d+(\.\d+)?([eE][+-]?\d+)?|0[xX][0-9a-fA-F]+")
+            .token("str", r#""([^"\\\n]|\\.)*""#)
+            .token("=", "=")
+            .token("==", "==")
+            .token("===", "===")
+            .token(";", ";")
+            .token_with_priority("any", r"[a-z]{2,3}", -1)
+            .keywords("identifier", &["let", "if"])
+    }
+
+    fn names(scanner: &Scanner, input: &str) -> Vec<String> {
+        let (lexemes, errors) = scanner.scan(input);
+        assert!(errors.is_empty());
+        lexemes.into_iter().map(|l| l.name).collect()
+    }
+
+    #[test]
+    fn test_longest_match() {
+        let scanner = spec().build().unwrap();
+        assert_eq!(
+            names(
+                &scanner,
+                "let x === 0x1F; /* a * b */ if == 1.5e-3 \"a\\\"b\""
+            ),
+            vec![
+                "let",
+                "identifier",
+                "===",
+                "number",
+                ";",
+                "if",
+                "==",
+                "number",
+                "str"
+            ]
+        );
+        // `letter` is not the keyword `let`, `ab` matches `any` as well but with a lower priority
+        assert_eq!(
+            names(&scanner, "letter ab"),
+            vec!["identifier", "identifier"]
+        );
+    }
+
+    #[test]
+    fn test_spans_and_errors() {
+        let scanner = spec().build().unwrap();
+        let (lexemes, errors) = scanner.scan("a #\n// c\nb");
+        assert_eq!(errors, vec![ScanError { pos: 2, found: '#' }]);
+        assert_eq!((lexemes[1].start, lexemes[1].end), (9, 10));
+        assert!(!lexemes[0].newline_before);
+        assert!(lexemes[1].newline_before);
+    }
+
+    #[test]
+    fn test_minimized_dfa() {
+        // the textbook example: 4 states plus none for the dead state
+        let scanner = LexerSpec::new().token("t", "(a|b)*abb").build().unwrap();
+        assert_eq!(scanner.states(), 4);
+        assert_eq!(names(&scanner, "abbaabb"), vec!["t"]);
+    }
+
+    #[test]
+    fn test_bad_specs() {
+        assert!(LexerSpec::new().token("t", "(a").build().is_err());
+        assert!(LexerSpec::new().token("t", "a*").build().is_err());
+        assert!(LexerSpec::new().token("t", "[z-a]").build().is_err());
+        assert!(LexerSpec::new().keywords("t", &["x"]).build().is_err());
+    }
+
+    #[test]
+    fn test_check_grammar() {
+        let grammar = grammar!(
+            "S",
+            prod!("S" => "let", "identifier", "=", "number", ";"),
+            prod!("S" => "const", "identifier", "=", "number", ";")
+        );
+        assert_eq!(
+            spec().check_grammar(&grammar),
+            Err(vec!["const".to_string()])
+        );
+
+        let scanner = spec().keywords("identifier", &["const"]).build().unwrap();
+        let (mut symbols, _) = scanner.symbols("const a = 1;");
+        assert!(Parser::new(grammar).parse_source(&mut symbols).is_ok());
+    }
+}
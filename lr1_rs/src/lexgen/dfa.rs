@@ -0,0 +1,132 @@
+//! Subset construction and minimization of the deterministic automaton used for scanning
+
+use super::nfa::Nfa;
+use std::collections::{BTreeSet, HashMap};
+
+/// A deterministic automaton over character classes: the code points are split into intervals
+/// that no token distinguishes, class `i` being `bounds[i]..bounds[i + 1]`
+#[derive(Debug, Clone)]
+pub struct Dfa {
+    pub bounds: Vec<u32>,
+    /// `transitions[state][class]`, `None` when there is no way to go on
+    pub transitions: Vec<Vec<Option<usize>>>,
+    /// The token accepted in each state
+    pub accept: Vec<Option<usize>>,
+    pub start: usize,
+}
+
+impl Dfa {
+    /// Subset construction. When a state accepts several tokens, the one with the highest
+    /// priority wins, then the one declared first.
+    pub fn new(nfa: &Nfa, priorities: &[i32]) -> Dfa {
+        let mut bounds: BTreeSet<u32> = BTreeSet::from([0, char::MAX as u32 + 1]);
+        for state in &nfa.states {
+            for (set, _) in &state.transitions {
+                for &(lo, hi) in &set.ranges {
+                    bounds.insert(lo);
+                    bounds.insert(hi + 1);
+                }
+            }
+        }
+        let bounds: Vec<u32> = bounds.into_iter().collect();
+        let classes = bounds.len() - 1;
+
+        let mut dfa = Dfa {
+            bounds,
+            transitions: Vec::new(),
+            accept: Vec::new(),
+            start: 0,
+        };
+        let mut sets = vec![nfa.closure([nfa.start])];
+        let mut index: HashMap<BTreeSet<usize>, usize> = HashMap::from([(sets[0].clone(), 0)]);
+
+        let mut i = 0;
+        while i < sets.len() {
+            let set = sets[i].clone();
+            let accept = set
+                .iter()
+                .filter_map(|&s| nfa.states[s].accept)
+                .min_by_key(|&token| (-priorities[token], token));
+            let mut transitions = Vec::with_capacity(classes);
+            for class in 0..classes {
+                // every character of a class behaves the same, so test its first one
+                let c = dfa.bounds[class];
+                let targets = nfa.closure(set.iter().flat_map(|&s| {
+                    nfa.states[s]
+                        .transitions
+                        .iter()
+                        .filter(move |(chars, _)| chars.contains(c))
+                        .map(|&(_, target)| target)
+                }));
+                if targets.is_empty() {
+                    transitions.push(None);
+                    continue;
+                }
+                let next = *index.entry(targets.clone()).or_insert_with(|| {
+                    sets.push(targets);
+                    sets.len() - 1
+                });
+                transitions.push(Some(next));
+            }
+            dfa.transitions.push(transitions);
+            dfa.accept.push(accept);
+            i += 1;
+        }
+        dfa
+    }
+
+    /// Merge equivalent states by refining the partition by accepted token until the states of
+    /// every block go to the same blocks
+    pub fn minimize(&self) -> Dfa {
+        let mut block: Vec<usize> = Vec::new();
+        let mut initial = HashMap::new();
+        for accept in &self.accept {
+            let next = initial.len();
+            block.push(*initial.entry(*accept).or_insert(next));
+        }
+        let mut count = initial.len();
+
+        loop {
+            let mut signatures = HashMap::new();
+            let refined: Vec<usize> = (0..self.transitions.len())
+                .map(|state| {
+                    let signature: (usize, Vec<Option<usize>>) = (
+                        block[state],
+                        self.transitions[state]
+                            .iter()
+                            .map(|t| t.map(|t| block[t]))
+                            .collect(),
+                    );
+                    let next = signatures.len();
+                    *signatures.entry(signature).or_insert(next)
+                })
+                .collect();
+            block = refined;
+            if signatures.len() == count {
+                break;
+            }
+            count = signatures.len();
+        }
+
+        let mut transitions = vec![Vec::new(); count];
+        let mut accept = vec![None; count];
+        for state in 0..self.transitions.len() {
+            transitions[block[state]] = self.transitions[state]
+                .iter()
+                .map(|t| t.map(|t| block[t]))
+                .collect();
+            accept[block[state]] = self.accept[state];
+        }
+        Dfa {
+            bounds: self.bounds.clone(),
+            transitions,
+            accept,
+            start: block[self.start],
+        }
+    }
+
+    pub fn step(&self, state: usize, c: char) -> Option<usize> {
+        let class = self.bounds.partition_point(|&b| b <= c as u32) - 1;
+        self.transitions[state][class]
+    }
+}
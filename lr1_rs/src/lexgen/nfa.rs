@@ -0,0 +1,93 @@
+//! Thompson construction of a nondeterministic automaton from the token regexes
+
+use super::regex::{CharSet, Regex};
+use std::collections::BTreeSet;
+
+#[derive(Debug, Clone, Default)]
+pub struct NfaState {
+    pub epsilon: Vec<usize>,
+    pub transitions: Vec<(CharSet, usize)>,
+    /// The token accepted in this state, as an index into the specification
+    pub accept: Option<usize>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Nfa {
+    pub states: Vec<NfaState>,
+    pub start: usize,
+}
+
+impl Nfa {
+    /// One automaton for all tokens: the start state has an ε-edge to the fragment of each
+    /// regex, whose final state accepts the regex's index
+    pub fn new(regexes: &[Regex]) -> Nfa {
+        let mut nfa = Nfa {
+            states: vec![NfaState::default()],
+            start: 0,
+        };
+        for (token, regex) in regexes.iter().enumerate() {
+            let (start, end) = nfa.fragment(regex);
+            nfa.states[0].epsilon.push(start);
+            nfa.states[end].accept = Some(token);
+        }
+        nfa
+    }
+
+    fn add_state(&mut self) -> usize {
+        self.states.push(NfaState::default());
+        self.states.len() - 1
+    }
+
+    /// Build the states for `regex`, returning its start and final states
+    fn fragment(&mut self, regex: &Regex) -> (usize, usize) {
+        let start = self.add_state();
+        let end = match regex {
+            Regex::Empty => start,
+            Regex::Set(set) => {
+                let end = self.add_state();
+                self.states[start].transitions.push((set.clone(), end));
+                end
+            }
+            Regex::Concat(items) => items.iter().fold(start, |last, item| {
+                let (s, e) = self.fragment(item);
+                self.states[last].epsilon.push(s);
+                e
+            }),
+            Regex::Alt(items) => {
+                let end = self.add_state();
+                for item in items {
+                    let (s, e) = self.fragment(item);
+                    self.states[start].epsilon.push(s);
+                    self.states[e].epsilon.push(end);
+                }
+                end
+            }
+            Regex::Star(inner) | Regex::Plus(inner) | Regex::Opt(inner) => {
+                let (s, e) = self.fragment(inner);
+                let end = self.add_state();
+                self.states[start].epsilon.push(s);
+                self.states[e].epsilon.push(end);
+                if !matches!(regex, Regex::Plus(_)) {
+                    self.states[start].epsilon.push(end);
+                }
+                if !matches!(regex, Regex::Opt(_)) {
+                    self.states[e].epsilon.push(s);
+                }
+                end
+            }
+        };
+        (start, end)
+    }
+
+    /// The states reachable from `states` through ε-edges
+    pub fn closure(&self, states: impl IntoIterator<Item = usize>) -> BTreeSet<usize> {
+        let mut closure = BTreeSet::new();
+        let mut stack: Vec<usize> = states.into_iter().collect();
+        while let Some(state) = stack.pop() {
+            if closure.insert(state) {
+                stack.extend(&self.states[state].epsilon);
+            }
+        }
+        closure
+    }
+}
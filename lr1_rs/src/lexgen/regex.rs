@@ -0,0 +1,345 @@
+//! Regular expressions of token specifications and the sets of characters they match
+
+/// A set of characters as sorted, disjoint, non-adjacent inclusive ranges of code points
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct CharSet {
+    pub ranges: Vec<(u32, u32)>,
+}
+
+const MAX_CHAR: u32 = char::MAX as u32;
+
+impl CharSet {
+    pub fn from_ranges(mut ranges: Vec<(u32, u32)>) -> Self {
+        ranges.sort();
+        let mut merged: Vec<(u32, u32)> = Vec::new();
+        for (lo, hi) in ranges {
+            match merged.last_mut() {
+                Some(last) if lo <= last.1.saturating_add(1) => last.1 = last.1.max(hi),
+                _ => merged.push((lo, hi)),
+            }
+        }
+        CharSet { ranges: merged }
+    }
+
+    pub fn char(c: char) -> Self {
+        CharSet::from_ranges(vec![(c as u32, c as u32)])
+    }
+
+    pub fn union(&self, other: &CharSet) -> Self {
+        CharSet::from_ranges(self.ranges.iter().chain(&other.ranges).cloned().collect())
+    }
+
+    pub fn negate(&self) -> Self {
+        let mut ranges = Vec::new();
+        let mut next = 0;
+        for &(lo, hi) in &self.ranges {
+            if lo > next {
+                ranges.push((next, lo - 1));
+            }
+            next = hi + 1;
+        }
+        if next <= MAX_CHAR {
+            ranges.push((next, MAX_CHAR));
+        }
+        CharSet { ranges }
+    }
+
+    pub fn contains(&self, c: u32) -> bool {
+        self.ranges.iter().any(|&(lo, hi)| lo <= c && c <= hi)
+    }
+
+    fn digit() -> Self {
+        CharSet::from_ranges(vec![('0' as u32, '9' as u32)])
+    }
+
+    fn word() -> Self {
+        CharSet::from_ranges(vec![
+            ('0' as u32, '9' as u32),
+            ('A' as u32, 'Z' as u32),
+            ('_' as u32, '_' as u32),
+            ('a' as u32, 'z' as u32),
+        ])
+    }
+
+    fn space() -> Self {
+        CharSet::from_ranges(
+            [
+                ' ', '\t', '\n', '\r', '\u{b}', '\u{c}', '\u{a0}', '\u{2028}', '\u{2029}',
+                '\u{feff}',
+            ]
+            .iter()
+            .map(|&c| (c as u32, c as u32))
+            .collect(),
+        )
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Regex {
+    /// Matches the empty string
+    Empty,
+    Set(CharSet),
+    Concat(Vec<Regex>),
+    Alt(Vec<Regex>),
+    Star(Box<Regex>),
+    Plus(Box<Regex>),
+    Opt(Box<Regex>),
+}
+
+impl Regex {
+    /// Parse the supported subset of the usual syntax: literals, `.`, escapes (`\d \w \s` and
+    /// their negations, `\n \t \xHH \uHHHH \u{H...}`, ...), classes `[a-z_]` and `[^...]`,
+    /// groups, `|`, `*`, `+`, `?` and counted repetition `{n}`, `{n,}`, `{n,m}`.
+    pub fn parse(pattern: &str) -> Result<Regex, String> {
+        let mut parser = RegexParser {
+            chars: pattern.chars().collect(),
+            pos: 0,
+        };
+        let regex = parser.alternation()?;
+        match parser.peek() {
+            None => Ok(regex),
+            Some(c) => Err(parser.error(&format!("unexpected '{}'", c))),
+        }
+    }
+
+    pub fn nullable(&self) -> bool {
+        match self {
+            Regex::Empty | Regex::Star(_) | Regex::Opt(_) => true,
+            Regex::Set(_) => false,
+            Regex::Concat(items) => items.iter().all(Regex::nullable),
+            Regex::Alt(items) => items.iter().any(Regex::nullable),
+            Regex::Plus(inner) => inner.nullable(),
+        }
+    }
+}
+
+struct RegexParser {
+    chars: Vec<char>,
+    pos: usize,
+}
+
+impl RegexParser {
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).cloned()
+    }
+
+    fn next(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.pos += 1;
+        Some(c)
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        if self.peek() == Some(c) {
+            self.pos += 1;
+            return true;
+        }
+        false
+    }
+
+    fn error(&self, msg: &str) -> String {
+        format!("{} at position {}", msg, self.pos)
+    }
+
+    fn alternation(&mut self) -> Result<Regex, String> {
+        let mut alternatives = vec![self.concatenation()?];
+        while self.eat('|') {
+            alternatives.push(self.concatenation()?);
+        }
+        Ok(match alternatives.len() {
+            1 => alternatives.pop().unwrap(),
+            _ => Regex::Alt(alternatives),
+        })
+    }
+
+    fn concatenation(&mut self) -> Result<Regex, String> {
+        let mut items = Vec::new();
+        while !matches!(self.peek(), None | Some('|') | Some(')')) {
+            items.push(self.repetition()?);
+        }
+        Ok(match items.len() {
+            0 => Regex::Empty,
+            1 => items.pop().unwrap(),
+            _ => Regex::Concat(items),
+        })
+    }
+
+    fn repetition(&mut self) -> Result<Regex, String> {
+        let mut regex = self.atom()?;
+        loop {
+            regex = match self.peek() {
+                Some('*') => Regex::Star(Box::new(regex)),
+                Some('+') => Regex::Plus(Box::new(regex)),
+                Some('?') => Regex::Opt(Box::new(regex)),
+                Some('{') => {
+                    self.next();
+                    let (min, max) = self.counts()?;
+                    repeat(regex, min, max)
+                }
+                _ => return Ok(regex),
+            };
+            // the operator, or the '}' of a counted repetition
+            self.next();
+        }
+    }
+
+    /// `n}`, `n,}` or `n,m}` after a '{', the '}' is left for the caller
+    fn counts(&mut self) -> Result<(usize, Option<usize>), String> {
+        let min = self.number()?;
+        let max = if self.eat(',') {
+            match self.peek() {
+                Some('}') => None,
+                _ => Some(self.number()?),
+            }
+        } else {
+            Some(min)
+        };
+        if self.peek() != Some('}') {
+            return Err(self.error("expected '}'"));
+        }
+        if max.is_some_and(|max| max < min) {
+            return Err(self.error("repetition range out of order"));
+        }
+        Ok((min, max))
+    }
+
+    fn number(&mut self) -> Result<usize, String> {
+        let start = self.pos;
+        while self.peek().is_some_and(|c| c.is_ascii_digit()) {
+            self.pos += 1;
+        }
+        let digits: String = self.chars[start..self.pos].iter().collect();
+        digits.parse().map_err(|_| self.error("expected a number"))
+    }
+
+    fn atom(&mut self) -> Result<Regex, String> {
+        match self.next() {
+            None => Err(self.error("unexpected end of pattern")),
+            Some('(') => {
+                let regex = self.alternation()?;
+                if !self.eat(')') {
+                    return Err(self.error("expected ')'"));
+                }
+                Ok(regex)
+            }
+            Some('[') => Ok(Regex::Set(self.class()?)),
+            Some('.') => Ok(Regex::Set(
+                CharSet::from_ranges(vec![
+                    ('\n' as u32, '\n' as u32),
+                    ('\r' as u32, '\r' as u32),
+                    (0x2028, 0x2029),
+                ])
+                .negate(),
+            )),
+            Some('\\') => Ok(Regex::Set(self.escape()?)),
+            Some(c @ ('*' | '+' | '?' | '{' | ')' | '|')) => {
+                self.pos -= 1;
+                Err(self.error(&format!("unexpected '{}'", c)))
+            }
+            Some(c) => Ok(Regex::Set(CharSet::char(c))),
+        }
+    }
+
+    /// The rest of a class after '['
+    fn class(&mut self) -> Result<CharSet, String> {
+        let negated = self.eat('^');
+        let mut set = CharSet::default();
+        let mut first = true;
+        loop {
+            let lo = match self.next() {
+                None => return Err(self.error("unterminated character class")),
+                Some(']') if !first => break,
+                Some('\\') => self.escape()?,
+                Some(c) => CharSet::char(c),
+            };
+            first = false;
+            // a range, unless the '-' is the last character of the class
+            if self.peek() == Some('-') && self.chars.get(self.pos + 1) != Some(&']') {
+                self.next();
+                let hi = match self.next() {
+                    None => return Err(self.error("unterminated character class")),
+                    Some('\\') => self.escape()?,
+                    Some(c) => CharSet::char(c),
+                };
+                match (&lo.ranges[..], &hi.ranges[..]) {
+                    ([(lo, lo2)], [(hi, hi2)]) if lo == lo2 && hi == hi2 && lo <= hi => {
+                        set = set.union(&CharSet::from_ranges(vec![(*lo, *hi)]));
+                    }
+                    _ => return Err(self.error("invalid range in character class")),
+                }
+            } else {
+                set = set.union(&lo);
+            }
+        }
+        Ok(if negated { set.negate() } else { set })
+    }
+
+    /// The rest of an escape sequence after '\'
+    fn escape(&mut self) -> Result<CharSet, String> {
+        let c = self
+            .next()
+            .ok_or_else(|| self.error("unexpected end of pattern"))?;
+        Ok(match c {
+            'd' => CharSet::digit(),
+            'D' => CharSet::digit().negate(),
+            'w' => CharSet::word(),
+            'W' => CharSet::word().negate(),
+            's' => CharSet::space(),
+            'S' => CharSet::space().negate(),
+            'n' => CharSet::char('\n'),
+            'r' => CharSet::char('\r'),
+            't' => CharSet::char('\t'),
+            'f' => CharSet::char('\u{c}'),
+            'v' => CharSet::char('\u{b}'),
+            '0' => CharSet::char('\0'),
+            'x' => self.hex(2)?,
+            'u' if self.eat('{') => {
+                let start = self.pos;
+                while self.peek().is_some_and(|c| c != '}') {
+                    self.pos += 1;
+                }
+                let hex: String = self.chars[start..self.pos].iter().collect();
+                if !self.eat('}') {
+                    return Err(self.error("expected '}'"));
+                }
+                self.code_point(&hex)?
+            }
+            'u' => self.hex(4)?,
+            c => CharSet::char(c),
+        })
+    }
+
+    fn hex(&mut self, len: usize) -> Result<CharSet, String> {
+        let end = (self.pos + len).min(self.chars.len());
+        let hex: String = self.chars[self.pos..end].iter().collect();
+        self.pos = end;
+        if hex.len() != len {
+            return Err(self.error("invalid escape"));
+        }
+        self.code_point(&hex)
+    }
+
+    fn code_point(&self, hex: &str) -> Result<CharSet, String> {
+        u32::from_str_radix(hex, 16)
+            .ok()
+            .filter(|&c| c <= MAX_CHAR)
+            .map(|c| CharSet::from_ranges(vec![(c, c)]))
+            .ok_or_else(|| self.error("invalid escape"))
+    }
+}
+
+/// `regex{min,max}`, `max` is unbounded if `None`
+fn repeat(regex: Regex, min: usize, max: Option<usize>) -> Regex {
+    let mut items = vec![regex.clone(); min];
+    match max {
+        None => items.push(Regex::Star(Box::new(regex))),
+        Some(max) => {
+            items.extend((min..max).map(|_| Regex::Opt(Box::new(regex.clone()))));
+        }
+    }
+    match items.len() {
+        0 => Regex::Empty,
+        1 => items.pop().unwrap(),
+        _ => Regex::Concat(items),
+    }
+}
@@ -1,12 +1,22 @@
+mod asi;
+mod automaton;
 mod grammar;
 mod item;
+mod lexgen;
+mod params;
 mod parser;
 mod prod;
+mod source;
 mod symbol;
+pub use asi::*;
+use automaton::*;
 pub use grammar::*;
 pub use item::*;
+pub use lexgen::*;
+pub use params::*;
 pub use parser::*;
 pub use prod::*;
+pub use source::*;
 pub use symbol::*;
 
 pub static FAKE_START: &str = "S'";
@@ -40,35 +50,7 @@ pub mod macros {
     #[macro_export]
     macro_rules! grammar {
         ($start:expr, $($prodshortcut:expr),*) => {
-            {
-                let mut grammar = Grammar::new(Symbol::NonTerminal($start.to_string()));
-
-                let prods = vec![$($prodshortcut),*];
-                let mut non_terminals = std::collections::HashSet::new();
-                for p in &prods {
-                    non_terminals.insert(p.lhs.clone());
-                }
-                let mut terminals = std::collections::HashSet::new();
-                for p in &prods {
-                    for s in &p.rhs {
-                        if !non_terminals.contains(s) {
-                            terminals.insert(s.clone());
-                        }
-                    }
-                }
-
-                for p in prods {
-                    grammar.add_production(NonTerm!(p.lhs), p.rhs.iter().map(|x| {
-                        if non_terminals.contains(x) {
-                            NonTerm!(x)
-                        } else {
-                            Term!(x)
-                        }
-                    }).collect());
-                }
-
-                grammar
-            }
+            Grammar::from_prods($start, vec![$($prodshortcut),*])
         };
     }
 }
@@ -0,0 +1,136 @@
+//! 带参数的产生式, 写法同 ECMAScript 规范: 左部的 `name[A, B]` 声明参数, 右部的 `name[+A, ?B, ~C]`
+//! 给参数赋值 (`?` 沿用左部的值, 没有提到的参数是关闭的), 单独一项 `[+A]` / `[~A]` 表示这条产生式只在
+//! 参数打开 / 关闭时存在. 展开后的非终结符名字带上打开的参数, 如 `expression[In]`, 都关闭时就是原名.
+
+use super::ProdShortCut;
+use std::collections::{HashMap, HashSet, VecDeque};
+
+enum Item<'a> {
+    Guard(bool, &'a str),
+    Symbol(&'a str, Vec<(char, &'a str)>),
+}
+
+fn parse(s: &str) -> Item<'_> {
+    if s.len() > 3 && (s.starts_with("[+") || s.starts_with("[~")) && s.ends_with(']') {
+        return Item::Guard(s.starts_with("[+"), s[2..s.len() - 1].trim());
+    }
+    match s.find('[') {
+        Some(i) if i > 0 && s.ends_with(']') => {
+            let args = s[i + 1..s.len() - 1]
+                .split(',')
+                .map(str::trim)
+                .filter(|a| !a.is_empty())
+                .map(|a| match a.chars().next() {
+                    Some(c @ ('?' | '+' | '~')) => (c, a[1..].trim()),
+                    _ => ('+', a),
+                })
+                .collect();
+            Item::Symbol(&s[..i], args)
+        }
+        _ => Item::Symbol(s, Vec::new()),
+    }
+}
+
+/// 从 `start` 出发, 把用到的参数组合展开成普通的产生式
+pub fn expand_params(start: &str, prods: Vec<ProdShortCut>) -> Vec<ProdShortCut> {
+    // 非终结符 -> (声明的参数, 产生式)
+    let mut rules: HashMap<&str, (Vec<&str>, Vec<Vec<Item>>)> = HashMap::new();
+    for p in &prods {
+        let Item::Symbol(name, params) = parse(&p.lhs) else {
+            panic!("invalid left hand side {}", p.lhs);
+        };
+        let rule = rules.entry(name).or_default();
+        if rule.0.is_empty() {
+            rule.0 = params.iter().map(|&(_, param)| param).collect();
+        }
+        rule.1.push(p.rhs.iter().map(|s| parse(s)).collect());
+    }
+
+    let instance = |name: &str, on: &[&str]| {
+        if on.is_empty() {
+            name.to_string()
+        } else {
+            format!("{}[{}]", name, on.join(","))
+        }
+    };
+
+    let mut result = Vec::new();
+    let mut seen = HashSet::new();
+    let mut queue = VecDeque::from([(start, Vec::new())]);
+    while let Some((name, on)) = queue.pop_front() {
+        let lhs = instance(name, &on);
+        if !seen.insert(lhs.clone()) {
+            continue;
+        }
+        for rhs in &rules[name].1 {
+            let allowed = rhs.iter().all(|item| match item {
+                Item::Guard(value, param) => on.contains(param) == *value,
+                _ => true,
+            });
+            if !allowed {
+                continue;
+            }
+            let mut symbols = Vec::new();
+            for item in rhs {
+                let Item::Symbol(symbol, args) = item else {
+                    continue;
+                };
+                let Some((params, _)) = rules.get(symbol) else {
+                    symbols.push(symbol.to_string());
+                    continue;
+                };
+                let callee: Vec<&str> = params
+                    .iter()
+                    .copied()
+                    .filter(|param| {
+                        args.iter().any(|&(c, arg)| {
+                            arg == *param && (c == '+' || (c == '?' && on.contains(param)))
+                        })
+                    })
+                    .collect();
+                symbols.push(instance(symbol, &callee));
+                queue.push_back((symbol, callee));
+            }
+            result.push(ProdShortCut {
+                lhs: lhs.clone(),
+                rhs: symbols,
+            });
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+
+    #[test]
+    fn test_expand_params() {
+        let prods = expand_params(
+            "s",
+            vec![
+                prod!("s" => "e[~In]", "e[+In]"),
+                prod!("e[In]" => "e[?In]", "+", "t"),
+                prod!("e[In]" => "[+In]", "e", "in", "t"),
+                prod!("e[In]" => "t"),
+                prod!("t" => "[", "e", "]"),
+            ],
+        );
+        let prods: Vec<String> = prods
+            .iter()
+            .map(|p| format!("{} -> {}", p.lhs, p.rhs.join(" ")))
+            .collect();
+        assert_eq!(
+            prods,
+            [
+                "s -> e e[In]",
+                "e -> e + t",
+                "e -> t",
+                "e[In] -> e[In] + t",
+                "e[In] -> e in t",
+                "e[In] -> t",
+                "t -> [ e ]",
+            ]
+        );
+    }
+}
@@ -1,13 +1,15 @@
 use super::*;
-use std::collections::{BTreeSet, HashMap, HashSet};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
 use std::fmt;
-use serde::{Serialize, Deserialize};
 
 // 语法分析器
 pub struct Parser {
     pub grammar: Grammar,
     pub action_table: HashMap<(usize, Symbol), Action>,
     pub goto_table: HashMap<(usize, Symbol), usize>,
+    pub asi: Option<Asi>,
+    pub conflicts: Vec<Conflict>,
 }
 
 #[derive(Debug, Clone)]
@@ -18,12 +20,28 @@ pub enum Action {
     Error,
 }
 
-#[derive(Debug, Clone)]
-struct State {
-    pub items: BTreeSet<LR1Item>,
-    pub index: usize,
+/// Why parsing failed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseError {
+    /// `symbol`, the `pos`-th terminal of the input (or the end marker after it), has no action
+    /// in `state`, which expects one of `expected`
+    Syntax {
+        pos: usize,
+        state: usize,
+        symbol: Symbol,
+        expected: Vec<Symbol>,
+    },
+    InvalidTransition,
 }
 
+/// 构造分析表时同一格里有多个动作, 表中保留 `chosen`
+#[derive(Debug, Clone)]
+pub struct Conflict {
+    pub state: usize,
+    pub symbol: Symbol,
+    pub chosen: Action,
+    pub rejected: Action,
+}
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub enum ASTNode {
@@ -42,6 +60,38 @@ impl fmt::Display for Action {
     }
 }
 
+impl fmt::Display for Conflict {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "conflict in state {} on {}: {} over {}",
+            self.state, self.symbol, self.chosen, self.rejected
+        )
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseError::Syntax {
+                pos,
+                state,
+                symbol,
+                expected,
+            } => {
+                write!(
+                    f,
+                    "Syntax error at position {}, state: {:?}, symbol: {:?}",
+                    pos, state, symbol
+                )?;
+                let expected: Vec<_> = expected.iter().map(|s| s.to_string()).collect();
+                write!(f, ", expected one of: {}", expected.join(" "))
+            }
+            ParseError::InvalidTransition => write!(f, "Invalid state transition"),
+        }
+    }
+}
+
 impl fmt::Display for ASTNode {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
@@ -66,6 +116,8 @@ impl Parser {
             grammar,
             action_table: HashMap::new(),
             goto_table: HashMap::new(),
+            asi: None,
+            conflicts: Vec::new(),
         };
 
         parser.build_parsing_table();
@@ -74,29 +126,75 @@ impl Parser {
 }
 
 impl Parser {
-    pub fn parse(&self, tokens: Vec<Symbol>) -> Result<ASTNode, String> {
+    pub fn parse(&self, tokens: Vec<Symbol>) -> Result<ASTNode, ParseError> {
+        self.parse_source(&mut tokens.into_iter())
+    }
+
+    /// Parse terminals pulled from `source` one at a time, the next one is only requested once
+    /// the parser is in the state that consumes it.
+    pub fn parse_source(&self, source: &mut impl TokenSource) -> Result<ASTNode, ParseError> {
         let mut pos = 0;
         let mut stack = vec![0];
+        let mut lookahead: Option<Symbol> = None;
+
+        // 自动插入分号: 被插入的分号之后的真实符号, 上一个移入的符号, 当前符号前是否换行
+        let mut held_back: Option<Symbol> = None;
+        let mut inserted_at = None;
+        let mut last_shifted: Option<Symbol> = None;
+        let mut newline = false;
 
         // 用于构建AST的节点栈
         let mut node_stack: Vec<ASTNode> = Vec::new();
 
         loop {
             let state = *stack.last().unwrap();
-            let symbol = if pos < tokens.len() {
-                &tokens[pos]
-            } else {
-                &Symbol::EndMarker
-            };
+            if lookahead.is_none() {
+                let accepts = |s: &Symbol| self.action_table.contains_key(&(state, s.clone()));
+                lookahead = Some(source.next_symbol(&accepts).unwrap_or(Symbol::EndMarker));
+                newline = source.newline_before();
+            }
+            let mut symbol = lookahead.clone().unwrap();
 
-            match self.action_table.get(&(state, symbol.clone())) {
+            if let Some(asi) = &self.asi {
+                if inserted_at != Some(pos) {
+                    let action = self.action_table.get(&(state, symbol.clone()));
+                    let restricted = newline
+                        && (last_shifted
+                            .as_ref()
+                            .is_some_and(|s| asi.restricted.contains(s))
+                            || asi.no_line_break_before.contains(&symbol));
+                    let terminator = self.inserted_terminator(asi, &stack, &symbol);
+                    let offending = action.is_none()
+                        && (newline
+                            || symbol == asi.close_brace
+                            || symbol == Symbol::EndMarker
+                            || terminator.is_some()
+                                && terminator == asi.do_while_terminator.as_ref());
+                    if (restricted || offending) && terminator.is_some() {
+                        held_back = lookahead.replace(asi.semicolon.clone());
+                        symbol = asi.semicolon.clone();
+                        inserted_at = Some(pos);
+                    }
+                }
+            }
+
+            let forbidden = self
+                .asi
+                .as_ref()
+                .is_some_and(|asi| newline && asi.never_after_line_break.contains(&symbol));
+            let action = self.action_table.get(&(state, symbol.clone()));
+            match action.filter(|_| !forbidden) {
                 Some(Action::Shift(next_state)) => {
                     stack.push(*next_state);
                     // 将终结符添加到节点栈
                     if let Symbol::Terminal(term) = symbol.clone() {
                         node_stack.push(ASTNode::Terminal(term));
                     }
-                    pos += 1;
+                    lookahead = held_back.take();
+                    if lookahead.is_none() {
+                        pos += 1;
+                    }
+                    last_shifted = Some(symbol);
                 }
                 Some(Action::Reduce(production)) => {
                     // 执行规约操作
@@ -124,7 +222,7 @@ impl Parser {
                             node_stack.push(ASTNode::NonTerminal(name.clone(), children));
                         }
                     } else {
-                        return Err("Invalid state transition".to_string());
+                        return Err(ParseError::InvalidTransition);
                     }
                 }
                 Some(Action::Accept) => {
@@ -134,122 +232,122 @@ impl Parser {
                         .unwrap_or(ASTNode::Terminal("empty".to_string())));
                 }
                 _ => {
-                    return Err(format!(
-                        "Syntax error at position {}, state: {:?}, symbol: {:?}",
-                        pos, state, symbol
-                    ));
+                    return Err(ParseError::Syntax {
+                        pos,
+                        state,
+                        symbol,
+                        expected: self.expected(state),
+                    });
                 }
             }
         }
     }
-    // 构建LR(1)项集族
-    fn build_canonical_collection(&mut self) -> Vec<State> {
-        let first = self.grammar.compute_first();
-        let mut states = Vec::new();
-        let mut state_map: HashMap<BTreeSet<LR1Item>, usize> = HashMap::new();
-
-        // 创建初始项集
-        let initial_production = Production {
-            lhs: Symbol::NonTerminal(FAKE_START.to_string()),
-            rhs: vec![self.grammar.start_symbol.clone()],
-        };
+    /// The terminals with an action in `state`
+    pub fn expected(&self, state: usize) -> Vec<Symbol> {
+        let mut expected: Vec<_> = self
+            .grammar
+            .terminals
+            .iter()
+            .chain([&Symbol::EndMarker])
+            .filter(|&s| self.action_table.contains_key(&(state, s.clone())))
+            .cloned()
+            .collect();
+        expected.sort();
+        expected
+    }
 
-        let initial_item = LR1Item {
-            production: initial_production,
-            dot_position: 0,
-            lookahead: Symbol::EndMarker,
-        };
+    // 构建Action表和Goto表, 冲突时移入优先于规约, 先出现的产生式优先于后出现的
+    fn build_parsing_table(&mut self) {
+        let automaton = Automaton::new(&self.grammar);
+        let accept = automaton.productions.len() - 1;
 
-        let initial_closure = initial_item.closure(&self.grammar, &first);
-        state_map.insert(initial_closure.clone(), 0);
-        states.push(State {
-            items: initial_closure,
-            index: 0,
-        });
-
-        let mut i = 0;
-        while i < states.len() {
-            let state = states[i].clone();
-
-            // 获取所有可能的下一个符号
-            let mut next_symbols = HashSet::new();
-            for item in &state.items {
-                if item.dot_position < item.production.rhs.len() {
-                    next_symbols.insert(item.production.rhs[item.dot_position].clone());
+        for state in 0..automaton.len() {
+            for (&symbol, &target) in &automaton.gotos[state] {
+                let symbol = automaton.symbols[symbol].clone();
+                if let Symbol::Terminal(_) = symbol {
+                    self.action_table
+                        .insert((state, symbol.clone()), Action::Shift(target));
                 }
+                self.goto_table.insert((state, symbol), target);
             }
 
-            // 对每个符号构建goto集合
-            for symbol in next_symbols {
-                let mut goto_set = BTreeSet::new();
-
-                // 构建goto项集
-                for item in &state.items {
-                    if item.dot_position < item.production.rhs.len()
-                        && item.production.rhs[item.dot_position] == symbol
-                    {
-                        let mut new_item = item.clone();
-                        new_item.dot_position += 1;
-                        let closure = new_item.closure(&self.grammar, &first);
-                        goto_set.extend(closure);
-                    }
+            let mut reductions = BTreeMap::new();
+            for ((p, dot), lookahead) in automaton.closure(state) {
+                if dot < automaton.productions[p].1.len() {
+                    continue;
                 }
-
-                if !goto_set.is_empty() {
-                    // 检查是否已存在相同的状态
-                    if let Some(&existing_index) = state_map.get(&goto_set) {
-                        self.goto_table.insert((i, symbol.clone()), existing_index);
-                    } else {
-                        let new_index = states.len();
-                        state_map.insert(goto_set.clone(), new_index);
-                        states.push(State {
-                            items: goto_set,
-                            index: new_index,
-                        });
-                        self.goto_table.insert((i, symbol.clone()), new_index);
+                for terminal in lookahead.iter() {
+                    let previous = reductions.entry(terminal).or_insert(p);
+                    if *previous != p {
+                        let (chosen, rejected) = ((*previous).min(p), (*previous).max(p));
+                        *previous = chosen;
+                        self.conflict(state, &automaton.symbols[terminal], chosen, rejected);
                     }
                 }
             }
-
-            i += 1;
-        }
-
-        states
-    }
-
-    // 构建Action表和Goto表
-    fn build_parsing_table(&mut self) {
-        let states = self.build_canonical_collection();
-
-        // 对每个状态构建Action表
-        for state in &states {
-            for item in &state.items {
-                if item.dot_position < item.production.rhs.len() {
-                    // 移入动作
-                    let symbol = &item.production.rhs[item.dot_position];
-                    if let Symbol::Terminal(_) = symbol {
-                        if let Some(&next_state) =
-                            self.goto_table.get(&(state.index, symbol.clone()))
-                        {
-                            self.action_table
-                                .insert((state.index, symbol.clone()), Action::Shift(next_state));
-                        }
-                    }
+            for (terminal, p) in reductions {
+                let symbol = automaton.symbols[terminal].clone();
+                let key = (state, symbol.clone());
+                if p == accept {
+                    self.action_table.insert(key, Action::Accept);
+                } else if let Some(shift) = self.action_table.get(&key).cloned() {
+                    let rejected = Action::Reduce(self.grammar.productions[p].clone());
+                    self.conflicts.push(Conflict {
+                        state,
+                        symbol,
+                        chosen: shift,
+                        rejected,
+                    });
                 } else {
-                    // 规约动作
-                    if item.production.lhs == Symbol::NonTerminal(FAKE_START.to_string())
-                        && item.lookahead == Symbol::EndMarker
-                    {
-                        self.action_table
-                            .insert((state.index, Symbol::EndMarker), Action::Accept);
-                    } else {
-                        self.action_table.insert(
-                            (state.index, item.lookahead.clone()),
-                            Action::Reduce(item.production.clone()),
-                        );
-                    }
+                    let reduce = Action::Reduce(self.grammar.productions[p].clone());
+                    self.action_table.insert(key, reduce);
                 }
             }
         }
     }
+
+    fn conflict(&mut self, state: usize, symbol: &Symbol, chosen: usize, rejected: usize) {
+        let production = |p: usize| {
+            self.grammar
+                .productions
+                .get(p)
+                .cloned()
+                .map_or(Action::Accept, Action::Reduce)
+        };
+        let conflict = Conflict {
+            state,
+            symbol: symbol.clone(),
+            chosen: production(chosen),
+            rejected: production(rejected),
+        };
+        self.conflicts.push(conflict);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+
+    #[test]
+    fn test_conflicts() {
+        let grammar = grammar!(
+            "S",
+            prod!("S" => "if", "S", "else", "S"),
+            prod!("S" => "if", "S"),
+            prod!("S" => "a")
+        );
+        let parser = Parser::new(grammar);
+        assert_eq!(parser.conflicts.len(), 1);
+        let conflict = &parser.conflicts[0];
+        assert_eq!(conflict.symbol, Term!("else"));
+        assert!(matches!(conflict.chosen, Action::Shift(_)));
+
+        // the dangling else belongs to the nearest if
+        let symbols = ["if", "if", "a", "else", "a"].iter().map(|s| Term!(s));
+        let tree = parser.parse(symbols.collect()).unwrap();
+        let ASTNode::NonTerminal(_, children) = tree else {
+            panic!("expected a nonterminal");
+        };
+        assert_eq!(children.len(), 2);
+    }
 }
@@ -0,0 +1,178 @@
+use super::Symbol;
+use toy_lang_lexer::error::LexError;
+use toy_lang_lexer::lexer::{Goal, Lexer};
+use toy_lang_lexer::token::{Token, TokenData, Type};
+
+/// Where the parser gets its terminals from.
+///
+/// `accepts` tells whether the current parser state has an action on a terminal, so that a
+/// source can tokenize context-dependent input (like '/' in JS) the way the parser expects.
+pub trait TokenSource {
+    fn next_symbol(&mut self, accepts: &dyn Fn(&Symbol) -> bool) -> Option<Symbol>;
+    /// Whether a line terminator came before the last symbol returned
+    fn newline_before(&self) -> bool {
+        false
+    }
+}
+
+impl<I: Iterator<Item = Symbol>> TokenSource for I {
+    fn next_symbol(&mut self, _accepts: &dyn Fn(&Symbol) -> bool) -> Option<Symbol> {
+        self.next()
+    }
+}
+
+/// Already lexed terminals, each paired with whether a line terminator came before it
+pub struct Symbols {
+    symbols: std::vec::IntoIter<(Symbol, bool)>,
+    newline: bool,
+}
+
+impl Symbols {
+    pub fn new(symbols: Vec<(Symbol, bool)>) -> Self {
+        Symbols {
+            symbols: symbols.into_iter(),
+            newline: false,
+        }
+    }
+}
+
+impl TokenSource for Symbols {
+    fn next_symbol(&mut self, _accepts: &dyn Fn(&Symbol) -> bool) -> Option<Symbol> {
+        let (symbol, newline) = self.symbols.next()?;
+        self.newline = newline;
+        Some(symbol)
+    }
+    fn newline_before(&self) -> bool {
+        self.newline
+    }
+}
+
+/// Feeds the parser straight from a lexer. The lexer is asked for a regular expression literal
+/// (instead of `/` or `/=`) when the current state has no action on a division but has one on
+/// a regular expression, e.g. at the start of an expression.
+pub struct LexerSource<F> {
+    lexer: Lexer,
+    symbolize: F,
+    division: Vec<Symbol>,
+    regex: Symbol,
+    newline: bool,
+    /// The tokens handed to the parser so far, in order
+    pub tokens: Vec<Token>,
+    /// The lexical errors met so far, they are never handed to the parser
+    pub errors: Vec<LexError>,
+}
+
+impl<F: Fn(&Token) -> Option<Symbol>> LexerSource<F> {
+    /// * `symbolize`: maps a token to its terminal, `None` to skip it
+    pub fn new(input: &str, symbolize: F) -> Self {
+        let symbol = |typ, val: &str| {
+            symbolize(&Token {
+                typ,
+                val: val.to_string(),
+                data: TokenData::None,
+                span: Default::default(),
+            })
+        };
+        let division = [symbol(Type::Divide, "/"), symbol(Type::DivideAssign, "/=")]
+            .into_iter()
+            .flatten()
+            .collect();
+        let regex = symbol(Type::RegularExpression, "/(?:)/")
+            .expect("regular expressions must not be skipped");
+        LexerSource {
+            lexer: Lexer::new(input),
+            symbolize,
+            division,
+            regex,
+            newline: false,
+            tokens: Vec::new(),
+            errors: Vec::new(),
+        }
+    }
+}
+
+impl<F: Fn(&Token) -> Option<Symbol>> TokenSource for LexerSource<F> {
+    fn next_symbol(&mut self, accepts: &dyn Fn(&Symbol) -> bool) -> Option<Symbol> {
+        let goal = if !self.division.iter().any(accepts) && accepts(&self.regex) {
+            Goal::RegExp
+        } else {
+            Goal::Div
+        };
+        self.newline = false;
+        while let Some(token) = self.lexer.next_token(goal) {
+            if let TokenData::Error(kind) = token.data {
+                self.errors.push(LexError {
+                    kind,
+                    span: token.span,
+                });
+                continue;
+            }
+            if let Some(symbol) = (self.symbolize)(&token) {
+                self.tokens.push(token);
+                return Some(symbol);
+            }
+            self.newline |= token.typ == Type::LineTerminator;
+        }
+        None
+    }
+    fn newline_before(&self) -> bool {
+        self.newline
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::*;
+
+    fn parse(input: &str) -> Result<Vec<Type>, ParseError> {
+        let parser = Parser::new(grammar!(
+            "E",
+            prod!("E" => "E", "/", "T"),
+            prod!("E" => "T"),
+            prod!("T" => "identifier"),
+            prod!("T" => "regularexpression"),
+            prod!("T" => "(", "E", ")")
+        ));
+        let mut source = LexerSource::new(input, |token| match token.typ {
+            Type::EOF | Type::LineTerminator => None,
+            Type::Identifier | Type::RegularExpression => {
+                Some(Term!(format!("{:?}", token.typ).to_lowercase()))
+            }
+            _ => Some(Term!(token.val)),
+        });
+        parser.parse_source(&mut source)?;
+        assert_eq!(source.errors.len(), input.matches('#').count());
+        Ok(source.tokens.iter().map(|t| t.typ).collect())
+    }
+
+    #[test]
+    fn test_regex_or_division() {
+        assert_eq!(
+            parse("a / b / c").unwrap(),
+            vec![
+                Type::Identifier,
+                Type::Divide,
+                Type::Identifier,
+                Type::Divide,
+                Type::Identifier
+            ]
+        );
+        assert_eq!(
+            parse("a # / b").unwrap(),
+            vec![Type::Identifier, Type::Divide, Type::Identifier]
+        );
+        assert_eq!(
+            parse("(/a/g / /b/) / c").unwrap(),
+            vec![
+                Type::LeftParen,
+                Type::RegularExpression,
+                Type::Divide,
+                Type::RegularExpression,
+                Type::RightParen,
+                Type::Divide,
+                Type::Identifier
+            ]
+        );
+    }
+}
@@ -0,0 +1,743 @@
+//! The JavaScript syntax tree, one node per construct of the language instead of one per grammar
+//! rule. The node kinds and their fields follow ESTree (see `estree.rs`), every node knows its
+//! byte range in the source.
+
+use js_lexer::token::Span;
+
+#[derive(Debug, Clone)]
+pub struct Program {
+    pub body: Vec<Statement>,
+    /// Whether the program has import or export statements
+    pub module: bool,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone)]
+pub struct Identifier {
+    pub name: String,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone)]
+pub enum Statement {
+    Expression {
+        expression: Expression,
+        /// The text of a `"use strict"`-like string at the start of a body
+        directive: Option<String>,
+        span: Span,
+    },
+    Block(Block),
+    Empty {
+        span: Span,
+    },
+    Debugger {
+        span: Span,
+    },
+    With {
+        object: Expression,
+        body: Box<Statement>,
+        span: Span,
+    },
+    Return {
+        argument: Option<Expression>,
+        span: Span,
+    },
+    Labeled {
+        label: Identifier,
+        body: Box<Statement>,
+        span: Span,
+    },
+    Break {
+        label: Option<Identifier>,
+        span: Span,
+    },
+    Continue {
+        label: Option<Identifier>,
+        span: Span,
+    },
+    If {
+        test: Expression,
+        consequent: Box<Statement>,
+        alternate: Option<Box<Statement>>,
+        span: Span,
+    },
+    Switch {
+        discriminant: Expression,
+        cases: Vec<SwitchCase>,
+        span: Span,
+    },
+    Throw {
+        argument: Expression,
+        span: Span,
+    },
+    Try {
+        block: Block,
+        handler: Option<CatchClause>,
+        finalizer: Option<Block>,
+        span: Span,
+    },
+    While {
+        test: Expression,
+        body: Box<Statement>,
+        span: Span,
+    },
+    DoWhile {
+        body: Box<Statement>,
+        test: Expression,
+        span: Span,
+    },
+    For {
+        init: Option<ForInit>,
+        test: Option<Expression>,
+        update: Option<Expression>,
+        body: Box<Statement>,
+        span: Span,
+    },
+    ForIn {
+        left: ForHead,
+        right: Expression,
+        body: Box<Statement>,
+        span: Span,
+    },
+    ForOf {
+        left: ForHead,
+        right: Expression,
+        body: Box<Statement>,
+        is_await: bool,
+        span: Span,
+    },
+    /// A function declaration, its `id` is only missing after `export default`
+    Function(Function),
+    Variable(VariableDeclaration),
+    Class(Class),
+    Import(ImportDeclaration),
+    ExportNamed(ExportNamedDeclaration),
+    ExportDefault {
+        declaration: ExportDefault,
+        span: Span,
+    },
+    ExportAll {
+        exported: Option<ModuleExportName>,
+        source: Literal,
+        span: Span,
+    },
+}
+
+#[derive(Debug, Clone)]
+pub struct Block {
+    pub body: Vec<Statement>,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone)]
+pub struct SwitchCase {
+    /// `None` for `default:`
+    pub test: Option<Expression>,
+    pub consequent: Vec<Statement>,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone)]
+pub struct CatchClause {
+    pub param: Pattern,
+    pub body: Block,
+    pub span: Span,
+}
+
+/// The first part of `for (init; test; update)`
+#[derive(Debug, Clone)]
+pub enum ForInit {
+    Variable(VariableDeclaration),
+    Expression(Expression),
+}
+
+/// What `for (... in/of` assigns to, a declaration without initializer or a target
+#[derive(Debug, Clone)]
+pub enum ForHead {
+    Variable(VariableDeclaration),
+    Pattern(Pattern),
+}
+
+#[derive(Debug, Clone)]
+pub struct VariableDeclaration {
+    pub kind: VariableKind,
+    pub declarations: Vec<VariableDeclarator>,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VariableKind {
+    Var,
+    Let,
+    Const,
+}
+
+#[derive(Debug, Clone)]
+pub struct VariableDeclarator {
+    pub id: Pattern,
+    pub init: Option<Expression>,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone)]
+pub enum Expression {
+    Identifier(Identifier),
+    Literal(Literal),
+    Template(TemplateLiteral),
+    TaggedTemplate {
+        tag: Box<Expression>,
+        quasi: TemplateLiteral,
+        span: Span,
+    },
+    This {
+        span: Span,
+    },
+    /// Only as the callee of a call or the object of a member expression
+    Super {
+        span: Span,
+    },
+    Array {
+        /// `None` for a hole
+        elements: Vec<Option<ExpressionOrSpread>>,
+        span: Span,
+    },
+    Object {
+        properties: Vec<ObjectProperty>,
+        span: Span,
+    },
+    Function(Box<Function>),
+    Arrow(Box<ArrowFunction>),
+    Class(Box<Class>),
+    Unary {
+        operator: UnaryOperator,
+        argument: Box<Expression>,
+        span: Span,
+    },
+    Update {
+        operator: UpdateOperator,
+        prefix: bool,
+        argument: Box<Expression>,
+        span: Span,
+    },
+    Binary {
+        operator: BinaryOperator,
+        left: Box<Expression>,
+        right: Box<Expression>,
+        span: Span,
+    },
+    Logical {
+        operator: LogicalOperator,
+        left: Box<Expression>,
+        right: Box<Expression>,
+        span: Span,
+    },
+    Assignment {
+        operator: AssignmentOperator,
+        left: Box<Pattern>,
+        right: Box<Expression>,
+        span: Span,
+    },
+    Conditional {
+        test: Box<Expression>,
+        consequent: Box<Expression>,
+        alternate: Box<Expression>,
+        span: Span,
+    },
+    Call {
+        callee: Box<Expression>,
+        arguments: Vec<ExpressionOrSpread>,
+        /// `f?.()`
+        optional: bool,
+        span: Span,
+    },
+    New {
+        callee: Box<Expression>,
+        arguments: Vec<ExpressionOrSpread>,
+        span: Span,
+    },
+    Member {
+        object: Box<Expression>,
+        property: MemberProperty,
+        /// `a?.b`
+        optional: bool,
+        span: Span,
+    },
+    /// The whole of `a?.b.c`, where a nullish `a` stops the evaluation
+    Chain {
+        expression: Box<Expression>,
+        span: Span,
+    },
+    Sequence {
+        expressions: Vec<Expression>,
+        span: Span,
+    },
+    Yield {
+        argument: Option<Box<Expression>>,
+        /// `yield*`
+        delegate: bool,
+        span: Span,
+    },
+    Await {
+        argument: Box<Expression>,
+        span: Span,
+    },
+    /// `import(source)`
+    Import {
+        source: Box<Expression>,
+        span: Span,
+    },
+    /// `new.target` or `import.meta`
+    MetaProperty {
+        meta: Identifier,
+        property: Identifier,
+        span: Span,
+    },
+    /// `#x`, only on the left of `in`. The name leaves out the `#`
+    PrivateIdentifier(Identifier),
+}
+
+#[derive(Debug, Clone)]
+pub struct Literal {
+    pub value: LiteralValue,
+    /// The source text
+    pub raw: String,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum LiteralValue {
+    Null,
+    Boolean(bool),
+    Number(f64),
+    String(String),
+    RegExp { pattern: String, flags: String },
+}
+
+#[derive(Debug, Clone)]
+pub struct TemplateLiteral {
+    /// One more than `expressions`, the text around them
+    pub quasis: Vec<TemplateElement>,
+    pub expressions: Vec<Expression>,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone)]
+pub struct TemplateElement {
+    pub raw: String,
+    /// `None` if the text has an invalid escape, which only a tagged template allows
+    pub cooked: Option<String>,
+    pub tail: bool,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone)]
+pub enum ExpressionOrSpread {
+    Expression(Expression),
+    Spread(SpreadElement),
+}
+
+#[derive(Debug, Clone)]
+pub struct SpreadElement {
+    pub argument: Expression,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone)]
+pub enum MemberProperty {
+    /// `a.b`
+    Identifier(Identifier),
+    /// `a.#b`
+    PrivateName(Identifier),
+    /// `a[b]`
+    Computed(Box<Expression>),
+}
+
+#[derive(Debug, Clone)]
+pub enum ObjectProperty {
+    Property(Property),
+    Spread(SpreadElement),
+}
+
+#[derive(Debug, Clone)]
+pub struct Property {
+    pub key: PropertyKey,
+    /// A function for methods, getters and setters
+    pub value: Expression,
+    pub kind: PropertyKind,
+    pub method: bool,
+    /// `{a}`, the key is also the value
+    pub shorthand: bool,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PropertyKind {
+    Init,
+    Get,
+    Set,
+}
+
+#[derive(Debug, Clone)]
+pub enum PropertyKey {
+    Identifier(Identifier),
+    /// A string or a number
+    Literal(Literal),
+    /// `[key]`
+    Computed(Expression),
+    /// `#key` in a class
+    PrivateName(Identifier),
+}
+
+#[derive(Debug, Clone)]
+pub struct Function {
+    pub id: Option<Identifier>,
+    pub params: Vec<Pattern>,
+    pub body: Block,
+    pub generator: bool,
+    pub is_async: bool,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone)]
+pub struct ArrowFunction {
+    pub params: Vec<Pattern>,
+    pub body: ArrowBody,
+    pub is_async: bool,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone)]
+pub enum ArrowBody {
+    Block(Block),
+    /// `x => x + 1`
+    Expression(Expression),
+}
+
+#[derive(Debug, Clone)]
+pub struct Class {
+    /// Only missing in class expressions and after `export default`
+    pub id: Option<Identifier>,
+    pub super_class: Option<Expression>,
+    pub body: ClassBody,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone)]
+pub struct ClassBody {
+    pub body: Vec<ClassMember>,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone)]
+pub enum ClassMember {
+    Method(MethodDefinition),
+    Field(FieldDefinition),
+    /// `static { ... }`
+    StaticBlock(Block),
+}
+
+#[derive(Debug, Clone)]
+pub struct MethodDefinition {
+    pub key: PropertyKey,
+    pub value: Function,
+    pub kind: MethodKind,
+    pub is_static: bool,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MethodKind {
+    Constructor,
+    Method,
+    Get,
+    Set,
+}
+
+#[derive(Debug, Clone)]
+pub struct FieldDefinition {
+    pub key: PropertyKey,
+    pub value: Option<Expression>,
+    pub is_static: bool,
+    pub span: Span,
+}
+
+/// What a declaration, a parameter or an assignment binds to
+#[derive(Debug, Clone)]
+pub enum Pattern {
+    Identifier(Identifier),
+    Object {
+        properties: Vec<PatternProperty>,
+        span: Span,
+    },
+    Array {
+        /// `None` for a hole
+        elements: Vec<Option<Pattern>>,
+        span: Span,
+    },
+    /// `...rest`, last in a list
+    Rest {
+        argument: Box<Pattern>,
+        span: Span,
+    },
+    /// A target with a default value, `a = 1`
+    Assignment {
+        left: Box<Pattern>,
+        right: Box<Expression>,
+        span: Span,
+    },
+    /// A property, only in assignments (`a.b = 1`, `[a.b] = c`)
+    Expression(Box<Expression>),
+}
+
+#[derive(Debug, Clone)]
+pub enum PatternProperty {
+    Property {
+        key: PropertyKey,
+        value: Pattern,
+        /// `{a}` or `{a = 1}`
+        shorthand: bool,
+        span: Span,
+    },
+    Rest {
+        argument: Pattern,
+        span: Span,
+    },
+}
+
+#[derive(Debug, Clone)]
+pub struct ImportDeclaration {
+    pub specifiers: Vec<ImportSpecifier>,
+    pub source: Literal,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone)]
+pub enum ImportSpecifier {
+    /// `import a from`
+    Default(Identifier),
+    /// `import * as a from`
+    Namespace { local: Identifier, span: Span },
+    /// `import {a as b} from`
+    Named {
+        imported: ModuleExportName,
+        local: Identifier,
+        span: Span,
+    },
+}
+
+/// A name in an import or export list, which may be a string
+#[derive(Debug, Clone)]
+pub enum ModuleExportName {
+    Identifier(Identifier),
+    String(Literal),
+}
+
+#[derive(Debug, Clone)]
+pub struct ExportNamedDeclaration {
+    /// `export var a`, the statement is a function, variable or class declaration
+    pub declaration: Option<Box<Statement>>,
+    /// `export {a as b}`
+    pub specifiers: Vec<ExportSpecifier>,
+    /// `export {a} from "m"`
+    pub source: Option<Literal>,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone)]
+pub struct ExportSpecifier {
+    pub local: ModuleExportName,
+    pub exported: ModuleExportName,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone)]
+pub enum ExportDefault {
+    Function(Function),
+    Class(Class),
+    Expression(Expression),
+}
+
+/// Operator enums with their source text
+macro_rules! operators {
+    ($($(#[$doc:meta])* $name:ident { $($op:ident => $text:literal),* $(,)? })*) => {$(
+        $(#[$doc])*
+        #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+        pub enum $name {
+            $($op),*
+        }
+
+        impl $name {
+            pub fn as_str(self) -> &'static str {
+                match self {
+                    $($name::$op => $text),*
+                }
+            }
+
+            pub fn from_text(text: &str) -> Option<Self> {
+                match text {
+                    $($text => Some($name::$op),)*
+                    _ => None,
+                }
+            }
+        }
+    )*};
+}
+
+operators! {
+    UnaryOperator {
+        Minus => "-",
+        Plus => "+",
+        Not => "!",
+        BitNot => "~",
+        Typeof => "typeof",
+        Void => "void",
+        Delete => "delete",
+    }
+    UpdateOperator {
+        Increment => "++",
+        Decrement => "--",
+    }
+    BinaryOperator {
+        Equal => "==",
+        NotEqual => "!=",
+        StrictEqual => "===",
+        StrictNotEqual => "!==",
+        Less => "<",
+        LessEqual => "<=",
+        Greater => ">",
+        GreaterEqual => ">=",
+        ShiftLeft => "<<",
+        ShiftRight => ">>",
+        UnsignedShiftRight => ">>>",
+        Add => "+",
+        Sub => "-",
+        Mul => "*",
+        Div => "/",
+        Mod => "%",
+        Exp => "**",
+        BitOr => "|",
+        BitXor => "^",
+        BitAnd => "&",
+        In => "in",
+        Instanceof => "instanceof",
+    }
+    /// The operators that may skip their right operand
+    LogicalOperator {
+        Or => "||",
+        And => "&&",
+        Coalesce => "??",
+    }
+    AssignmentOperator {
+        Assign => "=",
+        AddAssign => "+=",
+        SubAssign => "-=",
+        MulAssign => "*=",
+        DivAssign => "/=",
+        ModAssign => "%=",
+        ExpAssign => "**=",
+        ShiftLeftAssign => "<<=",
+        ShiftRightAssign => ">>=",
+        UnsignedShiftRightAssign => ">>>=",
+        BitOrAssign => "|=",
+        BitXorAssign => "^=",
+        BitAndAssign => "&=",
+        OrAssign => "||=",
+        AndAssign => "&&=",
+        CoalesceAssign => "??=",
+    }
+}
+
+impl VariableKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            VariableKind::Var => "var",
+            VariableKind::Let => "let",
+            VariableKind::Const => "const",
+        }
+    }
+}
+
+impl Statement {
+    pub fn span(&self) -> Span {
+        match self {
+            Statement::Block(block) => block.span,
+            Statement::Function(function) => function.span,
+            Statement::Variable(declaration) => declaration.span,
+            Statement::Class(class) => class.span,
+            Statement::Import(import) => import.span,
+            Statement::ExportNamed(export) => export.span,
+            Statement::Expression { span, .. }
+            | Statement::Empty { span }
+            | Statement::Debugger { span }
+            | Statement::With { span, .. }
+            | Statement::Return { span, .. }
+            | Statement::Labeled { span, .. }
+            | Statement::Break { span, .. }
+            | Statement::Continue { span, .. }
+            | Statement::If { span, .. }
+            | Statement::Switch { span, .. }
+            | Statement::Throw { span, .. }
+            | Statement::Try { span, .. }
+            | Statement::While { span, .. }
+            | Statement::DoWhile { span, .. }
+            | Statement::For { span, .. }
+            | Statement::ForIn { span, .. }
+            | Statement::ForOf { span, .. }
+            | Statement::ExportDefault { span, .. }
+            | Statement::ExportAll { span, .. } => *span,
+        }
+    }
+}
+
+impl Expression {
+    pub fn span(&self) -> Span {
+        match self {
+            Expression::Identifier(id) | Expression::PrivateIdentifier(id) => id.span,
+            Expression::Literal(literal) => literal.span,
+            Expression::Template(template) => template.span,
+            Expression::Function(function) => function.span,
+            Expression::Arrow(arrow) => arrow.span,
+            Expression::Class(class) => class.span,
+            Expression::TaggedTemplate { span, .. }
+            | Expression::This { span }
+            | Expression::Super { span }
+            | Expression::Array { span, .. }
+            | Expression::Object { span, .. }
+            | Expression::Unary { span, .. }
+            | Expression::Update { span, .. }
+            | Expression::Binary { span, .. }
+            | Expression::Logical { span, .. }
+            | Expression::Assignment { span, .. }
+            | Expression::Conditional { span, .. }
+            | Expression::Call { span, .. }
+            | Expression::New { span, .. }
+            | Expression::Member { span, .. }
+            | Expression::Chain { span, .. }
+            | Expression::Sequence { span, .. }
+            | Expression::Yield { span, .. }
+            | Expression::Await { span, .. }
+            | Expression::Import { span, .. }
+            | Expression::MetaProperty { span, .. } => *span,
+        }
+    }
+}
+
+impl Pattern {
+    pub fn span(&self) -> Span {
+        match self {
+            Pattern::Identifier(id) => id.span,
+            Pattern::Expression(expression) => expression.span(),
+            Pattern::Object { span, .. }
+            | Pattern::Array { span, .. }
+            | Pattern::Rest { span, .. }
+            | Pattern::Assignment { span, .. } => *span,
+        }
+    }
+}
+
+impl PropertyKey {
+    /// `[key]`, where the key is evaluated
+    pub fn computed(&self) -> bool {
+        matches!(self, PropertyKey::Computed(_))
+    }
+}
@@ -0,0 +1,4597 @@
+//! Translation of the JavaScript syntax tree into Python. Every construct goes to the closest
+//! Python one, what has no counterpart (yet) is reported as a diagnostic.
+//!
+//! Python has fewer places for a definition than JavaScript: function and class expressions
+//! become a `def` or `class` just before the statement they are in (`hoisted`), functions that
+//! only return an expression and assign nothing stay expressions as a `lambda`. A `def` declares
+//! the outer variables it assigns `nonlocal` or `global`. Arrow functions have the `this` of where
+//! they are, other functions get theirs as a first argument. Likewise an assignment to a property
+//! inside an expression is done by statements before the one it is in, its value kept in a
+//! temporary (`effects`).
+//!
+//! Loops become `while` and `for` loops. What Python's `break` and `continue` can't do, jumping
+//! out of more than one loop, to a label or to the update of a `for`, is done with flags (see
+//! `Jump`), a `switch` with cases falling through is a loop that runs once.
+//!
+//! Where a Python operator could behave differently from the JavaScript one, on mixed `+`, `==`,
+//! truth values, `/` by zero and such, the translation calls a helper of the `js_runtime` module
+//! instead, unless the kinds of the operands (`infer`) show the plain operator does the same.
+//! `undefined` is `None`, `null` is the runtime's `null`. Calls of the methods of strings and
+//! arrays are the Python of the table in `methods` on the same terms, templates are f-strings.
+//!
+//! Imports are Python imports of the modules `modules` resolves the specifiers to. The default
+//! export of a module is its `default`, the names it exports are its `__all__`.
+//!
+//! What a statement translates to is `Located` at it, so each line of the Python can be traced
+//! back to the JavaScript (`sourcemap`).
+
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::mem;
+use std::sync::OnceLock;
+
+use js_lexer::token::Span;
+
+use crate::ast::*;
+use crate::infer::{Kind, Kinds};
+use crate::methods::{self, Python};
+use crate::modules::{module_name, ModulePath, Packages};
+use crate::python::{
+    Alias, Arguments, BoolOp, CmpOp, Constant, ExceptHandler, Expr, Module, Operator, Stmt, UnaryOp,
+};
+use crate::scope::{Access, BindingId, BindingKind, ScopeId, ScopeKind, Scopes};
+use crate::visit::{self, bound_names, Visit};
+use crate::Diagnostic;
+
+/// The keywords of Python, `keyword.kwlist`
+const PYTHON_KEYWORDS: &[&str] = &[
+    "False", "None", "True", "and", "as", "assert", "async", "await", "break", "class", "continue",
+    "def", "del", "elif", "else", "except", "finally", "for", "from", "global", "if", "import",
+    "in", "is", "lambda", "nonlocal", "not", "or", "pass", "raise", "return", "try", "while",
+    "with", "yield",
+];
+
+/// The builtins of Python the translation uses
+const PYTHON_BUILTINS: &[&str] = &[
+    "Exception",
+    "float",
+    "isinstance",
+    "len",
+    "list",
+    "print",
+    "classmethod",
+    "property",
+    "staticmethod",
+    "super",
+];
+
+/// The module the runtime helpers are imported from, written next to the translation
+pub const RUNTIME_MODULE: &str = "js_runtime";
+
+/// The runtime module's source
+pub const RUNTIME: &str = include_str!("js_runtime.py");
+
+/// Globals of JavaScript the runtime has, imported when the program uses them without declaring
+/// them
+const RUNTIME_GLOBALS: &[&str] = &[
+    "console",
+    "Math",
+    "JSON",
+    "Object",
+    "Array",
+    "String",
+    "Number",
+    "Boolean",
+    "Set",
+    "Map",
+    "parseInt",
+    "parseFloat",
+    "isNaN",
+    "isFinite",
+];
+
+/// The error classes of the runtime, which `throw new Error(...)` raises as they are
+const RUNTIME_ERRORS: &[&str] = &[
+    "Error",
+    "TypeError",
+    "RangeError",
+    "ReferenceError",
+    "SyntaxError",
+];
+
+/// The public names of the runtime module, its functions, classes and values
+fn runtime_names() -> &'static HashSet<&'static str> {
+    static NAMES: OnceLock<HashSet<&str>> = OnceLock::new();
+    NAMES.get_or_init(|| {
+        let names = RUNTIME
+            .lines()
+            .filter_map(|line| match line.split_once(' ') {
+                Some(("def" | "class", rest)) => rest.split(['(', ':']).next(),
+                Some((name, rest)) if rest.starts_with("= ") => Some(name),
+                _ => None,
+            });
+        names
+            .filter(|name| {
+                !name.is_empty()
+                    && !name.starts_with('_')
+                    && name.chars().all(|c| c == '_' || c.is_ascii_alphanumeric())
+            })
+            .collect()
+    })
+}
+
+/// Python source for `program`, as its syntax tree. `packages` has the Python modules of the
+/// packages it imports
+pub fn translate(
+    program: &Program,
+    scopes: &Scopes,
+    packages: &Packages,
+    diagnostics: &mut Vec<Diagnostic>,
+) -> Module {
+    let (constructed, factories) = constructed(program, scopes);
+    let mut translator = Translator {
+        diagnostics,
+        kinds: Kinds::new(program),
+        scopes,
+        packages,
+        exports: None,
+        aliases: Vec::new(),
+        renames: renames(scopes),
+        constructed,
+        factories,
+        methods: defined_methods(program),
+        async_functions: async_functions(program, scopes),
+        iteration: iteration_bindings(program, scopes),
+        runtime: BTreeSet::new(),
+        temporaries: 0,
+        names: names(scopes),
+        hoisted: Vec::new(),
+        effects: None,
+        this: None,
+        base: None,
+        jumps: Vec::new(),
+    };
+    let mut body = translator.body(&program.body);
+    // __all__ = [...] after the imports, export {a as b} is b = a at the end
+    if let Some(exports) = translator.exports.take() {
+        let imports = body
+            .iter()
+            .take_while(|s| matches!(s.unlocated(), Stmt::Import(_) | Stmt::ImportFrom { .. }))
+            .count();
+        body.insert(imports, Stmt::assign("__all__", Expr::List(exports)));
+    }
+    body.append(&mut translator.aliases);
+    if !translator.runtime.is_empty() {
+        let names = translator.runtime.iter().map(|name| Alias {
+            name: name.to_string(),
+            asname: None,
+        });
+        body.insert(
+            0,
+            Stmt::ImportFrom {
+                module: RUNTIME_MODULE.to_string(),
+                names: names.collect(),
+                level: 0,
+            },
+        );
+    }
+    Module { body }
+}
+
+/// The Python names of the variables of the program, declared or not
+fn names(scopes: &Scopes) -> HashSet<String> {
+    let bindings = scopes.bindings().map(|(_, b)| b.name.as_str());
+    let references = scopes.references().iter().map(|r| r.name.as_str());
+    let references = references.map(python_name);
+    bindings.map(variable_name).chain(references).collect()
+}
+
+/// New names for the block scoped bindings that would be the same Python variable as a binding
+/// of an enclosing scope of theirs: a Python function has only the one scope. They get a number
+/// that makes them unique
+fn renames(scopes: &Scopes) -> HashMap<BindingId, String> {
+    let mut taken: HashSet<String> = scopes.bindings().map(|(_, b)| b.name.clone()).collect();
+    taken.extend(scopes.references().iter().map(|r| r.name.clone()));
+    let mut renames = HashMap::new();
+    for (id, binding) in scopes.bindings() {
+        let mut scope = scopes.scope(binding.scope);
+        if !matches!(scope.kind, ScopeKind::Block | ScopeKind::Catch) {
+            continue;
+        }
+        let shadows = loop {
+            let Some(parent) = scope.parent else {
+                break false;
+            };
+            scope = scopes.scope(parent);
+            if scope.names.contains_key(&binding.name) {
+                break true;
+            }
+            if !matches!(scope.kind, ScopeKind::Block | ScopeKind::Catch) {
+                break false;
+            }
+        };
+        if shadows {
+            let name = (1..)
+                .map(|n| format!("{}_{}", binding.name, n))
+                .find(|name| !taken.contains(name))
+                .unwrap();
+            taken.insert(name.clone());
+            renames.insert(id, name);
+        }
+    }
+    renames
+}
+
+/// A statement `break` or `continue` can leave. A Python `break` or `continue` leaves only the
+/// innermost Python loop, one that leaves more sets a flag of the statement it jumps to, breaks
+/// out of the innermost loop, and each loop on the way tests the flag after it to go on
+struct Jump {
+    kind: JumpKind,
+    labels: Vec<String>,
+    /// Whether it is a Python loop
+    python_loop: bool,
+    /// The flags of the jumps to it from inside other Python loops, made false before it
+    break_flag: Option<String>,
+    continue_flag: Option<String>,
+    /// What follows the Python loop for each flag of the jumps through it
+    checks: Vec<(String, Vec<Stmt>)>,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum JumpKind {
+    Loop,
+    Switch,
+    /// Another statement with a label
+    Block,
+}
+
+/// Whether a `break`, or `continue` if `continues`, in `statement` jumps to the statement with
+/// `labels` around it. `nested` says whether a jump without a label stops at a statement between
+fn jumps_to(statement: &Statement, labels: &[String], continues: bool, nested: bool) -> bool {
+    let any = |statements: &[Statement], nested| {
+        statements
+            .iter()
+            .any(|s| jumps_to(s, labels, continues, nested))
+    };
+    match statement {
+        Statement::Break { label, .. } | Statement::Continue { label, .. } => {
+            if continues != matches!(statement, Statement::Continue { .. }) {
+                return false;
+            }
+            match label {
+                Some(label) => labels.contains(&label.name),
+                None => !nested,
+            }
+        }
+        Statement::Block(block) => any(&block.body, nested),
+        Statement::Labeled { body, .. } | Statement::With { body, .. } => {
+            jumps_to(body, labels, continues, nested)
+        }
+        Statement::If {
+            consequent,
+            alternate,
+            ..
+        } => {
+            jumps_to(consequent, labels, continues, nested)
+                || alternate
+                    .as_ref()
+                    .is_some_and(|a| jumps_to(a, labels, continues, nested))
+        }
+        Statement::Switch { cases, .. } => cases
+            .iter()
+            .any(|case| any(&case.consequent, nested || !continues)),
+        Statement::Try {
+            block,
+            handler,
+            finalizer,
+            ..
+        } => {
+            any(&block.body, nested)
+                || handler.as_ref().is_some_and(|h| any(&h.body.body, nested))
+                || finalizer.as_ref().is_some_and(|f| any(&f.body, nested))
+        }
+        Statement::While { body, .. }
+        | Statement::DoWhile { body, .. }
+        | Statement::For { body, .. }
+        | Statement::ForIn { body, .. }
+        | Statement::ForOf { body, .. } => jumps_to(body, labels, continues, true),
+        _ => false,
+    }
+}
+
+struct Translator<'a> {
+    diagnostics: &'a mut Vec<Diagnostic>,
+    kinds: Kinds,
+    scopes: &'a Scopes,
+    packages: &'a Packages,
+    /// The names of `__all__`, if the program exports anything
+    exports: Option<Vec<Expr>>,
+    /// The assignments of exports that have another name than their variable
+    aliases: Vec<Stmt>,
+    /// The Python names of the bindings that can't keep their own
+    renames: HashMap<BindingId, String>,
+    /// The functions that are classes, `new` is used on them
+    constructed: HashSet<BindingId>,
+    /// The functions `new` is used on that return a value, which `js_new` calls
+    factories: HashSet<BindingId>,
+    /// The names of the methods the program defines, which a call on a value of unknown kind is
+    /// taken to be rather than one of a string or array
+    methods: HashSet<String>,
+    /// The async functions, whose calls outside of any function `js_run` runs to their end
+    async_functions: HashSet<BindingId>,
+    /// The bindings each iteration of their loop has its own of
+    iteration: HashSet<BindingId>,
+    /// The names used from the runtime module
+    runtime: BTreeSet<&'static str>,
+    /// How many names were made up, they are numbered
+    temporaries: usize,
+    /// The Python names of the program's variables, which made up names avoid
+    names: HashSet<String>,
+    /// The definitions of the function and class expressions in the statement being translated
+    hoisted: Vec<Stmt>,
+    /// The statements that do what Python can't inside the expression being translated, before
+    /// it, `None` where it isn't evaluated right away (see `lowered`)
+    effects: Option<Vec<Stmt>>,
+    /// What `this` is, the first argument of a method, `self`, or of a class method, `cls`
+    this: Option<&'static str>,
+    /// The constructor function the one being translated as a class inherits from
+    base: Option<String>,
+    /// The enclosing statements of the function a jump can leave, the innermost last
+    jumps: Vec<Jump>,
+}
+
+/// What a function returns, its statements or the expression of an arrow function
+enum FunctionBody<'b> {
+    Statements(&'b [Statement]),
+    Expression(&'b Expression),
+}
+
+/// A non-empty block
+fn suite(mut body: Vec<Stmt>) -> Vec<Stmt> {
+    if body.is_empty() {
+        body.push(Stmt::Pass);
+    }
+    body
+}
+
+/// `value` if it isn't `None`, else `default`
+fn coalesce(test: Expr, value: Expr, default: Expr) -> Expr {
+    Expr::IfExp {
+        test: Box::new(Expr::compare(test, CmpOp::IsNot, Expr::none())),
+        body: Box::new(value),
+        orelse: Box::new(default),
+    }
+}
+
+fn int(n: i64) -> Expr {
+    Expr::Constant(Constant::Int(n))
+}
+
+/// A JavaScript number, an `int` if it is whole
+fn number(n: f64) -> Expr {
+    if n.fract() == 0.0 && n.abs() < 9007199254740992.0 {
+        int(n as i64)
+    } else if n.is_finite() {
+        Expr::Constant(Constant::Float(n))
+    } else {
+        let name = match n.is_nan() {
+            true => "nan",
+            false if n > 0.0 => "inf",
+            false => "-inf",
+        };
+        Expr::call(Expr::name("float"), vec![Expr::str(name)])
+    }
+}
+
+/// How a number is written as a property name
+fn number_key(n: f64) -> String {
+    match n.fract() == 0.0 && n.abs() < 1e21 {
+        true => format!("{}", n as i64),
+        false => format!("{}", n),
+    }
+}
+
+/// The `console` of `console.log`, which is `print`
+fn console_log(e: &Expression) -> Option<&Identifier> {
+    match e {
+        Expression::Member {
+            object,
+            property: MemberProperty::Identifier(log),
+            optional: false,
+            ..
+        } if log.name == "log" => match object.as_ref() {
+            Expression::Identifier(console) if console.name == "console" => Some(console),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// A literal that can be a Python default value, which is evaluated once
+fn constant(e: &Expression) -> Option<Expr> {
+    match e {
+        Expression::Literal(literal) => match &literal.value {
+            LiteralValue::Null => None,
+            LiteralValue::Boolean(b) => Some(Expr::Constant(Constant::Bool(*b))),
+            LiteralValue::Number(n) => Some(number(*n)),
+            LiteralValue::String(s) => Some(Expr::str(s)),
+            LiteralValue::RegExp { .. } => None,
+        },
+        Expression::Unary {
+            operator: UnaryOperator::Minus,
+            argument,
+            ..
+        } => match argument.as_ref() {
+            Expression::Literal(Literal {
+                value: LiteralValue::Number(n),
+                ..
+            }) => Some(number(-n)),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Whether the expression is an integer literal, negative or not
+fn integer(e: &Expression) -> bool {
+    matches!(constant(e), Some(Expr::Constant(Constant::Int(_))))
+}
+
+fn string_literal(e: &Expression) -> Option<&str> {
+    match e {
+        Expression::Literal(Literal {
+            value: LiteralValue::String(s),
+            ..
+        }) => Some(s),
+        _ => None,
+    }
+}
+
+/// What a function that is a single expression returns
+fn returned(function: &Expression) -> Option<&Expression> {
+    match function {
+        Expression::Arrow(arrow) => match &arrow.body {
+            ArrowBody::Expression(e) => Some(e),
+            ArrowBody::Block(_) => None,
+        },
+        Expression::Function(function) => match function.body.body.as_slice() {
+            [Statement::Return {
+                argument: Some(e), ..
+            }] => Some(e),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// The operator of `x op= y`, which is `x = x op y`
+fn compound(operator: AssignmentOperator) -> Option<BinaryOperator> {
+    let op = match operator {
+        AssignmentOperator::AddAssign => BinaryOperator::Add,
+        AssignmentOperator::SubAssign => BinaryOperator::Sub,
+        AssignmentOperator::MulAssign => BinaryOperator::Mul,
+        AssignmentOperator::DivAssign => BinaryOperator::Div,
+        AssignmentOperator::ModAssign => BinaryOperator::Mod,
+        AssignmentOperator::ExpAssign => BinaryOperator::Exp,
+        AssignmentOperator::ShiftLeftAssign => BinaryOperator::ShiftLeft,
+        AssignmentOperator::ShiftRightAssign => BinaryOperator::ShiftRight,
+        AssignmentOperator::UnsignedShiftRightAssign => BinaryOperator::UnsignedShiftRight,
+        AssignmentOperator::BitOrAssign => BinaryOperator::BitOr,
+        AssignmentOperator::BitXorAssign => BinaryOperator::BitXor,
+        AssignmentOperator::BitAndAssign => BinaryOperator::BitAnd,
+        _ => return None,
+    };
+    Some(op)
+}
+
+/// `x = x op y` as `x op= y` where it is one
+fn update(target: Expr, value: Expr) -> Stmt {
+    match value {
+        Expr::BinOp { left, op, right } if *left == target => Stmt::AugAssign {
+            target,
+            op,
+            value: *right,
+        },
+        value => Stmt::Assign {
+            targets: vec![target],
+            value,
+        },
+    }
+}
+
+/// `left op right`, where `a and b and c` is one `BoolOp`
+fn bool_op(op: BoolOp, left: Expr, right: Expr) -> Expr {
+    let mut values = match left {
+        Expr::BoolOp {
+            op: left_op,
+            values,
+        } if left_op == op => values,
+        left => vec![left],
+    };
+    values.push(right);
+    Expr::BoolOp { op, values }
+}
+
+/// The function a statement declares, exported or not
+fn declared_function(statement: &Statement) -> Option<&Function> {
+    match statement {
+        Statement::Function(function)
+        | Statement::ExportDefault {
+            declaration: ExportDefault::Function(function),
+            ..
+        } => Some(function),
+        Statement::ExportNamed(ExportNamedDeclaration {
+            declaration: Some(declaration),
+            ..
+        }) => match declaration.as_ref() {
+            Statement::Function(function) => Some(function),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Whether the statement imports, re-exports included
+fn is_import(statement: &Statement) -> bool {
+    matches!(
+        statement,
+        Statement::Import(_)
+            | Statement::ExportAll { .. }
+            | Statement::ExportNamed(ExportNamedDeclaration {
+                source: Some(_),
+                ..
+            })
+    )
+}
+
+/// `import name as asname`, without `as` if they are the same
+fn alias(name: String, asname: String) -> Alias {
+    Alias {
+        asname: (asname != name).then_some(asname),
+        name,
+    }
+}
+
+/// What `statement` puts on the prototype of a constructor function the ES5 way
+enum Prototype<'a> {
+    /// `Foo.prototype.bar = function () {}`, `Foo.prototype.legs = 4` or
+    /// `Foo.prototype = { bar() {} }`
+    Members(Vec<(&'a Identifier, &'a Expression)>),
+    /// `Foo.prototype = Object.create(Base.prototype)`
+    Base(&'a Identifier),
+    /// `Foo.prototype.constructor = Foo`, which a class has already
+    Constructor,
+}
+
+/// The constructor function `statement` sets the prototype of, and what it puts on it
+fn prototype_part(statement: &Statement) -> Option<(&str, Prototype<'_>)> {
+    let Statement::Expression {
+        expression:
+            Expression::Assignment {
+                operator: AssignmentOperator::Assign,
+                left,
+                right,
+                ..
+            },
+        ..
+    } = statement
+    else {
+        return None;
+    };
+    let Pattern::Expression(target) = left.as_ref() else {
+        return None;
+    };
+    fn prototype(e: &Expression) -> Option<&Identifier> {
+        match e {
+            Expression::Member {
+                object,
+                property: MemberProperty::Identifier(property),
+                optional: false,
+                ..
+            } if property.name == "prototype" => match object.as_ref() {
+                Expression::Identifier(class) => Some(class),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+    // a function or literal, which can be evaluated where the class is
+    fn member(value: &Expression) -> bool {
+        matches!(value, Expression::Function(_))
+            || constant(value).is_some()
+            || matches!(
+                value,
+                Expression::Literal(Literal {
+                    value: LiteralValue::Null,
+                    ..
+                })
+            )
+    }
+    match (target.as_ref(), right.as_ref()) {
+        (
+            Expression::Member {
+                object,
+                property: MemberProperty::Identifier(name),
+                optional: false,
+                ..
+            },
+            value,
+        ) if prototype(object).is_some() => {
+            let class = &prototype(object)?.name;
+            match value {
+                Expression::Identifier(id) if name.name == "constructor" && id.name == *class => {
+                    Some((class, Prototype::Constructor))
+                }
+                value if member(value) => Some((class, Prototype::Members(vec![(name, value)]))),
+                _ => None,
+            }
+        }
+        (target, Expression::Object { properties, .. }) => {
+            let members = properties.iter().map(|property| match property {
+                ObjectProperty::Property(Property {
+                    key: PropertyKey::Identifier(name),
+                    value,
+                    kind: PropertyKind::Init,
+                    ..
+                }) if member(value) => Some((name, value)),
+                _ => None,
+            });
+            let members = Prototype::Members(members.collect::<Option<_>>()?);
+            Some((prototype(target)?.name.as_str(), members))
+        }
+        // Object.create(Base.prototype)
+        (
+            target,
+            Expression::Call {
+                callee,
+                arguments,
+                optional: false,
+                ..
+            },
+        ) => {
+            let Expression::Member {
+                object,
+                property: MemberProperty::Identifier(create),
+                optional: false,
+                ..
+            } = callee.as_ref()
+            else {
+                return None;
+            };
+            let [ExpressionOrSpread::Expression(base)] = arguments.as_slice() else {
+                return None;
+            };
+            match object.as_ref() {
+                Expression::Identifier(object)
+                    if object.name == "Object" && create.name == "create" =>
+                {
+                    Some((
+                        prototype(target)?.name.as_str(),
+                        Prototype::Base(prototype(base)?),
+                    ))
+                }
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+/// Whether two property names are the same
+fn same_key(a: &PropertyKey, b: &PropertyKey) -> bool {
+    match (a, b) {
+        (PropertyKey::Identifier(a), PropertyKey::Identifier(b))
+        | (PropertyKey::PrivateName(a), PropertyKey::PrivateName(b)) => a.name == b.name,
+        (PropertyKey::Literal(a), PropertyKey::Literal(b)) => a.value == b.value,
+        _ => false,
+    }
+}
+
+/// The Python name of a `#name`, which Python keeps private to the class
+fn private(id: &Identifier) -> String {
+    format!("__{}", id.name)
+}
+
+/// `if not test: break`
+fn break_unless(test: Expr) -> Stmt {
+    Stmt::If {
+        test: Expr::not(test),
+        body: vec![Stmt::Break],
+        orelse: Vec::new(),
+    }
+}
+
+/// `None` or `null`, which are compared with `is`
+fn is_singleton(e: &Expr) -> bool {
+    matches!(e, Expr::Constant(Constant::None)) || matches!(e, Expr::Name(name) if name == "null")
+}
+
+/// A number or string literal, which Python warns of comparing with `is`
+fn is_literal(e: &Expr) -> bool {
+    match e {
+        Expr::Constant(constant) => {
+            matches!(
+                constant,
+                Constant::Int(_) | Constant::Float(_) | Constant::Str(_)
+            )
+        }
+        Expr::UnaryOp { operand, .. } => is_literal(operand),
+        _ => false,
+    }
+}
+
+/// Whether the name is used anywhere in the statements
+fn mentions(statements: &[Statement], name: &str) -> bool {
+    struct Mentions<'n> {
+        name: &'n str,
+        found: bool,
+    }
+    impl<'a> Visit<'a> for Mentions<'_> {
+        fn visit_expression(&mut self, e: &'a Expression) {
+            match e {
+                Expression::Identifier(id) if id.name == self.name => self.found = true,
+                e => visit::walk_expression(self, e),
+            }
+        }
+
+        fn visit_pattern(&mut self, pattern: &'a Pattern) {
+            match pattern {
+                Pattern::Identifier(id) if id.name == self.name => self.found = true,
+                pattern => visit::walk_pattern(self, pattern),
+            }
+        }
+    }
+    let mut mentions = Mentions { name, found: false };
+    for statement in statements {
+        mentions.visit_statement(statement);
+    }
+    mentions.found
+}
+
+/// The `var`s of a function body declared without a value, not those of the functions in it
+fn uninitialized_vars(statements: &[Statement]) -> Vec<&Identifier> {
+    #[derive(Default)]
+    struct Vars<'a>(Vec<&'a Identifier>);
+    impl<'a> Visit<'a> for Vars<'a> {
+        fn visit_statement(&mut self, statement: &'a Statement) {
+            if let Statement::Variable(declaration)
+            | Statement::For {
+                init: Some(ForInit::Variable(declaration)),
+                ..
+            } = statement
+            {
+                for declarator in &declaration.declarations {
+                    match (&declarator.id, &declarator.init) {
+                        (Pattern::Identifier(id), None)
+                            if declaration.kind == VariableKind::Var =>
+                        {
+                            self.0.push(id)
+                        }
+                        _ => {}
+                    }
+                }
+            }
+            visit::walk_statement(self, statement);
+        }
+
+        fn visit_expression(&mut self, _: &'a Expression) {}
+
+        fn visit_pattern(&mut self, _: &'a Pattern) {}
+
+        fn visit_function(&mut self, _: &'a Function) {}
+
+        fn visit_class(&mut self, _: &'a Class) {}
+    }
+    let mut vars = Vars::default();
+    for statement in statements {
+        vars.visit_statement(statement);
+    }
+    vars.0
+}
+
+/// Whether `this` is used in the function, not counting the functions and classes in it, which
+/// have their own
+fn uses_this(function: &Function) -> bool {
+    struct This(bool);
+    impl<'a> Visit<'a> for This {
+        fn visit_expression(&mut self, e: &'a Expression) {
+            match e {
+                Expression::This { .. } => self.0 = true,
+                e => visit::walk_expression(self, e),
+            }
+        }
+
+        fn visit_function(&mut self, _: &'a Function) {}
+
+        fn visit_class(&mut self, _: &'a Class) {}
+    }
+    let mut this = This(false);
+    for statement in &function.body.body {
+        this.visit_statement(statement);
+    }
+    this.0
+}
+
+/// Whether the function returns a value, not counting the functions and classes in it
+fn returns_value(function: &Function) -> bool {
+    struct Returns(bool);
+    impl<'a> Visit<'a> for Returns {
+        fn visit_statement(&mut self, statement: &'a Statement) {
+            match statement {
+                Statement::Return {
+                    argument: Some(_), ..
+                } => self.0 = true,
+                statement => visit::walk_statement(self, statement),
+            }
+        }
+
+        fn visit_expression(&mut self, _: &'a Expression) {}
+
+        fn visit_function(&mut self, _: &'a Function) {}
+
+        fn visit_class(&mut self, _: &'a Class) {}
+    }
+    let mut returns = Returns(false);
+    for statement in &function.body.body {
+        returns.visit_statement(statement);
+    }
+    returns.0
+}
+
+/// Whether the expression assigns or updates something, which a `lambda` can't do but with `:=`
+/// to a name of its own
+fn assigns(e: &Expression) -> bool {
+    struct Assigns(bool);
+    impl<'a> Visit<'a> for Assigns {
+        fn visit_expression(&mut self, e: &'a Expression) {
+            match e {
+                Expression::Assignment { .. } | Expression::Update { .. } => self.0 = true,
+                e => visit::walk_expression(self, e),
+            }
+        }
+
+        fn visit_function(&mut self, _: &'a Function) {}
+
+        fn visit_arrow(&mut self, _: &'a ArrowFunction) {}
+    }
+    let mut assigns = Assigns(false);
+    assigns.visit_expression(e);
+    assigns.0
+}
+
+/// Whether a `lambda` can have the parameters: names, with defaults that are literals
+fn lambda_parameters(params: &[Pattern]) -> bool {
+    params.iter().all(|param| match param {
+        Pattern::Identifier(_) => true,
+        Pattern::Assignment { left, right, .. } => {
+            matches!(left.as_ref(), Pattern::Identifier(_)) && constant(right).is_some()
+        }
+        _ => false,
+    })
+}
+
+/// The functions `new` is used on by name: the classes, and the factories, which return a value
+/// and so can't be classes
+fn constructed(program: &Program, scopes: &Scopes) -> (HashSet<BindingId>, HashSet<BindingId>) {
+    struct New<'s> {
+        scopes: &'s Scopes,
+        functions: HashSet<BindingId>,
+        returning: HashSet<BindingId>,
+    }
+    impl<'a> Visit<'a> for New<'_> {
+        fn visit_expression(&mut self, e: &'a Expression) {
+            if let Expression::New { callee, .. } = e {
+                if let Expression::Identifier(id) = callee.as_ref() {
+                    self.functions.extend(self.scopes.binding_at(id.span));
+                }
+            }
+            visit::walk_expression(self, e)
+        }
+
+        fn visit_function(&mut self, function: &'a Function) {
+            if let Some(id) = function.id.as_ref().filter(|_| returns_value(function)) {
+                self.returning.extend(self.scopes.binding_at(id.span));
+            }
+            visit::walk_function(self, function)
+        }
+    }
+    let mut new = New {
+        scopes,
+        functions: HashSet::new(),
+        returning: HashSet::new(),
+    };
+    visit::walk_program(&mut new, program);
+    new.functions
+        .into_iter()
+        .partition(|function| !new.returning.contains(function))
+}
+
+/// The async functions declared by name or as the value of a variable
+fn async_functions(program: &Program, scopes: &Scopes) -> HashSet<BindingId> {
+    struct Async<'s> {
+        scopes: &'s Scopes,
+        functions: HashSet<BindingId>,
+    }
+    impl<'a> Visit<'a> for Async<'_> {
+        fn visit_statement(&mut self, statement: &'a Statement) {
+            if let Statement::Variable(declaration) = statement {
+                for declarator in &declaration.declarations {
+                    let is_async = match &declarator.init {
+                        Some(Expression::Function(function)) => function.is_async,
+                        Some(Expression::Arrow(arrow)) => arrow.is_async,
+                        _ => false,
+                    };
+                    if let (Pattern::Identifier(id), true) = (&declarator.id, is_async) {
+                        self.functions.extend(self.scopes.binding_at(id.span));
+                    }
+                }
+            }
+            visit::walk_statement(self, statement)
+        }
+
+        fn visit_function(&mut self, function: &'a Function) {
+            if let Some(id) = function.id.as_ref().filter(|_| function.is_async) {
+                self.functions.extend(self.scopes.binding_at(id.span));
+            }
+            visit::walk_function(self, function)
+        }
+    }
+    let mut functions = Async {
+        scopes,
+        functions: HashSet::new(),
+    };
+    visit::walk_program(&mut functions, program);
+    functions.functions
+}
+
+/// The names of the methods the program defines, in classes, object literals and functions
+/// assigned to properties
+fn defined_methods(program: &Program) -> HashSet<String> {
+    struct Methods(HashSet<String>);
+    impl<'a> Visit<'a> for Methods {
+        fn visit_expression(&mut self, e: &'a Expression) {
+            let function =
+                |e: &Expression| matches!(e, Expression::Function(_) | Expression::Arrow(_));
+            match e {
+                Expression::Object { properties, .. } => {
+                    for property in properties {
+                        if let ObjectProperty::Property(Property {
+                            key: PropertyKey::Identifier(name),
+                            value,
+                            ..
+                        }) = property
+                        {
+                            if function(value) {
+                                self.0.insert(name.name.clone());
+                            }
+                        }
+                    }
+                }
+                Expression::Assignment { left, right, .. } if function(right) => {
+                    if let Pattern::Expression(target) = left.as_ref() {
+                        if let Expression::Member {
+                            property: MemberProperty::Identifier(name),
+                            ..
+                        } = target.as_ref()
+                        {
+                            self.0.insert(name.name.clone());
+                        }
+                    }
+                }
+                _ => {}
+            }
+            visit::walk_expression(self, e)
+        }
+
+        fn visit_class(&mut self, class: &'a Class) {
+            for member in &class.body.body {
+                if let ClassMember::Method(MethodDefinition {
+                    key: PropertyKey::Identifier(name),
+                    kind: MethodKind::Method,
+                    ..
+                }) = member
+                {
+                    self.0.insert(name.name.clone());
+                }
+            }
+            visit::walk_class(self, class)
+        }
+    }
+    let mut methods = Methods(HashSet::new());
+    visit::walk_program(&mut methods, program);
+    methods.0
+}
+
+/// The `let`, `const` and class bindings of loop bodies and heads that a nested function refers
+/// to. Each iteration of the loop has its own, which the functions made in it keep
+fn iteration_bindings(program: &Program, scopes: &Scopes) -> HashSet<BindingId> {
+    struct Loops<'s> {
+        scopes: &'s Scopes,
+        /// How many loops of the function the walk is in
+        depth: usize,
+        /// The scopes in them
+        inside: HashSet<ScopeId>,
+    }
+    impl<'a> Visit<'a> for Loops<'_> {
+        fn visit_statement(&mut self, statement: &'a Statement) {
+            let is_loop = matches!(
+                statement,
+                Statement::For { .. }
+                    | Statement::ForIn { .. }
+                    | Statement::ForOf { .. }
+                    | Statement::While { .. }
+                    | Statement::DoWhile { .. }
+            );
+            if self.depth > 0 || is_loop {
+                let mut spans = vec![statement.span()];
+                if let Statement::Try {
+                    block,
+                    handler,
+                    finalizer,
+                    ..
+                } = statement
+                {
+                    spans.push(block.span);
+                    spans.extend(handler.iter().map(|handler| handler.span));
+                    spans.extend(finalizer.iter().map(|finalizer| finalizer.span));
+                }
+                let scopes = spans
+                    .into_iter()
+                    .filter_map(|span| self.scopes.scope_at(span));
+                self.inside.extend(scopes);
+            }
+            self.depth += usize::from(is_loop);
+            visit::walk_statement(self, statement);
+            self.depth -= usize::from(is_loop);
+        }
+
+        fn visit_function(&mut self, function: &'a Function) {
+            let depth = mem::take(&mut self.depth);
+            visit::walk_function(self, function);
+            self.depth = depth;
+        }
+
+        fn visit_arrow(&mut self, arrow: &'a ArrowFunction) {
+            let depth = mem::take(&mut self.depth);
+            visit::walk_arrow(self, arrow);
+            self.depth = depth;
+        }
+    }
+    let mut loops = Loops {
+        scopes,
+        depth: 0,
+        inside: HashSet::new(),
+    };
+    visit::walk_program(&mut loops, program);
+    scopes
+        .bindings()
+        .filter(|(_, b)| b.captured && b.kind.is_lexical() && loops.inside.contains(&b.scope))
+        .map(|(id, _)| id)
+        .collect()
+}
+
+/// A JavaScript name as a Python one. Python's keywords, and `self` and `cls`, which `this`
+/// becomes, get a trailing `_`
+pub fn python_name(name: &str) -> String {
+    let name = name.replace('$', "_dollar");
+    match PYTHON_KEYWORDS.contains(&name.as_str()) || name == "self" || name == "cls" {
+        true => format!("{}_", name),
+        false => name,
+    }
+}
+
+/// Whether a property name can be a Python attribute as it is: a name but a keyword, and not
+/// starting with `__`, which Python changes in a class
+fn is_attribute(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars.next().is_some_and(|c| c == '_' || c.is_alphabetic())
+        && chars.all(|c| c == '_' || c.is_alphanumeric())
+        && !PYTHON_KEYWORDS.contains(&name)
+        && !name.starts_with("__")
+}
+
+/// `object.name` as a place to assign to, `object["name"]` where the name can't be an attribute
+fn property_place(object: Expr, name: &str) -> Expr {
+    match is_attribute(name) {
+        true => Expr::attribute(object, name),
+        false => Expr::subscript(object, Expr::str(name)),
+    }
+}
+
+/// The Python name of a variable of the program, which mustn't hide a builtin the translation
+/// uses or a name the runtime module has, which the translation may import
+fn variable_name(name: &str) -> String {
+    let name = python_name(name);
+    match PYTHON_BUILTINS.contains(&name.as_str()) || runtime_names().contains(name.as_str()) {
+        true => format!("{}_", name),
+        false => name,
+    }
+}
+
+/// A target an assignment statement can have as it is
+fn is_simple_target(pattern: &Pattern) -> bool {
+    matches!(pattern, Pattern::Identifier(_) | Pattern::Expression(_))
+}
+
+impl Translator<'_> {
+    fn unsupported(&mut self, span: Span, what: &str) -> Expr {
+        self.diagnostics.push(Diagnostic {
+            span,
+            message: format!("unsupported: {}", what),
+        });
+        Expr::none()
+    }
+
+    /// A name no JavaScript name translates to
+    fn temporary(&mut self, base: &str) -> String {
+        loop {
+            self.temporaries += 1;
+            let name = format!("_{}{}", base, self.temporaries);
+            if !self.names.contains(&name) {
+                return name;
+            }
+        }
+    }
+
+    /// `value` if it can be evaluated again, else a temporary assigned it in `out`
+    fn reusable(&mut self, value: Expr, out: &mut Vec<Stmt>) -> Expr {
+        if let Expr::Name(_) = value {
+            return value;
+        }
+        let name = self.temporary("tmp");
+        out.push(Stmt::Assign {
+            targets: vec![Expr::Name(name.clone())],
+            value,
+        });
+        Expr::Name(name)
+    }
+
+    /// What `f` translates, with the statements doing what Python can't inside an expression
+    /// first in `out`
+    fn with_effects<T>(&mut self, out: &mut Vec<Stmt>, f: impl FnOnce(&mut Self) -> T) -> T {
+        let outer = self.effects.replace(mem::take(out));
+        let value = f(self);
+        *out = mem::replace(&mut self.effects, outer).unwrap();
+        value
+    }
+
+    /// An expression, with what Python can't do inside it, an assignment to a property or
+    /// pattern, `++` or `--` of a property, done first by the statements in `out`
+    fn lowered(&mut self, e: &Expression, out: &mut Vec<Stmt>) -> Expr {
+        self.with_effects(out, |this| this.expression(e))
+    }
+
+    /// What `f` translates where it may not be evaluated, or not right away, which has no
+    /// statements before it
+    fn deferred<T>(&mut self, f: impl FnOnce(&mut Self) -> T) -> T {
+        let outer = self.effects.take();
+        let value = f(self);
+        self.effects = outer;
+        value
+    }
+
+    /// Expressions evaluated from left to right. Where one has statements before it, those
+    /// before it that aren't names or constants are evaluated into temporaries ahead of them
+    fn in_order<T>(
+        &mut self,
+        items: impl IntoIterator<Item = T>,
+        mut f: impl FnMut(&mut Self, T) -> Expr,
+    ) -> Vec<Expr> {
+        let mut values: Vec<Expr> = Vec::new();
+        for item in items {
+            let mark = self.effects.as_ref().map_or(0, Vec::len);
+            let value = f(self, item);
+            if self.effects.as_ref().map_or(0, Vec::len) > mark {
+                let mut earlier = Vec::new();
+                for value in &mut values {
+                    if !matches!(value, Expr::Name(_) | Expr::Constant(_)) {
+                        let name = self.temporary("tmp");
+                        let value = mem::replace(value, Expr::Name(name.clone()));
+                        earlier.push(Stmt::assign(&name, value));
+                    }
+                }
+                self.effects.as_mut().unwrap().splice(mark..mark, earlier);
+            }
+            values.push(value);
+        }
+        values
+    }
+
+    /// `value` and what tests it, which evaluates it once, the test comes first
+    fn named(&mut self, value: Expr) -> (Expr, Expr) {
+        if let Expr::Name(_) = value {
+            return (value.clone(), value);
+        }
+        let name = self.temporary("tmp");
+        let test = Expr::NamedExpr {
+            target: name.clone(),
+            value: Box::new(value),
+        };
+        (test, Expr::Name(name))
+    }
+
+    /// A name of the runtime module, which is imported then
+    fn runtime(&mut self, name: &'static str) -> Expr {
+        self.runtime.insert(name);
+        Expr::name(name)
+    }
+
+    fn helper(&mut self, name: &'static str, args: Vec<Expr>) -> Expr {
+        let func = self.runtime(name);
+        Expr::call(func, args)
+    }
+
+    /// `value` as a Python `bool` test with the truth value it has in JavaScript
+    fn truth(&mut self, value: Expr, kind: Kind) -> Expr {
+        match value {
+            Expr::Constant(Constant::Float(_)) => self.helper("js_truthy", vec![value]),
+            Expr::Constant(_) => value,
+            value if kind.truthiness_agrees() => value,
+            value => self.helper("js_truthy", vec![value]),
+        }
+    }
+
+    /// Whether a value is `undefined` or `null`, or neither if `negate`. `test` is the value
+    /// as it is evaluated, `value` as it is read again if a second comparison is needed
+    fn nullish_test(&mut self, test: Expr, value: Expr, kind: Kind, negate: bool) -> Expr {
+        let (is, op) = match negate {
+            false => (CmpOp::Is, BoolOp::Or),
+            true => (CmpOp::IsNot, BoolOp::And),
+        };
+        if kind == Kind::Null {
+            let null = self.runtime("null");
+            return Expr::compare(test, is, null);
+        }
+        let undefined = Expr::compare(test, is, Expr::none());
+        match self.kinds.may_be_null(kind) {
+            true => {
+                let null = self.runtime("null");
+                Expr::BoolOp {
+                    op,
+                    values: vec![undefined, Expr::compare(value, is, null)],
+                }
+            }
+            false => undefined,
+        }
+    }
+
+    /// `value == null`, which evaluates it once
+    fn nullish(&mut self, value: Expr, kind: Kind, negate: bool) -> Expr {
+        let (test, value) = match kind != Kind::Null && self.kinds.may_be_null(kind) {
+            true => self.named(value),
+            false => (value, Expr::none()),
+        };
+        self.nullish_test(test, value, kind, negate)
+    }
+
+    fn name(&self, id: &Identifier) -> String {
+        match self.scopes.binding_at(id.span) {
+            Some(binding) => self.binding_name(binding),
+            None => python_name(&id.name),
+        }
+    }
+
+    /// Whether the identifier is a global the program neither declares nor assigns, which the
+    /// runtime may have
+    fn is_global(&self, id: &Identifier) -> bool {
+        self.scopes.binding_at(id.span).is_none()
+            && !self.scopes.references().iter().any(|reference| {
+                reference.name == id.name
+                    && reference.binding.is_none()
+                    && reference.access != Access::Read
+            })
+    }
+
+    fn is_constructed(&self, id: &Identifier) -> bool {
+        let binding = self.scopes.binding_at(id.span);
+        binding.is_some_and(|binding| self.constructed.contains(&binding))
+    }
+
+    fn is_factory(&self, id: &Identifier) -> bool {
+        let binding = self.scopes.binding_at(id.span);
+        binding.is_some_and(|binding| self.factories.contains(&binding))
+    }
+
+    fn binding_name(&self, binding: BindingId) -> String {
+        match self.renames.get(&binding) {
+            Some(name) => variable_name(name),
+            None => variable_name(&self.scopes.binding(binding).name),
+        }
+    }
+
+    // -----------------------------------------------------------------------------------------
+    // statements
+
+    /// A program or function body. Imports and function declarations go first, JavaScript
+    /// hoists them, and so are `var x` without a value, which are `x = None` up front. A function
+    /// that has methods put on its prototype in the body, or that `new` is used on, is a class,
+    /// unless it returns a value. One whose prototype is made from another's with `Object.create`
+    /// is a subclass of it, after it
+    fn body(&mut self, statements: &[Statement]) -> Vec<Stmt> {
+        let functions: HashSet<&str> = statements
+            .iter()
+            .filter_map(|s| match declared_function(s) {
+                Some(Function { id: Some(id), .. }) => Some(id.name.as_str()),
+                _ => None,
+            })
+            .collect();
+        let mut prototypes: HashMap<&str, Vec<(&Identifier, &Expression)>> = HashMap::new();
+        let mut bases: HashMap<&str, &Identifier> = HashMap::new();
+        let mut rest = Vec::new();
+        for statement in statements {
+            match prototype_part(statement) {
+                Some((class, part)) if functions.contains(class) => match part {
+                    Prototype::Members(members) => {
+                        prototypes.entry(class).or_default().extend(members)
+                    }
+                    Prototype::Base(base) => {
+                        bases.insert(class, base);
+                        prototypes.entry(class).or_default();
+                        prototypes.entry(base.name.as_str()).or_default();
+                    }
+                    Prototype::Constructor => {}
+                },
+                Some((_, Prototype::Base(_))) => {
+                    self.unsupported(
+                        statement.span(),
+                        "Object.create for the prototype of a function not declared here",
+                    );
+                }
+                _ => rest.push(statement),
+            }
+        }
+
+        let (imports, rest): (Vec<_>, Vec<_>) = rest.into_iter().partition(|s| is_import(s));
+        let (mut functions, others): (Vec<_>, Vec<_>) = rest
+            .into_iter()
+            .partition(|s| declared_function(s).is_some());
+        // a base class comes before its subclasses
+        let depth = |statement: &Statement| {
+            let mut class = declared_function(statement).and_then(|f| f.id.as_ref());
+            let mut depth = 0;
+            while let Some(base) = class.and_then(|class| bases.get(class.name.as_str())) {
+                depth += 1;
+                class = Some(base);
+                if depth > bases.len() {
+                    break;
+                }
+            }
+            depth
+        };
+        functions.sort_by_key(|statement| depth(statement));
+        let mut out = Vec::new();
+        let mut vars = Vec::new();
+        for id in uninitialized_vars(statements) {
+            // a parameter or function it redeclares keeps its value
+            let binding = self
+                .scopes
+                .binding_at(id.span)
+                .map(|b| self.scopes.binding(b));
+            let name = self.name(id);
+            if binding.is_some_and(|b| b.kind == BindingKind::Var) && !vars.contains(&name) {
+                vars.push(name);
+            }
+        }
+        for statement in imports {
+            self.statement(statement, &mut out);
+        }
+        out.extend(vars.iter().map(|name| Stmt::assign(name, Expr::none())));
+        for statement in functions.into_iter().chain(others) {
+            match declared_function(statement) {
+                Some(Function { id: Some(id), .. })
+                    if self.is_factory(id) && prototypes.contains_key(id.name.as_str()) =>
+                {
+                    self.unsupported(
+                        id.span,
+                        "prototype methods of a function that returns a value",
+                    );
+                    self.statement(statement, &mut out);
+                }
+                Some(function @ Function { id: Some(id), .. })
+                    if prototypes.contains_key(id.name.as_str()) || self.is_constructed(id) =>
+                {
+                    let members = prototypes
+                        .get(id.name.as_str())
+                        .map_or(&[][..], Vec::as_slice);
+                    let base = bases.get(id.name.as_str()).copied();
+                    let class = self.constructor_class(function, members, base);
+                    out.push(Stmt::located(statement.span(), class));
+                    self.exported(statement);
+                }
+                _ => self.statement(statement, &mut out),
+            }
+        }
+        out
+    }
+
+    fn statements(&mut self, statements: &[Statement]) -> Vec<Stmt> {
+        let mut out = Vec::new();
+        for statement in statements {
+            self.statement(statement, &mut out);
+        }
+        out
+    }
+
+    /// The statements of a loop body or a branch
+    fn branch(&mut self, statement: &Statement) -> Vec<Stmt> {
+        self.statements(std::slice::from_ref(statement))
+    }
+
+    fn statement(&mut self, statement: &Statement, out: &mut Vec<Stmt>) {
+        let outer = mem::take(&mut self.hoisted);
+        let mut translated = Vec::new();
+        self.translate_statement(statement, &mut translated);
+        let hoisted = mem::replace(&mut self.hoisted, outer);
+        let span = statement.span();
+        out.extend(
+            hoisted
+                .into_iter()
+                .chain(translated)
+                .map(|s| Stmt::located(span, s)),
+        );
+    }
+
+    fn translate_statement(&mut self, statement: &Statement, out: &mut Vec<Stmt>) {
+        match statement {
+            Statement::Expression { expression, .. } => self.effect(expression, out),
+            Statement::Block(block) => {
+                for statement in &block.body {
+                    self.statement(statement, out);
+                }
+            }
+            Statement::Empty { .. } | Statement::Debugger { .. } => {}
+            Statement::With { span, .. } => {
+                self.unsupported(*span, "with statements");
+            }
+            Statement::Return { argument, .. } => {
+                let value = argument.as_ref().map(|a| self.lowered(a, out));
+                out.push(Stmt::Return(value));
+            }
+            Statement::Labeled { .. } => {
+                let mut labels = Vec::new();
+                let mut body = statement;
+                while let Statement::Labeled {
+                    label, body: inner, ..
+                } = body
+                {
+                    labels.push(label.name.clone());
+                    body = inner;
+                }
+                self.labeled(body, labels, out);
+            }
+            Statement::Break { label, span } => self.jump(label.as_ref(), false, *span, out),
+            Statement::Continue { label, span } => self.jump(label.as_ref(), true, *span, out),
+            Statement::If {
+                test,
+                consequent,
+                alternate,
+                ..
+            } => {
+                let test = self.test(test, out);
+                let body = suite(self.branch(consequent));
+                let orelse = match alternate {
+                    Some(alternate) => self.branch(alternate),
+                    None => Vec::new(),
+                };
+                out.push(Stmt::If { test, body, orelse });
+            }
+            Statement::Switch { .. }
+            | Statement::While { .. }
+            | Statement::DoWhile { .. }
+            | Statement::For { .. }
+            | Statement::ForIn { .. }
+            | Statement::ForOf { .. } => self.breakable(statement, Vec::new(), out),
+            // throw new Error(...) raises it, other values are wrapped in an exception
+            Statement::Throw { argument, .. } => {
+                let error = matches!(argument, Expression::New { callee, .. }
+                    if matches!(callee.as_ref(), Expression::Identifier(id)
+                        if RUNTIME_ERRORS.contains(&id.name.as_str()) && self.is_global(id)));
+                let exc = self.lowered(argument, out);
+                let exc = match error {
+                    true => exc,
+                    false => self.helper("js_throw", vec![exc]),
+                };
+                out.push(Stmt::Raise(Some(exc)));
+            }
+            Statement::Try {
+                block,
+                handler,
+                finalizer,
+                ..
+            } => {
+                let body = suite(self.statements(&block.body));
+                let handlers = handler.iter().map(|h| self.catch_clause(h)).collect();
+                let finalbody = match finalizer {
+                    Some(finalizer) => suite(self.statements(&finalizer.body)),
+                    None => Vec::new(),
+                };
+                out.push(Stmt::Try {
+                    body,
+                    handlers,
+                    finalbody,
+                });
+            }
+            Statement::Function(function) => {
+                let name = match &function.id {
+                    Some(id) => self.name(id),
+                    None => self.temporary("fn"),
+                };
+                let def = self.function(name, function);
+                self.per_iteration_def(function.span, def, out);
+            }
+            Statement::Variable(declaration) => self.declaration(declaration, out),
+            Statement::Class(class) => {
+                let name = match &class.id {
+                    Some(id) => self.name(id),
+                    None => self.temporary("class"),
+                };
+                self.class(name, class, out);
+            }
+            Statement::Import(import) => self.import(import, out),
+            Statement::ExportNamed(export) => match &export.declaration {
+                Some(declaration) => {
+                    self.translate_statement(declaration, out);
+                    self.exported(statement);
+                }
+                None => self.export(export, out),
+            },
+            Statement::ExportDefault { declaration, .. } => {
+                match declaration {
+                    ExportDefault::Function(function) => {
+                        let name = function
+                            .id
+                            .as_ref()
+                            .map_or("default".into(), |id| self.name(id));
+                        out.push(self.function(name, function));
+                    }
+                    ExportDefault::Class(class) => {
+                        let name = class
+                            .id
+                            .as_ref()
+                            .map_or("default".into(), |id| self.name(id));
+                        self.class(name, class, out);
+                    }
+                    ExportDefault::Expression(e) => {
+                        let value = self.lowered(e, out);
+                        out.push(Stmt::assign("default", value));
+                    }
+                }
+                self.exported(statement);
+            }
+            Statement::ExportAll {
+                exported, source, ..
+            } => self.export_all(exported.as_ref(), source, out),
+        }
+    }
+
+    /// A statement with labels. One that isn't a loop or switch but has a `break` to it becomes a
+    /// loop that runs once
+    fn labeled(&mut self, body: &Statement, labels: Vec<String>, out: &mut Vec<Stmt>) {
+        match body {
+            Statement::Switch { .. }
+            | Statement::While { .. }
+            | Statement::DoWhile { .. }
+            | Statement::For { .. }
+            | Statement::ForIn { .. }
+            | Statement::ForOf { .. } => self.breakable(body, labels, out),
+            _ if jumps_to(body, &labels, false, true) => {
+                self.enter(JumpKind::Block, labels, true);
+                let mut once = self.branch(body);
+                once.push(Stmt::Break);
+                let once = Stmt::While {
+                    test: Expr::bool(true),
+                    body: once,
+                };
+                self.close(once, out);
+            }
+            // a label nothing jumps to changes nothing
+            _ => self.statement(body, out),
+        }
+    }
+
+    /// A loop or a switch, which has `labels`
+    fn breakable(&mut self, statement: &Statement, labels: Vec<String>, out: &mut Vec<Stmt>) {
+        let stmt = match statement {
+            Statement::While { test, body, .. } => {
+                self.enter(JumpKind::Loop, labels, true);
+                let mut prologue = Vec::new();
+                let test = self.test(test, &mut prologue);
+                let body = self.branch(body);
+                self.tested_loop(prologue, Some(test), body)
+            }
+            Statement::DoWhile { body, test, .. } => {
+                let continues = jumps_to(body, &labels, true, false);
+                self.enter(JumpKind::Loop, labels, true);
+                let mut body = self.branch(body);
+                let mut prologue = Vec::new();
+                let test = self.test(test, &mut prologue);
+                if !continues {
+                    // while True: ...; if not test: break
+                    body.append(&mut prologue);
+                    body.push(break_unless(test));
+                    Stmt::While {
+                        test: Expr::bool(true),
+                        body,
+                    }
+                } else {
+                    // `continue` goes on to the test, which the first iteration skips
+                    let first = self.temporary("first");
+                    out.push(Stmt::assign(&first, Expr::bool(true)));
+                    body.insert(0, Stmt::assign(&first, Expr::bool(false)));
+                    if prologue.is_empty() {
+                        // while first or test: first = False; ...
+                        Stmt::While {
+                            test: bool_op(BoolOp::Or, Expr::name(&first), test),
+                            body,
+                        }
+                    } else {
+                        prologue.push(break_unless(test));
+                        body.insert(
+                            0,
+                            Stmt::If {
+                                test: Expr::not(Expr::name(&first)),
+                                body: prologue,
+                                orelse: Vec::new(),
+                            },
+                        );
+                        Stmt::While {
+                            test: Expr::bool(true),
+                            body,
+                        }
+                    }
+                }
+            }
+            Statement::For {
+                init,
+                test,
+                update,
+                body,
+                ..
+            } => {
+                let continues = update.is_some() && jumps_to(body, &labels, true, false);
+                self.enter(JumpKind::Loop, labels, true);
+                match init {
+                    Some(ForInit::Variable(declaration)) => self.declaration(declaration, out),
+                    Some(ForInit::Expression(e)) => self.effect(e, out),
+                    None => {}
+                }
+                let mut prologue = Vec::new();
+                let test = test.as_ref().map(|test| self.test(test, &mut prologue));
+                let mut body = self.branch(body);
+                let mut next = Vec::new();
+                if let Some(update) = update {
+                    self.effect(update, &mut next);
+                }
+                if continues {
+                    // `continue` goes on to the update, which the first iteration skips:
+                    // while True: if not first: update; first = False; ...
+                    let first = self.temporary("first");
+                    out.push(Stmt::assign(&first, Expr::bool(true)));
+                    prologue.splice(
+                        0..0,
+                        [
+                            Stmt::If {
+                                test: Expr::not(Expr::name(&first)),
+                                body: next,
+                                orelse: Vec::new(),
+                            },
+                            Stmt::assign(&first, Expr::bool(false)),
+                        ],
+                    );
+                } else {
+                    // init; while test: ...; update
+                    body.append(&mut next);
+                }
+                self.tested_loop(prologue, test, body)
+            }
+            Statement::ForIn {
+                left, right, body, ..
+            } => {
+                self.enter(JumpKind::Loop, labels, true);
+                let mut prologue = Vec::new();
+                let target = self.for_target(left, &mut prologue);
+                let object = self.expression(right);
+                let iter = self.helper("js_keys", vec![object]);
+                prologue.extend(self.branch(body));
+                Stmt::For {
+                    target,
+                    iter,
+                    body: suite(prologue),
+                    is_async: false,
+                }
+            }
+            Statement::ForOf {
+                left,
+                right,
+                body,
+                is_await,
+                ..
+            } => {
+                self.enter(JumpKind::Loop, labels, true);
+                let mut prologue = Vec::new();
+                let target = self.for_target(left, &mut prologue);
+                let iter = self.expression(right);
+                prologue.extend(self.branch(body));
+                Stmt::For {
+                    target,
+                    iter,
+                    body: suite(prologue),
+                    is_async: *is_await,
+                }
+            }
+            Statement::Switch {
+                discriminant,
+                cases,
+                ..
+            } => return self.switch(discriminant, cases, labels, out),
+            _ => unreachable!(),
+        };
+        self.close(stmt, out);
+    }
+
+    /// `while test:`, or `while True:` with the statements the test needs first
+    fn tested_loop(
+        &mut self,
+        mut prologue: Vec<Stmt>,
+        test: Option<Expr>,
+        body: Vec<Stmt>,
+    ) -> Stmt {
+        match test {
+            Some(test) if prologue.is_empty() => Stmt::While {
+                test,
+                body: suite(body),
+            },
+            test => {
+                prologue.extend(test.map(break_unless));
+                prologue.extend(body);
+                Stmt::While {
+                    test: Expr::bool(true),
+                    body: suite(prologue),
+                }
+            }
+        }
+    }
+
+    fn enter(&mut self, kind: JumpKind, labels: Vec<String>, python_loop: bool) {
+        self.jumps.push(Jump {
+            kind,
+            labels,
+            python_loop,
+            break_flag: None,
+            continue_flag: None,
+            checks: Vec::new(),
+        });
+    }
+
+    /// The Python statement of the innermost statement a jump can leave, with the flags of the
+    /// jumps to it made false before it and the tests of the flags of the jumps through it after
+    fn close(&mut self, stmt: Stmt, out: &mut Vec<Stmt>) {
+        let jump = self.jumps.pop().unwrap();
+        for flag in jump.break_flag.iter().chain(&jump.continue_flag) {
+            out.push(Stmt::assign(flag, Expr::bool(false)));
+        }
+        out.push(stmt);
+        for (flag, body) in jump.checks {
+            out.push(Stmt::If {
+                test: Expr::Name(flag),
+                body,
+                orelse: Vec::new(),
+            });
+        }
+    }
+
+    /// `break` or `continue` to the statement with the label, else to the innermost loop, or
+    /// switch for `break`
+    fn jump(
+        &mut self,
+        label: Option<&Identifier>,
+        continues: bool,
+        span: Span,
+        out: &mut Vec<Stmt>,
+    ) {
+        let target = self.jumps.iter().rposition(|jump| match label {
+            Some(label) => jump.labels.contains(&label.name),
+            None => jump.kind == JumpKind::Loop || (!continues && jump.kind == JumpKind::Switch),
+        });
+        let Some(target) = target.filter(|&t| !continues || self.jumps[t].kind == JumpKind::Loop)
+        else {
+            self.unsupported(span, "a jump to no enclosing statement");
+            return;
+        };
+        let crossed: Vec<usize> = (target + 1..self.jumps.len())
+            .filter(|&i| self.jumps[i].python_loop)
+            .collect();
+        let Some(&outermost) = crossed.first() else {
+            out.push(if continues {
+                Stmt::Continue
+            } else {
+                Stmt::Break
+            });
+            return;
+        };
+        let jump = &self.jumps[target];
+        let flag = match continues {
+            true => jump.continue_flag.clone(),
+            false => jump.break_flag.clone(),
+        };
+        let flag = flag.unwrap_or_else(|| match continues {
+            true => self.temporary("continue"),
+            false => self.temporary("break"),
+        });
+        let jump = &mut self.jumps[target];
+        match continues {
+            true => jump.continue_flag = Some(flag.clone()),
+            false => jump.break_flag = Some(flag.clone()),
+        }
+        out.push(Stmt::assign(&flag, Expr::bool(true)));
+        out.push(Stmt::Break);
+        for i in crossed {
+            // the outermost loop on the way is in the statement jumped to
+            let then = match (i == outermost, continues) {
+                (true, true) => vec![Stmt::assign(&flag, Expr::bool(false)), Stmt::Continue],
+                _ => vec![Stmt::Break],
+            };
+            let checks = &mut self.jumps[i].checks;
+            if !checks.iter().any(|(f, _)| *f == flag) {
+                checks.push((flag.clone(), then));
+            }
+        }
+    }
+
+    /// What `for (... of` or `for (... in` assigns to. A pattern that isn't a list of names is
+    /// destructured at the start of the body, in `prologue`
+    fn for_target(&mut self, left: &ForHead, prologue: &mut Vec<Stmt>) -> Expr {
+        let pattern = match left {
+            ForHead::Variable(declaration) => &declaration.declarations[0].id,
+            ForHead::Pattern(pattern) => pattern,
+        };
+        match pattern {
+            Pattern::Identifier(_) | Pattern::Expression(_) => self.target(pattern),
+            // for (const [key, value] of entries)
+            Pattern::Array { elements, .. }
+                if !elements.is_empty()
+                    && elements
+                        .iter()
+                        .all(|e| matches!(e, Some(Pattern::Identifier(_)))) =>
+            {
+                let names = elements.iter().flatten().map(|e| self.target(e)).collect();
+                Expr::Tuple(names)
+            }
+            _ => {
+                let name = self.temporary("item");
+                self.assign(pattern, Expr::Name(name.clone()), prologue);
+                Expr::Name(name)
+            }
+        }
+    }
+
+    /// An if-elif chain comparing the discriminant with each case, cases without statements share
+    /// those of the next one. Unless a case falls through to the next one or a `break` leaves it
+    /// before its end, then it is a loop that runs once, see `fall_through`
+    fn switch(
+        &mut self,
+        discriminant: &Expression,
+        cases: &[SwitchCase],
+        labels: Vec<String>,
+        out: &mut Vec<Stmt>,
+    ) {
+        let kind = self.kinds.of(discriminant);
+        let value = self.lowered(discriminant, out);
+        let value = self.reusable(value, out);
+
+        let falls = cases[..cases.len().saturating_sub(1)].iter().any(|case| {
+            !matches!(
+                case.consequent.last(),
+                None | Some(
+                    Statement::Return { .. }
+                        | Statement::Throw { .. }
+                        | Statement::Continue { .. }
+                        | Statement::Break { .. }
+                )
+            )
+        });
+        let breaks = cases.iter().any(|case| {
+            let consequent = match case.consequent.split_last() {
+                Some((Statement::Break { label: None, .. }, rest)) => rest,
+                _ => &case.consequent,
+            };
+            consequent
+                .iter()
+                .any(|s| jumps_to(s, &labels, false, false))
+        });
+        if falls || breaks {
+            return self.fall_through((value, kind), cases, labels, out);
+        }
+
+        // (tests, whether default is among them, body)
+        let mut branches = Vec::new();
+        let mut tests = Vec::new();
+        let mut default = false;
+        self.enter(JumpKind::Switch, labels, false);
+        for (i, case) in cases.iter().enumerate() {
+            match &case.test {
+                Some(test) => {
+                    let test = self.operand(test);
+                    let test =
+                        self.operation(BinaryOperator::StrictEqual, (value.clone(), kind), test);
+                    tests.push(test);
+                }
+                None => default = true,
+            }
+            let last = i + 1 == cases.len();
+            if case.consequent.is_empty() && !last {
+                continue;
+            }
+            let consequent = match case.consequent.split_last() {
+                Some((Statement::Break { label: None, .. }, rest)) => rest,
+                _ => &case.consequent,
+            };
+            let body = suite(self.statements(consequent));
+            branches.push((mem::take(&mut tests), mem::take(&mut default), body));
+        }
+        self.jumps.pop();
+
+        // default is the else, wherever it is
+        let mut chain = Vec::new();
+        if let Some(i) = branches.iter().position(|(_, default, _)| *default) {
+            chain = branches.remove(i).2;
+        }
+        for (mut tests, _, body) in branches.into_iter().rev() {
+            let test = match tests.len() {
+                1 => tests.remove(0),
+                _ => Expr::BoolOp {
+                    op: BoolOp::Or,
+                    values: tests,
+                },
+            };
+            chain = vec![Stmt::If {
+                test,
+                body,
+                orelse: chain,
+            }];
+        }
+        out.extend(chain);
+    }
+
+    /// A switch as the number of the first case to run, then a loop that runs once with the
+    /// statements of each case, from that one on:
+    ///
+    /// ```text
+    /// _case1 = 0 if x == a else 1 if x == b else 2
+    /// while True:
+    ///     if _case1 <= 0:
+    ///         ...
+    ///     if _case1 <= 1:
+    ///         ...
+    ///     break
+    /// ```
+    fn fall_through(
+        &mut self,
+        (value, kind): (Expr, Kind),
+        cases: &[SwitchCase],
+        labels: Vec<String>,
+        out: &mut Vec<Stmt>,
+    ) {
+        self.enter(JumpKind::Switch, labels, true);
+        let mut tests = Vec::new();
+        let mut default = cases.len();
+        for (i, case) in cases.iter().enumerate() {
+            match &case.test {
+                Some(test) => {
+                    let test = self.operand(test);
+                    let test =
+                        self.operation(BinaryOperator::StrictEqual, (value.clone(), kind), test);
+                    tests.push((i, test));
+                }
+                None => default = i,
+            }
+        }
+        let first = tests
+            .into_iter()
+            .rev()
+            .fold(int(default as i64), |orelse, (i, test)| Expr::IfExp {
+                test: Box::new(test),
+                body: Box::new(int(i as i64)),
+                orelse: Box::new(orelse),
+            });
+        let case = self.temporary("case");
+        out.push(Stmt::assign(&case, first));
+
+        let mut body = Vec::new();
+        for (i, consequent) in cases.iter().map(|c| &c.consequent).enumerate() {
+            if consequent.is_empty() {
+                continue;
+            }
+            body.push(Stmt::If {
+                test: Expr::compare(Expr::name(&case), CmpOp::LtE, int(i as i64)),
+                body: suite(self.statements(consequent)),
+                orelse: Vec::new(),
+            });
+        }
+        body.push(Stmt::Break);
+        let once = Stmt::While {
+            test: Expr::bool(true),
+            body,
+        };
+        self.close(once, out);
+    }
+
+    /// `except Exception as e:`, where `e` becomes the thrown value. A pattern is destructured
+    /// from it
+    fn catch_clause(&mut self, clause: &CatchClause) -> ExceptHandler {
+        let mut body = Vec::new();
+        let name = match &clause.param {
+            Pattern::Identifier(id) => {
+                let name = self.name(id);
+                if mentions(&clause.body.body, &id.name) {
+                    let caught = self.helper("js_caught", vec![Expr::Name(name.clone())]);
+                    body.push(Stmt::Assign {
+                        targets: vec![Expr::Name(name.clone())],
+                        value: caught,
+                    });
+                }
+                name
+            }
+            pattern => {
+                let name = self.temporary("error");
+                let caught = self.helper("js_caught", vec![Expr::Name(name.clone())]);
+                self.assign(pattern, caught, &mut body);
+                name
+            }
+        };
+        body.extend(self.statements(&clause.body.body));
+        ExceptHandler {
+            typ: Some(Expr::name("Exception")),
+            name: Some(name),
+            body: suite(body),
+        }
+    }
+
+    // -----------------------------------------------------------------------------------------
+    // modules
+
+    /// The Python module of the source of an import or export
+    fn module(&self, source: &Literal) -> ModulePath {
+        match &source.value {
+            LiteralValue::String(specifier) => self.packages.resolve(specifier),
+            _ => unreachable!(),
+        }
+    }
+
+    /// A name of an import or export list, the Python name of what it names
+    fn export_name(&mut self, name: &ModuleExportName) -> Option<String> {
+        match name {
+            ModuleExportName::Identifier(id) => Some(variable_name(&id.name)),
+            ModuleExportName::String(literal) => match &literal.value {
+                LiteralValue::String(s) if module_name(s) == *s => Some(variable_name(s)),
+                _ => {
+                    self.unsupported(literal.span, "an export name that isn't a name");
+                    None
+                }
+            },
+        }
+    }
+
+    /// `import * as name`, or the `import` of a module for what it does if there is no name.
+    /// Relative ones are `from . import module`
+    fn namespace(&mut self, module: ModulePath, name: Option<String>, span: Span) -> Option<Stmt> {
+        if module.level == 0 {
+            let dotted = module.dotted();
+            return Some(Stmt::Import(vec![alias(
+                dotted.clone(),
+                name.unwrap_or(dotted),
+            )]));
+        }
+        let mut names = module.names;
+        let Some(last) = names.pop() else {
+            self.unsupported(span, "an import of the package the module is in");
+            return None;
+        };
+        Some(Stmt::ImportFrom {
+            module: names.join("."),
+            names: vec![alias(last.clone(), name.unwrap_or(last))],
+            level: module.level,
+        })
+    }
+
+    /// The default export of a module is its attribute `default`, the default import of a
+    /// package the package
+    fn import(&mut self, import: &ImportDeclaration, out: &mut Vec<Stmt>) {
+        let module = self.module(&import.source);
+        if import.specifiers.is_empty() {
+            out.extend(self.namespace(module, None, import.span));
+            return;
+        }
+        let mut names = Vec::new();
+        for specifier in &import.specifiers {
+            match specifier {
+                ImportSpecifier::Default(local) if module.level == 0 => {
+                    let name = alias(module.dotted(), self.name(local));
+                    out.push(Stmt::Import(vec![name]));
+                }
+                ImportSpecifier::Default(local) => {
+                    names.push(alias("default".to_string(), self.name(local)))
+                }
+                ImportSpecifier::Namespace { local, span } => {
+                    let name = self.name(local);
+                    out.extend(self.namespace(module.clone(), Some(name), *span));
+                }
+                ImportSpecifier::Named {
+                    imported, local, ..
+                } => {
+                    if let Some(imported) = self.export_name(imported) {
+                        names.push(alias(imported, self.name(local)));
+                    }
+                }
+            }
+        }
+        if !names.is_empty() {
+            out.push(Stmt::ImportFrom {
+                module: module.dotted(),
+                names,
+                level: module.level,
+            });
+        }
+    }
+
+    /// The names a declaration exports go in `__all__`, `export default` of a named function or
+    /// class is `default = name` at the end
+    fn exported(&mut self, statement: &Statement) {
+        if !matches!(
+            statement,
+            Statement::ExportNamed(_) | Statement::ExportDefault { .. }
+        ) {
+            return;
+        }
+        self.exports.get_or_insert_with(Vec::new);
+        match statement {
+            Statement::ExportNamed(ExportNamedDeclaration {
+                declaration: Some(declaration),
+                ..
+            }) => {
+                let mut names = Vec::new();
+                match declaration.as_ref() {
+                    Statement::Function(Function { id: Some(id), .. })
+                    | Statement::Class(Class { id: Some(id), .. }) => names.push(id),
+                    Statement::Variable(declaration) => {
+                        for declarator in &declaration.declarations {
+                            bound_names(&declarator.id, &mut names);
+                        }
+                    }
+                    _ => {}
+                }
+                let names: Vec<_> = names
+                    .into_iter()
+                    .map(|id| Expr::str(&self.name(id)))
+                    .collect();
+                self.exports.as_mut().unwrap().extend(names);
+            }
+            Statement::ExportDefault {
+                declaration:
+                    ExportDefault::Function(Function { id: Some(id), .. })
+                    | ExportDefault::Class(Class { id: Some(id), .. }),
+                ..
+            } => {
+                let name = Expr::Name(self.name(id));
+                self.aliases.push(Stmt::assign("default", name));
+            }
+            _ => {}
+        }
+    }
+
+    /// `export {a, b as c}` puts `a` and `c` in `__all__`, with `c = b` at the end. With a source
+    /// it is an import
+    fn export(&mut self, export: &ExportNamedDeclaration, out: &mut Vec<Stmt>) {
+        self.exports.get_or_insert_with(Vec::new);
+        let mut names = Vec::new();
+        for specifier in &export.specifiers {
+            let (Some(local), Some(exported)) = (
+                self.export_name(&specifier.local),
+                self.export_name(&specifier.exported),
+            ) else {
+                continue;
+            };
+            let local = match &specifier.local {
+                ModuleExportName::Identifier(id) if export.source.is_none() => self.name(id),
+                _ => local,
+            };
+            if exported != "default" {
+                self.exports.as_mut().unwrap().push(Expr::str(&exported));
+            }
+            match export.source {
+                Some(_) => names.push(alias(local, exported)),
+                None if local != exported => {
+                    let value = Expr::Name(local);
+                    self.aliases.push(Stmt::assign(&exported, value));
+                }
+                None => {}
+            }
+        }
+        if let (Some(source), false) = (&export.source, names.is_empty()) {
+            let module = self.module(source);
+            out.push(Stmt::ImportFrom {
+                module: module.dotted(),
+                names,
+                level: module.level,
+            });
+        }
+    }
+
+    /// `export * from "./m"` is `from .m import *`, and `__all__` has the `__all__` of `m`.
+    /// `export * as ns from` is a namespace import
+    fn export_all(
+        &mut self,
+        exported: Option<&ModuleExportName>,
+        source: &Literal,
+        out: &mut Vec<Stmt>,
+    ) {
+        self.exports.get_or_insert_with(Vec::new);
+        let module = self.module(source);
+        if let Some(exported) = exported {
+            if let Some(name) = self.export_name(exported) {
+                out.extend(self.namespace(module, Some(name.clone()), source.span));
+                self.exports.as_mut().unwrap().push(Expr::str(&name));
+            }
+            return;
+        }
+        let import = |names| Stmt::ImportFrom {
+            module: module.dotted(),
+            names,
+            level: module.level,
+        };
+        out.push(import(vec![alias("*".to_string(), "*".to_string())]));
+        // a package may have no __all__
+        if module.level > 0 {
+            let all = self.temporary("all");
+            out.push(import(vec![alias("__all__".to_string(), all.clone())]));
+            let names = Expr::Starred(Box::new(Expr::Name(all)));
+            self.exports.as_mut().unwrap().push(names);
+        }
+    }
+
+    fn declaration(&mut self, declaration: &VariableDeclaration, out: &mut Vec<Stmt>) {
+        for declarator in &declaration.declarations {
+            // `var x` is hoisted, it leaves x as it is where it is
+            if declaration.kind == VariableKind::Var
+                && declarator.init.is_none()
+                && matches!(declarator.id, Pattern::Identifier(_))
+            {
+                continue;
+            }
+            // const f = function () {}  =>  def f():
+            if let Pattern::Identifier(id) = &declarator.id {
+                let name = self.name(id);
+                let def = match &declarator.init {
+                    Some(Expression::Function(function)) if function.id.is_none() => {
+                        Some((function.span, self.function(name, function)))
+                    }
+                    Some(Expression::Arrow(arrow)) => {
+                        let body = match &arrow.body {
+                            ArrowBody::Block(block) => Some(FunctionBody::Statements(&block.body)),
+                            // one that can't be a lambda
+                            ArrowBody::Expression(e)
+                                if arrow.is_async
+                                    || assigns(e)
+                                    || !lambda_parameters(&arrow.params) =>
+                            {
+                                Some(FunctionBody::Expression(e))
+                            }
+                            ArrowBody::Expression(_) => None,
+                        };
+                        let (params, is_async, span) = (&arrow.params, arrow.is_async, arrow.span);
+                        body.map(|body| (span, self.def(name, span, params, body, is_async, None)))
+                    }
+                    _ => None,
+                };
+                if let Some((span, def)) = def {
+                    self.per_iteration_def(span, def, out);
+                    continue;
+                }
+            }
+            let value = match &declarator.init {
+                Some(init) => self.lowered(init, out),
+                None => Expr::none(),
+            };
+            self.assign(&declarator.id, value, out);
+        }
+    }
+
+    /// Statements assigning `value` to a target or pattern. Array patterns take their parts by
+    /// index from what the runtime's `js_unpack` makes a list of, unless the value is a long
+    /// enough list literal, object patterns by key with `js_get`
+    fn assign(&mut self, pattern: &Pattern, value: Expr, out: &mut Vec<Stmt>) {
+        match pattern {
+            Pattern::Identifier(_) | Pattern::Expression(_) => {
+                let target = self.target(pattern);
+                out.push(Stmt::Assign {
+                    targets: vec![target],
+                    value,
+                });
+            }
+            Pattern::Assignment { left, right, .. } => {
+                let value = self.reusable(value, out);
+                let default = self.expression(right);
+                let value = coalesce(value.clone(), value, default);
+                self.assign(left, value, out);
+            }
+            Pattern::Array { elements, .. } => {
+                let rest = matches!(elements.last(), Some(Some(Pattern::Rest { .. })));
+                let count = elements.len() - usize::from(rest);
+                let value = match value {
+                    Expr::List(items)
+                        if !rest
+                            && items.len() >= count
+                            && !items.iter().any(|e| matches!(e, Expr::Starred(_))) =>
+                    {
+                        Expr::List(items)
+                    }
+                    value => {
+                        let mut args = vec![value, int(count as i64)];
+                        if rest {
+                            args.push(Expr::bool(true));
+                        }
+                        self.helper("js_unpack", args)
+                    }
+                };
+                let source = self.reusable(value, out);
+                for (i, element) in elements.iter().enumerate() {
+                    match element {
+                        None => {}
+                        // the list of the rest comes last
+                        Some(Pattern::Rest { argument, .. }) => {
+                            let rest = Expr::subscript(source.clone(), int(i as i64));
+                            self.assign(argument, rest, out);
+                        }
+                        Some(element) => {
+                            let item = Expr::subscript(source.clone(), int(i as i64));
+                            self.assign(element, item, out);
+                        }
+                    }
+                }
+            }
+            Pattern::Object { properties, .. } => {
+                let source = self.reusable(value, out);
+                // the keys taken so far, which the rest leaves out
+                let mut keys = Vec::new();
+                let rest = properties
+                    .iter()
+                    .any(|p| matches!(p, PatternProperty::Rest { .. }));
+                for property in properties {
+                    match property {
+                        PatternProperty::Property { key, value, .. } => {
+                            let key = match self.property_key(key) {
+                                key @ Expr::Constant(_) => key,
+                                key if rest => self.reusable(key, out),
+                                key => key,
+                            };
+                            keys.push(key.clone());
+                            // a missing property is undefined, left to the default if any
+                            let item = self.helper("js_get", vec![source.clone(), key]);
+                            self.assign(value, item, out);
+                        }
+                        PatternProperty::Rest { argument, .. } => {
+                            let mut args = vec![source.clone()];
+                            args.append(&mut keys);
+                            let rest = self.helper("js_rest", args);
+                            self.assign(argument, rest, out);
+                        }
+                    }
+                }
+            }
+            Pattern::Rest { span, .. } => {
+                self.unsupported(*span, "rest elements outside of a list");
+            }
+        }
+    }
+
+    /// A name or property to assign to
+    fn target(&mut self, pattern: &Pattern) -> Expr {
+        match pattern {
+            Pattern::Identifier(id) => Expr::Name(self.name(id)),
+            Pattern::Expression(e) => self.place(e),
+            pattern => self.unsupported(pattern.span(), "a pattern here"),
+        }
+    }
+
+    /// A target that is read before it is written, its object and key evaluated once, into
+    /// temporaries in `out` unless they are names or constants
+    fn stable_target(&mut self, pattern: &Pattern, out: &mut Vec<Stmt>) -> Expr {
+        match pattern {
+            Pattern::Expression(e) => self.stable_place(e, out),
+            pattern => self.target(pattern),
+        }
+    }
+
+    /// `place`, its object and key evaluated once, into temporaries in `out` unless they are
+    /// names or constants
+    fn stable_place(&mut self, e: &Expression, out: &mut Vec<Stmt>) -> Expr {
+        let Expression::Member {
+            object,
+            property,
+            optional: false,
+            ..
+        } = e
+        else {
+            return self.place(e);
+        };
+        let object = self.expression(object);
+        let object = self.reusable(object, out);
+        match property {
+            MemberProperty::Identifier(id) => property_place(object, &id.name),
+            MemberProperty::Computed(key) => {
+                let key = match self.expression(key) {
+                    key @ Expr::Constant(_) => key,
+                    key => self.reusable(key, out),
+                };
+                Expr::subscript(object, key)
+            }
+            MemberProperty::PrivateName(id) => Expr::attribute(object, &private(id)),
+        }
+    }
+
+    fn target_kind(&self, pattern: &Pattern) -> Kind {
+        match pattern {
+            Pattern::Identifier(id) => self.kinds.variable(&id.name),
+            Pattern::Expression(e) => self.kinds.of(e),
+            _ => Kind::Unknown,
+        }
+    }
+
+    /// A property as a place to assign to, a plain attribute or subscript
+    fn place(&mut self, e: &Expression) -> Expr {
+        match e {
+            Expression::Member {
+                object,
+                property,
+                optional: false,
+                ..
+            } => {
+                let object = self.expression(object);
+                match property {
+                    MemberProperty::Identifier(id) => property_place(object, &id.name),
+                    MemberProperty::Computed(key) => Expr::subscript(object, self.expression(key)),
+                    MemberProperty::PrivateName(id) => Expr::attribute(object, &private(id)),
+                }
+            }
+            e => self.expression(e),
+        }
+    }
+
+    /// An expression evaluated for its side effects, as statements. Assignments, updates and
+    /// logical operators read better as statements than as expressions
+    fn effect(&mut self, e: &Expression, out: &mut Vec<Stmt>) {
+        match e {
+            Expression::Assignment {
+                operator,
+                left,
+                right,
+                ..
+            } => self.assignment(*operator, left, right, out),
+            Expression::Update {
+                operator, argument, ..
+            } => {
+                let kind = self.kinds.of(argument);
+                let target = self.stable_place(argument, out);
+                let value = self.increment(*operator, target.clone(), kind);
+                out.push(update(target, value));
+            }
+            Expression::Sequence { expressions, .. } => {
+                for e in expressions {
+                    self.effect(e, out);
+                }
+            }
+            Expression::Unary {
+                operator: UnaryOperator::Delete,
+                argument,
+                span,
+            } => match argument.as_ref() {
+                Expression::Member {
+                    property: MemberProperty::Identifier(_),
+                    ..
+                } => {
+                    let target = self.place(argument);
+                    out.push(Stmt::Delete(vec![target]));
+                }
+                Expression::Member { .. } => {
+                    let value = self.expression(e);
+                    out.push(Stmt::Expr(value));
+                }
+                _ => {
+                    self.unsupported(*span, "delete of something other than a property");
+                }
+            },
+            Expression::Unary {
+                operator: UnaryOperator::Void,
+                argument,
+                ..
+            } => self.effect(argument, out),
+            Expression::Call {
+                callee,
+                arguments,
+                optional: false,
+                ..
+            } if self.is_method_call(callee) => {
+                let call = self.method_call(callee, arguments, true, &mut Vec::new());
+                out.push(Stmt::Expr(call));
+            }
+            // main()  =>  js_run(main()), nothing awaits it
+            Expression::Call {
+                callee,
+                optional: false,
+                ..
+            } if self.is_async_entry(callee) => {
+                let call = self.expression(e);
+                out.push(Stmt::Expr(self.helper("js_run", vec![call])));
+            }
+            // a && b()  =>  if a: b()
+            Expression::Logical {
+                operator,
+                left,
+                right,
+                ..
+            } => {
+                let test = match operator {
+                    LogicalOperator::And => self.test(left, out),
+                    LogicalOperator::Or => Expr::not(self.test(left, out)),
+                    LogicalOperator::Coalesce => {
+                        let kind = self.kinds.of(left);
+                        let left = self.lowered(left, out);
+                        self.nullish(left, kind, false)
+                    }
+                };
+                let mut body = Vec::new();
+                self.effect(right, &mut body);
+                out.push(Stmt::If {
+                    test,
+                    body,
+                    orelse: Vec::new(),
+                });
+            }
+            Expression::Conditional {
+                test,
+                consequent,
+                alternate,
+                ..
+            } => {
+                let test = self.test(test, out);
+                let mut body = Vec::new();
+                self.effect(consequent, &mut body);
+                let mut orelse = Vec::new();
+                self.effect(alternate, &mut orelse);
+                out.push(Stmt::If { test, body, orelse });
+            }
+            e => {
+                let value = self.lowered(e, out);
+                out.push(Stmt::Expr(value));
+            }
+        }
+    }
+
+    fn assignment(
+        &mut self,
+        operator: AssignmentOperator,
+        left: &Pattern,
+        right: &Expression,
+        out: &mut Vec<Stmt>,
+    ) {
+        if let Some(op) = compound(operator) {
+            let target = self.stable_target(left, out);
+            let kind = self.target_kind(left);
+            let right = (self.lowered(right, out), self.kinds.of(right));
+            let value = self.operation(op, (target.clone(), kind), right);
+            out.push(update(target, value));
+            return;
+        }
+        if operator == AssignmentOperator::Assign {
+            self.plain_assignment(left, right, out);
+            return;
+        }
+        let kind = self.target_kind(left);
+        let target = self.stable_target(left, out);
+        let test = match operator {
+            // a ||= b  =>  if not a: a = b
+            AssignmentOperator::OrAssign => Expr::not(self.truth(target.clone(), kind)),
+            AssignmentOperator::AndAssign => self.truth(target.clone(), kind),
+            _ => self.nullish_test(target.clone(), target.clone(), kind, false),
+        };
+        let mut body = Vec::new();
+        let value = self.lowered(right, &mut body);
+        body.push(Stmt::Assign {
+            targets: vec![target],
+            value,
+        });
+        out.push(Stmt::If {
+            test,
+            body,
+            orelse: Vec::new(),
+        });
+    }
+
+    /// `a = b = c` is one statement, so is `[a, b] = [b, a]`
+    fn plain_assignment(&mut self, left: &Pattern, right: &Expression, out: &mut Vec<Stmt>) {
+        if is_simple_target(left) {
+            let mut targets = vec![self.target(left)];
+            let mut value = right;
+            while let Expression::Assignment {
+                operator: AssignmentOperator::Assign,
+                left,
+                right,
+                ..
+            } = value
+            {
+                if !is_simple_target(left) {
+                    break;
+                }
+                targets.push(self.target(left));
+                value = right;
+            }
+            let value = self.lowered(value, out);
+            out.push(Stmt::Assign { targets, value });
+            return;
+        }
+        if let (
+            Pattern::Array { elements, .. },
+            Expression::Array {
+                elements: values, ..
+            },
+        ) = (left, right)
+        {
+            let swap = elements.len() == values.len()
+                && elements
+                    .iter()
+                    .all(|e| e.as_ref().is_some_and(is_simple_target))
+                && values
+                    .iter()
+                    .all(|v| matches!(v, Some(ExpressionOrSpread::Expression(_))));
+            if swap {
+                let targets = elements.iter().flatten().map(|e| self.target(e)).collect();
+                let values = values.iter().flatten().map(|v| self.argument(v)).collect();
+                out.push(Stmt::Assign {
+                    targets: vec![Expr::Tuple(targets)],
+                    value: Expr::Tuple(values),
+                });
+                return;
+            }
+        }
+        let value = self.lowered(right, out);
+        self.assign(left, value, out);
+    }
+
+    // -----------------------------------------------------------------------------------------
+    // functions and classes
+
+    /// The `def` of a function. One that uses `this` gets it as `self`, its first argument, which
+    /// the runtime's `js_function` sets to what it is called on
+    fn function(&mut self, name: String, function: &Function) -> Stmt {
+        let this = uses_this(function);
+        let mut def = self.def(
+            name,
+            function.span,
+            &function.params,
+            FunctionBody::Statements(&function.body.body),
+            function.is_async,
+            this.then_some("self"),
+        );
+        if let (true, Stmt::FunctionDef { decorators, .. }) = (this, &mut def) {
+            decorators.push(self.runtime("js_function"));
+        }
+        def
+    }
+
+    /// A `def`. `this` is the first argument it takes for `this` in the body, `None` keeps what
+    /// `this` is outside, as in arrow functions. The variables of enclosing functions it assigns
+    /// are `nonlocal`, those of the program `global`
+    fn def(
+        &mut self,
+        name: String,
+        span: Span,
+        params: &[Pattern],
+        body: FunctionBody,
+        is_async: bool,
+        this: Option<&'static str>,
+    ) -> Stmt {
+        let outer_this = self.this;
+        let outer_jumps = mem::take(&mut self.jumps);
+        let outer_hoisted = mem::take(&mut self.hoisted);
+        let outer_effects = self.effects.take();
+        self.this = this.or(outer_this);
+
+        let mut statements = Vec::new();
+        let mut args = self.parameters(params, &mut statements);
+        // the parameters may have function expressions in them
+        statements.splice(0..0, mem::take(&mut self.hoisted));
+        match body {
+            FunctionBody::Statements(body) => statements.extend(self.body(body)),
+            FunctionBody::Expression(e) => {
+                let mut effects = Vec::new();
+                let value = self.lowered(e, &mut effects);
+                statements.append(&mut self.hoisted);
+                statements.append(&mut effects);
+                statements.push(Stmt::Return(Some(value)));
+            }
+        }
+        if let Some(this) = this {
+            args.args.insert(0, this.to_string());
+        }
+        if let Some(scope) = self.scopes.scope_at(span) {
+            let (mut globals, mut nonlocals) = (Vec::new(), Vec::new());
+            for binding in self.scopes.outer_writes(scope) {
+                let function = self.scopes.function_of(self.scopes.binding(binding).scope);
+                match function == self.scopes.root() {
+                    true => globals.push(self.binding_name(binding)),
+                    false => nonlocals.push(self.binding_name(binding)),
+                }
+            }
+            if !nonlocals.is_empty() {
+                statements.insert(0, Stmt::Nonlocal(nonlocals));
+            }
+            if !globals.is_empty() {
+                statements.insert(0, Stmt::Global(globals));
+            }
+        }
+
+        self.this = outer_this;
+        self.jumps = outer_jumps;
+        self.hoisted = outer_hoisted;
+        self.effects = outer_effects;
+        Stmt::FunctionDef {
+            name,
+            args,
+            body: suite(statements),
+            decorators: Vec::new(),
+            is_async,
+        }
+    }
+
+    /// Python parameters for `params`. A default that isn't a literal would be evaluated once in
+    /// Python, it is set in `prologue` instead, as are the parts of a pattern
+    fn parameters(&mut self, params: &[Pattern], prologue: &mut Vec<Stmt>) -> Arguments {
+        let mut args = Arguments::default();
+        for param in params {
+            let (target, default) = match param {
+                Pattern::Assignment { left, right, .. } => (left.as_ref(), Some(right)),
+                param => (param, None),
+            };
+            if let Pattern::Rest { argument, .. } = target {
+                // *args is a tuple
+                let name = match argument.as_ref() {
+                    Pattern::Identifier(id) => self.name(id),
+                    _ => self.temporary("args"),
+                };
+                let list = Expr::call(Expr::name("list"), vec![Expr::Name(name.clone())]);
+                self.assign(argument, list, prologue);
+                args.vararg = Some(name);
+                continue;
+            }
+            let name = match target {
+                Pattern::Identifier(id) => self.name(id),
+                _ => self.temporary("arg"),
+            };
+            let default = match default {
+                Some(default) => match constant(default) {
+                    Some(value) => Some(value),
+                    None => {
+                        let value = self.expression(default);
+                        prologue.push(Stmt::If {
+                            test: Expr::compare(Expr::Name(name.clone()), CmpOp::Is, Expr::none()),
+                            body: vec![Stmt::Assign {
+                                targets: vec![Expr::Name(name.clone())],
+                                value,
+                            }],
+                            orelse: Vec::new(),
+                        });
+                        Some(Expr::none())
+                    }
+                },
+                // no parameter without a default may follow one with a default
+                None if !args.defaults.is_empty() => Some(Expr::none()),
+                None => None,
+            };
+            args.args.push(name.clone());
+            args.defaults.extend(default);
+            if !matches!(target, Pattern::Identifier(_)) {
+                self.assign(target, Expr::Name(name), prologue);
+            }
+        }
+        args
+    }
+
+    /// A `lambda` if the body is an expression, else a `def` before the statement
+    fn arrow(&mut self, arrow: &ArrowFunction) -> Expr {
+        let body = match &arrow.body {
+            ArrowBody::Block(block) => FunctionBody::Statements(&block.body),
+            ArrowBody::Expression(e) if arrow.is_async => FunctionBody::Expression(e),
+            ArrowBody::Expression(e) => match self.lambda(&arrow.params, e) {
+                Some(lambda) => return self.per_iteration(arrow.span, lambda),
+                None => FunctionBody::Expression(e),
+            },
+        };
+        let name = self.temporary("fn");
+        let (params, is_async) = (&arrow.params, arrow.is_async);
+        let def = self.def(name.clone(), arrow.span, params, body, is_async, None);
+        let mut definitions = Vec::new();
+        self.per_iteration_def(arrow.span, def, &mut definitions);
+        self.hoisted.append(&mut definitions);
+        Expr::Name(name)
+    }
+
+    /// The bindings of loop iterations that the function at `span` keeps: those it reads, and
+    /// those it assigns that nothing but its own body uses
+    fn iteration_captures(&self, span: Span) -> Vec<BindingId> {
+        let Some(function) = self.scopes.scope_at(span) else {
+            return Vec::new();
+        };
+        let (mut captures, mut assigned) = (Vec::new(), Vec::new());
+        for reference in self.scopes.references() {
+            let Some(binding) = reference.binding else {
+                continue;
+            };
+            if !self.iteration.contains(&binding)
+                || !self.scopes.encloses(function, reference.function)
+                || self
+                    .scopes
+                    .encloses(function, self.scopes.binding(binding).scope)
+            {
+                continue;
+            }
+            if reference.access != Access::Read {
+                assigned.push(binding);
+            }
+            if !captures.contains(&binding) {
+                captures.push(binding);
+            }
+        }
+        let own = |binding: &BindingId| {
+            let mut references = self.scopes.references().iter();
+            references.all(|r| r.binding != Some(*binding) || r.function == function)
+        };
+        captures.retain(|binding| !assigned.contains(binding) || own(binding));
+        captures
+    }
+
+    /// A function made in a loop, keeping the bindings of the iteration: `lambda: i` is
+    /// `(lambda i: lambda: i)(i)`, a def before the statement is made by a function of them, as
+    /// `per_iteration_def` does
+    fn per_iteration(&mut self, span: Span, function: Expr) -> Expr {
+        let captures = self.iteration_captures(span);
+        if captures.is_empty() {
+            return function;
+        }
+        match function {
+            Expr::Lambda { .. } => {
+                let names: Vec<_> = captures.iter().map(|&b| self.binding_name(b)).collect();
+                let factory = Expr::Lambda {
+                    args: Arguments {
+                        args: names.clone(),
+                        ..Arguments::default()
+                    },
+                    body: Box::new(function),
+                };
+                Expr::call(factory, names.into_iter().map(Expr::Name).collect())
+            }
+            function => {
+                let def = self.hoisted.pop().unwrap();
+                let mut definitions = Vec::new();
+                self.per_iteration_def(span, def, &mut definitions);
+                self.hoisted.append(&mut definitions);
+                function
+            }
+        }
+    }
+
+    /// `def`, of a function made in a loop, in `out`. One that keeps bindings of the iteration is
+    /// made by a function of them: `def _make1(i): def f(): ...; return f`, then `f = _make1(i)`.
+    /// Those it assigns are `nonlocal` then, the parameters of the maker
+    fn per_iteration_def(&mut self, span: Span, mut def: Stmt, out: &mut Vec<Stmt>) {
+        let captures = self.iteration_captures(span);
+        let Stmt::FunctionDef { name, body, .. } = &mut def else {
+            unreachable!("a function is a def")
+        };
+        if captures.is_empty() {
+            out.push(def);
+            return;
+        }
+        let name = name.clone();
+        let names: Vec<_> = captures.iter().map(|&b| self.binding_name(b)).collect();
+        let mut nonlocals = Vec::new();
+        for statement in body.iter_mut() {
+            if let Stmt::Global(globals) = statement {
+                let (own, others) = globals.drain(..).partition(|g| names.contains(g));
+                nonlocals = own;
+                *globals = others;
+            }
+        }
+        body.retain(|s| !matches!(s, Stmt::Global(globals) if globals.is_empty()));
+        if !nonlocals.is_empty() {
+            match body.iter_mut().find(|s| matches!(s, Stmt::Nonlocal(_))) {
+                Some(Stmt::Nonlocal(names)) => names.append(&mut nonlocals),
+                _ => body.insert(0, Stmt::Nonlocal(nonlocals)),
+            }
+        }
+        let factory = self.temporary("make");
+        out.push(Stmt::FunctionDef {
+            name: factory.clone(),
+            args: Arguments {
+                args: names.clone(),
+                ..Arguments::default()
+            },
+            body: vec![def, Stmt::Return(Some(Expr::Name(name.clone())))],
+            decorators: Vec::new(),
+            is_async: false,
+        });
+        let call = Expr::call(
+            Expr::Name(factory),
+            names.into_iter().map(Expr::Name).collect(),
+        );
+        out.push(Stmt::assign(&name, call));
+    }
+
+    /// A `lambda` returning `body`, if the parameters are names and it assigns nothing
+    fn lambda(&mut self, params: &[Pattern], body: &Expression) -> Option<Expr> {
+        if !lambda_parameters(params) || assigns(body) {
+            return None;
+        }
+        let args = self.parameters(params, &mut Vec::new());
+        let outer = mem::take(&mut self.hoisted);
+        let body = self.expression(body);
+        let mut inner = mem::replace(&mut self.hoisted, outer);
+        if inner.is_empty() {
+            return Some(Expr::Lambda {
+                args,
+                body: Box::new(body),
+            });
+        }
+        // the definitions in it need the parameters
+        let name = self.temporary("fn");
+        inner.push(Stmt::Return(Some(body)));
+        self.hoisted.push(Stmt::FunctionDef {
+            name: name.clone(),
+            args,
+            body: inner,
+            decorators: Vec::new(),
+            is_async: false,
+        });
+        Some(Expr::Name(name))
+    }
+
+    /// A Python class, in `out`. The constructor is `__init__`, which sets the instance fields
+    /// first, after `super().__init__` in a subclass. Static fields are class attributes, static
+    /// methods that use `this` class methods, accessors are properties, of a `JSStatic` class if
+    /// they are static, and static blocks are methods called with the class after it. `#names`
+    /// are `__names`, which Python keeps private to the class as well
+    fn class(&mut self, name: String, class: &Class, out: &mut Vec<Stmt>) {
+        let mut bases: Vec<_> = class
+            .super_class
+            .iter()
+            .map(|s| self.expression(s))
+            .collect();
+        let mut body = Vec::new();
+        let mut constructor = None;
+        let mut fields = Vec::new();
+        let mut accessors = HashSet::new();
+        let mut static_blocks = Vec::new();
+        for member in &class.body.body {
+            match member {
+                ClassMember::Method(method) => {
+                    if method.kind == MethodKind::Constructor {
+                        constructor = Some(&method.value);
+                        continue;
+                    }
+                    let Some(name) = self.member_name(&method.key, method.span) else {
+                        continue;
+                    };
+                    if method.kind == MethodKind::Method {
+                        // a static method that uses `this` is a class method, `this` is `cls`
+                        let this = match (method.is_static, uses_this(&method.value)) {
+                            (false, _) => Some("self"),
+                            (true, true) => Some("cls"),
+                            (true, false) => None,
+                        };
+                        let mut def = self.method(name, &method.value, this);
+                        if let (Stmt::FunctionDef { decorators, .. }, true) =
+                            (&mut def, method.is_static)
+                        {
+                            let decorator = match this {
+                                Some(_) => "classmethod",
+                                None => "staticmethod",
+                            };
+                            decorators.push(Expr::name(decorator));
+                        }
+                        body.push(Stmt::located(method.span, def));
+                    } else if accessors.insert((name.clone(), method.is_static)) {
+                        // the getter and setter of a name go together, where the first one is
+                        let accessor = |kind| {
+                            class.body.body.iter().find_map(|member| match member {
+                                ClassMember::Method(m)
+                                    if m.kind == kind
+                                        && m.is_static == method.is_static
+                                        && same_key(&m.key, &method.key) =>
+                                {
+                                    Some(&m.value)
+                                }
+                                _ => None,
+                            })
+                        };
+                        let get = accessor(MethodKind::Get);
+                        let set = accessor(MethodKind::Set);
+                        let decorator = match method.is_static {
+                            true => self.runtime("js_static_property"),
+                            false => Expr::name("property"),
+                        };
+                        self.property(name, get, set, decorator, &mut body);
+                    }
+                }
+                ClassMember::Field(field) => {
+                    let Some(name) = self.member_name(&field.key, field.span) else {
+                        continue;
+                    };
+                    if field.is_static {
+                        let value = field.value.as_ref().map(|v| self.expression(v));
+                        body.push(Stmt::assign(&name, value.unwrap_or_else(Expr::none)));
+                        continue;
+                    }
+                    // translated as in a method, with the function expressions in them
+                    let outer_this = self.this.replace("self");
+                    let outer_hoisted = mem::take(&mut self.hoisted);
+                    let value = field.value.as_ref().map(|v| self.expression(v));
+                    fields.append(&mut self.hoisted);
+                    fields.push(Stmt::Assign {
+                        targets: vec![Expr::attribute(Expr::name("self"), &name)],
+                        value: value.unwrap_or_else(Expr::none),
+                    });
+                    self.this = outer_this;
+                    self.hoisted = outer_hoisted;
+                }
+                // def _static1(self): ... in the class, where its private names are, and
+                // C._static1(C) after it
+                ClassMember::StaticBlock(block) => {
+                    let function = self.temporary("static");
+                    let def = self.def(
+                        function.clone(),
+                        block.span,
+                        &[],
+                        FunctionBody::Statements(&block.body),
+                        false,
+                        Some("self"),
+                    );
+                    body.push(Stmt::located(block.span, def));
+                    let function = Expr::attribute(Expr::name(&name), &function);
+                    let call = Expr::call(function, vec![Expr::name(&name)]);
+                    static_blocks.push(Stmt::located(block.span, Stmt::Expr(call)));
+                }
+            }
+        }
+
+        let super_init = Expr::attribute(Expr::call(Expr::name("super"), Vec::new()), "__init__");
+        let init = match constructor {
+            Some(constructor) => {
+                Some(self.method("__init__".to_string(), constructor, Some("self")))
+            }
+            // def __init__(self, *args): super().__init__(*args)
+            None if !fields.is_empty() => {
+                let mut args = Arguments {
+                    args: vec!["self".to_string()],
+                    ..Arguments::default()
+                };
+                let mut body = Vec::new();
+                if class.super_class.is_some() {
+                    args.vararg = Some("args".to_string());
+                    let args = Expr::Starred(Box::new(Expr::name("args")));
+                    body.push(Stmt::Expr(Expr::call(super_init.clone(), vec![args])));
+                }
+                Some(Stmt::FunctionDef {
+                    name: "__init__".to_string(),
+                    args,
+                    body,
+                    decorators: Vec::new(),
+                    is_async: false,
+                })
+            }
+            None => None,
+        };
+        if let Some(mut init) = init {
+            if let Stmt::FunctionDef { body, .. } = &mut init {
+                if let [Stmt::Pass] = body.as_slice() {
+                    body.clear();
+                }
+                let at = match class.super_class {
+                    Some(_) => body
+                        .iter()
+                        .position(|s| {
+                            matches!(s.unlocated(), Stmt::Expr(Expr::Call { func, .. }) if **func == super_init)
+                        })
+                        .map_or(0, |i| i + 1),
+                    None => 0,
+                };
+                body.splice(at..at, fields);
+                if body.is_empty() {
+                    body.push(Stmt::Pass);
+                }
+            }
+            body.insert(0, init);
+        }
+        self.string_conversion(&mut body);
+        let static_accessors = accessors.iter().any(|(_, is_static)| *is_static);
+        if static_accessors {
+            bases.push(self.runtime("JSStatic"));
+        }
+        out.push(Stmt::ClassDef {
+            name,
+            bases,
+            body: suite(body),
+        });
+        out.append(&mut static_blocks);
+    }
+
+    /// The Python name of a method or field, `None` for what it can't be
+    fn member_name(&mut self, key: &PropertyKey, span: Span) -> Option<String> {
+        match key {
+            PropertyKey::Identifier(id) if is_attribute(&id.name) => Some(id.name.clone()),
+            PropertyKey::PrivateName(id) => Some(private(id)),
+            _ => {
+                self.unsupported(span, "a class member name that isn't a Python name");
+                None
+            }
+        }
+    }
+
+    /// A getter as a `@property`, a setter as its `.setter`, or the `property` of it alone.
+    /// `property` is the decorator, Python's or the runtime's for static accessors
+    fn property(
+        &mut self,
+        name: String,
+        get: Option<&Function>,
+        set: Option<&Function>,
+        property: Expr,
+        out: &mut Vec<Stmt>,
+    ) {
+        let decorate = |mut def: Stmt, decorator: Expr| {
+            if let Stmt::FunctionDef { decorators, .. } = &mut def {
+                decorators.push(decorator);
+            }
+            def
+        };
+        if let Some(get) = get {
+            let getter = self.method(name.clone(), get, Some("self"));
+            out.push(decorate(getter, property.clone()));
+        }
+        match (get, set) {
+            (Some(_), Some(set)) => {
+                let setter = self.method(name.clone(), set, Some("self"));
+                let decorator = Expr::attribute(Expr::name(&name), "setter");
+                out.push(decorate(setter, decorator));
+            }
+            // x = property(None, _set_x)
+            (None, Some(set)) => {
+                let setter = format!("_set_{}", name);
+                out.push(self.method(setter.clone(), set, Some("self")));
+                let property = Expr::call(property, vec![Expr::none(), Expr::Name(setter)]);
+                out.push(Stmt::assign(&name, property));
+            }
+            _ => {}
+        }
+    }
+
+    /// The class of a constructor function, with the methods of its prototype and its other
+    /// properties as class attributes. `Base.call(this, ...)` in a subclass is
+    /// `super().__init__(...)`
+    fn constructor_class(
+        &mut self,
+        function: &Function,
+        members: &[(&Identifier, &Expression)],
+        base: Option<&Identifier>,
+    ) -> Stmt {
+        let name = self.name(function.id.as_ref().unwrap());
+        let outer_base = mem::replace(&mut self.base, base.map(|base| base.name.clone()));
+        let mut body = vec![self.method("__init__".to_string(), function, Some("self"))];
+        for (member, value) in members {
+            if !is_attribute(&member.name) {
+                self.unsupported(member.span, "a class member name that isn't a Python name");
+                continue;
+            }
+            match value {
+                Expression::Function(function) => {
+                    body.push(self.method(member.name.clone(), function, Some("self")))
+                }
+                value => {
+                    let value = self.expression(value);
+                    body.push(Stmt::assign(&member.name, value));
+                }
+            }
+        }
+        self.base = outer_base;
+        self.string_conversion(&mut body);
+        Stmt::ClassDef {
+            name,
+            bases: base
+                .map(|base| Expr::Name(self.name(base)))
+                .into_iter()
+                .collect(),
+            body,
+        }
+    }
+
+    /// `__str__ = js_to_string` in a class with a `toString` method, which `str` and the runtime
+    /// call then, as JavaScript does when it converts an object to a string
+    fn string_conversion(&mut self, body: &mut Vec<Stmt>) {
+        let defines = body
+            .iter()
+            .any(|s| matches!(s.unlocated(), Stmt::FunctionDef { name, .. } if name == "toString"));
+        if defines {
+            body.push(Stmt::Assign {
+                targets: vec![Expr::name("__str__")],
+                value: self.runtime("js_to_string"),
+            });
+        }
+    }
+
+    /// The method of the base class `callee` calls with `this`: `__init__` for
+    /// `Base.call(this, ...)`, `m` for `Base.prototype.m.call(this, ...)`
+    fn base_method<'e>(
+        &self,
+        callee: &'e Expression,
+        arguments: &[ExpressionOrSpread],
+    ) -> Option<&'e str> {
+        let base = self.base.as_deref()?;
+        let is_base = |e: &Expression| matches!(e, Expression::Identifier(id) if id.name == base);
+        let Expression::Member {
+            object,
+            property: MemberProperty::Identifier(call),
+            optional: false,
+            ..
+        } = callee
+        else {
+            return None;
+        };
+        let this = matches!(
+            arguments.first(),
+            Some(ExpressionOrSpread::Expression(Expression::This { .. }))
+        );
+        if call.name != "call" || !this {
+            return None;
+        }
+        match object.as_ref() {
+            object if is_base(object) => Some("__init__"),
+            Expression::Member {
+                object,
+                property: MemberProperty::Identifier(method),
+                optional: false,
+                ..
+            } => match object.as_ref() {
+                Expression::Member {
+                    object,
+                    property: MemberProperty::Identifier(prototype),
+                    optional: false,
+                    ..
+                } if prototype.name == "prototype" && is_base(object) => Some(&method.name),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    fn method(&mut self, name: String, function: &Function, this: Option<&'static str>) -> Stmt {
+        self.def(
+            name,
+            function.span,
+            &function.params,
+            FunctionBody::Statements(&function.body.body),
+            function.is_async,
+            this,
+        )
+    }
+
+    // -----------------------------------------------------------------------------------------
+    // expressions
+
+    fn expression(&mut self, e: &Expression) -> Expr {
+        match e {
+            Expression::Identifier(id) => match id.name.as_str() {
+                "undefined" => Expr::none(),
+                "NaN" => number(f64::NAN),
+                "Infinity" => number(f64::INFINITY),
+                name => match RUNTIME_GLOBALS
+                    .iter()
+                    .chain(RUNTIME_ERRORS)
+                    .find(|global| **global == name)
+                {
+                    Some(global) if self.is_global(id) => self.runtime(global),
+                    _ => Expr::Name(self.name(id)),
+                },
+            },
+            Expression::Literal(literal) => match &literal.value {
+                LiteralValue::Null => self.runtime("null"),
+                LiteralValue::RegExp { .. } => {
+                    self.unsupported(literal.span, "regular expressions")
+                }
+                _ => constant(e).unwrap_or_else(Expr::none),
+            },
+            Expression::Template(template) => self.template(template),
+            Expression::TaggedTemplate { tag, quasi, .. } => self.tagged_template(tag, quasi),
+            Expression::This { span } => match self.this {
+                Some(this) => Expr::name(this),
+                None => self.unsupported(*span, "this outside of a method"),
+            },
+            Expression::Super { span } => self.unsupported(*span, "super here"),
+            Expression::Array { elements, .. } => {
+                let items = self.in_order(elements, |this, e| match e {
+                    Some(e) => this.argument(e),
+                    None => Expr::none(),
+                });
+                Expr::List(items)
+            }
+            Expression::Object { properties, .. } => self.object(properties),
+            // function (x) { return x + 1 }  =>  lambda x: x + 1
+            Expression::Function(function) => {
+                let plain = function.id.is_none() && !function.generator && !function.is_async;
+                if let [Statement::Return {
+                    argument: Some(body),
+                    ..
+                }] = function.body.body.as_slice()
+                {
+                    if plain && !uses_this(function) {
+                        if let Some(lambda) = self.lambda(&function.params, body) {
+                            return self.per_iteration(function.span, lambda);
+                        }
+                    }
+                }
+                let name = match &function.id {
+                    Some(id) => self.name(id),
+                    None => self.temporary("fn"),
+                };
+                let def = self.function(name.clone(), function);
+                let mut definitions = Vec::new();
+                self.per_iteration_def(function.span, def, &mut definitions);
+                self.hoisted.append(&mut definitions);
+                Expr::Name(name)
+            }
+            Expression::Arrow(arrow) => self.arrow(arrow),
+            Expression::Class(class) => {
+                let name = match &class.id {
+                    Some(id) => self.name(id),
+                    None => self.temporary("class"),
+                };
+                let mut definitions = Vec::new();
+                self.deferred(|this| this.class(name.clone(), class, &mut definitions));
+                self.hoisted.append(&mut definitions);
+                Expr::Name(name)
+            }
+            Expression::Unary {
+                operator,
+                argument,
+                span,
+            } => self.unary(*operator, argument, *span),
+            // i++  =>  (i := i + 1) - 1
+            Expression::Update {
+                operator,
+                prefix,
+                argument,
+                span,
+            } => {
+                let Expression::Identifier(id) = argument.as_ref() else {
+                    return self.effected(e, *span);
+                };
+                let name = self.name(id);
+                let kind = self.kinds.variable(&id.name);
+                let update = Expr::NamedExpr {
+                    target: name.clone(),
+                    value: Box::new(self.increment(*operator, Expr::Name(name), kind)),
+                };
+                let undo = match operator {
+                    UpdateOperator::Increment => Operator::Sub,
+                    UpdateOperator::Decrement => Operator::Add,
+                };
+                match prefix {
+                    true => update,
+                    false => Expr::binary(update, undo, int(1)),
+                }
+            }
+            Expression::Binary {
+                operator,
+                left,
+                right,
+                ..
+            } => {
+                let kinds = (self.kinds.of(left), self.kinds.of(right));
+                let mut values = self.in_order([left, right], |this, e| this.expression(e));
+                let right = (values.pop().unwrap(), kinds.1);
+                let left = (values.pop().unwrap(), kinds.0);
+                self.operation(*operator, left, right)
+            }
+            Expression::Logical {
+                operator,
+                left,
+                right,
+                ..
+            } => {
+                let left = self.operand(left);
+                let right = self.deferred(|this| this.expression(right));
+                self.logical(*operator, left, right)
+            }
+            // x = 1 inside an expression  =>  (x := 1)
+            Expression::Assignment {
+                operator,
+                left,
+                right,
+                span,
+            } => {
+                let Pattern::Identifier(id) = left.as_ref() else {
+                    return self.effected(e, *span);
+                };
+                let name = self.name(id);
+                let current = (Expr::Name(name.clone()), self.kinds.variable(&id.name));
+                let logical = match operator {
+                    AssignmentOperator::OrAssign => Some(LogicalOperator::Or),
+                    AssignmentOperator::AndAssign => Some(LogicalOperator::And),
+                    AssignmentOperator::CoalesceAssign => Some(LogicalOperator::Coalesce),
+                    _ => None,
+                };
+                let value = match (logical, compound(*operator)) {
+                    (Some(logical), _) => {
+                        let right = self.deferred(|this| this.expression(right));
+                        self.logical(logical, current, right)
+                    }
+                    (None, Some(op)) => {
+                        let right = self.operand(right);
+                        self.operation(op, current, right)
+                    }
+                    (None, None) => self.expression(right),
+                };
+                Expr::NamedExpr {
+                    target: name,
+                    value: Box::new(value),
+                }
+            }
+            Expression::Conditional {
+                test,
+                consequent,
+                alternate,
+                ..
+            } => {
+                let test = self.condition(test);
+                let (body, orelse) =
+                    self.deferred(|this| (this.expression(consequent), this.expression(alternate)));
+                Expr::IfExp {
+                    test: Box::new(test),
+                    body: Box::new(body),
+                    orelse: Box::new(orelse),
+                }
+            }
+            Expression::Call { .. } | Expression::Member { .. } => self.link(e, &mut Vec::new()),
+            // a?.b.c  =>  None if a is None else a.b.c
+            Expression::Chain { expression, .. } => {
+                let mut guards = Vec::new();
+                let value = self.deferred(|this| this.link(expression, &mut guards));
+                let test = match guards.len() {
+                    1 => guards.remove(0),
+                    _ => Expr::BoolOp {
+                        op: BoolOp::Or,
+                        values: guards,
+                    },
+                };
+                Expr::IfExp {
+                    test: Box::new(test),
+                    body: Box::new(Expr::none()),
+                    orelse: Box::new(value),
+                }
+            }
+            Expression::New {
+                callee, arguments, ..
+            } => {
+                let factory =
+                    matches!(callee.as_ref(), Expression::Identifier(id) if self.is_factory(id));
+                let func = self.expression(callee);
+                let args = self.in_order(arguments, |this, a| this.argument(a));
+                match factory {
+                    true => self.helper("js_new", [vec![func], args].concat()),
+                    false => Expr::call(func, args),
+                }
+            }
+            // (a, b)[-1]
+            Expression::Sequence { expressions, .. } => {
+                let items = expressions.iter().map(|e| self.expression(e)).collect();
+                Expr::subscript(Expr::Tuple(items), int(-1))
+            }
+            Expression::Yield {
+                argument, delegate, ..
+            } => {
+                let value = argument.as_ref().map(|a| Box::new(self.expression(a)));
+                match (value, delegate) {
+                    (Some(value), true) => Expr::YieldFrom(value),
+                    (value, _) => Expr::Yield(value),
+                }
+            }
+            Expression::Await { argument, .. } => Expr::Await(Box::new(self.expression(argument))),
+            Expression::Import { span, .. } => self.unsupported(*span, "import()"),
+            Expression::MetaProperty { span, .. } => {
+                self.unsupported(*span, "new.target and import.meta")
+            }
+            Expression::PrivateIdentifier(id) => self.unsupported(id.span, "private names"),
+        }
+    }
+
+    /// A condition, with what Python can't do inside an expression done first by the statements
+    /// in `out`: the leading expressions of a comma expression, and what `lowered` does
+    fn test(&mut self, e: &Expression, out: &mut Vec<Stmt>) -> Expr {
+        match e {
+            Expression::Sequence { expressions, .. } => {
+                let (last, leading) = expressions.split_last().unwrap();
+                for e in leading {
+                    self.effect(e, out);
+                }
+                self.test(last, out)
+            }
+            e => self.with_effects(out, |this| this.condition(e)),
+        }
+    }
+
+    /// An assignment to a property or pattern, or `++` or `--` of a property, inside an
+    /// expression: the statements in `out` do it, the value it has is a temporary
+    fn assigned(&mut self, e: &Expression, out: &mut Vec<Stmt>) -> Expr {
+        match e {
+            Expression::Update {
+                operator,
+                prefix: true,
+                argument,
+                ..
+            } => {
+                // ++a.n  =>  _tmp1 = js_number(a.n) + 1; a.n = _tmp1
+                let kind = self.kinds.of(argument);
+                let target = self.stable_place(argument, out);
+                let old = self.numeric(target.clone(), kind);
+                let value = self.increment(*operator, old, Kind::Number);
+                let value = self.reusable(value, out);
+                out.push(update(target, value.clone()));
+                value
+            }
+            Expression::Update {
+                operator, argument, ..
+            } => {
+                // a.n--  =>  _tmp1 = js_number(a.n); a.n = _tmp1 - 1
+                let kind = self.kinds.of(argument);
+                let target = self.stable_place(argument, out);
+                let old = self.numeric(target.clone(), kind);
+                let old = self.reusable(old, out);
+                let value = self.increment(*operator, old.clone(), Kind::Number);
+                out.push(update(target, value));
+                old
+            }
+            Expression::Assignment {
+                operator: AssignmentOperator::Assign,
+                left,
+                right,
+                ..
+            } => {
+                // a.b = f()  =>  _tmp1 = f(); a.b = _tmp1
+                let value = self.lowered(right, out);
+                let value = self.reusable(value, out);
+                self.assign(left, value.clone(), out);
+                value
+            }
+            Expression::Assignment {
+                operator,
+                left,
+                right,
+                ..
+            } => {
+                let kind = self.target_kind(left);
+                let target = self.stable_target(left, out);
+                if let Some(op) = compound(*operator) {
+                    // a.n += 2  =>  _tmp1 = a.n + 2; a.n = _tmp1
+                    let right = (self.lowered(right, out), self.kinds.of(right));
+                    let value = self.operation(op, (target.clone(), kind), right);
+                    let value = self.reusable(value, out);
+                    out.push(update(target, value.clone()));
+                    return value;
+                }
+                // a.n ||= f()  =>  _tmp1 = a.n; if not _tmp1: _tmp1 = f(); a.n = _tmp1
+                let current = self.reusable(target.clone(), out);
+                let test = match operator {
+                    AssignmentOperator::OrAssign => Expr::not(self.truth(current.clone(), kind)),
+                    AssignmentOperator::AndAssign => self.truth(current.clone(), kind),
+                    _ => self.nullish_test(current.clone(), current.clone(), kind, false),
+                };
+                let mut body = Vec::new();
+                let value = self.lowered(right, &mut body);
+                body.push(Stmt::Assign {
+                    targets: vec![current.clone()],
+                    value,
+                });
+                body.push(Stmt::Assign {
+                    targets: vec![target],
+                    value: current.clone(),
+                });
+                out.push(Stmt::If {
+                    test,
+                    body,
+                    orelse: Vec::new(),
+                });
+                current
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    /// What `assigned` makes of `e`, its statements among those before the expression it is in
+    fn effected(&mut self, e: &Expression, span: Span) -> Expr {
+        let Some(mut effects) = self.effects.take() else {
+            return self.unsupported(
+                span,
+                "assignment, ++ or -- of a property or pattern where it may not be evaluated",
+            );
+        };
+        let value = self.assigned(e, &mut effects);
+        self.effects = Some(effects);
+        value
+    }
+
+    /// An expression with its kind
+    fn operand(&mut self, e: &Expression) -> (Expr, Kind) {
+        let kind = self.kinds.of(e);
+        (self.expression(e), kind)
+    }
+
+    /// A test with the truth value `e` has in JavaScript
+    fn condition(&mut self, e: &Expression) -> Expr {
+        match e {
+            Expression::Logical {
+                operator: operator @ (LogicalOperator::And | LogicalOperator::Or),
+                left,
+                right,
+                ..
+            } => {
+                let op = match operator {
+                    LogicalOperator::And => BoolOp::And,
+                    _ => BoolOp::Or,
+                };
+                let left = self.condition(left);
+                let right = self.deferred(|this| this.condition(right));
+                bool_op(op, left, right)
+            }
+            Expression::Unary {
+                operator: UnaryOperator::Not,
+                argument,
+                ..
+            } => Expr::not(self.condition(argument)),
+            e => {
+                let (value, kind) = self.operand(e);
+                self.truth(value, kind)
+            }
+        }
+    }
+
+    fn unary(&mut self, operator: UnaryOperator, argument: &Expression, span: Span) -> Expr {
+        match operator {
+            UnaryOperator::Not => return Expr::not(self.condition(argument)),
+            UnaryOperator::Delete => return self.delete(argument, span),
+            _ => {}
+        }
+        let (value, kind) = self.operand(argument);
+        let number = kind == Kind::Number;
+        match operator {
+            UnaryOperator::Minus | UnaryOperator::Plus => {
+                let op = match operator {
+                    UnaryOperator::Minus => UnaryOp::USub,
+                    _ => UnaryOp::UAdd,
+                };
+                match (number, op) {
+                    (true, op) => Expr::UnaryOp {
+                        op,
+                        operand: Box::new(value),
+                    },
+                    // +x  =>  js_number(x)
+                    (false, UnaryOp::UAdd) => self.helper("js_number", vec![value]),
+                    (false, op) => Expr::UnaryOp {
+                        op,
+                        operand: Box::new(self.helper("js_number", vec![value])),
+                    },
+                }
+            }
+            UnaryOperator::BitNot => Expr::UnaryOp {
+                op: UnaryOp::Invert,
+                operand: Box::new(self.int32(value)),
+            },
+            UnaryOperator::Typeof => self.helper("js_typeof", vec![value]),
+            // void 0  =>  None, void f()  =>  (f(), None)[-1]
+            _ => match value {
+                Expr::Constant(_) => Expr::none(),
+                value => Expr::subscript(Expr::Tuple(vec![value, Expr::none()]), int(-1)),
+            },
+        }
+    }
+
+    /// `delete a.b`  =>  js_delete(a, "b")
+    fn delete(&mut self, argument: &Expression, span: Span) -> Expr {
+        let Expression::Member {
+            object,
+            property,
+            optional: false,
+            ..
+        } = argument
+        else {
+            return self.unsupported(span, "delete of something other than a property");
+        };
+        let object = self.expression(object);
+        let key = match property {
+            MemberProperty::Identifier(id) => Expr::str(&id.name),
+            MemberProperty::Computed(key) => self.expression(key),
+            MemberProperty::PrivateName(id) => return self.unsupported(id.span, "private names"),
+        };
+        self.helper("js_delete", vec![object, key])
+    }
+
+    /// The value after `++` or `--`
+    fn increment(&mut self, operator: UpdateOperator, current: Expr, kind: Kind) -> Expr {
+        let op = match operator {
+            UpdateOperator::Increment => Operator::Add,
+            UpdateOperator::Decrement => Operator::Sub,
+        };
+        let current = self.numeric(current, kind);
+        Expr::binary(current, op, int(1))
+    }
+
+    /// A number operand, which is converted unless it is one
+    fn numeric(&mut self, value: Expr, kind: Kind) -> Expr {
+        match kind {
+            Kind::Number => value,
+            _ => self.helper("js_number", vec![value]),
+        }
+    }
+
+    /// A value as a string, as `+` and templates convert it
+    fn string(&mut self, value: Expr, kind: Kind) -> Expr {
+        match (value, kind) {
+            (value, Kind::String) => value,
+            (Expr::Constant(Constant::Int(n)), _) => Expr::str(&n.to_string()),
+            (Expr::Constant(Constant::Bool(b)), _) => Expr::str(if b { "true" } else { "false" }),
+            (Expr::Constant(Constant::None), _) => Expr::str("undefined"),
+            (value, _) => self.helper("js_str", vec![value]),
+        }
+    }
+
+    /// A bitwise operand, a 32-bit integer
+    fn int32(&mut self, value: Expr) -> Expr {
+        match value {
+            Expr::Constant(Constant::Int(n)) if i32::try_from(n).is_ok() => value,
+            value => self.helper("js_int32", vec![value]),
+        }
+    }
+
+    /// `left operator right`, plain Python where the kinds of the operands make it the same
+    fn operation(
+        &mut self,
+        operator: BinaryOperator,
+        (left, left_kind): (Expr, Kind),
+        (right, right_kind): (Expr, Kind),
+    ) -> Expr {
+        let kinds = (left_kind, right_kind);
+        // numbers with numbers or strings with strings
+        let alike = matches!(
+            kinds,
+            (Kind::Number, Kind::Number) | (Kind::String, Kind::String)
+        );
+        let helper = match operator {
+            BinaryOperator::Add => match kinds {
+                _ if alike => return Expr::binary(left, Operator::Add, right),
+                (Kind::String, _) => {
+                    let right = self.string(right, right_kind);
+                    return Expr::binary(left, Operator::Add, right);
+                }
+                (_, Kind::String) => {
+                    let left = self.string(left, left_kind);
+                    return Expr::binary(left, Operator::Add, right);
+                }
+                _ => "js_add",
+            },
+            BinaryOperator::Sub | BinaryOperator::Mul | BinaryOperator::Exp => {
+                let op = match operator {
+                    BinaryOperator::Sub => Operator::Sub,
+                    BinaryOperator::Mul => Operator::Mult,
+                    _ => Operator::Pow,
+                };
+                let left = self.numeric(left, left_kind);
+                let right = self.numeric(right, right_kind);
+                return Expr::binary(left, op, right);
+            }
+            // division by a number other than 0 is the same
+            BinaryOperator::Div => match &right {
+                Expr::Constant(Constant::Int(n)) if *n != 0 => {
+                    let left = self.numeric(left, left_kind);
+                    return Expr::binary(left, Operator::Div, right);
+                }
+                Expr::Constant(Constant::Float(n)) if *n != 0.0 => {
+                    let left = self.numeric(left, left_kind);
+                    return Expr::binary(left, Operator::Div, right);
+                }
+                _ => "js_div",
+            },
+            // the sign of the remainder differs
+            BinaryOperator::Mod => "js_mod",
+            BinaryOperator::BitOr | BinaryOperator::BitXor | BinaryOperator::BitAnd => {
+                let op = match operator {
+                    BinaryOperator::BitOr => Operator::BitOr,
+                    BinaryOperator::BitXor => Operator::BitXor,
+                    _ => Operator::BitAnd,
+                };
+                let left = self.int32(left);
+                let right = self.int32(right);
+                return Expr::binary(left, op, right);
+            }
+            BinaryOperator::ShiftLeft => "js_shl",
+            BinaryOperator::ShiftRight => "js_shr",
+            BinaryOperator::UnsignedShiftRight => "js_ushr",
+            BinaryOperator::Less
+            | BinaryOperator::LessEqual
+            | BinaryOperator::Greater
+            | BinaryOperator::GreaterEqual => {
+                let (op, helper) = match operator {
+                    BinaryOperator::Less => (CmpOp::Lt, "js_lt"),
+                    BinaryOperator::LessEqual => (CmpOp::LtE, "js_le"),
+                    BinaryOperator::Greater => (CmpOp::Gt, "js_gt"),
+                    _ => (CmpOp::GtE, "js_ge"),
+                };
+                match alike {
+                    true => return Expr::compare(left, op, right),
+                    false => helper,
+                }
+            }
+            BinaryOperator::StrictEqual | BinaryOperator::StrictNotEqual => {
+                let negate = operator == BinaryOperator::StrictNotEqual;
+                let (eq, is) = match negate {
+                    false => (CmpOp::Eq, CmpOp::Is),
+                    true => (CmpOp::NotEq, CmpOp::IsNot),
+                };
+                let literal = is_literal(&left) || is_literal(&right);
+                return match kinds {
+                    // null === 0 is false, `0 is null` a warning
+                    (Kind::Undefined | Kind::Null, _) | (_, Kind::Undefined | Kind::Null)
+                        if literal =>
+                    {
+                        let test = self.helper("js_strict_eq", vec![left, right]);
+                        match negate {
+                            true => Expr::not(test),
+                            false => test,
+                        }
+                    }
+                    // x === null  =>  x is null
+                    (Kind::Undefined | Kind::Null, _) if !is_singleton(&right) => {
+                        Expr::compare(right, is, left)
+                    }
+                    (_, Kind::Undefined | Kind::Null) | (Kind::Undefined | Kind::Null, _) => {
+                        Expr::compare(left, is, right)
+                    }
+                    (Kind::String, _) | (_, Kind::String) => Expr::compare(left, eq, right),
+                    (Kind::Number, Kind::Number) | (Kind::Boolean, Kind::Boolean) => {
+                        Expr::compare(left, eq, right)
+                    }
+                    _ => {
+                        let test = self.helper("js_strict_eq", vec![left, right]);
+                        match negate {
+                            true => Expr::not(test),
+                            false => test,
+                        }
+                    }
+                };
+            }
+            BinaryOperator::Equal | BinaryOperator::NotEqual => {
+                let negate = operator == BinaryOperator::NotEqual;
+                let literal = is_literal(&left) || is_literal(&right);
+                return match kinds {
+                    // x == null  =>  x is None or x is null
+                    (Kind::Undefined | Kind::Null, _) if !literal => {
+                        self.nullish(right, right_kind, negate)
+                    }
+                    (_, Kind::Undefined | Kind::Null) if !literal => {
+                        self.nullish(left, left_kind, negate)
+                    }
+                    _ if alike || kinds == (Kind::Boolean, Kind::Boolean) => {
+                        let op = match negate {
+                            false => CmpOp::Eq,
+                            true => CmpOp::NotEq,
+                        };
+                        Expr::compare(left, op, right)
+                    }
+                    _ => {
+                        let test = self.helper("js_eq", vec![left, right]);
+                        match negate {
+                            true => Expr::not(test),
+                            false => test,
+                        }
+                    }
+                };
+            }
+            BinaryOperator::In => "js_in",
+            BinaryOperator::Instanceof => {
+                return Expr::call(Expr::name("isinstance"), vec![left, right]);
+            }
+        };
+        self.helper(helper, vec![left, right])
+    }
+
+    /// `left && right`, `left || right` or `left ?? right`, which evaluate `left` once
+    fn logical(
+        &mut self,
+        operator: LogicalOperator,
+        (left, kind): (Expr, Kind),
+        right: Expr,
+    ) -> Expr {
+        let op = match operator {
+            LogicalOperator::And => BoolOp::And,
+            LogicalOperator::Or => BoolOp::Or,
+            // a ?? b  =>  a if a is not None else b
+            LogicalOperator::Coalesce => {
+                let (test, value) = self.named(left);
+                let test = self.nullish_test(test, value.clone(), kind, true);
+                return Expr::IfExp {
+                    test: Box::new(test),
+                    body: Box::new(value),
+                    orelse: Box::new(right),
+                };
+            }
+        };
+        if kind.truthiness_agrees() {
+            return bool_op(op, left, right);
+        }
+        // a && b  =>  b if js_truthy(_tmp := a) else _tmp
+        let (test, value) = self.named(left);
+        let test = self.helper("js_truthy", vec![test]);
+        let (body, orelse) = match op {
+            BoolOp::And => (right, value),
+            BoolOp::Or => (value, right),
+        };
+        Expr::IfExp {
+            test: Box::new(test),
+            body: Box::new(body),
+            orelse: Box::new(orelse),
+        }
+    }
+
+    /// A call or member expression. An optional link tests its object first: the test goes in
+    /// `guards`, the object is evaluated in it once
+    fn link(&mut self, e: &Expression, guards: &mut Vec<Expr>) -> Expr {
+        match e {
+            Expression::Member {
+                object,
+                property,
+                optional,
+                ..
+            } => {
+                let kind = self.kinds.of(object);
+                let object = match object.as_ref() {
+                    Expression::Super { .. } => Expr::call(Expr::name("super"), Vec::new()),
+                    object => self.link(object, guards),
+                };
+                let object = match optional {
+                    true => self.guard(object, guards),
+                    false => object,
+                };
+                match property {
+                    // the length of a string or array is len(), of what may be one the runtime's
+                    MemberProperty::Identifier(id) if id.name == "length" => match kind {
+                        Kind::String | Kind::Array => Expr::call(Expr::name("len"), vec![object]),
+                        Kind::Unknown => self.helper("js_get", vec![object, Expr::str("length")]),
+                        _ => Expr::attribute(object, "length"),
+                    },
+                    MemberProperty::Identifier(id) if is_attribute(&id.name) => {
+                        Expr::attribute(object, &id.name)
+                    }
+                    MemberProperty::Identifier(id) => {
+                        self.helper("js_get", vec![object, Expr::str(&id.name)])
+                    }
+                    // a key that may be an index is looked up by the runtime, which has no
+                    // IndexError
+                    MemberProperty::Computed(key) => match key.as_ref() {
+                        Expression::Literal(Literal {
+                            value: LiteralValue::String(_),
+                            ..
+                        }) => Expr::subscript(object, self.expression(key)),
+                        key => {
+                            let key = self.expression(key);
+                            self.helper("js_get", vec![object, key])
+                        }
+                    },
+                    MemberProperty::PrivateName(id) => Expr::attribute(object, &private(id)),
+                }
+            }
+            // Base.call(this, ...)  =>  super().__init__(...)
+            Expression::Call {
+                callee,
+                arguments,
+                optional: false,
+                ..
+            } if self.base_method(callee, arguments).is_some() => {
+                let method = self.base_method(callee, arguments).unwrap();
+                let func = Expr::attribute(Expr::call(Expr::name("super"), Vec::new()), method);
+                let args = self.in_order(&arguments[1..], |this, a| this.argument(a));
+                Expr::call(func, args)
+            }
+            Expression::Call {
+                callee,
+                arguments,
+                optional: false,
+                ..
+            } if self.is_method_call(callee) => self.method_call(callee, arguments, false, guards),
+            Expression::Call {
+                callee,
+                arguments,
+                optional,
+                ..
+            } => {
+                // console.log of strings is print
+                let print = console_log(callee).is_some_and(|console| self.is_global(console))
+                    && arguments.iter().all(|a| {
+                        matches!(a, ExpressionOrSpread::Expression(e)
+                            if self.kinds.of(e) == Kind::String)
+                    });
+                let func = match callee.as_ref() {
+                    // super(...) in a constructor
+                    Expression::Super { .. } => {
+                        Expr::attribute(Expr::call(Expr::name("super"), Vec::new()), "__init__")
+                    }
+                    _ if print => Expr::name("print"),
+                    callee => self.link(callee, guards),
+                };
+                let func = match optional {
+                    true => self.guard(func, guards),
+                    false => func,
+                };
+                let args = self.in_order(arguments, |this, a| this.argument(a));
+                Expr::call(func, args)
+            }
+            e => self.expression(e),
+        }
+    }
+
+    /// Whether the callee is an async function of the program called outside of any function
+    fn is_async_entry(&self, callee: &Expression) -> bool {
+        let Expression::Identifier(id) = callee else {
+            return false;
+        };
+        self.scopes.reference_at(id.span).is_some_and(|reference| {
+            reference.function == self.scopes.root()
+                && reference
+                    .binding
+                    .is_some_and(|binding| self.async_functions.contains(&binding))
+        })
+    }
+
+    /// Whether the callee is a method of a string, array or number, or one strings, arrays or
+    /// numbers have of a value that may be one, and not a method of the program's own
+    fn is_method_call(&self, callee: &Expression) -> bool {
+        let Expression::Member {
+            object,
+            property: MemberProperty::Identifier(id),
+            optional: false,
+            ..
+        } = callee
+        else {
+            return false;
+        };
+        if let Expression::Super { .. } = object.as_ref() {
+            return false;
+        }
+        match self.kinds.of(object) {
+            Kind::String | Kind::Array | Kind::Number => true,
+            // every value has a `toString`, which `js_method` calls as it is where it isn't a
+            // string's, array's or number's
+            Kind::Unknown => {
+                methods::is_method(&id.name)
+                    && (id.name == "toString" || !self.methods.contains(&id.name))
+            }
+            Kind::Boolean => id.name == "toString",
+            _ => false,
+        }
+    }
+
+    /// A call of a method of strings or arrays, the Python of `methods` where the kinds of the
+    /// object and the arguments fit it, else the runtime's `js_method`. `effect` says whether the
+    /// value of the call is unused
+    fn method_call(
+        &mut self,
+        callee: &Expression,
+        arguments: &[ExpressionOrSpread],
+        effect: bool,
+        guards: &mut Vec<Expr>,
+    ) -> Expr {
+        let Expression::Member {
+            object,
+            property: MemberProperty::Identifier(id),
+            ..
+        } = callee
+        else {
+            unreachable!()
+        };
+        let kind = self.kinds.of(object);
+        let receiver = self.link(object, guards);
+        let python = methods::method(kind, &id.name).map_or(Python::Runtime, |m| m.python);
+        let args: Option<Vec<&Expression>> = arguments
+            .iter()
+            .map(|argument| match argument {
+                ExpressionOrSpread::Expression(e) => Some(e),
+                ExpressionOrSpread::Spread(_) => None,
+            })
+            .collect();
+        let strings = |args: &[&Expression]| args.iter().all(|a| self.kinds.of(a) == Kind::String);
+        let call = match (python, args.as_deref()) {
+            (Python::Method(name, kinds), Some(args))
+                if args.len() == kinds.len()
+                    && args
+                        .iter()
+                        .zip(kinds)
+                        .all(|(a, kind)| self.kinds.of(a) == *kind) =>
+            {
+                let args = args.iter().map(|a| self.expression(a)).collect();
+                Some(Expr::call(Expr::attribute(receiver.clone(), name), args))
+            }
+            // s.includes(t)  =>  t in s
+            (Python::In, Some([value])) if strings(&[value]) => {
+                let value = self.expression(value);
+                Some(Expr::compare(value, CmpOp::In, receiver.clone()))
+            }
+            // s.slice(1, -1)  =>  s[1:-1]
+            (Python::Slice, Some(args)) if args.len() <= 2 && args.iter().all(|a| integer(a)) => {
+                let mut bounds = args.iter().map(|a| Box::new(constant(a).unwrap()));
+                let (lower, upper) = (bounds.next(), bounds.next());
+                Some(Expr::subscript(
+                    receiver.clone(),
+                    Expr::Slice { lower, upper },
+                ))
+            }
+            (Python::Split, Some([separator]))
+                if string_literal(separator).is_some_and(|s| !s.is_empty()) =>
+            {
+                let separator = self.expression(separator);
+                Some(Expr::call(
+                    Expr::attribute(receiver.clone(), "split"),
+                    vec![separator],
+                ))
+            }
+            (Python::Repeat, Some([count])) if integer(count) => {
+                let count = self.expression(count);
+                Some(Expr::binary(receiver.clone(), Operator::Mult, count))
+            }
+            (Python::Concat, Some([value])) if strings(&[value]) => {
+                let value = self.expression(value);
+                Some(Expr::binary(receiver.clone(), Operator::Add, value))
+            }
+            // the replacement of a string has `$&` and such in JavaScript
+            (Python::Replace { all }, Some([pattern, replacement]))
+                if strings(&[pattern])
+                    && string_literal(replacement).is_some_and(|s| !s.contains('$')) =>
+            {
+                let mut args = vec![self.expression(pattern), self.expression(replacement)];
+                if !all {
+                    args.push(int(1));
+                }
+                Some(Expr::call(
+                    Expr::attribute(receiver.clone(), "replace"),
+                    args,
+                ))
+            }
+            (Python::Append, Some([value])) if effect => {
+                let value = self.expression(value);
+                Some(Expr::call(
+                    Expr::attribute(receiver.clone(), "append"),
+                    vec![value],
+                ))
+            }
+            // a.map(x => x * 2)  =>  [js_number(x) * 2 for x in a]
+            (Python::Map | Python::Filter, Some([callback])) => {
+                let returned = returned(callback).map(|e| self.kinds.of(e));
+                match (self.expression(callback), returned) {
+                    (Expr::Lambda { args: lambda, body }, Some(kind))
+                        if lambda.args.len() == 1
+                            && lambda.defaults.is_empty()
+                            && lambda.vararg.is_none() =>
+                    {
+                        let target = lambda.args[0].clone();
+                        let (element, condition) = match python {
+                            Python::Map => (*body, None),
+                            _ => {
+                                let test = self.truth(*body, kind);
+                                (Expr::Name(target.clone()), Some(Box::new(test)))
+                            }
+                        };
+                        Some(Expr::ListComp {
+                            element: Box::new(element),
+                            target,
+                            iter: Box::new(receiver.clone()),
+                            condition,
+                        })
+                    }
+                    (callback, _) => {
+                        let args = vec![receiver.clone(), Expr::str(&id.name), callback];
+                        Some(self.helper("js_method", args))
+                    }
+                }
+            }
+            _ => None,
+        };
+        call.unwrap_or_else(|| {
+            let mut args = vec![receiver, Expr::str(&id.name)];
+            args.extend(self.in_order(arguments, |this, a| this.argument(a)));
+            self.helper("js_method", args)
+        })
+    }
+
+    fn guard(&mut self, value: Expr, guards: &mut Vec<Expr>) -> Expr {
+        let (test, value) = self.named(value);
+        match self.nullish_test(test, value.clone(), Kind::Unknown, false) {
+            Expr::BoolOp {
+                op: BoolOp::Or,
+                values,
+            } => guards.extend(values),
+            guard => guards.push(guard),
+        }
+        value
+    }
+
+    fn argument(&mut self, argument: &ExpressionOrSpread) -> Expr {
+        match argument {
+            ExpressionOrSpread::Expression(e) => self.expression(e),
+            ExpressionOrSpread::Spread(spread) => {
+                Expr::Starred(Box::new(self.expression(&spread.argument)))
+            }
+        }
+    }
+
+    /// A `JSObject` of a dict, methods are functions defined before it and the getter and setter
+    /// of a name are a `js_accessor` of them
+    fn object(&mut self, properties: &[ObjectProperty]) -> Expr {
+        let mut keys = Vec::new();
+        let mut values = Vec::new();
+        let mut accessors: Vec<&PropertyKey> = Vec::new();
+        for property in properties {
+            match property {
+                ObjectProperty::Property(property) if property.kind != PropertyKind::Init => {
+                    if accessors.iter().any(|key| same_key(key, &property.key)) {
+                        continue;
+                    }
+                    accessors.push(&property.key);
+                    // the getter and setter of a name go together, where the first one is
+                    let mut accessor = |kind| {
+                        let function = properties.iter().find_map(|p| match p {
+                            ObjectProperty::Property(p)
+                                if p.kind == kind
+                                    && (std::ptr::eq(p, property)
+                                        || same_key(&p.key, &property.key)) =>
+                            {
+                                Some(&p.value)
+                            }
+                            _ => None,
+                        });
+                        function.map_or_else(Expr::none, |f| self.expression(f))
+                    };
+                    let get = accessor(PropertyKind::Get);
+                    let set = accessor(PropertyKind::Set);
+                    keys.push(Some(self.property_key(&property.key)));
+                    values.push(self.helper("js_accessor", vec![get, set]));
+                }
+                ObjectProperty::Property(property) => {
+                    keys.push(Some(self.property_key(&property.key)));
+                    values.push(self.expression(&property.value));
+                }
+                ObjectProperty::Spread(spread) => {
+                    keys.push(None);
+                    values.push(self.expression(&spread.argument));
+                }
+            }
+        }
+        let args = match keys.is_empty() {
+            true => Vec::new(),
+            false => vec![Expr::Dict { keys, values }],
+        };
+        self.helper("JSObject", args)
+    }
+
+    /// The key of a property as a dict key, a string unless it is computed
+    fn property_key(&mut self, key: &PropertyKey) -> Expr {
+        match key {
+            PropertyKey::Identifier(id) => Expr::str(&id.name),
+            PropertyKey::Literal(literal) => match &literal.value {
+                LiteralValue::Number(n) => Expr::str(&number_key(*n)),
+                LiteralValue::String(s) => Expr::str(s),
+                _ => Expr::str(&literal.raw),
+            },
+            PropertyKey::Computed(e) => self.expression(e),
+            PropertyKey::PrivateName(id) => self.unsupported(id.span, "private names"),
+        }
+    }
+
+    /// `a${b}c`  =>  f"a{js_str(b)}c"
+    fn template(&mut self, template: &TemplateLiteral) -> Expr {
+        let mut parts: Vec<Expr> = Vec::new();
+        for (i, quasi) in template.quasis.iter().enumerate() {
+            let text = quasi.cooked.as_ref().unwrap_or(&quasi.raw);
+            let mut values = vec![Expr::str(text)];
+            if let Some(e) = template.expressions.get(i) {
+                let (value, kind) = self.operand(e);
+                values.push(self.string(value, kind));
+            }
+            for value in values {
+                match (parts.last_mut(), value) {
+                    (
+                        Some(Expr::Constant(Constant::Str(last))),
+                        Expr::Constant(Constant::Str(s)),
+                    ) => last.push_str(&s),
+                    (_, Expr::Constant(Constant::Str(s))) if s.is_empty() => {}
+                    (_, value) => parts.push(value),
+                }
+            }
+        }
+        match parts.as_slice() {
+            [] => Expr::str(""),
+            [Expr::Constant(Constant::Str(_))] => parts.pop().unwrap(),
+            _ => Expr::JoinedStr(parts),
+        }
+    }
+
+    /// tag`a${b}c`  =>  tag(js_template(["a", "c"]), b), the strings have the raw ones too
+    fn tagged_template(&mut self, tag: &Expression, quasi: &TemplateLiteral) -> Expr {
+        let func = self.expression(tag);
+        let cooked = quasi.quasis.iter().map(|q| match &q.cooked {
+            Some(text) => Expr::str(text),
+            None => Expr::none(),
+        });
+        let raw: Vec<_> = quasi.quasis.iter().map(|q| Expr::str(&q.raw)).collect();
+        let mut strings = vec![Expr::List(cooked.collect())];
+        if quasi
+            .quasis
+            .iter()
+            .any(|q| q.cooked.as_ref() != Some(&q.raw))
+        {
+            strings.push(Expr::List(raw));
+        }
+        let mut args = vec![self.helper("js_template", strings)];
+        args.extend(quasi.expressions.iter().map(|e| self.expression(e)));
+        Expr::call(func, args)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{lower, python, scope, test_parse};
+    use std::fs;
+    use std::path::Path;
+    use std::process::Command;
+
+    fn translated(source: &str) -> String {
+        python::unparse(&translated_module(source))
+    }
+
+    fn translated_module(source: &str) -> Module {
+        let (tree, lexemes) = test_parse(source);
+        let mut diagnostics = Vec::new();
+        let program = lower::lower(&tree, &lexemes, source);
+        let scopes = scope::analyze(&program, &mut diagnostics);
+        let module = translate(&program, &scopes, &Packages::default(), &mut diagnostics);
+        let messages: Vec<_> = diagnostics.iter().map(|d| &d.message).collect();
+        assert!(messages.is_empty(), "{:?}", messages);
+        module
+    }
+
+    #[test]
+    fn test_translate() {
+        let python = translated;
+
+        assert_eq!(
+            python("let x = 1, y; const [a, b] = [y, x]; x += a ?? 2; i++"),
+            "from js_runtime import js_add\nx = 1\ny = None\n_tmp1 = [y, x]\na = _tmp1[0]\n\
+             b = _tmp1[1]\nx = js_add(x, a if a is not None else 2)\ni += 1\n"
+        );
+        // made up names aren't those of the program
+        assert_eq!(
+            python("let _tmp1 = 4; const [a, b] = [1, 2]; console.log(_tmp1)"),
+            "from js_runtime import console\n_tmp1 = 4\n_tmp2 = [1, 2]\na = _tmp2[0]\n\
+             b = _tmp2[1]\nconsole.log(_tmp1)\n"
+        );
+        assert_eq!(
+            python(
+                "function f(n, k = []) {\n  if (n === null || !k) return;\n  \
+                 while (n > 0) { n--; if (n % 2) continue; else break }\n  \
+                 return n => n * 2\n}\nconsole.log(f(3), greet?.name)"
+            ),
+            "from js_runtime import console, js_gt, js_mod, js_number, js_truthy, null\n\n\
+             def f(n, k=None):\n    if k is None:\n        k = []\n    \
+             if n is null or not js_truthy(k):\n        return\n    while js_gt(n, 0):\n        \
+             n = js_number(n) - 1\n        if js_truthy(js_mod(n, 2)):\n            continue\n        \
+             else:\n            break\n    return lambda n: js_number(n) * 2\n\n\
+             console.log(f(3), None if greet is None or greet is null else greet.name)\n"
+        );
+        assert_eq!(
+            python(
+                "class B extends A {\n  constructor(x) { super(x); this.x = x }\n  \
+                 static make() { return new B(1) }\n}\n\
+                 switch (k) { case 1: case 2: a(); break; default: b() }"
+            ),
+            "from js_runtime import js_strict_eq\n\nclass B(A):\n    def __init__(self, x):\n        super().__init__(x)\n        \
+             self.x = x\n\n    @staticmethod\n    def make():\n        return B(1)\n\n\
+             if js_strict_eq(k, 1) or js_strict_eq(k, 2):\n    a()\nelse:\n    b()\n"
+        );
+    }
+
+    /// Plain operators where the kinds are known
+    #[test]
+    fn test_known_kinds() {
+        let python = translated;
+
+        assert_eq!(
+            python(
+                "let n = 0, s = \"n\";\nwhile (n < 10) n += 3;\ns += n;\n\
+                 if (n === 12 && s) n = n / 2;\nconsole.log(s + 1, `${s}!`)"
+            ),
+            "from js_runtime import js_str\nn = 0\ns = \"n\"\nwhile n < 10:\n    n += 3\n\
+             s += js_str(n)\nif n == 12 and s:\n    n = n / 2\nprint(s + \"1\", f\"{s}!\")\n"
+        );
+    }
+
+    /// Fields, accessors and private names, a constructor function with its prototype
+    #[test]
+    fn test_classes() {
+        let python = translated;
+
+        assert_eq!(
+            python(
+                "class A extends B { #n = 1; static k = 2; get n() { return this.#n } }\n\
+                 function P() {}\nP.prototype.f = function () { return 1 }"
+            ),
+            "class P:\n    def __init__(self):\n        pass\n\n    def f(self):\n        \
+             return 1\n\nclass A(B):\n    def __init__(self, *args):\n        \
+             super().__init__(*args)\n        self.__n = 1\n\n    k = 2\n\n    @property\n    \
+             def n(self):\n        return self.__n\n"
+        );
+    }
+
+    /// A block's own binding doesn't overwrite the one it shadows
+    #[test]
+    fn test_block_scopes() {
+        let python = translated;
+
+        assert_eq!(
+            python("let x = \"a\"; { let x = \"b\"; console.log(x) } console.log(x)"),
+            "x = \"a\"\nx_1 = \"b\"\nprint(x_1)\nprint(x)\n"
+        );
+    }
+
+    /// Closures assigning outer variables, and functions with a `this`
+    #[test]
+    fn test_closures() {
+        let python = translated;
+
+        assert_eq!(
+            python(
+                "let n = 0; const inc = () => n++;\n\
+                 function f() { let k = 0; return [() => k, () => { k = 1 }] }\n\
+                 const o = { g: function () { return () => this.n } }"
+            ),
+            "from js_runtime import JSObject, js_function\n\ndef f():\n    k = 0\n\n    \
+             def _fn1():\n        nonlocal k\n        k = 1\n\n    return [lambda: k, _fn1]\n\n\
+             n = 0\n\ndef inc():\n    global n\n    return (n := n + 1) - 1\n\n\
+             @js_function\ndef _fn2(self):\n    return lambda: self.n\n\n\
+             o = JSObject({\"g\": _fn2})\n"
+        );
+        // each iteration has its own i, var j is the loop's
+        assert_eq!(
+            python("for (let i of [1]) { var j = i; f(() => i + j) }"),
+            "from js_runtime import js_add\nfor i in [1]:\n    j = i\n    \
+             f((lambda i: lambda: js_add(i, j))(i))\n"
+        );
+    }
+
+    /// Templates, and the methods of strings and arrays
+    #[test]
+    fn test_templates_and_methods() {
+        let python = translated;
+
+        assert_eq!(
+            python(
+                "const s = \"a b\", xs = [1, 2];\nxs.push(s.length);\n\
+                 let t = `${s.toUpperCase()} {${`${xs.map(x => x + 1)}`}}`;\n\
+                 t = xs.filter(x => x > 1).join() + tag`${t.split(\" \")}`"
+            ),
+            "from js_runtime import js_add, js_gt, js_method, js_str, js_template\n\
+             s = \"a b\"\nxs = [1, 2]\nxs.append(len(s))\n\
+             t = f'{s.upper()} {{{f\"{js_str([js_add(x, 1) for x in xs])}\"}}}'\n\
+             t = js_method([x for x in xs if js_gt(x, 1)], \"join\") + \
+             js_str(tag(js_template([\"\", \"\"]), t.split(\" \")))\n"
+        );
+    }
+
+    /// Imports and exports
+    #[test]
+    fn test_modules() {
+        let python = translated;
+
+        assert_eq!(
+            python(
+                "export const a = 1;\nexport default function f() {}\nexport { a as b };\n\
+                 import g, { h as i } from \"../g.js\";\nimport * as ns from \"./lib/ns\";\n\
+                 import \"pkg/sub\";\nexport * from \"./c\";"
+            ),
+            "from ..g import default as g, h as i\nfrom .lib import ns\nimport pkg.sub\n\
+             from .c import *\nfrom .c import __all__ as _all1\n__all__ = [*_all1, \"a\", \"b\"]\n\n\
+             def f():\n    pass\n\na = 1\ndefault = f\nb = a\n"
+        );
+        // a name of Python's builtins is one with _ in every module
+        assert_eq!(
+            python(
+                "import { print } from \"./p\";\nexport const len = print;\nexport { len as list }"
+            ),
+            "from .p import print_\n__all__ = [\"len_\", \"list_\"]\nlen_ = print_\nlist_ = len_\n"
+        );
+    }
+
+    /// The lines of the Python say which statement of the JavaScript they come from
+    #[test]
+    fn test_located() {
+        let source = "let a = [];\nif (a) {\n  a.push(f(x => x))\n} else if (b) c()\n\
+                      class C { m() { return 1 } }";
+        let module = translated_module(source);
+        let comment = |span: Span| format!("js: {}", span.line_col(source).0);
+        let (text, lines) = python::unparse_located(&module, Some(&comment));
+        assert_eq!(
+            text,
+            "from js_runtime import js_truthy\n# js: 1\na = []\n# js: 2\nif js_truthy(a):\n    \
+             # js: 3\n    a.append(f(lambda x: x))\n# js: 4\nelif js_truthy(b):\n    c()\n\n\
+             # js: 5\nclass C:\n    def m(self):\n        return 1\n"
+        );
+        let lines: Vec<_> = lines
+            .iter()
+            .map(|span| span.map(|span| span.line_col(source).0))
+            .collect();
+        let expected = [
+            None,
+            Some(1),
+            Some(1),
+            Some(2),
+            Some(2),
+            Some(3),
+            Some(3),
+            Some(4),
+        ];
+        assert_eq!(lines[..8], expected);
+        assert_eq!(lines.len(), text.lines().count());
+    }
+
+    /// Each `.js` of `case/flow` translates to the `.py` next to it, which prints the `.out` next
+    /// to it as node does for the JavaScript, if there is a `python3` to run it
+    #[test]
+    fn test_control_flow() {
+        let cases = Path::new(env!("CARGO_MANIFEST_DIR")).join("case/flow");
+        let runtime = std::env::temp_dir().join("js_parser_test_control_flow");
+        fs::create_dir_all(&runtime).unwrap();
+        fs::write(runtime.join("js_runtime.py"), RUNTIME).unwrap();
+
+        let mut sources: Vec<_> = fs::read_dir(&cases)
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .filter(|path| path.extension().is_some_and(|e| e == "js"))
+            .collect();
+        sources.sort();
+        assert!(!sources.is_empty());
+        for source in sources {
+            let golden = source.with_extension("py");
+            assert_eq!(
+                translated(&fs::read_to_string(&source).unwrap()),
+                fs::read_to_string(&golden).unwrap(),
+                "{}",
+                source.display()
+            );
+            let run = Command::new("python3")
+                .arg(&golden)
+                .env("PYTHONPATH", &runtime)
+                .output();
+            let Ok(run) = run else {
+                eprintln!("no python3 to run {}", golden.display());
+                continue;
+            };
+            assert_eq!(
+                String::from_utf8_lossy(&run.stdout),
+                fs::read_to_string(source.with_extension("out")).unwrap(),
+                "{}: {}",
+                golden.display(),
+                String::from_utf8_lossy(&run.stderr)
+            );
+        }
+    }
+}
@@ -0,0 +1,474 @@
+//! Cover grammars. An array or object literal on the left of `=` (or in a `for await` head) is
+//! parsed as an expression, the parser can't tell it is a pattern until it sees what follows. Like
+//! the specification, the covered literal is reparsed here as an assignment pattern once it is
+//! known to be one. Arrow function parameters, parsed as a parenthesized expression or as the
+//! arguments of `async(...)`, get the same treatment. The other targets of an assignment or an
+//! update have to be identifiers or property accesses, which the grammar doesn't check either.
+
+use lr1_rs::ASTNode;
+
+/// A literal that can't be read as a pattern, a `{a = 1}` outside of one or another target that
+/// can't be assigned to
+#[derive(Debug)]
+pub struct CoverError {
+    /// Index of the first terminal of the offending node among the terminals of the tree
+    pub terminal: usize,
+    pub message: &'static str,
+}
+
+type Result<T> = std::result::Result<T, CoverError>;
+
+/// Reparse the covered patterns of `tree` in place
+pub fn reparse(tree: &mut ASTNode) -> Result<()> {
+    walk(tree, 0)
+}
+
+/// The name of a nonterminal without its parameters
+fn base(name: &str) -> &str {
+    name.split('[').next().unwrap_or(name)
+}
+
+/// Rename a nonterminal, keeping its parameters
+fn rename(name: &str, to: &str) -> String {
+    format!("{}{}", to, &name[base(name).len()..])
+}
+
+fn terminals(node: &ASTNode) -> usize {
+    match node {
+        ASTNode::Terminal(_) => 1,
+        ASTNode::NonTerminal(_, children) => children.iter().map(terminals).sum(),
+    }
+}
+
+/// Follow the chain of single children down to the node that does the work
+fn innermost(node: &ASTNode) -> &ASTNode {
+    match node {
+        ASTNode::NonTerminal(_, children) if children.len() == 1 => innermost(&children[0]),
+        _ => node,
+    }
+}
+
+fn innermost_mut(node: &mut ASTNode) -> &mut ASTNode {
+    if !matches!(node, ASTNode::NonTerminal(_, children) if children.len() == 1) {
+        return node;
+    }
+    let ASTNode::NonTerminal(_, children) = node else {
+        unreachable!()
+    };
+    innermost_mut(&mut children[0])
+}
+
+fn is(node: &ASTNode, names: &[&str]) -> bool {
+    matches!(node, ASTNode::NonTerminal(name, _) if names.contains(&base(name)))
+}
+
+fn is_terminal(node: &ASTNode, terminal: &str) -> bool {
+    matches!(node, ASTNode::Terminal(t) if t == terminal)
+}
+
+fn is_literal(node: &ASTNode) -> bool {
+    is(innermost(node), &["arrayLiteral", "objectLiteral"])
+}
+
+/// `a = b`, not `a += b`
+fn is_assignment(children: &[ASTNode]) -> bool {
+    children.len() == 3 && is_terminal(innermost(&children[1]), "=")
+}
+
+/// Identifiers and property accesses, possibly parenthesized
+fn is_simple(node: &ASTNode) -> bool {
+    match innermost(node) {
+        ASTNode::Terminal(t) => {
+            matches!(t.as_str(), "identifier" | "yield" | "await" | "async")
+                || crate::CONTEXTUAL_KEYWORDS.contains(&t.as_str())
+        }
+        ASTNode::NonTerminal(name, children) => match base(name) {
+            "memberExpression" | "callExpression" => {
+                children.len() >= 3
+                    && (is_terminal(&children[1], ".") || is_terminal(&children[1], "["))
+            }
+            "superProperty" => true,
+            "primaryExpression" => {
+                children.len() == 3 && !is_literal(&children[1]) && is_simple(&children[1])
+            }
+            _ => false,
+        },
+    }
+}
+
+fn walk(node: &mut ASTNode, start: usize) -> Result<()> {
+    let ASTNode::NonTerminal(name, children) = node else {
+        return Ok(());
+    };
+    match base(name) {
+        "coverInitializedName" => {
+            return Err(CoverError {
+                terminal: start,
+                message: "shorthand property initializer outside of a pattern",
+            })
+        }
+        "assignmentExpression" if is_assignment(children) && is_literal(&children[0]) => {
+            let value = start + terminals(&children[0]) + 1;
+            pattern(&mut children[0], start)?;
+            return walk(&mut children[2], value);
+        }
+        "assignmentExpression" if children.len() == 3 && !is_simple(&children[0]) => {
+            return Err(CoverError {
+                terminal: start,
+                message: "invalid assignment target",
+            })
+        }
+        "updateExpression" if children.len() == 2 => {
+            let (argument, start) =
+                match is_terminal(&children[0], "++") || is_terminal(&children[0], "--") {
+                    true => (&children[1], start + 1),
+                    false => (&children[0], start),
+                };
+            if !is_simple(argument) {
+                return Err(CoverError {
+                    terminal: start,
+                    message: "invalid update target",
+                });
+            }
+        }
+        "forBinding" if is_literal(&children[0]) => return pattern(&mut children[0], start),
+        // a pattern may repeat it, a literal can't
+        "objectLiteral" if proto_setters(children) > 1 => {
+            return Err(CoverError {
+                terminal: start,
+                message: "duplicate __proto__ property",
+            })
+        }
+        "arrowFunction" => parameters(&mut children[0], start)?,
+        _ => {}
+    }
+    walk_children(children, start)
+}
+
+/// The `__proto__: value` properties among `children` of an object literal. A `"__proto__"` key
+/// counts too, but string literals don't keep their text
+fn proto_setters(children: &[ASTNode]) -> usize {
+    children
+        .iter()
+        .map(|child| match child {
+            ASTNode::NonTerminal(name, list) if base(name) == "propertyDefinitionList" => {
+                proto_setters(list)
+            }
+            ASTNode::NonTerminal(name, property) if base(name) == "propertyDefinition" => {
+                let setter = property.len() == 3
+                    && is_terminal(&property[1], ":")
+                    && is_terminal(innermost(&property[0]), "__proto__");
+                setter as usize
+            }
+            _ => 0,
+        })
+        .sum()
+}
+
+fn walk_children(children: &mut [ASTNode], mut start: usize) -> Result<()> {
+    for child in children {
+        walk(child, start)?;
+        start += terminals(child);
+    }
+    Ok(())
+}
+
+/// The parameters of an arrow function: identifiers or patterns, with optional defaults
+fn parameters(node: &mut ASTNode, mut start: usize) -> Result<()> {
+    let lists = [
+        "arrowParameters",
+        "coverCallExpressionAndAsyncArrowHead",
+        "arguments",
+        "argumentList",
+        "spreadElement",
+        "expression",
+    ];
+    let ASTNode::NonTerminal(name, children) = node else {
+        return Ok(());
+    };
+    if !lists.contains(&base(name)) {
+        return Ok(());
+    }
+    for child in children {
+        match child {
+            ASTNode::Terminal(_) => {}
+            ASTNode::NonTerminal(name, _) if lists.contains(&base(name)) => {
+                parameters(child, start)?
+            }
+            ASTNode::NonTerminal(name, _) if base(name) == "bindingRestElement" => {}
+            _ => parameter(child, start)?,
+        }
+        start += terminals(child);
+    }
+    Ok(())
+}
+
+fn parameter(node: &mut ASTNode, start: usize) -> Result<()> {
+    let node = innermost_mut(node);
+    let target = match node {
+        ASTNode::NonTerminal(name, children)
+            if base(name) == "assignmentExpression" && is_assignment(children) =>
+        {
+            &mut children[0]
+        }
+        _ => node,
+    };
+    if is_literal(target) {
+        pattern(target, start)?;
+    }
+    if !binds_identifiers(target) {
+        return Err(CoverError {
+            terminal: start,
+            message: "invalid arrow function parameter",
+        });
+    }
+    Ok(())
+}
+
+/// Whether `node` is an identifier or a pattern binding identifiers only, the targets of an arrow
+/// function parameter can't be properties
+fn binds_identifiers(node: &ASTNode) -> bool {
+    let node = innermost(node);
+    let ASTNode::NonTerminal(name, children) = node else {
+        return is_simple(node);
+    };
+    let parts = || {
+        children
+            .iter()
+            .filter(|c| !matches!(c, ASTNode::Terminal(_)) && !is(c, &["elision_"]))
+    };
+    match base(name) {
+        "arrayAssignmentPattern"
+        | "objectAssignmentPattern"
+        | "assignmentElementList"
+        | "assignmentPropertyList" => parts().all(binds_identifiers),
+        "assignmentElement" => binds_identifiers(&children[0]),
+        "assignmentRestElement" | "assignmentRestProperty" => binds_identifiers(&children[1]),
+        // `a`, `a = 1` or `key: target`
+        "assignmentProperty" => children.len() < 3 || binds_identifiers(&children[2]),
+        _ => false,
+    }
+}
+
+/// Reparse the array or object literal at the bottom of `node` as an assignment pattern
+fn pattern(node: &mut ASTNode, start: usize) -> Result<()> {
+    let ASTNode::NonTerminal(name, children) = innermost_mut(node) else {
+        unreachable!()
+    };
+    let array = base(name) == "arrayLiteral";
+    *name = rename(
+        name,
+        if array {
+            "arrayAssignmentPattern"
+        } else {
+            "objectAssignmentPattern"
+        },
+    );
+    // `[a, ...b]`, a rest can't be followed by a comma
+    let rest = children.len() == 3;
+    let mut start = start;
+    for child in children.iter_mut() {
+        match array {
+            true if is(child, &["elementList"]) => elements(child, start, rest)?,
+            false if is(child, &["propertyDefinitionList"]) => properties(child, start, rest)?,
+            _ => {}
+        }
+        start += terminals(child);
+    }
+    Ok(())
+}
+
+/// `elementList`, whose last element may be a rest element if `rest`
+fn elements(node: &mut ASTNode, start: usize, rest: bool) -> Result<()> {
+    let ASTNode::NonTerminal(name, children) = node else {
+        unreachable!()
+    };
+    *name = rename(name, "assignmentElementList");
+    let mut start = start;
+    let last = children.len() - 1;
+    for (i, child) in children.iter_mut().enumerate() {
+        if i == last {
+            if is(child, &["spreadElement"]) {
+                rest_element(child, start, rest)?;
+            } else {
+                element(child, start)?;
+            }
+        } else if is(child, &["elementList"]) {
+            elements(child, start, false)?;
+        }
+        start += terminals(child);
+    }
+    Ok(())
+}
+
+/// `propertyDefinitionList`, whose last property may be a rest property if `rest`
+fn properties(node: &mut ASTNode, start: usize, rest: bool) -> Result<()> {
+    let ASTNode::NonTerminal(name, children) = node else {
+        unreachable!()
+    };
+    *name = rename(name, "assignmentPropertyList");
+    let mut start = start;
+    let last = children.len() - 1;
+    for (i, child) in children.iter_mut().enumerate() {
+        if i == last {
+            property(child, start, rest)?;
+        } else if is(child, &["propertyDefinitionList"]) {
+            properties(child, start, false)?;
+        }
+        start += terminals(child);
+    }
+    Ok(())
+}
+
+fn property(node: &mut ASTNode, start: usize, rest: bool) -> Result<()> {
+    let ASTNode::NonTerminal(name, children) = node else {
+        unreachable!()
+    };
+    let invalid = CoverError {
+        terminal: start,
+        message: "invalid destructuring assignment target",
+    };
+    if is_terminal(&children[0], "...") {
+        if !rest {
+            return Err(CoverError {
+                terminal: start,
+                message: "rest property must be last",
+            });
+        }
+        if is_literal(&children[1]) || !is_simple(&children[1]) {
+            return Err(invalid);
+        }
+        *name = rename(name, "assignmentRestProperty");
+        return walk(&mut children[1], start + 1);
+    }
+    *name = rename(name, "assignmentProperty");
+    match children.len() {
+        // `a` or `a = 1`
+        1 if is(&children[0], &["identifierReference"]) || is_terminal(&children[0], "async") => {
+            Ok(())
+        }
+        1 if is(&children[0], &["coverInitializedName"]) => {
+            let ASTNode::NonTerminal(_, cover) = children.remove(0) else {
+                unreachable!()
+            };
+            children.extend(cover);
+            let value = start + terminals(&children[0]);
+            walk(&mut children[1], value)
+        }
+        // `key: target`
+        3 => {
+            walk(&mut children[0], start)?;
+            let start = start + terminals(&children[0]) + 1;
+            element(&mut children[2], start)
+        }
+        _ => Err(invalid),
+    }
+}
+
+/// `...target`
+fn rest_element(node: &mut ASTNode, start: usize, rest: bool) -> Result<()> {
+    if !rest {
+        return Err(CoverError {
+            terminal: start,
+            message: "rest element must be last",
+        });
+    }
+    let ASTNode::NonTerminal(name, children) = node else {
+        unreachable!()
+    };
+    *name = rename(name, "assignmentRestElement");
+    target(&mut children[1], start + 1)
+}
+
+/// A target with an optional default value
+fn element(node: &mut ASTNode, start: usize) -> Result<()> {
+    if let ASTNode::NonTerminal(name, children) = innermost_mut(node) {
+        if base(name) == "assignmentExpression" && is_assignment(children) {
+            let value = start + terminals(&children[0]) + 1;
+            target(&mut children[0], start)?;
+            walk(&mut children[2], value)?;
+            *name = rename(name, "assignmentElement");
+            return Ok(());
+        }
+    }
+    target(node, start)
+}
+
+fn target(node: &mut ASTNode, start: usize) -> Result<()> {
+    if is_literal(node) {
+        return pattern(node, start);
+    }
+    if !is_simple(node) {
+        return Err(CoverError {
+            terminal: start,
+            message: "invalid destructuring assignment target",
+        });
+    }
+    walk(node, start)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test_parse_tree;
+
+    fn names(node: &ASTNode, out: &mut Vec<String>) {
+        if let ASTNode::NonTerminal(name, children) = node {
+            out.push(base(name).to_string());
+            children.iter().for_each(|c| names(c, out));
+        }
+    }
+
+    #[test]
+    fn test_reparse() {
+        let reparse = |source: &str| {
+            let (mut tree, _) = test_parse_tree(source);
+            let result = reparse(&mut tree).map_err(|e| (e.terminal, e.message));
+            let mut nodes = Vec::new();
+            names(&tree, &mut nodes);
+            (nodes, result)
+        };
+
+        let (nodes, result) = reparse("[a, {b: c.d = 1, ...e}, ...f] = g;");
+        assert!(result.is_ok());
+        for name in [
+            "arrayAssignmentPattern",
+            "objectAssignmentPattern",
+            "assignmentElement",
+            "assignmentRestProperty",
+            "assignmentRestElement",
+        ] {
+            assert!(nodes.iter().any(|n| n == name), "no {}", name);
+        }
+        assert!(!nodes
+            .iter()
+            .any(|n| n == "arrayLiteral" || n == "objectLiteral"));
+
+        let (nodes, result) = reparse("x = ({a = 1}, [b], c = {}, ...d) => [a, b];");
+        assert!(result.is_ok());
+        assert!(nodes.iter().any(|n| n == "arrayLiteral"));
+        assert!(reparse("x = async ([a], {b: c}, ...[d]) => a;").1.is_ok());
+        assert_eq!(
+            reparse("x = (a, [b.c]) => a;").1,
+            Err((5, "invalid arrow function parameter"))
+        );
+
+        assert_eq!(
+            reparse("x = {a = 1};").1,
+            Err((3, "shorthand property initializer outside of a pattern"))
+        );
+        assert_eq!(
+            reparse("[a, b + 1] = c;").1,
+            Err((3, "invalid destructuring assignment target"))
+        );
+        assert_eq!(
+            reparse("x = {__proto__: a, b, __proto__: c};").1,
+            Err((2, "duplicate __proto__ property"))
+        );
+        assert!(reparse("({__proto__: a, __proto__: b} = c);").1.is_ok());
+        assert_eq!(
+            reparse("({...a, b} = c);").1,
+            Err((2, "rest property must be last"))
+        );
+    }
+}
@@ -0,0 +1,763 @@
+//! ESTree JSON for the syntax tree of `ast.rs`, the format other JavaScript tools read and write.
+//! Every node has its `type` and, like acorn's, `start` and `end`, which are byte offsets here.
+
+use crate::ast::*;
+use js_lexer::token::Span;
+use serde_json::{json, Value};
+
+pub fn program(program: &Program) -> Value {
+    let source_type = if program.module { "module" } else { "script" };
+    node(
+        "Program",
+        program.span,
+        json!({
+            "body": statements(&program.body),
+            "sourceType": source_type,
+        }),
+    )
+}
+
+/// `fields` with the type and the range of the node
+fn node(kind: &str, span: Span, mut fields: Value) -> Value {
+    if let Value::Object(map) = &mut fields {
+        map.insert("type".to_string(), json!(kind));
+        map.insert("start".to_string(), json!(span.start));
+        map.insert("end".to_string(), json!(span.end));
+    }
+    fields
+}
+
+fn optional<T>(value: &Option<T>, to_json: impl Fn(&T) -> Value) -> Value {
+    value.as_ref().map_or(Value::Null, to_json)
+}
+
+fn statements(statements: &[Statement]) -> Value {
+    statements.iter().map(statement).collect()
+}
+
+fn identifier(id: &Identifier) -> Value {
+    node("Identifier", id.span, json!({ "name": id.name }))
+}
+
+fn private_identifier(id: &Identifier) -> Value {
+    node("PrivateIdentifier", id.span, json!({ "name": id.name }))
+}
+
+fn block(block: &Block) -> Value {
+    node(
+        "BlockStatement",
+        block.span,
+        json!({ "body": statements(&block.body) }),
+    )
+}
+
+fn statement(statement: &Statement) -> Value {
+    let span = statement.span();
+    match statement {
+        Statement::Expression {
+            expression: e,
+            directive,
+            ..
+        } => {
+            let mut fields = json!({ "expression": expression(e) });
+            if let Some(directive) = directive {
+                fields["directive"] = json!(directive);
+            }
+            node("ExpressionStatement", span, fields)
+        }
+        Statement::Block(b) => block(b),
+        Statement::Empty { .. } => node("EmptyStatement", span, json!({})),
+        Statement::Debugger { .. } => node("DebuggerStatement", span, json!({})),
+        Statement::With { object, body, .. } => node(
+            "WithStatement",
+            span,
+            json!({ "object": expression(object), "body": self::statement(body) }),
+        ),
+        Statement::Return { argument, .. } => node(
+            "ReturnStatement",
+            span,
+            json!({ "argument": optional(argument, expression) }),
+        ),
+        Statement::Labeled { label, body, .. } => node(
+            "LabeledStatement",
+            span,
+            json!({ "label": identifier(label), "body": self::statement(body) }),
+        ),
+        Statement::Break { label, .. } => node(
+            "BreakStatement",
+            span,
+            json!({ "label": optional(label, identifier) }),
+        ),
+        Statement::Continue { label, .. } => node(
+            "ContinueStatement",
+            span,
+            json!({ "label": optional(label, identifier) }),
+        ),
+        Statement::If {
+            test,
+            consequent,
+            alternate,
+            ..
+        } => node(
+            "IfStatement",
+            span,
+            json!({
+                "test": expression(test),
+                "consequent": self::statement(consequent),
+                "alternate": optional(alternate, |s| self::statement(s)),
+            }),
+        ),
+        Statement::Switch {
+            discriminant,
+            cases,
+            ..
+        } => {
+            let cases: Vec<_> = cases
+                .iter()
+                .map(|c| {
+                    node(
+                        "SwitchCase",
+                        c.span,
+                        json!({
+                            "test": optional(&c.test, expression),
+                            "consequent": statements(&c.consequent),
+                        }),
+                    )
+                })
+                .collect();
+            node(
+                "SwitchStatement",
+                span,
+                json!({ "discriminant": expression(discriminant), "cases": cases }),
+            )
+        }
+        Statement::Throw { argument, .. } => node(
+            "ThrowStatement",
+            span,
+            json!({ "argument": expression(argument) }),
+        ),
+        Statement::Try {
+            block: b,
+            handler,
+            finalizer,
+            ..
+        } => {
+            let handler = optional(handler, |h| {
+                node(
+                    "CatchClause",
+                    h.span,
+                    json!({ "param": pattern(&h.param), "body": block(&h.body) }),
+                )
+            });
+            node(
+                "TryStatement",
+                span,
+                json!({
+                    "block": block(b),
+                    "handler": handler,
+                    "finalizer": optional(finalizer, block),
+                }),
+            )
+        }
+        Statement::While { test, body, .. } => node(
+            "WhileStatement",
+            span,
+            json!({ "test": expression(test), "body": self::statement(body) }),
+        ),
+        Statement::DoWhile { body, test, .. } => node(
+            "DoWhileStatement",
+            span,
+            json!({ "body": self::statement(body), "test": expression(test) }),
+        ),
+        Statement::For {
+            init,
+            test,
+            update,
+            body,
+            ..
+        } => {
+            let init = optional(init, |init| match init {
+                ForInit::Variable(declaration) => variable_declaration(declaration),
+                ForInit::Expression(e) => expression(e),
+            });
+            node(
+                "ForStatement",
+                span,
+                json!({
+                    "init": init,
+                    "test": optional(test, expression),
+                    "update": optional(update, expression),
+                    "body": self::statement(body),
+                }),
+            )
+        }
+        Statement::ForIn {
+            left, right, body, ..
+        } => node(
+            "ForInStatement",
+            span,
+            json!({
+                "left": for_head(left),
+                "right": expression(right),
+                "body": self::statement(body),
+            }),
+        ),
+        Statement::ForOf {
+            left,
+            right,
+            body,
+            is_await,
+            ..
+        } => node(
+            "ForOfStatement",
+            span,
+            json!({
+                "await": is_await,
+                "left": for_head(left),
+                "right": expression(right),
+                "body": self::statement(body),
+            }),
+        ),
+        Statement::Function(f) => function("FunctionDeclaration", f),
+        Statement::Variable(declaration) => variable_declaration(declaration),
+        Statement::Class(c) => class("ClassDeclaration", c),
+        Statement::Import(import) => {
+            let specifiers: Vec<_> = import
+                .specifiers
+                .iter()
+                .map(|s| match s {
+                    ImportSpecifier::Default(local) => node(
+                        "ImportDefaultSpecifier",
+                        local.span,
+                        json!({ "local": identifier(local) }),
+                    ),
+                    ImportSpecifier::Namespace { local, span } => node(
+                        "ImportNamespaceSpecifier",
+                        *span,
+                        json!({ "local": identifier(local) }),
+                    ),
+                    ImportSpecifier::Named {
+                        imported,
+                        local,
+                        span,
+                    } => node(
+                        "ImportSpecifier",
+                        *span,
+                        json!({
+                            "imported": module_export_name(imported),
+                            "local": identifier(local),
+                        }),
+                    ),
+                })
+                .collect();
+            node(
+                "ImportDeclaration",
+                span,
+                json!({ "specifiers": specifiers, "source": literal(&import.source) }),
+            )
+        }
+        Statement::ExportNamed(export) => {
+            let specifiers: Vec<_> = export
+                .specifiers
+                .iter()
+                .map(|s| {
+                    node(
+                        "ExportSpecifier",
+                        s.span,
+                        json!({
+                            "local": module_export_name(&s.local),
+                            "exported": module_export_name(&s.exported),
+                        }),
+                    )
+                })
+                .collect();
+            node(
+                "ExportNamedDeclaration",
+                span,
+                json!({
+                    "declaration": optional(&export.declaration, |s| self::statement(s)),
+                    "specifiers": specifiers,
+                    "source": optional(&export.source, literal),
+                }),
+            )
+        }
+        Statement::ExportDefault { declaration, .. } => {
+            let declaration = match declaration {
+                ExportDefault::Function(f) => function("FunctionDeclaration", f),
+                ExportDefault::Class(c) => class("ClassDeclaration", c),
+                ExportDefault::Expression(e) => expression(e),
+            };
+            node(
+                "ExportDefaultDeclaration",
+                span,
+                json!({ "declaration": declaration }),
+            )
+        }
+        Statement::ExportAll {
+            exported, source, ..
+        } => node(
+            "ExportAllDeclaration",
+            span,
+            json!({
+                "exported": optional(exported, module_export_name),
+                "source": literal(source),
+            }),
+        ),
+    }
+}
+
+fn variable_declaration(declaration: &VariableDeclaration) -> Value {
+    let declarations: Vec<_> = declaration
+        .declarations
+        .iter()
+        .map(|d| {
+            node(
+                "VariableDeclarator",
+                d.span,
+                json!({ "id": pattern(&d.id), "init": optional(&d.init, expression) }),
+            )
+        })
+        .collect();
+    node(
+        "VariableDeclaration",
+        declaration.span,
+        json!({ "declarations": declarations, "kind": declaration.kind.as_str() }),
+    )
+}
+
+fn for_head(head: &ForHead) -> Value {
+    match head {
+        ForHead::Variable(declaration) => variable_declaration(declaration),
+        ForHead::Pattern(p) => pattern(p),
+    }
+}
+
+fn module_export_name(name: &ModuleExportName) -> Value {
+    match name {
+        ModuleExportName::Identifier(id) => identifier(id),
+        ModuleExportName::String(s) => literal(s),
+    }
+}
+
+fn function(kind: &str, f: &Function) -> Value {
+    node(
+        kind,
+        f.span,
+        json!({
+            "id": optional(&f.id, identifier),
+            "expression": false,
+            "generator": f.generator,
+            "async": f.is_async,
+            "params": f.params.iter().map(pattern).collect::<Vec<_>>(),
+            "body": block(&f.body),
+        }),
+    )
+}
+
+fn class(kind: &str, c: &Class) -> Value {
+    let body: Vec<_> = c.body.body.iter().map(class_member).collect();
+    node(
+        kind,
+        c.span,
+        json!({
+            "id": optional(&c.id, identifier),
+            "superClass": optional(&c.super_class, expression),
+            "body": node("ClassBody", c.body.span, json!({ "body": body })),
+        }),
+    )
+}
+
+fn class_member(member: &ClassMember) -> Value {
+    match member {
+        ClassMember::Method(m) => {
+            let kind = match m.kind {
+                MethodKind::Constructor => "constructor",
+                MethodKind::Method => "method",
+                MethodKind::Get => "get",
+                MethodKind::Set => "set",
+            };
+            node(
+                "MethodDefinition",
+                m.span,
+                json!({
+                    "static": m.is_static,
+                    "computed": m.key.computed(),
+                    "key": property_key(&m.key),
+                    "kind": kind,
+                    "value": function("FunctionExpression", &m.value),
+                }),
+            )
+        }
+        ClassMember::Field(f) => node(
+            "PropertyDefinition",
+            f.span,
+            json!({
+                "static": f.is_static,
+                "computed": f.key.computed(),
+                "key": property_key(&f.key),
+                "value": optional(&f.value, expression),
+            }),
+        ),
+        ClassMember::StaticBlock(b) => node(
+            "StaticBlock",
+            b.span,
+            json!({ "body": statements(&b.body) }),
+        ),
+    }
+}
+
+fn property_key(key: &PropertyKey) -> Value {
+    match key {
+        PropertyKey::Identifier(id) => identifier(id),
+        PropertyKey::Literal(l) => literal(l),
+        PropertyKey::Computed(e) => expression(e),
+        PropertyKey::PrivateName(id) => private_identifier(id),
+    }
+}
+
+fn literal(literal: &Literal) -> Value {
+    let mut fields = json!({ "raw": literal.raw });
+    fields["value"] = match &literal.value {
+        LiteralValue::Null => Value::Null,
+        LiteralValue::Boolean(b) => json!(b),
+        // whole numbers as integers, the way JavaScript prints them
+        LiteralValue::Number(n) if n.fract() == 0.0 && n.abs() < 9007199254740992.0 => {
+            json!(*n as i64)
+        }
+        LiteralValue::Number(n) => json!(n),
+        LiteralValue::String(s) => json!(s),
+        LiteralValue::RegExp { pattern, flags } => {
+            fields["regex"] = json!({ "pattern": pattern, "flags": flags });
+            Value::Null
+        }
+    };
+    node("Literal", literal.span, fields)
+}
+
+fn template(template: &TemplateLiteral) -> Value {
+    let quasis: Vec<_> = template
+        .quasis
+        .iter()
+        .map(|q| {
+            node(
+                "TemplateElement",
+                q.span,
+                json!({
+                    "value": { "raw": q.raw, "cooked": q.cooked },
+                    "tail": q.tail,
+                }),
+            )
+        })
+        .collect();
+    let expressions: Vec<_> = template.expressions.iter().map(expression).collect();
+    node(
+        "TemplateLiteral",
+        template.span,
+        json!({ "expressions": expressions, "quasis": quasis }),
+    )
+}
+
+fn expression_or_spread(e: &ExpressionOrSpread) -> Value {
+    match e {
+        ExpressionOrSpread::Expression(e) => expression(e),
+        ExpressionOrSpread::Spread(spread) => node(
+            "SpreadElement",
+            spread.span,
+            json!({ "argument": expression(&spread.argument) }),
+        ),
+    }
+}
+
+fn expression(e: &Expression) -> Value {
+    let span = e.span();
+    let list = |items: &[ExpressionOrSpread]| -> Vec<_> {
+        items.iter().map(expression_or_spread).collect()
+    };
+    match e {
+        Expression::Identifier(id) => identifier(id),
+        Expression::PrivateIdentifier(id) => private_identifier(id),
+        Expression::Literal(l) => literal(l),
+        Expression::Template(t) => template(t),
+        Expression::TaggedTemplate { tag, quasi, .. } => node(
+            "TaggedTemplateExpression",
+            span,
+            json!({ "tag": expression(tag), "quasi": template(quasi) }),
+        ),
+        Expression::This { .. } => node("ThisExpression", span, json!({})),
+        Expression::Super { .. } => node("Super", span, json!({})),
+        Expression::Array { elements, .. } => {
+            let elements: Vec<_> = elements
+                .iter()
+                .map(|e| optional(e, expression_or_spread))
+                .collect();
+            node("ArrayExpression", span, json!({ "elements": elements }))
+        }
+        Expression::Object { properties, .. } => {
+            let properties: Vec<_> = properties
+                .iter()
+                .map(|p| match p {
+                    ObjectProperty::Property(p) => {
+                        let kind = match p.kind {
+                            PropertyKind::Init => "init",
+                            PropertyKind::Get => "get",
+                            PropertyKind::Set => "set",
+                        };
+                        node(
+                            "Property",
+                            p.span,
+                            json!({
+                                "method": p.method,
+                                "shorthand": p.shorthand,
+                                "computed": p.key.computed(),
+                                "key": property_key(&p.key),
+                                "value": expression(&p.value),
+                                "kind": kind,
+                            }),
+                        )
+                    }
+                    ObjectProperty::Spread(spread) => node(
+                        "SpreadElement",
+                        spread.span,
+                        json!({ "argument": expression(&spread.argument) }),
+                    ),
+                })
+                .collect();
+            node(
+                "ObjectExpression",
+                span,
+                json!({ "properties": properties }),
+            )
+        }
+        Expression::Function(f) => function("FunctionExpression", f),
+        Expression::Arrow(arrow) => {
+            let (is_expression, body) = match &arrow.body {
+                ArrowBody::Block(b) => (false, block(b)),
+                ArrowBody::Expression(e) => (true, expression(e)),
+            };
+            node(
+                "ArrowFunctionExpression",
+                span,
+                json!({
+                    "id": null,
+                    "expression": is_expression,
+                    "generator": false,
+                    "async": arrow.is_async,
+                    "params": arrow.params.iter().map(pattern).collect::<Vec<_>>(),
+                    "body": body,
+                }),
+            )
+        }
+        Expression::Class(c) => class("ClassExpression", c),
+        Expression::Unary {
+            operator, argument, ..
+        } => node(
+            "UnaryExpression",
+            span,
+            json!({
+                "operator": operator.as_str(),
+                "prefix": true,
+                "argument": expression(argument),
+            }),
+        ),
+        Expression::Update {
+            operator,
+            prefix,
+            argument,
+            ..
+        } => node(
+            "UpdateExpression",
+            span,
+            json!({
+                "operator": operator.as_str(),
+                "prefix": prefix,
+                "argument": expression(argument),
+            }),
+        ),
+        Expression::Binary {
+            operator,
+            left,
+            right,
+            ..
+        } => node(
+            "BinaryExpression",
+            span,
+            json!({
+                "left": expression(left),
+                "operator": operator.as_str(),
+                "right": expression(right),
+            }),
+        ),
+        Expression::Logical {
+            operator,
+            left,
+            right,
+            ..
+        } => node(
+            "LogicalExpression",
+            span,
+            json!({
+                "left": expression(left),
+                "operator": operator.as_str(),
+                "right": expression(right),
+            }),
+        ),
+        Expression::Assignment {
+            operator,
+            left,
+            right,
+            ..
+        } => node(
+            "AssignmentExpression",
+            span,
+            json!({
+                "operator": operator.as_str(),
+                "left": pattern(left),
+                "right": expression(right),
+            }),
+        ),
+        Expression::Conditional {
+            test,
+            consequent,
+            alternate,
+            ..
+        } => node(
+            "ConditionalExpression",
+            span,
+            json!({
+                "test": expression(test),
+                "consequent": expression(consequent),
+                "alternate": expression(alternate),
+            }),
+        ),
+        Expression::Call {
+            callee,
+            arguments,
+            optional,
+            ..
+        } => node(
+            "CallExpression",
+            span,
+            json!({
+                "callee": expression(callee),
+                "arguments": list(arguments),
+                "optional": optional,
+            }),
+        ),
+        Expression::New {
+            callee, arguments, ..
+        } => node(
+            "NewExpression",
+            span,
+            json!({ "callee": expression(callee), "arguments": list(arguments) }),
+        ),
+        Expression::Member {
+            object,
+            property,
+            optional,
+            ..
+        } => {
+            let (computed, property) = match property {
+                MemberProperty::Identifier(id) => (false, identifier(id)),
+                MemberProperty::PrivateName(id) => (false, private_identifier(id)),
+                MemberProperty::Computed(e) => (true, expression(e)),
+            };
+            node(
+                "MemberExpression",
+                span,
+                json!({
+                    "object": expression(object),
+                    "property": property,
+                    "computed": computed,
+                    "optional": optional,
+                }),
+            )
+        }
+        Expression::Chain { expression: e, .. } => node(
+            "ChainExpression",
+            span,
+            json!({ "expression": expression(e) }),
+        ),
+        Expression::Sequence { expressions, .. } => node(
+            "SequenceExpression",
+            span,
+            json!({ "expressions": expressions.iter().map(expression).collect::<Vec<_>>() }),
+        ),
+        Expression::Yield {
+            argument, delegate, ..
+        } => node(
+            "YieldExpression",
+            span,
+            json!({
+                "delegate": delegate,
+                "argument": optional(argument, |a| expression(a)),
+            }),
+        ),
+        Expression::Await { argument, .. } => node(
+            "AwaitExpression",
+            span,
+            json!({ "argument": expression(argument) }),
+        ),
+        Expression::Import { source, .. } => node(
+            "ImportExpression",
+            span,
+            json!({ "source": expression(source) }),
+        ),
+        Expression::MetaProperty { meta, property, .. } => node(
+            "MetaProperty",
+            span,
+            json!({ "meta": identifier(meta), "property": identifier(property) }),
+        ),
+    }
+}
+
+fn pattern(p: &Pattern) -> Value {
+    let span = p.span();
+    match p {
+        Pattern::Identifier(id) => identifier(id),
+        Pattern::Expression(e) => expression(e),
+        Pattern::Object { properties, .. } => {
+            let properties: Vec<_> = properties
+                .iter()
+                .map(|p| match p {
+                    PatternProperty::Property {
+                        key,
+                        value,
+                        shorthand,
+                        span,
+                    } => node(
+                        "Property",
+                        *span,
+                        json!({
+                            "method": false,
+                            "shorthand": shorthand,
+                            "computed": key.computed(),
+                            "key": property_key(key),
+                            "value": pattern(value),
+                            "kind": "init",
+                        }),
+                    ),
+                    PatternProperty::Rest { argument, span } => node(
+                        "RestElement",
+                        *span,
+                        json!({ "argument": pattern(argument) }),
+                    ),
+                })
+                .collect();
+            node("ObjectPattern", span, json!({ "properties": properties }))
+        }
+        Pattern::Array { elements, .. } => {
+            let elements: Vec<_> = elements.iter().map(|e| optional(e, pattern)).collect();
+            node("ArrayPattern", span, json!({ "elements": elements }))
+        }
+        Pattern::Rest { argument, .. } => node(
+            "RestElement",
+            span,
+            json!({ "argument": pattern(argument) }),
+        ),
+        Pattern::Assignment { left, right, .. } => node(
+            "AssignmentPattern",
+            span,
+            json!({ "left": pattern(left), "right": expression(right) }),
+        ),
+    }
+}
@@ -0,0 +1,362 @@
+//! What kind of value an expression has, as far as the program shows without running it. A
+//! variable has a kind if every value assigned to it anywhere has that kind; variables are told
+//! apart by name only, so all variables of one name share what is assigned to any of them.
+
+use std::collections::HashMap;
+
+use crate::ast::*;
+use crate::methods;
+use crate::visit::{self, bound_names, Visit};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Kind {
+    /// No value yet, only while solving
+    Never,
+    Undefined,
+    Null,
+    Boolean,
+    Number,
+    String,
+    Array,
+    /// Objects and functions
+    Object,
+    Unknown,
+}
+
+impl Kind {
+    fn join(self, other: Kind) -> Kind {
+        match (self, other) {
+            (Kind::Never, k) | (k, Kind::Never) => k,
+            (a, b) if a == b => a,
+            _ => Kind::Unknown,
+        }
+    }
+
+    /// Whether Python's truth value of such a value is JavaScript's. Python numbers disagree
+    /// about `NaN`, lists and dicts about being empty
+    pub fn truthiness_agrees(self) -> bool {
+        matches!(
+            self,
+            Kind::Boolean | Kind::String | Kind::Null | Kind::Undefined
+        )
+    }
+}
+
+/// The kinds of the variables of a program
+pub struct Kinds {
+    variables: HashMap<String, Kind>,
+    /// Whether there is a `null` anywhere, without one no value is `null`
+    nulls: bool,
+}
+
+/// Where the value of a variable comes from
+enum Source<'a> {
+    Kind(Kind),
+    Value(&'a Expression),
+    /// `x op= value`, where the old value of `x` counts
+    Compound(BinaryOperator, &'a Expression),
+}
+
+#[derive(Default)]
+struct Collector<'a> {
+    sources: HashMap<&'a str, Vec<Source<'a>>>,
+    nulls: bool,
+}
+
+impl<'a> Collector<'a> {
+    fn add(&mut self, name: &'a str, source: Source<'a>) {
+        self.sources.entry(name).or_default().push(source);
+    }
+
+    /// Names whose value isn't known, from parameters, patterns and loop heads
+    fn unknown(&mut self, pattern: &'a Pattern) {
+        let mut names = Vec::new();
+        bound_names(pattern, &mut names);
+        for id in names {
+            self.add(&id.name, Source::Kind(Kind::Unknown));
+        }
+    }
+
+    fn declaration(&mut self, declaration: &'a VariableDeclaration) {
+        for declarator in &declaration.declarations {
+            match (&declarator.id, &declarator.init) {
+                (Pattern::Identifier(id), Some(init)) => self.add(&id.name, Source::Value(init)),
+                (Pattern::Identifier(id), None) => {
+                    self.add(&id.name, Source::Kind(Kind::Undefined))
+                }
+                (pattern, _) => self.unknown(pattern),
+            }
+        }
+    }
+}
+
+impl<'a> Visit<'a> for Collector<'a> {
+    fn visit_statement(&mut self, statement: &'a Statement) {
+        match statement {
+            Statement::Variable(declaration)
+            | Statement::For {
+                init: Some(ForInit::Variable(declaration)),
+                ..
+            } => self.declaration(declaration),
+            Statement::ForIn { left, .. } | Statement::ForOf { left, .. } => match left {
+                ForHead::Variable(declaration) => {
+                    for declarator in &declaration.declarations {
+                        self.unknown(&declarator.id);
+                    }
+                }
+                ForHead::Pattern(pattern) => self.unknown(pattern),
+            },
+            Statement::Try {
+                handler: Some(handler),
+                ..
+            } => self.unknown(&handler.param),
+            Statement::Import(import) => {
+                for specifier in &import.specifiers {
+                    let local = match specifier {
+                        ImportSpecifier::Default(local)
+                        | ImportSpecifier::Namespace { local, .. }
+                        | ImportSpecifier::Named { local, .. } => local,
+                    };
+                    self.add(&local.name, Source::Kind(Kind::Unknown));
+                }
+            }
+            _ => {}
+        }
+        visit::walk_statement(self, statement);
+    }
+
+    fn visit_expression(&mut self, expression: &'a Expression) {
+        match expression {
+            Expression::Assignment {
+                operator,
+                left,
+                right,
+                ..
+            } => match left.as_ref() {
+                Pattern::Identifier(id) => {
+                    let source = match operator {
+                        AssignmentOperator::Assign
+                        | AssignmentOperator::OrAssign
+                        | AssignmentOperator::AndAssign
+                        | AssignmentOperator::CoalesceAssign => Source::Value(right),
+                        AssignmentOperator::AddAssign => {
+                            Source::Compound(BinaryOperator::Add, right)
+                        }
+                        _ => Source::Kind(Kind::Number),
+                    };
+                    self.add(&id.name, source);
+                }
+                pattern => self.unknown(pattern),
+            },
+            Expression::Update { argument, .. } => {
+                if let Expression::Identifier(id) = argument.as_ref() {
+                    self.add(&id.name, Source::Kind(Kind::Number));
+                }
+            }
+            Expression::Literal(Literal {
+                value: LiteralValue::Null,
+                ..
+            }) => self.nulls = true,
+            _ => {}
+        }
+        visit::walk_expression(self, expression);
+    }
+
+    fn visit_function(&mut self, function: &'a Function) {
+        if let Some(id) = &function.id {
+            self.add(&id.name, Source::Kind(Kind::Object));
+        }
+        for param in &function.params {
+            self.unknown(param);
+        }
+        visit::walk_function(self, function);
+    }
+
+    fn visit_arrow(&mut self, arrow: &'a ArrowFunction) {
+        for param in &arrow.params {
+            self.unknown(param);
+        }
+        visit::walk_arrow(self, arrow);
+    }
+
+    fn visit_class(&mut self, class: &'a Class) {
+        if let Some(id) = &class.id {
+            self.add(&id.name, Source::Kind(Kind::Object));
+        }
+        visit::walk_class(self, class);
+    }
+}
+
+impl Kinds {
+    /// The kinds of the variables of `program`: each starts with no value and takes on the kinds
+    /// of what is assigned to it until nothing changes
+    pub fn new(program: &Program) -> Kinds {
+        let mut collector = Collector::default();
+        visit::walk_program(&mut collector, program);
+        let mut kinds = Kinds {
+            variables: collector
+                .sources
+                .keys()
+                .map(|name| (name.to_string(), Kind::Never))
+                .collect(),
+            nulls: collector.nulls,
+        };
+        loop {
+            let mut changed = false;
+            for (name, sources) in &collector.sources {
+                let kind = sources.iter().fold(Kind::Never, |kind, source| {
+                    let source = match source {
+                        Source::Kind(kind) => *kind,
+                        Source::Value(value) => kinds.of(value),
+                        Source::Compound(operator, value) => {
+                            kinds.binary(*operator, kinds.variables[*name], kinds.of(value))
+                        }
+                    };
+                    kind.join(source)
+                });
+                if kinds.variables[*name] != kind {
+                    kinds.variables.insert(name.to_string(), kind);
+                    changed = true;
+                }
+            }
+            if !changed {
+                break;
+            }
+        }
+        // only assigned from each other
+        for kind in kinds.variables.values_mut() {
+            if *kind == Kind::Never {
+                *kind = Kind::Unknown;
+            }
+        }
+        kinds
+    }
+
+    /// Whether the program declares or assigns a variable of that name
+    pub fn is_declared(&self, name: &str) -> bool {
+        self.variables.contains_key(name)
+    }
+
+    /// Whether a value of that kind may be `null`
+    pub fn may_be_null(&self, kind: Kind) -> bool {
+        kind == Kind::Null || (kind == Kind::Unknown && self.nulls)
+    }
+
+    pub fn variable(&self, name: &str) -> Kind {
+        self.variables.get(name).copied().unwrap_or(Kind::Unknown)
+    }
+
+    pub fn of(&self, expression: &Expression) -> Kind {
+        match expression {
+            Expression::Identifier(id) => match id.name.as_str() {
+                "undefined" if !self.is_declared("undefined") => Kind::Undefined,
+                "NaN" | "Infinity" if !self.is_declared(&id.name) => Kind::Number,
+                name => self.variable(name),
+            },
+            Expression::Literal(literal) => match literal.value {
+                LiteralValue::Null => Kind::Null,
+                LiteralValue::Boolean(_) => Kind::Boolean,
+                LiteralValue::Number(_) => Kind::Number,
+                LiteralValue::String(_) => Kind::String,
+                LiteralValue::RegExp { .. } => Kind::Object,
+            },
+            Expression::Template(_) => Kind::String,
+            Expression::Array { .. } => Kind::Array,
+            Expression::Object { .. }
+            | Expression::Function(_)
+            | Expression::Arrow(_)
+            | Expression::Class(_)
+            | Expression::New { .. } => Kind::Object,
+            Expression::Unary { operator, .. } => match operator {
+                UnaryOperator::Minus | UnaryOperator::Plus | UnaryOperator::BitNot => Kind::Number,
+                UnaryOperator::Not | UnaryOperator::Delete => Kind::Boolean,
+                UnaryOperator::Typeof => Kind::String,
+                UnaryOperator::Void => Kind::Undefined,
+            },
+            Expression::Update { .. } => Kind::Number,
+            Expression::Binary {
+                operator,
+                left,
+                right,
+                ..
+            } => self.binary(*operator, self.of(left), self.of(right)),
+            Expression::Logical { left, right, .. } => self.of(left).join(self.of(right)),
+            Expression::Conditional {
+                consequent,
+                alternate,
+                ..
+            } => self.of(consequent).join(self.of(alternate)),
+            Expression::Assignment {
+                operator,
+                left,
+                right,
+                ..
+            } => {
+                let current = match left.as_ref() {
+                    Pattern::Identifier(id) => self.variable(&id.name),
+                    _ => Kind::Unknown,
+                };
+                match operator {
+                    AssignmentOperator::Assign => self.of(right),
+                    AssignmentOperator::AddAssign => {
+                        self.binary(BinaryOperator::Add, current, self.of(right))
+                    }
+                    AssignmentOperator::OrAssign
+                    | AssignmentOperator::AndAssign
+                    | AssignmentOperator::CoalesceAssign => current.join(self.of(right)),
+                    _ => Kind::Number,
+                }
+            }
+            Expression::Sequence { expressions, .. } => expressions
+                .last()
+                .map_or(Kind::Undefined, |last| self.of(last)),
+            Expression::Member {
+                object,
+                property: MemberProperty::Identifier(id),
+                optional: false,
+                ..
+            } if id.name == "length" && matches!(self.of(object), Kind::String | Kind::Array) => {
+                Kind::Number
+            }
+            Expression::Call {
+                callee,
+                optional: false,
+                ..
+            } => match callee.as_ref() {
+                Expression::Member {
+                    object,
+                    property: MemberProperty::Identifier(id),
+                    optional: false,
+                    ..
+                } => {
+                    methods::method(self.of(object), &id.name).map_or(Kind::Unknown, |m| m.returns)
+                }
+                _ => Kind::Unknown,
+            },
+            _ => Kind::Unknown,
+        }
+    }
+
+    fn binary(&self, operator: BinaryOperator, left: Kind, right: Kind) -> Kind {
+        match operator {
+            BinaryOperator::Add => match (left, right) {
+                (Kind::Never, _) | (_, Kind::Never) => Kind::Never,
+                (Kind::String, _) | (_, Kind::String) => Kind::String,
+                (Kind::Array | Kind::Object | Kind::Unknown, _)
+                | (_, Kind::Array | Kind::Object | Kind::Unknown) => Kind::Unknown,
+                _ => Kind::Number,
+            },
+            BinaryOperator::Equal
+            | BinaryOperator::NotEqual
+            | BinaryOperator::StrictEqual
+            | BinaryOperator::StrictNotEqual
+            | BinaryOperator::Less
+            | BinaryOperator::LessEqual
+            | BinaryOperator::Greater
+            | BinaryOperator::GreaterEqual
+            | BinaryOperator::In
+            | BinaryOperator::Instanceof => Kind::Boolean,
+            _ => Kind::Number,
+        }
+    }
+}
@@ -0,0 +1,1413 @@
+"""JavaScript semantics for Python translated from JavaScript.
+
+`undefined` is None, `null` is the falsy singleton below, plain objects are `JSObject`s and
+arrays are lists. The translator calls these helpers only where the plain Python operation could
+differ from the JavaScript one.
+"""
+
+import asyncio
+import builtins
+import functools
+import inspect
+import json
+import math
+import random
+import re
+import sys
+
+undefined = None
+
+
+class _Null:
+    __slots__ = ()
+
+    def __bool__(self):
+        return False
+
+    def __repr__(self):
+        return "null"
+
+
+null = _Null()
+
+_NUMBER = re.compile(r"[+-]?(\d+\.?\d*|\.\d+)([eE][+-]?\d+)?")
+_INDEX = re.compile(r"0|[1-9]\d*")
+_IDENTIFIER = re.compile(r"[A-Za-z_]\w*", re.ASCII)
+
+
+def _key(key):
+    return key if isinstance(key, str) else js_str(key)
+
+
+class JSObject(dict):
+    """A plain object: a dict of string keys, which are its attributes too. A missing property is
+    undefined, and objects are equal only to themselves."""
+
+    __slots__ = ()
+
+    def __init__(self, *args, **kwargs):
+        super().__init__()
+        for key, value in dict(*args, **kwargs).items():
+            self[key] = value
+
+    def __getattribute__(self, name):
+        if name.startswith("__") and name.endswith("__"):
+            return object.__getattribute__(self, name)
+        return _bound(dict.get(self, name), self)
+
+    def __setattr__(self, name, value):
+        self[name] = value
+
+    def __delattr__(self, name):
+        dict.pop(self, name, None)
+
+    def __getitem__(self, key):
+        return _bound(dict.get(self, _key(key)), self)
+
+    def __setitem__(self, key, value):
+        accessor = dict.get(self, _key(key))
+        if isinstance(accessor, js_accessor):
+            accessor.write(self, value)
+        else:
+            dict.__setitem__(self, _key(key), value)
+
+    def __delitem__(self, key):
+        dict.pop(self, _key(key), None)
+
+    def __contains__(self, key):
+        return dict.__contains__(self, _key(key))
+
+    __eq__ = object.__eq__
+    __ne__ = object.__ne__
+    __hash__ = object.__hash__
+
+
+class js_function:
+    """A function that uses `this`, which it gets as its first argument: the object it is a
+    property of when called as a method, undefined when called on its own"""
+
+    def __init__(self, function):
+        self.function = function
+        self.__name__ = function.__name__
+
+    def __call__(self, *args):
+        return self.function(None, *args)
+
+    def __get__(self, obj, owner=None):
+        return self if obj is None else self.bind(obj)
+
+    def bind(self, this, *args):
+        bound = functools.partial(self.function, this, *args)
+        bound.__name__ = "bound " + self.__name__
+        return bound
+
+    def call(self, this=None, *args):
+        return self.function(this, *args)
+
+    def apply(self, this=None, args=None):
+        return self.function(this, *(args or []))
+
+
+class js_accessor:
+    """The getter and setter of a property of a plain object, which it runs on reading and
+    writing the property"""
+
+    def __init__(self, get=None, set=None):
+        self.get = get
+        self.set = set
+
+    def read(self, obj):
+        return None if self.get is None else _bound(self.get, obj)()
+
+    def write(self, obj, value):
+        if self.set is not None:
+            _bound(self.set, obj)(value)
+
+
+class js_static_property(property):
+    """A static getter and setter of a class, a property of the class rather than its instances.
+    Writing it needs the class to be a `JSStatic`."""
+
+    def __get__(self, obj, owner=None):
+        return super().__get__(owner)
+
+
+class _StaticAccessors(type):
+    def __setattr__(cls, name, value):
+        for base in cls.__mro__:
+            accessor = base.__dict__.get(name)
+            if isinstance(accessor, js_static_property):
+                return accessor.__set__(cls, value)
+        super().__setattr__(name, value)
+
+
+class JSStatic(metaclass=_StaticAccessors):
+    """The base of the classes with static accessors"""
+
+
+def _bound(value, obj):
+    if isinstance(value, js_accessor):
+        return value.read(obj)
+    return value.bind(obj) if isinstance(value, js_function) else value
+
+
+def js_new(constructor, *args):
+    """`new constructor(...args)` of a function that isn't a class: what it returns if that is an
+    object, else the object it got as `this`"""
+    this = JSObject()
+    if isinstance(constructor, js_function):
+        value = constructor.function(this, *args)
+    else:
+        value = constructor(*args)
+    return value if _is_object(value) else this
+
+
+def js_run(value):
+    """A call of an async function outside of any function, which nothing awaits: run to its end"""
+    return asyncio.run(value) if inspect.iscoroutine(value) else value
+
+
+# -------------------------------------------------------------------------------------------------
+# conversions
+
+
+def _is_object(value):
+    return not (
+        value is None or value is null or isinstance(value, (bool, int, float, str))
+    )
+
+
+def _primitive(value):
+    return js_str(value) if _is_object(value) else value
+
+
+def _whole(n):
+    """An integral float as an int, as long as it is exact"""
+    if isinstance(n, float) and n.is_integer() and abs(n) < 2**53:
+        return int(n)
+    return n
+
+
+def js_typeof(value):
+    if value is None:
+        return "undefined"
+    if value is null:
+        return "object"
+    if isinstance(value, bool):
+        return "boolean"
+    if isinstance(value, (int, float)):
+        return "number"
+    if isinstance(value, str):
+        return "string"
+    if callable(value):
+        return "function"
+    return "object"
+
+
+def js_truthy(value):
+    if isinstance(value, float):
+        return not (value == 0 or math.isnan(value))
+    if value is None or isinstance(value, (bool, int, str, _Null)):
+        return bool(value)
+    return True
+
+
+def js_number(value):
+    if isinstance(value, bool):
+        return int(value)
+    if isinstance(value, (int, float)):
+        return value
+    if value is None:
+        return math.nan
+    if value is null:
+        return 0
+    if _is_object(value):
+        return js_number(_primitive(value))
+    s = value.strip()
+    if not s:
+        return 0
+    if _NUMBER.fullmatch(s):
+        return _whole(float(s))
+    if s in ("Infinity", "+Infinity", "-Infinity"):
+        return -math.inf if s[0] == "-" else math.inf
+    if s[:2] in ("0x", "0X"):
+        try:
+            return int(s[2:], 16)
+        except ValueError:
+            pass
+    return math.nan
+
+
+def _float_str(n):
+    if math.isnan(n):
+        return "NaN"
+    if math.isinf(n):
+        return "Infinity" if n > 0 else "-Infinity"
+    if n.is_integer() and abs(n) < 1e21:
+        return str(int(n))
+    return re.sub(r"e([+-])0*(\d)", r"e\1\2", repr(n))
+
+
+def js_str(value):
+    if isinstance(value, str):
+        return value
+    if value is None:
+        return "undefined"
+    if value is null:
+        return "null"
+    if isinstance(value, bool):
+        return "true" if value else "false"
+    if isinstance(value, int):
+        return str(value)
+    if isinstance(value, float):
+        return _float_str(value)
+    if isinstance(value, list):
+        return ",".join("" if v is None or v is null else js_str(v) for v in value)
+    if isinstance(value, JSObject):
+        to_string = js_get(value, "toString")
+        return js_str(to_string()) if callable(to_string) else "[object Object]"
+    if not callable(value) and type(value).__str__ is object.__str__:
+        return "[object Object]"
+    return str(value)
+
+
+def js_to_string(obj):
+    """The `__str__` of a class with a `toString` method"""
+    return js_str(obj.toString())
+
+
+def js_int32(value):
+    n = js_number(value)
+    if isinstance(n, float):
+        if not math.isfinite(n):
+            return 0
+        n = int(n)
+    n &= 0xFFFFFFFF
+    return n - 2**32 if n >= 2**31 else n
+
+
+def _uint32(value):
+    return js_int32(value) & 0xFFFFFFFF
+
+
+# -------------------------------------------------------------------------------------------------
+# operators
+
+
+def js_add(a, b):
+    a, b = _primitive(a), _primitive(b)
+    if isinstance(a, str) or isinstance(b, str):
+        return js_str(a) + js_str(b)
+    return js_number(a) + js_number(b)
+
+
+def js_div(a, b):
+    a, b = js_number(a), js_number(b)
+    if b == 0:
+        if a == 0 or math.isnan(a):
+            return math.nan
+        return math.inf if (a > 0) == (math.copysign(1, b) > 0) else -math.inf
+    return _whole(a / b)
+
+
+def js_mod(a, b):
+    a, b = js_number(a), js_number(b)
+    if b == 0 or math.isnan(a) or math.isnan(b) or math.isinf(a):
+        return math.nan
+    if isinstance(a, int) and isinstance(b, int):
+        r = abs(a) % abs(b)
+        return -r if a < 0 else r
+    return math.fmod(a, b)
+
+
+def js_shl(a, b):
+    return js_int32(js_int32(a) << (_uint32(b) & 31))
+
+
+def js_shr(a, b):
+    return js_int32(a) >> (_uint32(b) & 31)
+
+
+def js_ushr(a, b):
+    return _uint32(a) >> (_uint32(b) & 31)
+
+
+def js_strict_eq(a, b):
+    if _is_object(a) or _is_object(b) or isinstance(a, bool) or isinstance(b, bool):
+        return a is b
+    if isinstance(a, (int, float)) and isinstance(b, (int, float)):
+        return a == b
+    return type(a) is type(b) and a == b
+
+
+def js_eq(a, b):
+    if a is None or a is null:
+        return b is None or b is null
+    if b is None or b is null:
+        return False
+    if _is_object(a) and _is_object(b):
+        return a is b
+    a, b = _primitive(a), _primitive(b)
+    if isinstance(a, str) and isinstance(b, str):
+        return a == b
+    return js_number(a) == js_number(b)
+
+
+def _compare(a, b):
+    """-1, 0 or 1, None if either is NaN"""
+    a, b = _primitive(a), _primitive(b)
+    if not (isinstance(a, str) and isinstance(b, str)):
+        a, b = js_number(a), js_number(b)
+        if math.isnan(a) or math.isnan(b):
+            return None
+    return (a > b) - (a < b)
+
+
+def js_lt(a, b):
+    c = _compare(a, b)
+    return c is not None and c < 0
+
+
+def js_le(a, b):
+    c = _compare(a, b)
+    return c is not None and c <= 0
+
+
+def js_gt(a, b):
+    c = _compare(a, b)
+    return c is not None and c > 0
+
+
+def js_ge(a, b):
+    c = _compare(a, b)
+    return c is not None and c >= 0
+
+
+# -------------------------------------------------------------------------------------------------
+# properties
+
+
+def _index(sequence, key):
+    """`key` as an index of `sequence`, None if it isn't one"""
+    if isinstance(key, str) and _INDEX.fullmatch(key):
+        key = int(key)
+    if isinstance(key, float) and key.is_integer():
+        key = int(key)
+    if isinstance(key, int) and not isinstance(key, bool) and 0 <= key < len(sequence):
+        return key
+    return None
+
+
+def _check(obj, key):
+    if obj is None or obj is null:
+        raise TypeError(
+            "Cannot read properties of %s (reading '%s')" % (js_str(obj), js_str(key))
+        )
+
+
+def js_get(obj, key):
+    """`obj[key]`, undefined where Python would raise"""
+    _check(obj, key)
+    if isinstance(obj, (list, str)):
+        i = _index(obj, key)
+        if i is not None:
+            return obj[i]
+        return len(obj) if key == "length" else None
+    if isinstance(obj, dict):
+        return _bound(dict.get(obj, _key(key)), obj)
+    return getattr(obj, _key(key), None)
+
+
+def js_unpack(value, count, rest=False):
+    """What an array pattern takes apart: the first `count` values `value` iterates over,
+    undefined for those it hasn't, then a list of the others if there is a rest element"""
+    if value is None or value is null or isinstance(value, dict):
+        raise TypeError("%s is not iterable" % js_str(value))
+    items = iter(value)
+    values = [next(items, None) for _ in range(count)]
+    if rest:
+        values.append(list(items))
+    return values
+
+
+def js_rest(obj, *keys):
+    """What the rest property of an object pattern takes: a copy of `obj` without `keys`"""
+    keys = {_key(key) for key in keys}
+    return JSObject((key, value) for key, value in _entries(obj) if key not in keys)
+
+
+def js_in(key, obj):
+    if obj is None or obj is null or not _is_object(obj):
+        raise TypeError("Cannot use 'in' operator to search for '%s'" % js_str(key))
+    if isinstance(obj, list):
+        return _index(obj, key) is not None or key == "length"
+    if isinstance(obj, dict):
+        return dict.__contains__(obj, _key(key))
+    return hasattr(obj, _key(key))
+
+
+def js_delete(obj, key):
+    _check(obj, key)
+    if isinstance(obj, list):
+        i = _index(obj, key)
+        if i is not None:
+            obj[i] = None
+    elif isinstance(obj, dict):
+        dict.pop(obj, _key(key), None)
+    elif hasattr(obj, _key(key)):
+        delattr(obj, _key(key))
+    return True
+
+
+def js_keys(obj):
+    """The names `for (... in obj)` goes through"""
+    if obj is None or obj is null:
+        return []
+    if isinstance(obj, (list, str)):
+        return [str(i) for i in range(len(obj))]
+    if isinstance(obj, dict):
+        return list(dict.keys(obj))
+    return [name for name in getattr(obj, "__dict__", {}) if not name.startswith("_")]
+
+
+# -------------------------------------------------------------------------------------------------
+# methods
+
+
+def _integer(value, default=0):
+    """An integral argument, `default` if there is none"""
+    if value is None:
+        return default
+    n = js_number(value)
+    if isinstance(n, float):
+        if math.isnan(n):
+            return 0
+        if math.isinf(n):
+            return n
+        return int(n)
+    return n
+
+
+def _clamp(value, length, default=0):
+    """A position argument between 0 and `length`"""
+    return min(max(_integer(value, default), 0), length)
+
+
+def _relative(value, length, default=0):
+    """A start or end argument, negative ones count from the end"""
+    n = _integer(value, default)
+    return max(length + n, 0) if n < 0 else min(n, length)
+
+
+def _callback(function):
+    """`function` taking as many of the arguments JavaScript passes a callback as it has
+    parameters, Python's functions don't take more"""
+    try:
+        parameters = inspect.signature(function).parameters.values()
+    except (builtins.TypeError, ValueError):
+        return function
+    if any(p.kind == p.VAR_POSITIONAL for p in parameters):
+        return function
+    count = len(parameters)
+    return lambda *args: function(*args[:count])
+
+
+def _same_value_zero(a, b):
+    if isinstance(a, float) and isinstance(b, float) and math.isnan(a) and math.isnan(b):
+        return True
+    return js_strict_eq(a, b)
+
+
+def _at(sequence, index=0):
+    i = _integer(index)
+    i = i + len(sequence) if i < 0 else i
+    return sequence[i] if 0 <= i < len(sequence) else None
+
+
+def _slice(sequence, start=None, end=None):
+    length = len(sequence)
+    return sequence[_relative(start, length) : _relative(end, length, length)]
+
+
+def _char_at(s, index=0):
+    i = _integer(index)
+    return s[i] if 0 <= i < len(s) else ""
+
+
+def _char_code_at(s, index=0):
+    i = _integer(index)
+    return ord(s[i]) if 0 <= i < len(s) else math.nan
+
+
+def _includes(s, search, position=0):
+    return js_str(search) in s[_clamp(position, len(s)) :]
+
+
+def _starts_with(s, search, position=0):
+    return s.startswith(js_str(search), _clamp(position, len(s)))
+
+
+def _ends_with(s, search, end=None):
+    return s.endswith(js_str(search), 0, _clamp(end, len(s), len(s)))
+
+
+def _string_index_of(s, search, position=0):
+    return s.find(js_str(search), _clamp(position, len(s)))
+
+
+def _string_last_index_of(s, search, position=None):
+    search = js_str(search)
+    return s.rfind(search, 0, _clamp(position, len(s), len(s)) + len(search))
+
+
+def _substring(s, start=0, end=None):
+    start, end = _clamp(start, len(s)), _clamp(end, len(s), len(s))
+    return s[min(start, end) : max(start, end)]
+
+
+def _split(s, separator=None, limit=None):
+    if separator is None:
+        parts = [s]
+    elif js_str(separator) == "":
+        parts = list(s)
+    else:
+        parts = s.split(js_str(separator))
+    return parts if limit is None else parts[: _uint32(limit)]
+
+
+def _repeat(s, count=0):
+    n = _integer(count)
+    if n < 0 or math.isinf(n):
+        raise RangeError("Invalid count value: %s" % js_str(count))
+    return s * n
+
+
+def _pad(s, length, fill, start):
+    length = _integer(length)
+    fill = " " if fill is None else js_str(fill)
+    if length <= len(s) or not fill:
+        return s
+    padding = (fill * length)[: length - len(s)]
+    return padding + s if start else s + padding
+
+
+def _expand(replacement, match, position, s):
+    """`replacement` with its `$` patterns replaced"""
+    parts = {"$": "$", "&": match, "`": s[:position], "'": s[position + len(match) :]}
+    return re.sub(r"\$([$&`'])", lambda m: parts[m.group(1)], replacement)
+
+
+def _replace(s, pattern, replacement, all=False):
+    pattern = js_str(pattern)
+    positions, i = [], s.find(pattern)
+    while i != -1:
+        positions.append(i)
+        if not all:
+            break
+        i = s.find(pattern, i + (len(pattern) or 1))
+    out, last = [], 0
+    for i in positions:
+        out.append(s[last:i])
+        if callable(replacement):
+            out.append(js_str(_callback(replacement)(pattern, i, s)))
+        else:
+            out.append(_expand(js_str(replacement), pattern, i, s))
+        last = i + len(pattern)
+    out.append(s[last:])
+    return "".join(out)
+
+
+def _push(a, *values):
+    a.extend(values)
+    return len(a)
+
+
+def _unshift(a, *values):
+    a[0:0] = values
+    return len(a)
+
+
+def _index_of(a, value, start=0):
+    for i in range(_relative(start, len(a)), len(a)):
+        if js_strict_eq(a[i], value):
+            return i
+    return -1
+
+
+def _last_index_of(a, value, start=None):
+    end = len(a) - 1 if start is None else _integer(start)
+    end = len(a) + end if end < 0 else min(end, len(a) - 1)
+    for i in range(end, -1, -1):
+        if js_strict_eq(a[i], value):
+            return i
+    return -1
+
+
+def _join(a, separator=None):
+    separator = "," if separator is None else js_str(separator)
+    return separator.join("" if v is None or v is null else js_str(v) for v in a)
+
+
+def _map(a, function):
+    function = _callback(function)
+    return [function(v, i, a) for i, v in enumerate(a)]
+
+
+def _filter(a, function):
+    function = _callback(function)
+    return [v for i, v in enumerate(a) if js_truthy(function(v, i, a))]
+
+
+def _for_each(a, function):
+    function = _callback(function)
+    for i, v in enumerate(a):
+        function(v, i, a)
+
+
+def _find_index(a, function):
+    function = _callback(function)
+    for i, v in enumerate(a):
+        if js_truthy(function(v, i, a)):
+            return i
+    return -1
+
+
+def _find(a, function):
+    i = _find_index(a, function)
+    return None if i < 0 else a[i]
+
+
+def _some(a, function):
+    return _find_index(a, function) >= 0
+
+
+def _every(a, function):
+    function = _callback(function)
+    return all(js_truthy(function(v, i, a)) for i, v in enumerate(a))
+
+
+def _reduce(a, function, *initial):
+    function = _callback(function)
+    items = list(enumerate(a))
+    if initial:
+        value = initial[0]
+    elif items:
+        value = items.pop(0)[1]
+    else:
+        raise TypeError("Reduce of empty array with no initial value")
+    for i, v in items:
+        value = function(value, v, i, a)
+    return value
+
+
+def _reduce_right(a, function, *initial):
+    function = _callback(function)
+    items = list(enumerate(a))[::-1]
+    if initial:
+        value = initial[0]
+    elif items:
+        value = items.pop(0)[1]
+    else:
+        raise TypeError("Reduce of empty array with no initial value")
+    for i, v in items:
+        value = function(value, v, i, a)
+    return value
+
+
+def _flat(a, depth=1):
+    depth = 1 if depth is None else js_number(depth)
+    out = []
+    for v in a:
+        if isinstance(v, list) and depth >= 1:
+            out.extend(_flat(v, depth - 1))
+        else:
+            out.append(v)
+    return out
+
+
+def _flat_map(a, function):
+    return _flat(_map(a, function))
+
+
+def _concat(a, *values):
+    out = list(a)
+    for value in values:
+        if isinstance(value, list):
+            out.extend(value)
+        else:
+            out.append(value)
+    return out
+
+
+def _reverse(a):
+    a.reverse()
+    return a
+
+
+def _sort(a, compare=None):
+    """Strings in order by default, undefined last"""
+    if compare is None:
+        a.sort(key=lambda v: (v is None, "" if v is None else js_str(v)))
+        return a
+
+    def order(x, y):
+        n = js_number(compare(x, y))
+        return 0 if math.isnan(n) else n
+
+    a.sort(key=functools.cmp_to_key(order))
+    return a
+
+
+def _splice(a, start, *args):
+    start = _relative(start, len(a))
+    count = len(a) - start if not args else _clamp(args[0], len(a) - start)
+    removed = a[start : start + count]
+    a[start : start + count] = args[1:]
+    return removed
+
+
+def _fill(a, value, start=None, end=None):
+    for i in range(_relative(start, len(a)), _relative(end, len(a), len(a))):
+        a[i] = value
+    return a
+
+
+_STRING_METHODS = {
+    "at": _at,
+    "charAt": _char_at,
+    "charCodeAt": _char_code_at,
+    "concat": lambda s, *values: s + "".join(js_str(v) for v in values),
+    "endsWith": _ends_with,
+    "includes": _includes,
+    "indexOf": _string_index_of,
+    "lastIndexOf": _string_last_index_of,
+    "padEnd": lambda s, length, fill=None: _pad(s, length, fill, False),
+    "padStart": lambda s, length, fill=None: _pad(s, length, fill, True),
+    "repeat": _repeat,
+    "replace": _replace,
+    "replaceAll": lambda s, pattern, replacement: _replace(s, pattern, replacement, True),
+    "slice": _slice,
+    "split": _split,
+    "startsWith": _starts_with,
+    "substring": _substring,
+    "toLowerCase": str.lower,
+    "toString": str,
+    "toUpperCase": str.upper,
+    "trim": str.strip,
+    "trimEnd": str.rstrip,
+    "trimStart": str.lstrip,
+}
+
+_ARRAY_METHODS = {
+    "at": _at,
+    "concat": _concat,
+    "every": _every,
+    "fill": _fill,
+    "filter": _filter,
+    "find": _find,
+    "findIndex": _find_index,
+    "flat": _flat,
+    "flatMap": _flat_map,
+    "forEach": _for_each,
+    "includes": lambda a, value: any(_same_value_zero(v, value) for v in a),
+    "indexOf": _index_of,
+    "join": _join,
+    "lastIndexOf": _last_index_of,
+    "map": _map,
+    "pop": lambda a: a.pop() if a else None,
+    "push": _push,
+    "reduce": _reduce,
+    "reduceRight": _reduce_right,
+    "reverse": _reverse,
+    "shift": lambda a: a.pop(0) if a else None,
+    "slice": _slice,
+    "some": _some,
+    "sort": _sort,
+    "splice": _splice,
+    "toString": js_str,
+    "unshift": _unshift,
+}
+
+
+def _to_fixed(n, digits=0):
+    if not math.isfinite(n) or abs(n) >= 1e21:
+        return js_str(n)
+    # the exact value of the float, its halves rounded away from zero
+    digits = int(js_number(digits or 0))
+    numerator, denominator = float(n).as_integer_ratio()
+    whole, remainder = divmod(abs(numerator) * 10**digits, denominator)
+    whole += 2 * remainder >= denominator
+    s = str(whole).rjust(digits + 1, "0")
+    sign = "-" if n < 0 else ""
+    return sign + (s[:-digits] + "." + s[-digits:] if digits else s)
+
+
+def _number_to_string(n, radix=None):
+    radix = 10 if radix is None else js_int32(radix)
+    if radix == 10 or not float(n).is_integer():
+        return js_str(n)
+    digits, n, out = "0123456789abcdefghijklmnopqrstuvwxyz", int(n), ""
+    sign, n = "-" if n < 0 else "", abs(n)
+    while True:
+        n, digit = divmod(n, radix)
+        out = digits[digit] + out
+        if not n:
+            return sign + out
+
+
+_NUMBER_METHODS = {
+    "toFixed": _to_fixed,
+    "toString": _number_to_string,
+}
+
+
+def js_method(obj, name, *args):
+    """`obj.name(*args)`, strings, arrays and numbers have the methods of JavaScript's"""
+    if isinstance(obj, str) and name in _STRING_METHODS:
+        return _STRING_METHODS[name](obj, *args)
+    if isinstance(obj, list) and name in _ARRAY_METHODS:
+        return _ARRAY_METHODS[name](obj, *args)
+    number = isinstance(obj, (int, float)) and not isinstance(obj, bool)
+    if number and name in _NUMBER_METHODS:
+        return _NUMBER_METHODS[name](obj, *args)
+    if name == "toString" and not _is_object(obj) and obj is not None and obj is not null:
+        return js_str(obj)
+    method = js_get(obj, name)
+    if not callable(method):
+        raise TypeError("%s.%s is not a function" % (js_typeof(obj), name))
+    return method(*args)
+
+
+class _TemplateStrings(list):
+    """The strings of a tagged template, the raw ones are `raw`"""
+
+
+def js_template(strings, raw=None):
+    """The first argument of a tag"""
+    template = _TemplateStrings(strings)
+    template.raw = list(strings if raw is None else raw)
+    return template
+
+
+# -------------------------------------------------------------------------------------------------
+# errors
+
+
+class Error(Exception):
+    def __init__(self, message=None):
+        super().__init__(message)
+        self.message = "" if message is None else js_str(message)
+        self.name = type(self).__name__
+
+    def __str__(self):
+        return "%s: %s" % (self.name, self.message) if self.message else self.name
+
+
+class TypeError(Error):
+    pass
+
+
+class RangeError(Error):
+    pass
+
+
+class ReferenceError(Error):
+    pass
+
+
+class SyntaxError(Error):
+    pass
+
+
+class JSError(Exception):
+    """A thrown value that isn't an exception"""
+
+    def __init__(self, value):
+        super().__init__(js_str(value))
+        self.value = value
+
+
+def js_throw(value):
+    """What `raise` raises for `throw value`"""
+    return value if isinstance(value, BaseException) else JSError(value)
+
+
+def js_caught(error):
+    """What `catch (e)` sees of a Python exception"""
+    if isinstance(error, JSError):
+        return error.value
+    if isinstance(error, Error):
+        return error
+    if isinstance(error, RecursionError):
+        return RangeError("Maximum call stack size exceeded")
+    if isinstance(error, (builtins.TypeError, AttributeError, KeyError, IndexError)):
+        return TypeError(str(error))
+    return Error(str(error))
+
+
+# -------------------------------------------------------------------------------------------------
+# globals
+
+
+def _number_function(function):
+    """A function of numbers that gives NaN where Python's raises, as `Math`'s do"""
+
+    def call(*args):
+        try:
+            return _whole(function(*(js_number(a) for a in args)))
+        except (ValueError, ZeroDivisionError):
+            return math.nan
+        except OverflowError:
+            return math.inf
+
+    return call
+
+
+def _rounding(function):
+    """A rounding function that leaves NaN, the infinities and integers as they are"""
+    return _number_function(lambda n: n if not math.isfinite(n) else function(n))
+
+
+def _log(function):
+    return _number_function(lambda n: -math.inf if n == 0 else function(n))
+
+
+def _sign(n):
+    return n if n == 0 or math.isnan(n) else math.copysign(1, n)
+
+
+def _pow(base, exponent):
+    if math.isnan(exponent) or abs(base) == 1 and math.isinf(exponent):
+        return math.nan
+    return math.pow(base, exponent)
+
+
+def _extreme(function, empty):
+    def call(*values):
+        numbers = [js_number(v) for v in values]
+        if any(math.isnan(n) for n in numbers):
+            return math.nan
+        return function(numbers) if numbers else empty
+
+    return call
+
+
+class _Math:
+    E = math.e
+    LN2 = math.log(2)
+    LN10 = math.log(10)
+    LOG2E = 1 / math.log(2)
+    LOG10E = 1 / math.log(10)
+    PI = math.pi
+    SQRT1_2 = math.sqrt(0.5)
+    SQRT2 = math.sqrt(2)
+
+    abs = staticmethod(_number_function(abs))
+    ceil = staticmethod(_rounding(math.ceil))
+    floor = staticmethod(_rounding(math.floor))
+    round = staticmethod(_rounding(lambda n: math.floor(n + 0.5)))
+    trunc = staticmethod(_rounding(math.trunc))
+    sign = staticmethod(_number_function(_sign))
+    sqrt = staticmethod(_number_function(math.sqrt))
+    cbrt = staticmethod(_number_function(lambda n: _sign(n) * abs(n) ** (1 / 3)))
+    pow = staticmethod(_number_function(_pow))
+    exp = staticmethod(_number_function(math.exp))
+    expm1 = staticmethod(_number_function(math.expm1))
+    log = staticmethod(_log(math.log))
+    log2 = staticmethod(_log(math.log2))
+    log10 = staticmethod(_log(math.log10))
+    log1p = staticmethod(_number_function(lambda n: -math.inf if n == -1 else math.log1p(n)))
+    sin = staticmethod(_number_function(math.sin))
+    cos = staticmethod(_number_function(math.cos))
+    tan = staticmethod(_number_function(math.tan))
+    asin = staticmethod(_number_function(math.asin))
+    acos = staticmethod(_number_function(math.acos))
+    atan = staticmethod(_number_function(math.atan))
+    atan2 = staticmethod(_number_function(math.atan2))
+    sinh = staticmethod(_number_function(math.sinh))
+    cosh = staticmethod(_number_function(math.cosh))
+    tanh = staticmethod(_number_function(math.tanh))
+    hypot = staticmethod(_number_function(lambda *numbers: math.hypot(*numbers)))
+    max = staticmethod(_extreme(builtins.max, -math.inf))
+    min = staticmethod(_extreme(builtins.min, math.inf))
+
+    @staticmethod
+    def random():
+        return random.random()
+
+
+Math = _Math()
+
+_FLOAT = re.compile(r"[+-]?(Infinity|(\d+\.?\d*|\.\d+)([eE][+-]?\d+)?)")
+
+
+def parseFloat(string=None):
+    match = _FLOAT.match(js_str(string).lstrip())
+    if not match:
+        return math.nan
+    number = match.group()
+    if number.lstrip("+-") == "Infinity":
+        return -math.inf if number[0] == "-" else math.inf
+    return _whole(float(number))
+
+
+def parseInt(string=None, radix=None):
+    s = js_str(string).lstrip()
+    sign = 1
+    if s[:1] in ("+", "-"):
+        sign, s = (-1 if s[0] == "-" else 1), s[1:]
+    radix = js_int32(radix)
+    if radix in (0, 16) and s[:2] in ("0x", "0X"):
+        radix, s = 16, s[2:]
+    elif radix == 0:
+        radix = 10
+    elif not 2 <= radix <= 36:
+        return math.nan
+    digits = ""
+    for c in s:
+        if not (c.isascii() and c.isalnum() and int(c, 36) < radix):
+            break
+        digits += c
+    return sign * int(digits, radix) if digits else math.nan
+
+
+def isNaN(value=None):
+    return math.isnan(js_number(value))
+
+
+def isFinite(value=None):
+    return math.isfinite(js_number(value))
+
+
+def Boolean(*value):
+    return js_truthy(value[0]) if value else False
+
+
+def Number(*value):
+    return js_number(value[0]) if value else 0
+
+
+Number.EPSILON = 2.0**-52
+Number.MAX_SAFE_INTEGER = 2**53 - 1
+Number.MIN_SAFE_INTEGER = -(2**53 - 1)
+Number.MAX_VALUE = sys.float_info.max
+Number.MIN_VALUE = 5e-324
+Number.POSITIVE_INFINITY = math.inf
+Number.NEGATIVE_INFINITY = -math.inf
+Number.NaN = math.nan
+Number.isNaN = lambda value=None: isinstance(value, float) and math.isnan(value)
+Number.isFinite = lambda value=None: (
+    isinstance(value, (int, float)) and not isinstance(value, bool) and math.isfinite(value)
+)
+Number.isInteger = lambda value=None: Number.isFinite(value) and float(value).is_integer()
+Number.isSafeInteger = lambda value=None: (
+    Number.isInteger(value) and abs(value) <= Number.MAX_SAFE_INTEGER
+)
+Number.parseFloat = parseFloat
+Number.parseInt = parseInt
+
+
+def String(*value):
+    return js_str(value[0]) if value else ""
+
+
+String.fromCharCode = lambda *codes: "".join(chr(_uint32(c) & 0xFFFF) for c in codes)
+String.fromCodePoint = lambda *codes: "".join(chr(js_int32(c)) for c in codes)
+
+
+def _set(obj, key, value):
+    """`obj[key] = value`"""
+    if isinstance(obj, dict):
+        obj[_key(key)] = value
+    else:
+        setattr(obj, _key(key), value)
+
+
+def _entries(obj):
+    _check(obj, "keys")
+    return [(key, js_get(obj, key)) for key in js_keys(obj)]
+
+
+def Object(value=None):
+    return JSObject() if value is None or value is null else value
+
+
+Object.keys = lambda obj: [key for key, _ in _entries(obj)]
+Object.values = lambda obj: [value for _, value in _entries(obj)]
+Object.entries = lambda obj: [[key, value] for key, value in _entries(obj)]
+Object.fromEntries = lambda entries: JSObject((_key(k), v) for k, v in entries)
+Object.freeze = lambda obj: obj
+
+
+def _assign(target, *sources):
+    for source in sources:
+        if source is not None and source is not null:
+            for key, value in _entries(source):
+                _set(target, key, value)
+    return target
+
+
+Object.assign = _assign
+
+
+def Array(*values):
+    if len(values) == 1 and isinstance(values[0], (int, float)) and not isinstance(values[0], bool):
+        if not Number.isSafeInteger(values[0]) or values[0] < 0:
+            raise RangeError("Invalid array length")
+        return [None] * int(values[0])
+    return list(values)
+
+
+def _array_from(items, function=None):
+    """`Array.from`, a keyword of Python's, an attribute `js_get` finds"""
+    if isinstance(items, dict):
+        items = [js_get(items, i) for i in range(_integer(js_get(items, "length")))]
+    values = list(items) if items is not None and items is not null else []
+    if function is None:
+        return values
+    function = _callback(function)
+    return [function(v, i) for i, v in enumerate(values)]
+
+
+Array.isArray = lambda value=None: isinstance(value, list)
+setattr(Array, "from", _array_from)
+Array.of = lambda *values: list(values)
+
+
+def _json_value(value):
+    """`value` as the `json` module writes it, `_json_value` for what JSON leaves out"""
+    if value is None or callable(value):
+        return _json_value
+    if value is null:
+        return None
+    if isinstance(value, (bool, str)):
+        return value
+    if isinstance(value, (int, float)):
+        return _whole(value) if math.isfinite(value) else None
+    if isinstance(value, list):
+        values = (_json_value(v) for v in value)
+        return [None if v is _json_value else v for v in values]
+    if isinstance(value, dict):
+        items = [(k, _bound(v, value)) for k, v in dict.items(value)]
+    elif isinstance(value, BaseException):
+        items = []
+    else:
+        items = _entries(value)
+    values = ((k, _json_value(v)) for k, v in items)
+    return {k: v for k, v in values if v is not _json_value}
+
+
+def _json_stringify(value, replacer=None, space=None):
+    if replacer is not None and replacer is not null:
+        raise TypeError("JSON.stringify replacers aren't supported")
+    value = _json_value(value)
+    if value is _json_value:
+        return None
+    if isinstance(space, (int, float)) and not isinstance(space, bool):
+        space = min(10, _integer(space))
+    indent = space[:10] if isinstance(space, str) else space
+    if not indent or not isinstance(indent, (int, str)):
+        return json.dumps(value, ensure_ascii=False, separators=(",", ":"))
+    return json.dumps(value, ensure_ascii=False, indent=indent, separators=(",", ": "))
+
+
+def _from_json(value):
+    """A value `json` read, with JSON's null as null rather than undefined"""
+    if value is None:
+        return null
+    if isinstance(value, list):
+        return [_from_json(v) for v in value]
+    if isinstance(value, dict):
+        return JSObject((k, _from_json(v)) for k, v in dict.items(value))
+    return value
+
+
+def _json_parse(text, reviver=None):
+    if reviver is not None and reviver is not null:
+        raise TypeError("JSON.parse revivers aren't supported")
+
+    def invalid(constant):
+        raise ValueError("No number %s in JSON" % constant)
+
+    try:
+        value = json.loads(
+            js_str(text), parse_float=lambda s: _whole(float(s)), parse_constant=invalid
+        )
+    except ValueError as error:
+        raise SyntaxError(str(error)) from None
+    return _from_json(value)
+
+
+class _JSON:
+    stringify = staticmethod(_json_stringify)
+    parse = staticmethod(_json_parse)
+
+
+JSON = _JSON()
+
+
+def _entry_key(value):
+    """What tells the values of a Set or the keys of a Map apart, as SameValueZero does"""
+    if isinstance(value, float) and math.isnan(value):
+        return (float, "NaN")
+    if isinstance(value, bool):
+        return (bool, value)
+    if value is None or value is null or isinstance(value, (int, float, str)):
+        return value
+    return (id, id(value))
+
+
+class Set:
+    def __init__(self, values=None):
+        self._values = {}
+        if values is not None and values is not null:
+            for value in values:
+                self.add(value)
+
+    @property
+    def size(self):
+        return len(self._values)
+
+    def add(self, value):
+        self._values.setdefault(_entry_key(value), value)
+        return self
+
+    def has(self, value):
+        return _entry_key(value) in self._values
+
+    def delete(self, value):
+        return self._values.pop(_entry_key(value), _entry_key) is not _entry_key
+
+    def clear(self):
+        self._values.clear()
+
+    def __iter__(self):
+        return iter(list(self._values.values()))
+
+    values = keys = __iter__
+
+    def entries(self):
+        return iter([[v, v] for v in self._values.values()])
+
+    def forEach(self, function, this=None):
+        function = _callback(function)
+        for value in self:
+            function(value, value, self)
+
+
+class Map:
+    def __init__(self, entries=None):
+        self._entries = {}
+        if entries is not None and entries is not null:
+            for key, value in entries:
+                self.set(key, value)
+
+    @property
+    def size(self):
+        return len(self._entries)
+
+    def get(self, key):
+        entry = self._entries.get(_entry_key(key))
+        return entry[1] if entry else None
+
+    def set(self, key, value):
+        entry = self._entries.setdefault(_entry_key(key), [key, value])
+        entry[1] = value
+        return self
+
+    def has(self, key):
+        return _entry_key(key) in self._entries
+
+    def delete(self, key):
+        return self._entries.pop(_entry_key(key), None) is not None
+
+    def clear(self):
+        self._entries.clear()
+
+    def __iter__(self):
+        return iter([list(entry) for entry in self._entries.values()])
+
+    entries = __iter__
+
+    def keys(self):
+        return iter([key for key, _ in self._entries.values()])
+
+    def values(self):
+        return iter([value for _, value in self._entries.values()])
+
+    def forEach(self, function, this=None):
+        function = _callback(function)
+        for key, value in self:
+            function(value, key, self)
+
+
+# -------------------------------------------------------------------------------------------------
+# console
+
+
+def _quote(s):
+    return "'" + s.replace("\\", "\\\\").replace("'", "\\'").replace("\n", "\\n") + "'"
+
+
+def _inspect(value, depth=0):
+    """A value as node prints it"""
+    if isinstance(value, str):
+        return _quote(value) if depth else value
+    if value is None or value is null or isinstance(value, (bool, int, float)):
+        return js_str(value)
+    if isinstance(value, BaseException):
+        return str(value)
+    if isinstance(value, js_accessor):
+        kinds = [("Getter", value.get), ("Setter", value.set)]
+        return "[%s]" % "/".join(kind for kind, f in kinds if f is not None)
+    if isinstance(value, type):
+        return "[class %s]" % value.__name__
+    if callable(value):
+        name = getattr(value, "__name__", "")
+        if not name or name.startswith("<") or name.startswith("_fn"):
+            return "[Function (anonymous)]"
+        return "[Function: %s]" % name
+    if isinstance(value, list):
+        if not value:
+            return "[]"
+        if depth > 2:
+            return "[Array]"
+        return "[ %s ]" % ", ".join(_inspect(v, depth + 1) for v in value)
+    if isinstance(value, (Set, Map)):
+        if not value.size:
+            return "%s(0) {}" % type(value).__name__
+        if isinstance(value, Set):
+            values = (_inspect(v, depth + 1) for v in value)
+        else:
+            values = ("%s => %s" % (_inspect(k, depth + 1), _inspect(v, depth + 1)) for k, v in value)
+        return "%s(%d) { %s }" % (type(value).__name__, value.size, ", ".join(values))
+    if isinstance(value, dict):
+        items, prefix = dict.items(value), ""
+    else:
+        attributes = getattr(value, "__dict__", {}).items()
+        items = [(k, v) for k, v in attributes if not k.startswith("_")]
+        prefix = type(value).__name__ + " "
+    if not items:
+        return prefix + "{}"
+    if depth > 2:
+        return "[Object]"
+    fields = []
+    for k, v in items:
+        k = k if _IDENTIFIER.fullmatch(k) else _quote(k)
+        fields.append("%s: %s" % (k, _inspect(v, depth + 1)))
+    return "%s{ %s }" % (prefix, ", ".join(fields))
+
+
+class _Console:
+    def log(self, *values):
+        print(" ".join(_inspect(v) for v in values))
+
+    info = debug = log
+
+    def error(self, *values):
+        print(" ".join(_inspect(v) for v in values), file=sys.stderr)
+
+    warn = error
+
+
+console = _Console()
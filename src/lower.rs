@@ -0,0 +1,1368 @@
+//! Lowering of the parse tree to the syntax tree of `ast.rs`. The parse tree has a node per grammar
+//! rule, with the plumbing that comes with it (`sourceElements`, `elseClause_`, `eos`, chains of
+//! single children down to `primaryExpression`) and terminals that only know their symbol. Its
+//! terminals are matched with the lexemes first, the way `terminal_span` does, so that every node
+//! has a source range and identifiers and literals their text.
+
+use crate::ast::*;
+use crate::Lexeme;
+use js_lexer::statefn_impl::cook_template;
+use js_lexer::token::{Span, TokenData};
+use lr1_rs::{ASTNode, Symbol};
+
+/// Lower the parse tree of `source`, after `cover::reparse`
+pub fn lower(tree: &ASTNode, lexemes: &[Lexeme], source: &str) -> Program {
+    let (mut next, mut end) = (0, 0);
+    let root = Node::new(tree, lexemes, source, &mut next, &mut end);
+    let body = body(&root);
+    let module = body.iter().any(|s| {
+        matches!(
+            s,
+            Statement::Import(_)
+                | Statement::ExportNamed(_)
+                | Statement::ExportDefault { .. }
+                | Statement::ExportAll { .. }
+        )
+    });
+    Program {
+        body,
+        module,
+        span: Span {
+            start: 0,
+            end: source.len(),
+        },
+    }
+}
+
+/// What an automatically inserted semicolon carries
+static NO_DATA: TokenData = TokenData::None;
+
+/// A node of the parse tree with its source range
+enum Node<'a> {
+    Token {
+        symbol: &'a str,
+        /// Empty for an inserted semicolon
+        text: &'a str,
+        data: &'a TokenData,
+        span: Span,
+    },
+    Tree {
+        /// Without the parameters
+        name: &'a str,
+        children: Vec<Node<'a>>,
+        span: Span,
+    },
+}
+
+impl<'a> Node<'a> {
+    /// `next` is the first lexeme not matched yet, `end` where the last matched one ends. A
+    /// terminal that isn't the next lexeme is a semicolon inserted there, it takes no room
+    fn new(
+        node: &'a ASTNode,
+        lexemes: &'a [Lexeme],
+        source: &'a str,
+        next: &mut usize,
+        end: &mut usize,
+    ) -> Self {
+        match node {
+            ASTNode::Terminal(symbol) => match lexemes.get(*next) {
+                Some(l) if matches!(&l.symbol, Symbol::Terminal(s) if s == symbol) => {
+                    *next += 1;
+                    *end = l.span.end;
+                    Node::Token {
+                        symbol,
+                        text: &source[l.span.start..l.span.end],
+                        data: &l.data,
+                        span: l.span,
+                    }
+                }
+                _ => Node::Token {
+                    symbol,
+                    text: "",
+                    data: &NO_DATA,
+                    span: Span {
+                        start: *end,
+                        end: *end,
+                    },
+                },
+            },
+            ASTNode::NonTerminal(name, children) => {
+                let children: Vec<_> = children
+                    .iter()
+                    .map(|c| Node::new(c, lexemes, source, next, end))
+                    .collect();
+                let spans: Vec<_> = children
+                    .iter()
+                    .map(Node::span)
+                    .filter(|s| s.start < s.end)
+                    .collect();
+                let span = match (spans.first(), spans.last()) {
+                    (Some(first), Some(last)) => Span {
+                        start: first.start,
+                        end: last.end,
+                    },
+                    _ => Span {
+                        start: *end,
+                        end: *end,
+                    },
+                };
+                Node::Tree {
+                    name: name.split('[').next().unwrap_or(name),
+                    children,
+                    span,
+                }
+            }
+        }
+    }
+
+    /// The rule of a nonterminal or the symbol of a terminal
+    fn name(&self) -> &'a str {
+        match self {
+            Node::Token { symbol, .. } => symbol,
+            Node::Tree { name, .. } => name,
+        }
+    }
+
+    fn is(&self, name: &str) -> bool {
+        self.name() == name
+    }
+
+    fn span(&self) -> Span {
+        match self {
+            Node::Token { span, .. } | Node::Tree { span, .. } => *span,
+        }
+    }
+
+    fn children(&self) -> &[Node<'a>] {
+        match self {
+            Node::Token { .. } => &[],
+            Node::Tree { children, .. } => children,
+        }
+    }
+
+    fn text(&self) -> &'a str {
+        match self {
+            Node::Token { text, .. } => text,
+            Node::Tree { .. } => self.first_token().text(),
+        }
+    }
+
+    fn first_token(&self) -> &Node<'a> {
+        match self {
+            Node::Tree { children, .. } if !children.is_empty() => children[0].first_token(),
+            _ => self,
+        }
+    }
+
+    /// Follow the chain of single children down to the node that does the work
+    fn innermost(&self) -> &Node<'a> {
+        match self.children() {
+            [child] => child.innermost(),
+            _ => self,
+        }
+    }
+
+    /// The items of the (left or right recursive) `list` among the children, separators left out
+    fn items(&self, list: &str) -> Vec<&Node<'a>> {
+        let mut items = Vec::new();
+        for child in self.children() {
+            match child {
+                Node::Tree { name, .. } if *name == list => items.extend(child.items(list)),
+                Node::Tree { .. } => items.push(child),
+                Node::Token { .. } => {}
+            }
+        }
+        items
+    }
+
+    fn child(&self, name: &str) -> Option<&Node<'a>> {
+        self.children().iter().find(|c| c.is(name))
+    }
+}
+
+/// A range from the start of `from` to the end of `to`
+fn between(from: Span, to: Span) -> Span {
+    Span {
+        start: from.start,
+        end: to.end,
+    }
+}
+
+// ---------------------------------------------------------------------------------------------
+// statements
+
+/// The statements of a program, a block or a case clause
+fn statements(node: &Node, out: &mut Vec<Statement>) {
+    match node.name() {
+        "program" | "sourceElements" | "sourceElement" | "statementList" | "statementList_" => {
+            node.children().iter().for_each(|c| statements(c, out))
+        }
+        _ => out.push(statement(node)),
+    }
+}
+
+/// The statements of a program or a function, with their directive prologue
+fn body(node: &Node) -> Vec<Statement> {
+    let mut body = Vec::new();
+    statements(node, &mut body);
+    for statement in body.iter_mut() {
+        let Statement::Expression {
+            expression: Expression::Literal(literal),
+            directive,
+            span,
+        } = statement
+        else {
+            break;
+        };
+        // `("use strict")` is no directive
+        if !matches!(literal.value, LiteralValue::String(_)) || literal.span.start != span.start {
+            break;
+        }
+        *directive = Some(literal.raw[1..literal.raw.len() - 1].to_string());
+    }
+    body
+}
+
+fn block(node: &Node) -> Block {
+    let mut body = Vec::new();
+    statements(&node.children()[1], &mut body);
+    Block {
+        body,
+        span: node.span(),
+    }
+}
+
+fn function_body(node: &Node) -> Block {
+    Block {
+        body: body(&node.children()[1]),
+        span: node.span(),
+    }
+}
+
+fn statement(node: &Node) -> Statement {
+    let c = node.children();
+    let span = node.span();
+    let boxed = |node: &Node| Box::new(statement(node));
+    match node.name() {
+        "statement" | "iterationStatement" | "variableStatement" => statement(&c[0]),
+        "block" => Statement::Block(block(node)),
+        "emptyStatement_" => Statement::Empty { span },
+        "debuggerStatement" => Statement::Debugger { span },
+        "varDeclaration" | "letDeclaration" | "constDeclaration" => {
+            Statement::Variable(variable_declaration(&c[0], &c[1], span))
+        }
+        "functionDeclaration" => Statement::Function(function(node)),
+        "classDeclaration" => Statement::Class(class(node)),
+        "expressionStatement" => Statement::Expression {
+            expression: expression(&c[0]),
+            directive: None,
+            span,
+        },
+        "ifStatement" => Statement::If {
+            test: expression(&c[2]),
+            consequent: boxed(&c[4]),
+            alternate: c[5].children().get(1).map(boxed),
+            span,
+        },
+        "whileStatement" => Statement::While {
+            test: expression(&c[2]),
+            body: boxed(&c[4]),
+            span,
+        },
+        "doWhileStatement" => Statement::DoWhile {
+            body: boxed(&c[1]),
+            test: expression(&c[4]),
+            span,
+        },
+        "forStatement" => Statement::For {
+            init: for_init(&c[2]),
+            test: optional_expression(&c[4]),
+            update: optional_expression(&c[6]),
+            body: boxed(&c[8]),
+            span,
+        },
+        "forInStatement" => Statement::ForIn {
+            left: for_head(&c[2]),
+            right: expression(&c[4]),
+            body: boxed(&c[6]),
+            span,
+        },
+        "forOfStatement" => {
+            // `for await (`
+            let is_await = c[1].is("await");
+            let c = &c[is_await as usize..];
+            Statement::ForOf {
+                left: for_head(&c[2]),
+                right: expression(&c[4]),
+                body: boxed(&c[6]),
+                is_await,
+                span,
+            }
+        }
+        "continueStatement" => Statement::Continue {
+            label: optional_identifier(&c[1]),
+            span,
+        },
+        "breakStatement" => Statement::Break {
+            label: optional_identifier(&c[1]),
+            span,
+        },
+        "returnStatement" => Statement::Return {
+            argument: optional_expression(&c[1]),
+            span,
+        },
+        "withStatement" => Statement::With {
+            object: expression(&c[2]),
+            body: boxed(&c[4]),
+            span,
+        },
+        "labelledStatement" => Statement::Labeled {
+            label: identifier(&c[0]),
+            body: boxed(&c[2]),
+            span,
+        },
+        "switchStatement" => Statement::Switch {
+            discriminant: expression(&c[2]),
+            cases: c[5].items("caseClauses_").into_iter().map(case).collect(),
+            span,
+        },
+        "throwStatement" => Statement::Throw {
+            argument: expression(&c[1]),
+            span,
+        },
+        "tryStatement" => Statement::Try {
+            block: block(&c[1]),
+            handler: c[2].children().first().map(catch_clause),
+            finalizer: c[3].children().first().map(|f| block(&f.children()[1])),
+            span,
+        },
+        "importStatement" => Statement::Import(import(node)),
+        "exportStatement" => export(node),
+        name => unreachable!("{} is not a statement", name),
+    }
+}
+
+/// `case test: ...` or `default: ...`
+fn case(node: &Node) -> SwitchCase {
+    let c = node.children();
+    let mut consequent = Vec::new();
+    statements(&c[c.len() - 1], &mut consequent);
+    SwitchCase {
+        test: node.is("caseClause").then(|| expression(&c[1])),
+        consequent,
+        span: node.span(),
+    }
+}
+
+fn catch_clause(node: &Node) -> CatchClause {
+    let c = node.children();
+    CatchClause {
+        param: binding(&c[2].children()[0]),
+        body: block(&c[4]),
+        span: node.span(),
+    }
+}
+
+/// `expression_`, `forCondition_` and the like
+fn optional_expression(node: &Node) -> Option<Expression> {
+    node.children().first().map(expression)
+}
+
+/// `identifier_`
+fn optional_identifier(node: &Node) -> Option<Identifier> {
+    node.children().first().map(identifier)
+}
+
+fn variable_kind(keyword: &Node) -> VariableKind {
+    match keyword.name() {
+        "var" => VariableKind::Var,
+        "let" => VariableKind::Let,
+        _ => VariableKind::Const,
+    }
+}
+
+/// `var`, `let` or `const` and a `variableDeclarationList`
+fn variable_declaration(keyword: &Node, list: &Node, span: Span) -> VariableDeclaration {
+    let declarations = list
+        .items("variableDeclarationList")
+        .into_iter()
+        .map(|d| {
+            let c = d.children();
+            VariableDeclarator {
+                id: binding(&c[0]),
+                init: initializer(&c[1]),
+                span: d.span(),
+            }
+        })
+        .collect();
+    VariableDeclaration {
+        kind: variable_kind(keyword),
+        declarations,
+        span,
+    }
+}
+
+/// `initializer_` or `initializer`, the value after `=`
+fn initializer(node: &Node) -> Option<Expression> {
+    match node.name() {
+        "initializer" => Some(expression(&node.children()[1])),
+        _ => node.children().first().and_then(initializer),
+    }
+}
+
+fn for_init(node: &Node) -> Option<ForInit> {
+    match node.children() {
+        [] => None,
+        [e] => Some(ForInit::Expression(expression(e))),
+        [keyword, list] => Some(ForInit::Variable(variable_declaration(
+            keyword,
+            list,
+            node.span(),
+        ))),
+        _ => unreachable!(),
+    }
+}
+
+/// `forBinding`
+fn for_head(node: &Node) -> ForHead {
+    match node.children() {
+        [target] => ForHead::Pattern(target_pattern(target)),
+        [keyword, id] => ForHead::Variable(VariableDeclaration {
+            kind: variable_kind(keyword),
+            declarations: vec![VariableDeclarator {
+                id: binding(id),
+                init: None,
+                span: id.span(),
+            }],
+            span: node.span(),
+        }),
+        _ => unreachable!(),
+    }
+}
+
+// ---------------------------------------------------------------------------------------------
+// modules
+
+fn import(node: &Node) -> ImportDeclaration {
+    let c = node.children();
+    let mut specifiers = Vec::new();
+    if c[1].is("importClause") {
+        for clause in c[1]
+            .children()
+            .iter()
+            .filter(|c| matches!(c, Node::Tree { .. }))
+        {
+            match clause.name() {
+                "importedBinding" => specifiers.push(ImportSpecifier::Default(identifier(clause))),
+                "namespaceImport" => specifiers.push(ImportSpecifier::Namespace {
+                    local: identifier(&clause.children()[2]),
+                    span: clause.span(),
+                }),
+                _ => {
+                    for s in clause.items("importsList") {
+                        let local = identifier(&s.children()[s.children().len() - 1]);
+                        let imported = match s.children() {
+                            [_] => ModuleExportName::Identifier(local.clone()),
+                            [name, ..] => module_export_name(name),
+                            [] => unreachable!(),
+                        };
+                        specifiers.push(ImportSpecifier::Named {
+                            imported,
+                            local,
+                            span: s.span(),
+                        });
+                    }
+                }
+            }
+        }
+    }
+    let source = match c[1].is("str") {
+        true => &c[1],
+        false => &c[2].children()[1],
+    };
+    ImportDeclaration {
+        specifiers,
+        source: literal(source),
+        span: node.span(),
+    }
+}
+
+fn export(node: &Node) -> Statement {
+    let c = node.children();
+    let span = node.span();
+    let named = |declaration, specifiers, source| {
+        Statement::ExportNamed(ExportNamedDeclaration {
+            declaration,
+            specifiers,
+            source,
+            span,
+        })
+    };
+    match c[1].name() {
+        "exportFromClause" => {
+            let source = literal(&c[2].children()[1]);
+            match c[1].children() {
+                [names] if names.is("namedExports") => {
+                    named(None, export_specifiers(names), Some(source))
+                }
+                star => Statement::ExportAll {
+                    exported: star.get(2).map(module_export_name),
+                    source,
+                    span,
+                },
+            }
+        }
+        "namedExports" => named(None, export_specifiers(&c[1]), None),
+        "default" => Statement::ExportDefault {
+            declaration: match c[2].name() {
+                "functionExpression" => ExportDefault::Function(function(&c[2])),
+                "classExpression" => ExportDefault::Class(class(&c[2])),
+                _ => ExportDefault::Expression(expression(&c[2])),
+            },
+            span,
+        },
+        _ => named(Some(Box::new(statement(&c[1]))), Vec::new(), None),
+    }
+}
+
+fn export_specifiers(node: &Node) -> Vec<ExportSpecifier> {
+    node.items("exportsList")
+        .into_iter()
+        .map(|s| {
+            let c = s.children();
+            ExportSpecifier {
+                local: module_export_name(&c[0]),
+                exported: module_export_name(&c[c.len() - 1]),
+                span: s.span(),
+            }
+        })
+        .collect()
+}
+
+fn module_export_name(node: &Node) -> ModuleExportName {
+    let name = &node.children()[0];
+    match name.is("str") {
+        true => ModuleExportName::String(literal(name)),
+        false => ModuleExportName::Identifier(identifier(name)),
+    }
+}
+
+// ---------------------------------------------------------------------------------------------
+// functions and classes
+
+/// A function declaration or expression
+fn function(node: &Node) -> Function {
+    let c = node.children();
+    let id = c.iter().find_map(|c| match c.name() {
+        "bindingIdentifier" => Some(identifier(c)),
+        "identifier_" => optional_identifier(c),
+        _ => None,
+    });
+    Function {
+        id,
+        params: parameters(node.child("parameters").unwrap()),
+        body: function_body(node.child("block").unwrap()),
+        generator: c.iter().any(|c| c.is("*")),
+        is_async: c[0].is("async"),
+        span: node.span(),
+    }
+}
+
+/// `parameters`, the formal parameters between parentheses
+fn parameters(node: &Node) -> Vec<Pattern> {
+    node.items("parameterList")
+        .into_iter()
+        .map(|p| match p.name() {
+            "bindingRestElement" => binding(p),
+            _ => binding_element(&p.children()[0]),
+        })
+        .collect()
+}
+
+fn arrow_function(node: &Node) -> ArrowFunction {
+    let c = node.children();
+    let params = match c[0].is("async") {
+        // `async x => ...`
+        true => vec![binding(&c[1])],
+        false => arrow_parameters(&c[0]),
+    };
+    let body = &c[c.len() - 1].children()[0];
+    ArrowFunction {
+        params,
+        body: match body.is("block") {
+            true => ArrowBody::Block(function_body(body)),
+            false => ArrowBody::Expression(expression(body)),
+        },
+        is_async: c[0].is("async") || c[0].is("coverCallExpressionAndAsyncArrowHead"),
+        span: node.span(),
+    }
+}
+
+/// The parameters of an arrow function, covered by a parenthesized expression or the arguments of
+/// `async(...)` and reparsed by `cover::reparse`
+fn arrow_parameters(node: &Node) -> Vec<Pattern> {
+    let items = match node.is("arrowParameters") {
+        true => node.items("expression"),
+        false => node.children()[1].items("argumentList"),
+    };
+    items
+        .into_iter()
+        .map(|p| match p.name() {
+            "bindingIdentifier" | "bindingRestElement" => binding(p),
+            "spreadElement" => Pattern::Rest {
+                argument: Box::new(target_pattern(&p.children()[1])),
+                span: p.span(),
+            },
+            _ => {
+                let inner = p.innermost();
+                match inner.children() {
+                    [left, operator, right]
+                        if inner.is("assignmentExpression") && operator.innermost().is("=") =>
+                    {
+                        Pattern::Assignment {
+                            left: Box::new(target_pattern(left)),
+                            right: Box::new(expression(right)),
+                            span: inner.span(),
+                        }
+                    }
+                    _ => target_pattern(p),
+                }
+            }
+        })
+        .collect()
+}
+
+/// A class declaration or expression
+fn class(node: &Node) -> Class {
+    let c = node.children();
+    let id = match c[1].is("identifier_") {
+        true => optional_identifier(&c[1]),
+        false => Some(identifier(&c[1])),
+    };
+    let tail = c[2].children();
+    let body = tail[2]
+        .items("classElementList_")
+        .into_iter()
+        .filter_map(class_member)
+        .collect();
+    Class {
+        id,
+        super_class: tail[0].children().get(1).map(expression),
+        body: ClassBody {
+            body,
+            span: between(tail[1].span(), tail[3].span()),
+        },
+        span: node.span(),
+    }
+}
+
+/// `classElement`, `None` for a stray `;`
+fn class_member(node: &Node) -> Option<ClassMember> {
+    let c = node.children();
+    let is_static = c.len() > 1 && c[0].is("static");
+    let member = &c[is_static as usize];
+    let span = node.span();
+    Some(match member.name() {
+        "methodDefinition" => {
+            let (key, value, kind) = method(member);
+            let constructor = match &key {
+                PropertyKey::Identifier(id) => id.name == "constructor",
+                PropertyKey::Literal(literal) => {
+                    literal.value == LiteralValue::String("constructor".to_string())
+                }
+                _ => false,
+            };
+            ClassMember::Method(MethodDefinition {
+                kind: match kind {
+                    MethodKind::Method if constructor && !is_static => MethodKind::Constructor,
+                    kind => kind,
+                },
+                key,
+                value,
+                is_static,
+                span,
+            })
+        }
+        "fieldDefinition" => ClassMember::Field(FieldDefinition {
+            key: property_name(&member.children()[0]),
+            value: initializer(&member.children()[1]),
+            is_static,
+            span,
+        }),
+        "classStaticBlock" => {
+            let mut body = Vec::new();
+            statements(&member.children()[2], &mut body);
+            ClassMember::StaticBlock(Block { body, span })
+        }
+        _ => return None,
+    })
+}
+
+/// A method, getter or setter of a class or an object literal. The function starts at its
+/// parameters
+fn method(node: &Node) -> (PropertyKey, Function, MethodKind) {
+    let c = node.children();
+    let name = c.iter().position(|c| c.is("classElementName")).unwrap();
+    let params = match node.child("parameters") {
+        Some(p) => parameters(p),
+        // a setter
+        None => node
+            .child("parameter")
+            .map(|p| binding_element(&p.children()[0]))
+            .into_iter()
+            .collect(),
+    };
+    let kind = match c[0].name() {
+        "get" if name == 1 => MethodKind::Get,
+        "set" if name == 1 => MethodKind::Set,
+        _ => MethodKind::Method,
+    };
+    let value = Function {
+        id: None,
+        params,
+        body: function_body(node.child("block").unwrap()),
+        generator: c.iter().any(|c| c.is("*")),
+        is_async: c[0].is("async"),
+        span: between(c[name + 1].span(), node.span()),
+    };
+    (property_name(&c[name]), value, kind)
+}
+
+/// `propertyName` or `classElementName`
+fn property_name(node: &Node) -> PropertyKey {
+    let c = node.children();
+    match c {
+        [name] if name.is("propertyName") => property_name(name),
+        [name] if name.is("privateidentifier") => PropertyKey::PrivateName(identifier(name)),
+        [name] if name.is("identifierName") => PropertyKey::Identifier(identifier(name)),
+        [token] => PropertyKey::Literal(literal(token)),
+        _ => PropertyKey::Computed(expression(&c[1])),
+    }
+}
+
+// ---------------------------------------------------------------------------------------------
+// patterns
+
+/// A declared name or a binding pattern, possibly with a default value
+fn binding(node: &Node) -> Pattern {
+    let c = node.children();
+    let span = node.span();
+    match node.name() {
+        "bindingPattern" | "catchParameter" => binding(&c[0]),
+        "objectBindingPattern" => Pattern::Object {
+            properties: node
+                .items("bindingPropertyList")
+                .into_iter()
+                .map(|p| match p.children() {
+                    [_, rest] if p.is("bindingRestProperty") => PatternProperty::Rest {
+                        argument: binding(rest),
+                        span: p.span(),
+                    },
+                    [single] => PatternProperty::Property {
+                        key: PropertyKey::Identifier(identifier(single)),
+                        value: binding_element(single),
+                        shorthand: true,
+                        span: p.span(),
+                    },
+                    [key, _, value] => PatternProperty::Property {
+                        key: property_name(key),
+                        value: binding_element(value),
+                        shorthand: false,
+                        span: p.span(),
+                    },
+                    _ => unreachable!(),
+                })
+                .collect(),
+            span,
+        },
+        "arrayBindingPattern" => {
+            let mut elements = Vec::new();
+            array_elements(
+                node,
+                "bindingElementList",
+                &mut elements,
+                &|e| match e.name() {
+                    "bindingRestElement_" => binding(&e.children()[0]),
+                    _ => binding_element(e),
+                },
+            );
+            Pattern::Array { elements, span }
+        }
+        "bindingRestElement" => Pattern::Rest {
+            argument: Box::new(binding(&c[1])),
+            span,
+        },
+        "bindingElement" | "singleNameBinding" => binding_element(node),
+        _ => Pattern::Identifier(identifier(node)),
+    }
+}
+
+/// `bindingElement` or `singleNameBinding`, a target with an optional default value
+fn binding_element(node: &Node) -> Pattern {
+    let c = node.children();
+    match c {
+        [single] => binding_element(single),
+        [target, value] => {
+            let target = binding(target);
+            match initializer(value) {
+                Some(value) => Pattern::Assignment {
+                    left: Box::new(target),
+                    right: Box::new(value),
+                    span: node.span(),
+                },
+                None => target,
+            }
+        }
+        _ => binding(node),
+    }
+}
+
+/// The elements of an array literal or pattern, `list` is the list rule of its items. Holes are
+/// `None`
+fn array_elements<T>(node: &Node, list: &str, out: &mut Vec<Option<T>>, item: &dyn Fn(&Node) -> T) {
+    for child in node.children() {
+        match child {
+            Node::Tree { name, .. } if *name == list => array_elements(child, list, out, item),
+            Node::Tree {
+                name: "elision_", ..
+            } => {
+                fn commas(node: &Node) -> usize {
+                    match node {
+                        Node::Token { .. } => 1,
+                        Node::Tree { children, .. } => children.iter().map(commas).sum(),
+                    }
+                }
+                out.extend((0..commas(child)).map(|_| None));
+            }
+            // an empty `bindingRestElement_`
+            Node::Tree { children, .. } if children.is_empty() => {}
+            Node::Tree { .. } => out.push(Some(item(child))),
+            Node::Token { .. } => {}
+        }
+    }
+}
+
+/// The target of an assignment: an identifier, a property, or a literal reparsed as a pattern
+fn target_pattern(node: &Node) -> Pattern {
+    let inner = node.innermost();
+    let c = inner.children();
+    let span = inner.span();
+    match inner.name() {
+        "arrayAssignmentPattern" => {
+            let mut elements = Vec::new();
+            array_elements(
+                inner,
+                "assignmentElementList",
+                &mut elements,
+                &|e| match e.name() {
+                    "assignmentRestElement" => Pattern::Rest {
+                        argument: Box::new(target_pattern(&e.children()[1])),
+                        span: e.span(),
+                    },
+                    _ => target_pattern(e),
+                },
+            );
+            Pattern::Array { elements, span }
+        }
+        "objectAssignmentPattern" => Pattern::Object {
+            properties: inner
+                .items("assignmentPropertyList")
+                .into_iter()
+                .map(|p| {
+                    let c = p.children();
+                    let span = p.span();
+                    match c {
+                        [_, rest] if p.is("assignmentRestProperty") => PatternProperty::Rest {
+                            argument: target_pattern(rest),
+                            span,
+                        },
+                        [key, _, value] => PatternProperty::Property {
+                            key: property_name(key),
+                            value: target_pattern(value),
+                            shorthand: false,
+                            span,
+                        },
+                        // `a` or `a = 1`
+                        _ => {
+                            let id = identifier(&c[0]);
+                            let value = match c.get(1).and_then(initializer) {
+                                Some(value) => Pattern::Assignment {
+                                    left: Box::new(Pattern::Identifier(id.clone())),
+                                    right: Box::new(value),
+                                    span,
+                                },
+                                None => Pattern::Identifier(id.clone()),
+                            };
+                            PatternProperty::Property {
+                                key: PropertyKey::Identifier(id),
+                                value,
+                                shorthand: true,
+                                span,
+                            }
+                        }
+                    }
+                })
+                .collect(),
+            span,
+        },
+        "assignmentElement" => Pattern::Assignment {
+            left: Box::new(target_pattern(&c[0])),
+            right: Box::new(expression(&c[2])),
+            span,
+        },
+        _ => match expression(node) {
+            Expression::Identifier(id) => Pattern::Identifier(id),
+            e => Pattern::Expression(Box::new(e)),
+        },
+    }
+}
+
+// ---------------------------------------------------------------------------------------------
+// expressions
+
+fn expression(node: &Node) -> Expression {
+    let c = node.children();
+    let span = node.span();
+    let boxed = |node: &Node| Box::new(expression(node));
+    if let Node::Token { symbol, .. } = node {
+        return match *symbol {
+            "this" => Expression::This { span },
+            "super" => Expression::Super { span },
+            "null" | "true" | "false" | "number" | "str" | "regularexpression" => {
+                Expression::Literal(literal(node))
+            }
+            "privateidentifier" => Expression::PrivateIdentifier(identifier(node)),
+            _ => Expression::Identifier(identifier(node)),
+        };
+    }
+    match node.name() {
+        "templateLiteral" => Expression::Template(template(node)),
+        "yieldExpression" => Expression::Yield {
+            argument: (c.len() > 1).then(|| boxed(&c[c.len() - 1])),
+            delegate: c.len() == 3,
+            span,
+        },
+        _ if c.len() == 1 => expression(&c[0]),
+        "expression" => Expression::Sequence {
+            expressions: node
+                .items("expression")
+                .into_iter()
+                .map(expression)
+                .collect(),
+            span,
+        },
+        "assignmentExpression" => Expression::Assignment {
+            operator: AssignmentOperator::from_text(c[1].text()).unwrap(),
+            left: Box::new(target_pattern(&c[0])),
+            right: boxed(&c[2]),
+            span,
+        },
+        "conditionalExpression" => Expression::Conditional {
+            test: boxed(&c[0]),
+            consequent: boxed(&c[2]),
+            alternate: boxed(&c[4]),
+            span,
+        },
+        "coalesceExpression" | "logicalOrExpression" | "logicalAndExpression" => {
+            Expression::Logical {
+                operator: LogicalOperator::from_text(c[1].text()).unwrap(),
+                left: boxed(&c[0]),
+                right: boxed(&c[2]),
+                span,
+            }
+        }
+        "bitwiseOrExpression"
+        | "bitwiseXorExpression"
+        | "bitwiseAndExpression"
+        | "equalityExpression"
+        | "relationalExpression"
+        | "shiftExpression"
+        | "additiveExpression"
+        | "multiplicativeExpression"
+        | "exponentiationExpression" => Expression::Binary {
+            operator: BinaryOperator::from_text(c[1].text()).unwrap(),
+            left: boxed(&c[0]),
+            right: boxed(&c[2]),
+            span,
+        },
+        "unaryExpression" => Expression::Unary {
+            operator: UnaryOperator::from_text(c[0].text()).unwrap(),
+            argument: boxed(&c[1]),
+            span,
+        },
+        "awaitExpression" => Expression::Await {
+            argument: boxed(&c[1]),
+            span,
+        },
+        "updateExpression" => {
+            let prefix = matches!(c[0], Node::Token { .. });
+            let (operator, argument) = if prefix {
+                (&c[0], &c[1])
+            } else {
+                (&c[1], &c[0])
+            };
+            Expression::Update {
+                operator: UpdateOperator::from_text(operator.text()).unwrap(),
+                prefix,
+                argument: boxed(argument),
+                span,
+            }
+        }
+        "newExpression" | "memberExpression" if c[0].is("new") => Expression::New {
+            callee: boxed(&c[1]),
+            arguments: c.get(2).map(arguments).unwrap_or_default(),
+            span,
+        },
+        "memberExpression"
+        | "callExpression"
+        | "superProperty"
+        | "superCall"
+        | "coverCallExpressionAndAsyncArrowHead" => link(expression(&c[0]), &c[1..], false, span),
+        "importCall" => Expression::Import {
+            source: boxed(&c[2]),
+            span,
+        },
+        "metaProperty" => Expression::MetaProperty {
+            meta: identifier(&c[0]),
+            property: identifier(&c[2]),
+            span,
+        },
+        "optionalExpression" => Expression::Chain {
+            expression: Box::new(optional_expression_chain(node)),
+            span,
+        },
+        "primaryExpression" => expression(&c[1]),
+        "arrayLiteral" => {
+            let mut elements = Vec::new();
+            array_elements(node, "elementList", &mut elements, &expression_or_spread);
+            Expression::Array { elements, span }
+        }
+        "objectLiteral" => Expression::Object {
+            properties: node
+                .items("propertyDefinitionList")
+                .into_iter()
+                .map(object_property)
+                .collect(),
+            span,
+        },
+        "functionExpression" => Expression::Function(Box::new(function(node))),
+        "classExpression" => Expression::Class(Box::new(class(node))),
+        "arrowFunction" => Expression::Arrow(Box::new(arrow_function(node))),
+        name => unreachable!("{} is not an expression", name),
+    }
+}
+
+/// A property access, call or tagged template on `object`, `rest` is what follows it
+fn link(object: Expression, rest: &[Node], optional: bool, span: Span) -> Expression {
+    let object = Box::new(object);
+    let property = match rest[0].name() {
+        "arguments" => {
+            return Expression::Call {
+                callee: object,
+                arguments: arguments(&rest[0]),
+                optional,
+                span,
+            }
+        }
+        "templateLiteral" => {
+            return Expression::TaggedTemplate {
+                tag: object,
+                quasi: template(&rest[0]),
+                span,
+            }
+        }
+        "[" => MemberProperty::Computed(Box::new(expression(&rest[1]))),
+        "." => member_property(&rest[1]),
+        // right after `?.`
+        _ => member_property(&rest[0]),
+    };
+    Expression::Member {
+        object,
+        property,
+        optional,
+        span,
+    }
+}
+
+fn member_property(node: &Node) -> MemberProperty {
+    match node.is("privateidentifier") {
+        true => MemberProperty::PrivateName(identifier(node)),
+        false => MemberProperty::Identifier(identifier(node)),
+    }
+}
+
+/// The chain of `optionalExpression` without the `Chain` around it
+fn optional_expression_chain(node: &Node) -> Expression {
+    let c = node.children();
+    let object = match c[0].is("optionalExpression") {
+        true => optional_expression_chain(&c[0]),
+        false => expression(&c[0]),
+    };
+    optional_chain(&c[1], object)
+}
+
+/// The links of `optionalChain` applied to `object`, the first one after `?.`
+fn optional_chain(node: &Node, object: Expression) -> Expression {
+    let c = node.children();
+    let span = between(object.span(), node.span());
+    match c[0].is("?.") {
+        true => link(object, &c[1..], true, span),
+        false => link(optional_chain(&c[0], object), &c[1..], false, span),
+    }
+}
+
+fn arguments(node: &Node) -> Vec<ExpressionOrSpread> {
+    node.items("argumentList")
+        .into_iter()
+        .map(expression_or_spread)
+        .collect()
+}
+
+fn expression_or_spread(node: &Node) -> ExpressionOrSpread {
+    match node.is("spreadElement") {
+        true => ExpressionOrSpread::Spread(SpreadElement {
+            argument: expression(&node.children()[1]),
+            span: node.span(),
+        }),
+        false => ExpressionOrSpread::Expression(expression(node)),
+    }
+}
+
+/// `propertyDefinition`
+fn object_property(node: &Node) -> ObjectProperty {
+    let c = node.children();
+    let span = node.span();
+    let property = match c {
+        [spread, argument] if spread.is("...") => {
+            return ObjectProperty::Spread(SpreadElement {
+                argument: expression(argument),
+                span,
+            })
+        }
+        [method_] if method_.is("methodDefinition") => {
+            let (key, value, kind) = method(method_);
+            Property {
+                key,
+                value: Expression::Function(Box::new(value)),
+                kind: match kind {
+                    MethodKind::Get => PropertyKind::Get,
+                    MethodKind::Set => PropertyKind::Set,
+                    _ => PropertyKind::Init,
+                },
+                method: kind == MethodKind::Method,
+                shorthand: false,
+                span,
+            }
+        }
+        [key, _, value] => Property {
+            key: property_name(key),
+            value: expression(value),
+            kind: PropertyKind::Init,
+            method: false,
+            shorthand: false,
+            span,
+        },
+        // `{a}`, `{a = 1}` only makes it to a pattern
+        _ => {
+            let id = identifier(&c[0]);
+            Property {
+                key: PropertyKey::Identifier(id.clone()),
+                value: Expression::Identifier(id),
+                kind: PropertyKind::Init,
+                method: false,
+                shorthand: true,
+                span,
+            }
+        }
+    };
+    ObjectProperty::Property(property)
+}
+
+fn template(node: &Node) -> TemplateLiteral {
+    let span = node.span();
+    let mut quasis = Vec::new();
+    let mut expressions = Vec::new();
+    let mut node = node;
+    loop {
+        for child in node.children() {
+            match child {
+                Node::Token {
+                    symbol,
+                    data: TokenData::Template { raw, cooked },
+                    span,
+                    ..
+                } => {
+                    // the text is between `` ` `` or `}` and `${` or `` ` ``
+                    let tail = matches!(*symbol, "templatetail" | "nosubstitutiontemplate");
+                    quasis.push(TemplateElement {
+                        raw: raw.clone(),
+                        cooked: cooked.clone(),
+                        tail,
+                        span: Span {
+                            start: span.start + 1,
+                            end: span.end - if tail { 1 } else { 2 },
+                        },
+                    });
+                }
+                Node::Tree {
+                    name: "templateSpans",
+                    ..
+                } => {}
+                _ => expressions.push(expression(child)),
+            }
+        }
+        match node.child("templateSpans") {
+            Some(spans) => node = spans,
+            None => break,
+        }
+    }
+    TemplateLiteral {
+        quasis,
+        expressions,
+        span,
+    }
+}
+
+/// A literal token
+fn literal(node: &Node) -> Literal {
+    let Node::Token {
+        symbol,
+        text,
+        data,
+        span,
+    } = node.first_token()
+    else {
+        unreachable!()
+    };
+    let value = match (*symbol, data) {
+        ("null", _) => LiteralValue::Null,
+        ("true", _) => LiteralValue::Boolean(true),
+        ("false", _) => LiteralValue::Boolean(false),
+        ("number", _) => LiteralValue::Number(number(text)),
+        (_, TokenData::RegExp { pattern, flags }) => LiteralValue::RegExp {
+            pattern: pattern.clone(),
+            flags: flags.clone(),
+        },
+        // a legacy octal escape is left as it is
+        _ => {
+            let body = &text[1..text.len() - 1];
+            LiteralValue::String(cook_template(body).unwrap_or_else(|| body.to_string()))
+        }
+    };
+    Literal {
+        value,
+        raw: text.to_string(),
+        span: *span,
+    }
+}
+
+/// The value of a numeric literal
+fn number(text: &str) -> f64 {
+    let text = text.replace('_', "");
+    let radix = match text.get(..2).map(|p| p.to_ascii_lowercase()).as_deref() {
+        Some("0x") => 16,
+        Some("0o") => 8,
+        Some("0b") => 2,
+        _ => return text.parse().unwrap_or(f64::NAN),
+    };
+    text[2..].chars().fold(0.0, |n, d| {
+        n * radix as f64 + d.to_digit(radix).unwrap_or(0) as f64
+    })
+}
+
+/// The identifier a node is made of, without the `#` of a private name
+fn identifier(node: &Node) -> Identifier {
+    let token = node.first_token();
+    Identifier {
+        name: token.text().trim_start_matches('#').to_string(),
+        span: token.span(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{estree, test_parse};
+    use serde_json::Value;
+
+    #[test]
+    fn test_lower() {
+        let estree = |source: &str| {
+            let (tree, lexemes) = test_parse(source);
+            estree::program(&lower(&tree, &lexemes, source))
+        };
+        let kind = |node: &Value| node["type"].as_str().unwrap().to_string();
+
+        let program = estree("'use strict'\nlet [a, , ...b] = c, d\nx = 1");
+        let body = &program["body"];
+        assert_eq!(program["sourceType"], "script");
+        assert_eq!(body[0]["directive"], "use strict");
+        let declaration = &body[1];
+        assert_eq!(declaration["kind"], "let");
+        let elements = &declaration["declarations"][0]["id"]["elements"];
+        assert_eq!(kind(&elements[0]), "Identifier");
+        assert_eq!(elements[1], Value::Null);
+        assert_eq!(kind(&elements[2]), "RestElement");
+        assert_eq!(declaration["declarations"][1]["init"], Value::Null);
+        // the inserted semicolons take no room
+        assert_eq!(declaration["start"], 13);
+        assert_eq!(declaration["end"], 35);
+        assert_eq!(body[2]["end"], 41);
+
+        let program = estree("a?.b.c(1); [x.y = 2, {z}] = w;");
+        let chain = &program["body"][0]["expression"];
+        assert_eq!(kind(chain), "ChainExpression");
+        assert_eq!(chain["expression"]["callee"]["object"]["optional"], true);
+        assert_eq!(chain["expression"]["optional"], false);
+        let pattern = &program["body"][1]["expression"]["left"];
+        assert_eq!(kind(pattern), "ArrayPattern");
+        assert_eq!(kind(&pattern["elements"][0]["left"]), "MemberExpression");
+        assert_eq!(pattern["elements"][1]["properties"][0]["shorthand"], true);
+
+        let program = estree("export default class extends B { constructor() { super() } }");
+        assert_eq!(program["sourceType"], "module");
+        let class = &program["body"][0]["declaration"];
+        assert_eq!(class["id"], Value::Null);
+        assert_eq!(class["body"]["body"][0]["kind"], "constructor");
+        assert_eq!(class["body"]["body"][0]["value"]["start"], 44);
+
+        let numbers = [
+            ("1.5", 1.5),
+            (".5", 0.5),
+            ("1.", 1.0),
+            ("1e3", 1e3),
+            ("2E-7", 2e-7),
+            ("1.5e+10", 1.5e10),
+            ("0x1F", 31.0),
+            ("0o17", 15.0),
+            ("0b101", 5.0),
+            ("1_000", 1000.0),
+            ("0xFF_FF", 65535.0),
+        ];
+        let source = numbers.map(|(raw, _)| raw).join(", ");
+        let program = estree(&format!("[{}]", source));
+        let elements = &program["body"][0]["expression"]["elements"];
+        for (i, (raw, value)) in numbers.into_iter().enumerate() {
+            assert_eq!(kind(&elements[i]), "Literal");
+            assert_eq!(elements[i]["raw"], raw);
+            assert_eq!(elements[i]["value"].as_f64(), Some(value));
+        }
+
+        let strings = [
+            (r"'it\'s'", "it's"),
+            (r#""say \"hi\"""#, "say \"hi\""),
+            (r#""é\x41""#, "éA"),
+            ("'héllo ☃'", "héllo ☃"),
+            ("'a\\\nb'", "ab"),
+            ("'c\\\r\nd'", "cd"),
+        ];
+        let source = strings.map(|(raw, _)| raw).join(", ");
+        let program = estree(&format!("[{}]", source));
+        let elements = &program["body"][0]["expression"]["elements"];
+        for (i, (raw, value)) in strings.into_iter().enumerate() {
+            assert_eq!(kind(&elements[i]), "Literal");
+            assert_eq!(elements[i]["raw"], raw);
+            assert_eq!(elements[i]["value"], value);
+        }
+    }
+}
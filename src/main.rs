@@ -1,294 +1,1202 @@
-use std::{fs::File, io::Write, path::Path};
+mod ast;
+mod codegen;
+mod cover;
+mod estree;
+mod infer;
+mod lower;
+mod methods;
+mod modules;
+mod python;
+mod scope;
+mod sourcemap;
+mod visit;
 
-use js_lexer::lexer::*;
+use std::{
+    collections::HashSet,
+    fs::File,
+    io::Write,
+    path::{Path, PathBuf},
+};
+
+use js_lexer::token::{Span, Token, TokenData};
 use lr1_rs::*;
+use modules::{module_name, Packages, EXTENSIONS};
+
+/// Words that can't be identifiers, though they can still name properties
+const RESERVED_WORDS: &[&str] = &[
+    "break", "case", "catch", "class", "const", "continue", "debugger", "default", "delete",
+    "do", "else", "enum", "export", "extends", "false", "finally", "for", "function", "if",
+    "import", "in", "instanceof", "let", "new", "null", "return", "super", "switch", "this",
+    "throw", "true", "try", "typeof", "var", "void", "while", "with", "yield", "await",
+];
 
+/// Words with a meaning in some places that are ordinary identifiers everywhere else
+/// (`async` is spelled out instead, `async (` has to start an async arrow head, see `memberExpression`)
+const CONTEXTUAL_KEYWORDS: &[&str] = &[
+    "target", "get", "set", "static", "of", "as", "from", "meta", "__proto__",
+];
+
+/// Nonterminals take parameters the way the specification writes them (see `expand_params`).
+/// `[NoBrace]` / `[NoDecl]` rule out a leading `{` / `function` or `class`, an expression statement
+/// has both, the body of an arrow function the first and `export default` the second. `[Yield]` / `[Await]` are on inside generator / async bodies, where `yield` / `await`
+/// are operators instead of identifiers
 fn js_grammar() -> Grammar {
-    grammar!(
-        "program",
+    let mut prods = vec![
+        prod!("program[Yield, Await]" => "sourceElements[?Yield, ?Await]"),
+        prod!("program[Yield, Await]" => ),
         //
-        prod!("program" => "sourceElements", "eof"),
-        prod!("program" => "eof"),
+        prod!("sourceElements[Yield, Await]" => "sourceElement[?Yield, ?Await]", "sourceElements[?Yield, ?Await]"),
+        prod!("sourceElements[Yield, Await]" => "sourceElement[?Yield, ?Await]"),
+        prod!("sourceElement[Yield, Await]" => "statement[?Yield, ?Await]"),
+        prod!("sourceElement[Yield, Await]" => "importStatement"),
+        prod!("sourceElement[Yield, Await]" => "exportStatement"),
+        prod!("statementList[Yield, Await]" => "statement[?Yield, ?Await]"),
+        prod!("statementList[Yield, Await]" => "statement[?Yield, ?Await]", "statementList[?Yield, ?Await]"),
         //
-        prod!("sourceElements" => "sourceElement", "sourceElements"),
-        prod!("sourceElements" => "sourceElement"),
-        prod!("sourceElement" => "statement"),
-        prod!("statementList" => "statement"),
-        prod!("statementList" => "statement", "statementList"),
+        prod!("statement[Yield, Await]" => "block[?Yield, ?Await]"),
+        prod!("statement[Yield, Await]" => "variableStatement[?Yield, ?Await]"),
+        prod!("statement[Yield, Await]" => "emptyStatement_"),
+        prod!("statement[Yield, Await]" => "classDeclaration[?Yield, ?Await]"),
+        prod!("statement[Yield, Await]" => "functionDeclaration[?Yield, ?Await]"),
+        prod!("statement[Yield, Await]" => "expressionStatement[?Yield, ?Await]"),
+        prod!("statement[Yield, Await]" => "ifStatement[?Yield, ?Await]"),
+        prod!("statement[Yield, Await]" => "iterationStatement[?Yield, ?Await]"),
+        prod!("statement[Yield, Await]" => "continueStatement[?Yield, ?Await]"),
+        prod!("statement[Yield, Await]" => "breakStatement[?Yield, ?Await]"),
+        prod!("statement[Yield, Await]" => "returnStatement[?Yield, ?Await]"),
+        prod!("statement[Yield, Await]" => "withStatement[?Yield, ?Await]"),
+        prod!("statement[Yield, Await]" => "labelledStatement[?Yield, ?Await]"),
+        prod!("statement[Yield, Await]" => "switchStatement[?Yield, ?Await]"),
+        prod!("statement[Yield, Await]" => "throwStatement[?Yield, ?Await]"),
+        prod!("statement[Yield, Await]" => "tryStatement[?Yield, ?Await]"),
+        prod!("statement[Yield, Await]" => "debuggerStatement"),
         //
-        prod!("statement" => "block"),
-        prod!("statement" => "variableStatement"),
-        prod!("statement" => "importStatement"),
-        prod!("statement" => "exportStatement"),
-        prod!("statement" => "emptyStatement_"),
-        prod!("statement" => "classDeclaration"),
-        prod!("statement" => "functionDeclaration"),
-        prod!("statement" => "expressionStatement"),
-        prod!("statement" => "ifStatement"),
-        prod!("statement" => "iterationStatement"),
-        prod!("statement" => "continueStatement"),
-        prod!("statement" => "breakStatement"),
-        prod!("statement" => "returnStatement"),
-        prod!("statement" => "yieldStatement"),
-        prod!("statement" => "withStatement"),
-        prod!("statement" => "labelledStatement"),
-        prod!("statement" => "switchStatement"),
-        prod!("statement" => "throwStatement"),
-        prod!("statement" => "tryStatement"),
-        prod!("statement" => "debuggerStatement"),
+        prod!("block[Yield, Await]" => "{", "statementList_[?Yield, ?Await]", "}"),
+        prod!("emptyStatement_" => ";"),
         //
-        prod!("importStatement" => "import", "importFromBlock"),
-        prod!("importFromBlock" => "str", "eos"),
-        prod!("exportStatement" => "export", "exportClause", "eos"),        //export
-        prod!("exportClause" => "exportedElementList"),
-        prod!("exportedElementList" => "identifier", "exportedElementList"),
-        prod!("exportedElementList" => "identifier"),
-        prod!("eos" => "eof"),
-        prod!("eos" => "semicolon"),
+        //import, only at the top level
+        prod!("importStatement" => "import", "importClause", "fromClause", "eos"),
+        prod!("importStatement" => "import", "str", "eos"),
+        prod!("importClause" => "importedBinding"),                                         //default
+        prod!("importClause" => "namespaceImport"),
+        prod!("importClause" => "namedImports"),
+        prod!("importClause" => "importedBinding", ",", "namespaceImport"),
+        prod!("importClause" => "importedBinding", ",", "namedImports"),
+        prod!("importedBinding" => "bindingIdentifier[+Await]"),
+        prod!("namespaceImport" => "*", "as", "importedBinding"),
+        prod!("namedImports" => "{", "}"),
+        prod!("namedImports" => "{", "importsList", "}"),
+        prod!("namedImports" => "{", "importsList", ",", "}"),
+        prod!("importsList" => "importSpecifier"),
+        prod!("importsList" => "importsList", ",", "importSpecifier"),
+        prod!("importSpecifier" => "importedBinding"),
+        prod!("importSpecifier" => "moduleExportName", "as", "importedBinding"),
+        prod!("moduleExportName" => "identifierName"),
+        prod!("moduleExportName" => "str"),
+        prod!("fromClause" => "from", "str"),
+        //export, only at the top level
+        prod!("exportStatement" => "export", "exportFromClause", "fromClause", "eos"),
+        prod!("exportStatement" => "export", "namedExports", "eos"),
+        prod!("exportStatement" => "export", "variableStatement"),
+        prod!("exportStatement" => "export", "functionDeclaration"),
+        prod!("exportStatement" => "export", "classDeclaration"),
+        prod!("exportStatement" => "export", "default", "functionExpression"),              //the name is optional here
+        prod!("exportStatement" => "export", "default", "classExpression"),
+        prod!("exportStatement" => "export", "default", "assignmentExpression[+NoDecl]", "eos"),
+        prod!("exportFromClause" => "*"),
+        prod!("exportFromClause" => "*", "as", "moduleExportName"),
+        prod!("exportFromClause" => "namedExports"),
+        prod!("namedExports" => "{", "}"),
+        prod!("namedExports" => "{", "exportsList", "}"),
+        prod!("namedExports" => "{", "exportsList", ",", "}"),
+        prod!("exportsList" => "exportSpecifier"),
+        prod!("exportsList" => "exportsList", ",", "exportSpecifier"),
+        prod!("exportSpecifier" => "moduleExportName"),
+        prod!("exportSpecifier" => "moduleExportName", "as", "moduleExportName"),
+        prod!("eos" => ";"),
+        prod!("doWhileEos" => ";"),
         //var
-        prod!("variableStatement" => "varDeclaration"),
-        prod!("variableStatement" => "letDeclaration"),
-        prod!("variableStatement" => "constDeclaration"),
-        prod!("varDeclaration" => "var", "variableDeclarationList", "eos"),
-        prod!("letDeclaration" => "let", "variableDeclarationList", "eos"),
-        prod!("constDeclaration" => "const", "variableDeclarationList", "eos"),
-        prod!("variableDeclarationList" => "variableDeclaration", "comma", "variableDeclarationList"),
-        prod!("variableDeclarationList" => "variableDeclaration"),
-        prod!("variableDeclaration" => "identifier", "initializer_"),                      //initializer_ => (optional) initializer
-        prod!("initializer_" => "=", "expression"),
-        prod!("initializer_" => ""),
+        prod!("variableStatement[Yield, Await]" => "varDeclaration[?Yield, ?Await]"),
+        prod!("variableStatement[Yield, Await]" => "letDeclaration[?Yield, ?Await]"),
+        prod!("variableStatement[Yield, Await]" => "constDeclaration[?Yield, ?Await]"),
+        prod!("varDeclaration[Yield, Await]" => "var", "variableDeclarationList[?Yield, ?Await]", "eos"),
+        prod!("letDeclaration[Yield, Await]" => "let", "variableDeclarationList[?Yield, ?Await]", "eos"),
+        prod!("constDeclaration[Yield, Await]" => "const", "variableDeclarationList[?Yield, ?Await]", "eos"),
+        prod!("variableDeclarationList[NoIn, Yield, Await]" => "variableDeclaration[?NoIn, ?Yield, ?Await]", ",", "variableDeclarationList[?NoIn, ?Yield, ?Await]"),
+        prod!("variableDeclarationList[NoIn, Yield, Await]" => "variableDeclaration[?NoIn, ?Yield, ?Await]"),
+        prod!("variableDeclaration[NoIn, Yield, Await]" => "bindingIdentifier[?Yield, ?Await]", "initializer_[?NoIn, ?Yield, ?Await]"),                      //initializer_ => (optional) initializer
+        prod!("variableDeclaration[NoIn, Yield, Await]" => "bindingPattern[?Yield, ?Await]", "initializer[?NoIn, ?Yield, ?Await]"),
+        prod!("initializer_[NoIn, Yield, Await]" => "initializer[?NoIn, ?Yield, ?Await]"),
+        prod!("initializer_[NoIn, Yield, Await]" => ),
+        prod!("initializer[NoIn, Yield, Await]" => "=", "assignmentExpression[?NoIn, ?Yield, ?Await]"),
+        //binding patterns
+        prod!("bindingPattern[Yield, Await]" => "objectBindingPattern[?Yield, ?Await]"),
+        prod!("bindingPattern[Yield, Await]" => "arrayBindingPattern[?Yield, ?Await]"),
+        prod!("objectBindingPattern[Yield, Await]" => "{", "}"),
+        prod!("objectBindingPattern[Yield, Await]" => "{", "bindingRestProperty[?Yield, ?Await]", "}"),
+        prod!("objectBindingPattern[Yield, Await]" => "{", "bindingPropertyList[?Yield, ?Await]", "}"),
+        prod!("objectBindingPattern[Yield, Await]" => "{", "bindingPropertyList[?Yield, ?Await]", ",", "}"),
+        prod!("objectBindingPattern[Yield, Await]" => "{", "bindingPropertyList[?Yield, ?Await]", ",", "bindingRestProperty[?Yield, ?Await]", "}"),
+        prod!("bindingRestProperty[Yield, Await]" => "...", "bindingIdentifier[?Yield, ?Await]"),
+        prod!("bindingPropertyList[Yield, Await]" => "bindingProperty[?Yield, ?Await]"),
+        prod!("bindingPropertyList[Yield, Await]" => "bindingPropertyList[?Yield, ?Await]", ",", "bindingProperty[?Yield, ?Await]"),
+        prod!("bindingProperty[Yield, Await]" => "singleNameBinding[?Yield, ?Await]"),
+        prod!("bindingProperty[Yield, Await]" => "propertyName[?Yield, ?Await]", ":", "bindingElement[?Yield, ?Await]"),
+        prod!("arrayBindingPattern[Yield, Await]" => "[", "elision_", "bindingRestElement_[?Yield, ?Await]", "]"),
+        prod!("arrayBindingPattern[Yield, Await]" => "[", "bindingElementList[?Yield, ?Await]", "]"),
+        prod!("arrayBindingPattern[Yield, Await]" => "[", "bindingElementList[?Yield, ?Await]", ",", "elision_", "bindingRestElement_[?Yield, ?Await]", "]"),
+        prod!("bindingElementList[Yield, Await]" => "elision_", "bindingElement[?Yield, ?Await]"),
+        prod!("bindingElementList[Yield, Await]" => "bindingElementList[?Yield, ?Await]", ",", "elision_", "bindingElement[?Yield, ?Await]"),
+        prod!("bindingRestElement_[Yield, Await]" => "bindingRestElement[?Yield, ?Await]"),
+        prod!("bindingRestElement_[Yield, Await]" => ),
+        prod!("bindingRestElement[Yield, Await]" => "...", "bindingIdentifier[?Yield, ?Await]"),
+        prod!("bindingRestElement[Yield, Await]" => "...", "bindingPattern[?Yield, ?Await]"),
+        prod!("bindingElement[Yield, Await]" => "singleNameBinding[?Yield, ?Await]"),
+        prod!("bindingElement[Yield, Await]" => "bindingPattern[?Yield, ?Await]", "initializer_[?Yield, ?Await]"),
+        prod!("singleNameBinding[Yield, Await]" => "bindingIdentifier[?Yield, ?Await]", "initializer_[?Yield, ?Await]"),
+        prod!("elision_" => "elision"),                                                    //holes
+        prod!("elision_" => ),
+        prod!("elision" => ","),
+        prod!("elision" => "elision", ","),
         //function
-        prod!("statement" => "functionExpression"),
+        prod!("functionDeclaration[Yield, Await]" => "function", "bindingIdentifier[?Yield, ?Await]", "parameters", "block"),
+        prod!("functionDeclaration[Yield, Await]" => "function", "*", "bindingIdentifier[?Yield, ?Await]", "parameters[+Yield]", "block[+Yield]"),
+        prod!("functionDeclaration[Yield, Await]" => "async", "function", "bindingIdentifier[?Yield, ?Await]", "parameters[+Await]", "block[+Await]"),
+        prod!("functionDeclaration[Yield, Await]" => "async", "function", "*", "bindingIdentifier[?Yield, ?Await]", "parameters[+Yield, +Await]", "block[+Yield, +Await]"),
         prod!("functionExpression" => "function", "identifier_", "parameters", "block"),   //identifier_ => (optional) identifier
-        prod!("functionExpression" => "arrowFunction"),
-        prod!("arrowFunction" => "parameters", "=>", "expression"),
-        prod!("parameters" => "(", "parameterList", ")"),
-        prod!("parameterList" => "identifier", "comma", "parameterList"),
-        prod!("parameterList" => "identifier"),
-        prod!("parameterList" => ""),
+        prod!("functionExpression" => "function", "*", "identifier_[+Yield]", "parameters[+Yield]", "block[+Yield]"),
+        prod!("functionExpression" => "async", "function", "identifier_[+Await]", "parameters[+Await]", "block[+Await]"),
+        prod!("functionExpression" => "async", "function", "*", "identifier_[+Yield, +Await]", "parameters[+Yield, +Await]", "block[+Yield, +Await]"),
+        prod!("arrowFunction[NoIn, Yield, Await]" => "arrowParameters[?Yield, ?Await]", "=>", "conciseBody[?NoIn]"),
+        prod!("arrowFunction[NoIn, Yield, Await]" => "async", "bindingIdentifier[+Await]", "=>", "conciseBody[?NoIn, +Await]"),
+        prod!("arrowFunction[NoIn, Yield, Await]" => "coverCallExpressionAndAsyncArrowHead[?Yield, ?Await]", "=>", "conciseBody[?NoIn, +Await]"),
+        prod!("conciseBody[NoIn, Await]" => "assignmentExpression[+NoBrace, ?NoIn, ?Await]"),
+        prod!("conciseBody[NoIn, Await]" => "block[?Await]"),
+        prod!("arrowParameters[Yield, Await]" => "bindingIdentifier[?Yield, ?Await]"),
+        prod!("arrowParameters[Yield, Await]" => "(", ")"),
+        prod!("arrowParameters[Yield, Await]" => "(", "expression[?Yield, ?Await]", ")"),                         //the parameters are parsed as an expression
+        prod!("arrowParameters[Yield, Await]" => "(", "expression[?Yield, ?Await]", ",", ")"),
+        prod!("arrowParameters[Yield, Await]" => "(", "bindingRestElement[?Yield, ?Await]", ")"),
+        prod!("arrowParameters[Yield, Await]" => "(", "expression[?Yield, ?Await]", ",", "bindingRestElement[?Yield, ?Await]", ")"),
+        prod!("parameters[Yield, Await]" => "(", ")"),
+        prod!("parameters[Yield, Await]" => "(", "bindingRestElement[?Yield, ?Await]", ")"),
+        prod!("parameters[Yield, Await]" => "(", "parameterList[?Yield, ?Await]", ")"),
+        prod!("parameters[Yield, Await]" => "(", "parameterList[?Yield, ?Await]", ",", ")"),
+        prod!("parameters[Yield, Await]" => "(", "parameterList[?Yield, ?Await]", ",", "bindingRestElement[?Yield, ?Await]", ")"),
+        prod!("parameterList[Yield, Await]" => "parameter[?Yield, ?Await]"),
+        prod!("parameterList[Yield, Await]" => "parameterList[?Yield, ?Await]", ",", "parameter[?Yield, ?Await]"),
+        prod!("parameter[Yield, Await]" => "bindingElement[?Yield, ?Await]"),
+        prod!("identifier_[Yield, Await]" => "bindingIdentifier[?Yield, ?Await]"),
+        prod!("identifier_[Yield, Await]" => ),
         //class
-        prod!("classDeclaration" => "class", "identifier", "classBody"),
-        prod!("classBody" => "{", "classElementList", "}"),
-        prod!("classElementList" => "classElement", "classElementList"),
-        prod!("classElementList" => "classElement"),
-        prod!("classElement" => "methodDefinition"),
-        prod!("classElement" => "propertyDefinition"),
-        prod!("methodDefinition" => "identifier", "parameters", "block"),
-        prod!("propertyDefinition" => "identifier", ":", "expression"),
-        //deconstructing assignment
-        prod!("variableStatement" => "destructuringAssignment"),
-        prod!("destructuringAssignment" => "let", "arrayDestructuring", "=", "expression"),
-        prod!("destructuringAssignment" => "const", "objectDestructuring", "=", "expression"),
-        prod!("arrayDestructuring" => "[", "elementList", "]"),
-        prod!("elementList" => "identifier", "comma", "elementList"),
-        prod!("elementList" => "identifier"),
-        prod!("objectDestructuring" => "{", "propertyList", "}"),
-        prod!("propertyList" => "identifier", "comma", "propertyList"),
-        prod!("propertyList" => "identifier"),
+        prod!("classDeclaration[Yield, Await]" => "class", "bindingIdentifier[?Yield, ?Await]", "classTail[?Yield, ?Await]"),
+        prod!("classExpression[Yield, Await]" => "class", "identifier_[?Yield, ?Await]", "classTail[?Yield, ?Await]"),
+        prod!("classTail[Yield, Await]" => "classHeritage_[?Yield, ?Await]", "{", "classElementList_[?Yield, ?Await]", "}"),
+        prod!("classHeritage_[Yield, Await]" => "extends", "leftHandSideExpression[?Yield, ?Await]"),
+        prod!("classHeritage_[Yield, Await]" => ),
+        prod!("classElementList_[Yield, Await]" => "classElement[?Yield, ?Await]", "classElementList_[?Yield, ?Await]"),
+        prod!("classElementList_[Yield, Await]" => ),
+        prod!("classElement[Yield, Await]" => "methodDefinition[?Yield, ?Await]"),
+        prod!("classElement[Yield, Await]" => "static", "methodDefinition[?Yield, ?Await]"),
+        prod!("classElement[Yield, Await]" => "fieldDefinition[?Yield, ?Await]", "eos"),
+        prod!("classElement[Yield, Await]" => "static", "fieldDefinition[?Yield, ?Await]", "eos"),
+        prod!("classElement[Yield, Await]" => "classStaticBlock[?Yield, ?Await]"),
+        prod!("classElement[Yield, Await]" => ";"),
+        prod!("fieldDefinition[Yield, Await]" => "classElementName[?Yield, ?Await]", "initializer_[?Yield, ?Await]"),
+        prod!("classStaticBlock[Yield, Await]" => "static", "{", "statementList_[+Await]", "}"),
+        prod!("classElementName[Yield, Await]" => "propertyName[?Yield, ?Await]"),
+        prod!("classElementName[Yield, Await]" => "privateidentifier"),
+        prod!("methodDefinition[Yield, Await]" => "classElementName[?Yield, ?Await]", "parameters", "block"),
+        prod!("methodDefinition[Yield, Await]" => "*", "classElementName[?Yield, ?Await]", "parameters[+Yield]", "block[+Yield]"),
+        prod!("methodDefinition[Yield, Await]" => "async", "classElementName[?Yield, ?Await]", "parameters[+Await]", "block[+Await]"),
+        prod!("methodDefinition[Yield, Await]" => "async", "*", "classElementName[?Yield, ?Await]", "parameters[+Yield, +Await]", "block[+Yield, +Await]"),
+        prod!("methodDefinition[Yield, Await]" => "get", "classElementName[?Yield, ?Await]", "(", ")", "block"),
+        prod!("methodDefinition[Yield, Await]" => "set", "classElementName[?Yield, ?Await]", "(", "parameter", ")", "block"),
+        prod!("propertyName[Yield, Await]" => "identifierName"),
+        prod!("propertyName[Yield, Await]" => "str"),
+        prod!("propertyName[Yield, Await]" => "number"),
+        prod!("propertyName[Yield, Await]" => "[", "assignmentExpression[?Yield, ?Await]", "]"),
         //template
-        prod!("expression" => "templateLiteral"),
-        prod!("templateLiteral" => "`", "templateCharacters", "`"),
-        prod!("templateCharacters" => "templateCharacter", "templateCharacters"),
-        prod!("templateCharacters" => "templateCharacter"),
+        prod!("templateLiteral[Yield, Await]" => "nosubstitutiontemplate"),
+        prod!("templateLiteral[Yield, Await]" => "templatehead", "expression[?Yield, ?Await]", "templateSpans[?Yield, ?Await]"),
+        prod!("templateSpans[Yield, Await]" => "templatemiddle", "expression[?Yield, ?Await]", "templateSpans[?Yield, ?Await]"),
+        prod!("templateSpans[Yield, Await]" => "templatetail"),
         //expression
-        prod!("expressionStatement" => "expression", "eos"),
+        prod!("expressionStatement[Yield, Await]" => "expression[+NoBrace, +NoDecl, ?Yield, ?Await]", "eos"),
         //if
-        prod!("ifStatement" => "if", "(", "expression", ")", "statement", "elseClause_"),
-        prod!("elseClause_" => "else", "statement"),
-        prod!("elseClause_" => ""),
+        prod!("ifStatement[Yield, Await]" => "if", "(", "expression[?Yield, ?Await]", ")", "statement[?Yield, ?Await]", "elseClause_[?Yield, ?Await]"),
+        prod!("elseClause_[Yield, Await]" => "else", "statement[?Yield, ?Await]"),
+        prod!("elseClause_[Yield, Await]" => ),
         //iteration
-        prod!("iterationStatement" => "whileStatement"),
-        prod!("iterationStatement" => "doWhileStatement"),
-        prod!("iterationStatement" => "forStatement"),
-        prod!("whileStatement" => "while", "(", "expression", ")", "statement"),
-        prod!("doWhileStatement" => "do", "statement", "while", "(", "expression", ")", "eos"),
+        prod!("iterationStatement[Yield, Await]" => "whileStatement[?Yield, ?Await]"),
+        prod!("iterationStatement[Yield, Await]" => "doWhileStatement[?Yield, ?Await]"),
+        prod!("iterationStatement[Yield, Await]" => "forStatement[?Yield, ?Await]"),
+        prod!("iterationStatement[Yield, Await]" => "forInStatement[?Yield, ?Await]"),
+        prod!("iterationStatement[Yield, Await]" => "forOfStatement[?Yield, ?Await]"),
+        prod!("whileStatement[Yield, Await]" => "while", "(", "expression[?Yield, ?Await]", ")", "statement[?Yield, ?Await]"),
+        prod!("doWhileStatement[Yield, Await]" => "do", "statement[?Yield, ?Await]", "while", "(", "expression[?Yield, ?Await]", ")", "doWhileEos"),
         //for
-        prod!("forStatement" => "for", "(", "forInit_", ";", "forCondition_", ";", "forUpdate_", ")", "statement"),
-        prod!("forInit_" => "variableStatement"),
-        prod!("forInit_" => "expression"),
-        prod!("forInit_" => ""),
-        prod!("forCondition_" => "expression"),
-        prod!("forCondition_" => ""),
-        prod!("forUpdate_" => "expression"),
-        prod!("forUpdate_" => ""),
+        prod!("forStatement[Yield, Await]" => "for", "(", "forInit_[?Yield, ?Await]", ";", "forCondition_[?Yield, ?Await]", ";", "forUpdate_[?Yield, ?Await]", ")", "statement[?Yield, ?Await]"),
+        prod!("forInit_[Yield, Await]" => "expression[+NoIn, ?Yield, ?Await]"),
+        prod!("forInit_[Yield, Await]" => "var", "variableDeclarationList[+NoIn, ?Yield, ?Await]"),
+        prod!("forInit_[Yield, Await]" => "let", "variableDeclarationList[+NoIn, ?Yield, ?Await]"),
+        prod!("forInit_[Yield, Await]" => "const", "variableDeclarationList[+NoIn, ?Yield, ?Await]"),
+        prod!("forInit_[Yield, Await]" => ),
+        prod!("forCondition_[Yield, Await]" => "expression[?Yield, ?Await]"),
+        prod!("forCondition_[Yield, Await]" => ),
+        prod!("forUpdate_[Yield, Await]" => "expression[?Yield, ?Await]"),
+        prod!("forUpdate_[Yield, Await]" => ),
+        prod!("forInStatement[Yield, Await]" => "for", "(", "forBinding[?Yield, ?Await]", "in", "expression[?Yield, ?Await]", ")", "statement[?Yield, ?Await]"),
+        prod!("forOfStatement[Yield, Await]" => "for", "(", "forBinding[+NoAsync, ?Yield, ?Await]", "of", "assignmentExpression[?Yield, ?Await]", ")", "statement[?Yield, ?Await]"),
+        prod!("forOfStatement[Yield, Await]" => "[+Await]", "for", "await", "(", "forBinding[+NoAsync, ?Yield, ?Await]", "of", "assignmentExpression[?Yield, ?Await]", ")", "statement[?Yield, ?Await]"),
+        prod!("forBinding[NoAsync, Yield, Await]" => "var", "bindingIdentifier[?Yield, ?Await]"),
+        prod!("forBinding[NoAsync, Yield, Await]" => "let", "bindingIdentifier[?Yield, ?Await]"),
+        prod!("forBinding[NoAsync, Yield, Await]" => "const", "bindingIdentifier[?Yield, ?Await]"),
+        prod!("forBinding[NoAsync, Yield, Await]" => "var", "bindingPattern[?Yield, ?Await]"),
+        prod!("forBinding[NoAsync, Yield, Await]" => "let", "bindingPattern[?Yield, ?Await]"),
+        prod!("forBinding[NoAsync, Yield, Await]" => "const", "bindingPattern[?Yield, ?Await]"),
+        prod!("forBinding[NoAsync, Yield, Await]" => "leftHandSideExpression[?Yield, ?Await, ?NoAsync]"),     //array and object literals are reparsed as patterns
         //continue
-        prod!("continueStatement" => "continue", "identifier_", "eos"),
+        prod!("continueStatement[Yield, Await]" => "continue", "identifier_[?Yield, ?Await]", "eos"),
         //break
-        prod!("breakStatement" => "break", "identifier_", "eos"),
+        prod!("breakStatement[Yield, Await]" => "break", "identifier_[?Yield, ?Await]", "eos"),
         //return
-        prod!("returnStatement" => "return", "expression_", "eos"),
-        prod!("expression_" => "expression"),
-        prod!("expression_" => ""),
-        //yield
-        prod!("yieldStatement" => "yield", "expression_", "eos"),
+        prod!("returnStatement[Yield, Await]" => "return", "expression_[?Yield, ?Await]", "eos"),
+        prod!("expression_[Yield, Await]" => "expression[?Yield, ?Await]"),
+        prod!("expression_[Yield, Await]" => ),
         //with
-        prod!("withStatement" => "with", "(", "expression", ")", "statement"),
+        prod!("withStatement[Yield, Await]" => "with", "(", "expression[?Yield, ?Await]", ")", "statement[?Yield, ?Await]"),
         //labelled
-        prod!("labelledStatement" => "identifier", ":", "statement"),
+        prod!("labelledStatement[Yield, Await]" => "identifier", ":", "statement[?Yield, ?Await]"),
         //switch
-        prod!("switchStatement" => "switch", "(", "expression", ")", "{", "caseBlock", "}"),
-        prod!("caseBlock" => "caseClauses_", "defaultClause_", "caseClauses_"),
-        prod!("caseClauses_" => "caseClause", "caseClauses_"),
-        prod!("caseClauses_" => ""),
-        prod!("caseClause" => "case", "expression", ":", "statementList_"),
-        prod!("defaultClause_" => "defaultClause"),
-        prod!("defaultClause_" => ""),
-        prod!("defaultClause" => "default", ":", "statementList_"),
-        prod!("statementList_" => "statementList"),
-        prod!("statementList_" => ""),
+        prod!("switchStatement[Yield, Await]" => "switch", "(", "expression[?Yield, ?Await]", ")", "{", "caseBlock[?Yield, ?Await]", "}"),
+        prod!("caseBlock[Yield, Await]" => "caseClauses_[?Yield, ?Await]"),
+        prod!("caseBlock[Yield, Await]" => "caseClauses_[?Yield, ?Await]", "defaultClause[?Yield, ?Await]", "caseClauses_[?Yield, ?Await]"),
+        prod!("caseClauses_[Yield, Await]" => "caseClause[?Yield, ?Await]", "caseClauses_[?Yield, ?Await]"),
+        prod!("caseClauses_[Yield, Await]" => ),
+        prod!("caseClause[Yield, Await]" => "case", "expression[?Yield, ?Await]", ":", "statementList_[?Yield, ?Await]"),
+        prod!("defaultClause[Yield, Await]" => "default", ":", "statementList_[?Yield, ?Await]"),
+        prod!("statementList_[Yield, Await]" => "statementList[?Yield, ?Await]"),
+        prod!("statementList_[Yield, Await]" => ),
         //throw
-        prod!("throwStatement" => "throw", "expression", "eos"),
+        prod!("throwStatement[Yield, Await]" => "throw", "expression[?Yield, ?Await]", "eos"),
         //try
-        prod!("tryStatement" => "try", "block", "catchClause_", "finallyClause_"),
-        prod!("catchClause_" => "catchClause"),
-        prod!("catchClause_" => ""),
-        prod!("catchClause" => "catch", "(", "identifier", ")", "block"),
-        prod!("finallyClause_" => "finallyClause"),
-        prod!("finallyClause_" => ""),
-        prod!("finallyClause" => "finally", "block"),
+        prod!("tryStatement[Yield, Await]" => "try", "block[?Yield, ?Await]", "catchClause_[?Yield, ?Await]", "finallyClause_[?Yield, ?Await]"),
+        prod!("catchClause_[Yield, Await]" => "catchClause[?Yield, ?Await]"),
+        prod!("catchClause_[Yield, Await]" => ),
+        prod!("catchClause[Yield, Await]" => "catch", "(", "catchParameter[?Yield, ?Await]", ")", "block[?Yield, ?Await]"),
+        prod!("catchParameter[Yield, Await]" => "bindingIdentifier[?Yield, ?Await]"),
+        prod!("catchParameter[Yield, Await]" => "bindingPattern[?Yield, ?Await]"),
+        prod!("finallyClause_[Yield, Await]" => "finallyClause[?Yield, ?Await]"),
+        prod!("finallyClause_[Yield, Await]" => ),
+        prod!("finallyClause[Yield, Await]" => "finally", "block[?Yield, ?Await]"),
         //debugger
-        prod!("debuggerStatement" => "debugger", "eos")
-        //expression
-        prod!("expression" => "assignmentExpression"),
-        prod!("assignmentExpression" => "logicalOrExpression"),
-        prod!("assignmentExpression" => "identifier", "=", "logicalOrExpression"),
-        prod!("assignmentExpression" => "identifier", "+=", "logicalOrExpression"),
-        prod!("assignmentExpression" => "identifier", "-=", "logicalOrExpression"),
-
-        prod!("logicalOrExpression" => "logicalAndExpression"),
-        prod!("logicalOrExpression" => "logicalOrExpression", "||", "logicalAndExpression"),
-        prod!("logicalAndExpression" => "equalityExpression"),
-        prod!("logicalAndExpression" => "logicalAndExpression", "&&", "equalityExpression"),
-        prod!("equalityExpression" => "relationalExpression"),
-        prod!("equalityExpression" => "equalityExpression", "==", "relationalExpression"),
-        prod!("equalityExpression" => "equalityExpression", "!=", "relationalExpression"),
-        prod!("relationalExpression" => "additiveExpression"),
-        prod!("relationalExpression" => "relationalExpression", "<", "additiveExpression"),
-        prod!("relationalExpression" => "relationalExpression", ">", "additiveExpression"),
-        prod!("relationalExpression" => "relationalExpression", "<=", "additiveExpression"),
-        prod!("relationalExpression" => "relationalExpression", ">=", "additiveExpression"),
-        prod!("additiveExpression" => "multiplicativeExpression"),
-        prod!("additiveExpression" => "additiveExpression", "+", "multiplicativeExpression"),
-        prod!("additiveExpression" => "additiveExpression", "-", "multiplicativeExpression"),
-        prod!("multiplicativeExpression" => "unaryExpression"),
-        prod!("multiplicativeExpression" => "multiplicativeExpression", "*", "unaryExpression"),
-        prod!("multiplicativeExpression" => "multiplicativeExpression", "/", "unaryExpression"),
-        prod!("unaryExpression" => "primaryExpression"),
-        prod!("unaryExpression" => "-", "unaryExpression"),
-        prod!("unaryExpression" => "!", "unaryExpression"),
-        prod!("primaryExpression" => "identifier"),
-        prod!("primaryExpression" => "literal"),
-        prod!("primaryExpression" => "(", "expression", ")")
-        //func
-        prod!("expression" => "identifier", "(", "argumentList_", ")"),
-        prod!("argumentList_" => "expression", ",", "argumentList_"),
-        prod!("argumentList_" => "expression"),
-        prod!("argumentList_" => ""),
+        prod!("debuggerStatement" => "debugger", "eos"),
+        //expression, [NoIn]: `in` is not an operator in the head of a for statement
+        prod!("expression[NoBrace, NoDecl, NoIn, Yield, Await]" => "assignmentExpression[?NoBrace, ?NoDecl, ?NoIn, ?Yield, ?Await]"),
+        prod!("expression[NoBrace, NoDecl, NoIn, Yield, Await]" => "expression[?NoBrace, ?NoDecl, ?NoIn, ?Yield, ?Await]", ",", "assignmentExpression[?NoIn, ?Yield, ?Await]"),
+        prod!("assignmentExpression[NoBrace, NoDecl, NoIn, Yield, Await]" => "conditionalExpression[?NoBrace, ?NoDecl, ?NoIn, ?Yield, ?Await]"),
+        prod!("assignmentExpression[NoBrace, NoDecl, NoIn, Yield, Await]" => "arrowFunction[?NoIn, ?Yield, ?Await]"),
+        prod!("assignmentExpression[NoBrace, NoDecl, NoIn, Yield, Await]" => "[+Yield]", "yieldExpression[?NoIn, ?Await]"),
+        prod!("yieldExpression[NoIn, Await]" => "yield"),                          //no line break after yield
+        prod!("yieldExpression[NoIn, Await]" => "yield", "assignmentExpression[?NoIn, +Yield, ?Await]"),
+        prod!("yieldExpression[NoIn, Await]" => "yield", "*", "assignmentExpression[?NoIn, +Yield, ?Await]"),
+        prod!("assignmentExpression[NoBrace, NoDecl, NoIn, Yield, Await]" => "leftHandSideExpression[?NoBrace, ?NoDecl, ?Yield, ?Await]", "assignmentOperator", "assignmentExpression[?NoIn, ?Yield, ?Await]"),
+        prod!("assignmentOperator" => "="),
+        prod!("assignmentOperator" => "*="),
+        prod!("assignmentOperator" => "/="),
+        prod!("assignmentOperator" => "%="),
+        prod!("assignmentOperator" => "+="),
+        prod!("assignmentOperator" => "-="),
+        prod!("assignmentOperator" => "<<="),
+        prod!("assignmentOperator" => ">>="),
+        prod!("assignmentOperator" => ">>>="),
+        prod!("assignmentOperator" => "&="),
+        prod!("assignmentOperator" => "^="),
+        prod!("assignmentOperator" => "|="),
+        prod!("assignmentOperator" => "**="),
+        prod!("assignmentOperator" => "&&="),
+        prod!("assignmentOperator" => "||="),
+        prod!("assignmentOperator" => "??="),
+        prod!("conditionalExpression[NoBrace, NoDecl, NoIn, Yield, Await]" => "shortCircuitExpression[?NoBrace, ?NoDecl, ?NoIn, ?Yield, ?Await]"),
+        prod!("conditionalExpression[NoBrace, NoDecl, NoIn, Yield, Await]" => "shortCircuitExpression[?NoBrace, ?NoDecl, ?NoIn, ?Yield, ?Await]", "?", "assignmentExpression[?Yield, ?Await]", ":", "assignmentExpression[?NoIn, ?Yield, ?Await]"),
+        prod!("shortCircuitExpression[NoBrace, NoDecl, NoIn, Yield, Await]" => "logicalOrExpression[?NoBrace, ?NoDecl, ?NoIn, ?Yield, ?Await]"),
+        prod!("shortCircuitExpression[NoBrace, NoDecl, NoIn, Yield, Await]" => "coalesceExpression[?NoBrace, ?NoDecl, ?NoIn, ?Yield, ?Await]"),
+        prod!("coalesceExpression[NoBrace, NoDecl, NoIn, Yield, Await]" => "coalesceExpressionHead[?NoBrace, ?NoDecl, ?NoIn, ?Yield, ?Await]", "??", "bitwiseOrExpression[?NoIn, ?Yield, ?Await]"),  //?? doesn't mix with || and && without parentheses
+        prod!("coalesceExpressionHead[NoBrace, NoDecl, NoIn, Yield, Await]" => "coalesceExpression[?NoBrace, ?NoDecl, ?NoIn, ?Yield, ?Await]"),
+        prod!("coalesceExpressionHead[NoBrace, NoDecl, NoIn, Yield, Await]" => "bitwiseOrExpression[?NoBrace, ?NoDecl, ?NoIn, ?Yield, ?Await]"),
+        prod!("logicalOrExpression[NoBrace, NoDecl, NoIn, Yield, Await]" => "logicalAndExpression[?NoBrace, ?NoDecl, ?NoIn, ?Yield, ?Await]"),
+        prod!("logicalOrExpression[NoBrace, NoDecl, NoIn, Yield, Await]" => "logicalOrExpression[?NoBrace, ?NoDecl, ?NoIn, ?Yield, ?Await]", "||", "logicalAndExpression[?NoIn, ?Yield, ?Await]"),
+        prod!("logicalAndExpression[NoBrace, NoDecl, NoIn, Yield, Await]" => "bitwiseOrExpression[?NoBrace, ?NoDecl, ?NoIn, ?Yield, ?Await]"),
+        prod!("logicalAndExpression[NoBrace, NoDecl, NoIn, Yield, Await]" => "logicalAndExpression[?NoBrace, ?NoDecl, ?NoIn, ?Yield, ?Await]", "&&", "bitwiseOrExpression[?NoIn, ?Yield, ?Await]"),
+        prod!("bitwiseOrExpression[NoBrace, NoDecl, NoIn, Yield, Await]" => "bitwiseXorExpression[?NoBrace, ?NoDecl, ?NoIn, ?Yield, ?Await]"),
+        prod!("bitwiseOrExpression[NoBrace, NoDecl, NoIn, Yield, Await]" => "bitwiseOrExpression[?NoBrace, ?NoDecl, ?NoIn, ?Yield, ?Await]", "|", "bitwiseXorExpression[?NoIn, ?Yield, ?Await]"),
+        prod!("bitwiseXorExpression[NoBrace, NoDecl, NoIn, Yield, Await]" => "bitwiseAndExpression[?NoBrace, ?NoDecl, ?NoIn, ?Yield, ?Await]"),
+        prod!("bitwiseXorExpression[NoBrace, NoDecl, NoIn, Yield, Await]" => "bitwiseXorExpression[?NoBrace, ?NoDecl, ?NoIn, ?Yield, ?Await]", "^", "bitwiseAndExpression[?NoIn, ?Yield, ?Await]"),
+        prod!("bitwiseAndExpression[NoBrace, NoDecl, NoIn, Yield, Await]" => "equalityExpression[?NoBrace, ?NoDecl, ?NoIn, ?Yield, ?Await]"),
+        prod!("bitwiseAndExpression[NoBrace, NoDecl, NoIn, Yield, Await]" => "bitwiseAndExpression[?NoBrace, ?NoDecl, ?NoIn, ?Yield, ?Await]", "&", "equalityExpression[?NoIn, ?Yield, ?Await]"),
+        prod!("equalityExpression[NoBrace, NoDecl, NoIn, Yield, Await]" => "relationalExpression[?NoBrace, ?NoDecl, ?NoIn, ?Yield, ?Await]"),
+        prod!("equalityExpression[NoBrace, NoDecl, NoIn, Yield, Await]" => "equalityExpression[?NoBrace, ?NoDecl, ?NoIn, ?Yield, ?Await]", "==", "relationalExpression[?NoIn, ?Yield, ?Await]"),
+        prod!("equalityExpression[NoBrace, NoDecl, NoIn, Yield, Await]" => "equalityExpression[?NoBrace, ?NoDecl, ?NoIn, ?Yield, ?Await]", "!=", "relationalExpression[?NoIn, ?Yield, ?Await]"),
+        prod!("equalityExpression[NoBrace, NoDecl, NoIn, Yield, Await]" => "equalityExpression[?NoBrace, ?NoDecl, ?NoIn, ?Yield, ?Await]", "===", "relationalExpression[?NoIn, ?Yield, ?Await]"),
+        prod!("equalityExpression[NoBrace, NoDecl, NoIn, Yield, Await]" => "equalityExpression[?NoBrace, ?NoDecl, ?NoIn, ?Yield, ?Await]", "!==", "relationalExpression[?NoIn, ?Yield, ?Await]"),
+        prod!("relationalExpression[NoBrace, NoDecl, NoIn, Yield, Await]" => "shiftExpression[?NoBrace, ?NoDecl, ?Yield, ?Await]"),
+        prod!("relationalExpression[NoBrace, NoDecl, NoIn, Yield, Await]" => "relationalExpression[?NoBrace, ?NoDecl, ?NoIn, ?Yield, ?Await]", "<", "shiftExpression[?Yield, ?Await]"),
+        prod!("relationalExpression[NoBrace, NoDecl, NoIn, Yield, Await]" => "relationalExpression[?NoBrace, ?NoDecl, ?NoIn, ?Yield, ?Await]", ">", "shiftExpression[?Yield, ?Await]"),
+        prod!("relationalExpression[NoBrace, NoDecl, NoIn, Yield, Await]" => "relationalExpression[?NoBrace, ?NoDecl, ?NoIn, ?Yield, ?Await]", "<=", "shiftExpression[?Yield, ?Await]"),
+        prod!("relationalExpression[NoBrace, NoDecl, NoIn, Yield, Await]" => "relationalExpression[?NoBrace, ?NoDecl, ?NoIn, ?Yield, ?Await]", ">=", "shiftExpression[?Yield, ?Await]"),
+        prod!("relationalExpression[NoBrace, NoDecl, NoIn, Yield, Await]" => "relationalExpression[?NoBrace, ?NoDecl, ?NoIn, ?Yield, ?Await]", "instanceof", "shiftExpression[?Yield, ?Await]"),
+        prod!("relationalExpression[NoBrace, NoDecl, NoIn, Yield, Await]" => "[~NoIn]", "relationalExpression[?NoBrace, ?NoDecl, ?NoIn, ?Yield, ?Await]", "in", "shiftExpression[?Yield, ?Await]"),
+        prod!("relationalExpression[NoBrace, NoDecl, NoIn, Yield, Await]" => "[~NoIn]", "privateidentifier", "in", "shiftExpression[?Yield, ?Await]"),
+        prod!("shiftExpression[NoBrace, NoDecl, Yield, Await]" => "additiveExpression[?NoBrace, ?NoDecl, ?Yield, ?Await]"),
+        prod!("shiftExpression[NoBrace, NoDecl, Yield, Await]" => "shiftExpression[?NoBrace, ?NoDecl, ?Yield, ?Await]", "<<", "additiveExpression[?Yield, ?Await]"),
+        prod!("shiftExpression[NoBrace, NoDecl, Yield, Await]" => "shiftExpression[?NoBrace, ?NoDecl, ?Yield, ?Await]", ">>", "additiveExpression[?Yield, ?Await]"),
+        prod!("shiftExpression[NoBrace, NoDecl, Yield, Await]" => "shiftExpression[?NoBrace, ?NoDecl, ?Yield, ?Await]", ">>>", "additiveExpression[?Yield, ?Await]"),
+        prod!("additiveExpression[NoBrace, NoDecl, Yield, Await]" => "multiplicativeExpression[?NoBrace, ?NoDecl, ?Yield, ?Await]"),
+        prod!("additiveExpression[NoBrace, NoDecl, Yield, Await]" => "additiveExpression[?NoBrace, ?NoDecl, ?Yield, ?Await]", "+", "multiplicativeExpression[?Yield, ?Await]"),
+        prod!("additiveExpression[NoBrace, NoDecl, Yield, Await]" => "additiveExpression[?NoBrace, ?NoDecl, ?Yield, ?Await]", "-", "multiplicativeExpression[?Yield, ?Await]"),
+        prod!("multiplicativeExpression[NoBrace, NoDecl, Yield, Await]" => "exponentiationExpression[?NoBrace, ?NoDecl, ?Yield, ?Await]"),
+        prod!("multiplicativeExpression[NoBrace, NoDecl, Yield, Await]" => "multiplicativeExpression[?NoBrace, ?NoDecl, ?Yield, ?Await]", "*", "exponentiationExpression[?Yield, ?Await]"),
+        prod!("multiplicativeExpression[NoBrace, NoDecl, Yield, Await]" => "multiplicativeExpression[?NoBrace, ?NoDecl, ?Yield, ?Await]", "/", "exponentiationExpression[?Yield, ?Await]"),
+        prod!("multiplicativeExpression[NoBrace, NoDecl, Yield, Await]" => "multiplicativeExpression[?NoBrace, ?NoDecl, ?Yield, ?Await]", "%", "exponentiationExpression[?Yield, ?Await]"),
+        prod!("exponentiationExpression[NoBrace, NoDecl, Yield, Await]" => "unaryExpression[?NoBrace, ?NoDecl, ?Yield, ?Await]"),
+        prod!("exponentiationExpression[NoBrace, NoDecl, Yield, Await]" => "updateExpression[?NoBrace, ?NoDecl, ?Yield, ?Await]", "**", "exponentiationExpression[?Yield, ?Await]"),
+        prod!("unaryExpression[NoBrace, NoDecl, Yield, Await]" => "updateExpression[?NoBrace, ?NoDecl, ?Yield, ?Await]"),
+        prod!("unaryExpression[NoBrace, NoDecl, Yield, Await]" => "delete", "unaryExpression[?Yield, ?Await]"),
+        prod!("unaryExpression[NoBrace, NoDecl, Yield, Await]" => "void", "unaryExpression[?Yield, ?Await]"),
+        prod!("unaryExpression[NoBrace, NoDecl, Yield, Await]" => "typeof", "unaryExpression[?Yield, ?Await]"),
+        prod!("unaryExpression[NoBrace, NoDecl, Yield, Await]" => "+", "unaryExpression[?Yield, ?Await]"),
+        prod!("unaryExpression[NoBrace, NoDecl, Yield, Await]" => "-", "unaryExpression[?Yield, ?Await]"),
+        prod!("unaryExpression[NoBrace, NoDecl, Yield, Await]" => "~", "unaryExpression[?Yield, ?Await]"),
+        prod!("unaryExpression[NoBrace, NoDecl, Yield, Await]" => "!", "unaryExpression[?Yield, ?Await]"),
+        prod!("unaryExpression[NoBrace, NoDecl, Yield, Await]" => "[+Await]", "awaitExpression[?Yield]"),
+        prod!("awaitExpression[Yield]" => "await", "unaryExpression[?Yield, +Await]"),
+        prod!("updateExpression[NoBrace, NoDecl, Yield, Await]" => "leftHandSideExpression[?NoBrace, ?NoDecl, ?Yield, ?Await]"),
+        prod!("updateExpression[NoBrace, NoDecl, Yield, Await]" => "leftHandSideExpression[?NoBrace, ?NoDecl, ?Yield, ?Await]", "++"),                //no line break before ++ and --
+        prod!("updateExpression[NoBrace, NoDecl, Yield, Await]" => "leftHandSideExpression[?NoBrace, ?NoDecl, ?Yield, ?Await]", "--"),
+        prod!("updateExpression[NoBrace, NoDecl, Yield, Await]" => "++", "unaryExpression[?Yield, ?Await]"),
+        prod!("updateExpression[NoBrace, NoDecl, Yield, Await]" => "--", "unaryExpression[?Yield, ?Await]"),
+        //member access, calls and new
+        prod!("leftHandSideExpression[NoBrace, NoDecl, Yield, Await, NoAsync]" => "newExpression[?NoBrace, ?NoDecl, ?Yield, ?Await, ?NoAsync]"),
+        prod!("leftHandSideExpression[NoBrace, NoDecl, Yield, Await, NoAsync]" => "callExpression[?NoBrace, ?NoDecl, ?Yield, ?Await]"),
+        prod!("leftHandSideExpression[NoBrace, NoDecl, Yield, Await, NoAsync]" => "optionalExpression[?NoBrace, ?NoDecl, ?Yield, ?Await]"),
+        prod!("newExpression[NoBrace, NoDecl, Yield, Await, NoAsync]" => "memberExpression[?NoBrace, ?NoDecl, ?Yield, ?Await, ?NoAsync]"),
+        prod!("newExpression[NoBrace, NoDecl, Yield, Await, NoAsync]" => "new", "newExpression[?Yield, ?Await]"),
+        prod!("memberExpression[NoBrace, NoDecl, Yield, Await, NoAsync]" => "primaryExpression[?NoBrace, ?NoDecl, ?Yield, ?Await]"),
+        prod!("memberExpression[NoBrace, NoDecl, Yield, Await, NoAsync]" => "[~NoAsync]", "async"),         //not before `(` (an async arrow head) or as a for-of target
+        prod!("memberExpression[NoBrace, NoDecl, Yield, Await, NoAsync]" => "memberExpression[?NoBrace, ?NoDecl, ?Yield, ?Await]", "[", "expression[?Yield, ?Await]", "]"),
+        prod!("memberExpression[NoBrace, NoDecl, Yield, Await, NoAsync]" => "memberExpression[?NoBrace, ?NoDecl, ?Yield, ?Await]", ".", "identifierName"),
+        prod!("memberExpression[NoBrace, NoDecl, Yield, Await, NoAsync]" => "memberExpression[?NoBrace, ?NoDecl, ?Yield, ?Await]", ".", "privateidentifier"),
+        prod!("memberExpression[NoBrace, NoDecl, Yield, Await, NoAsync]" => "memberExpression[?NoBrace, ?NoDecl, ?Yield, ?Await]", "templateLiteral[?Yield, ?Await]"),
+        prod!("memberExpression[NoBrace, NoDecl, Yield, Await, NoAsync]" => "superProperty[?Yield, ?Await]"),
+        prod!("memberExpression[NoBrace, NoDecl, Yield, Await, NoAsync]" => "metaProperty"),
+        prod!("memberExpression[NoBrace, NoDecl, Yield, Await, NoAsync]" => "new", "memberExpression[?Yield, ?Await]", "arguments[?Yield, ?Await]"),
+        prod!("superProperty[Yield, Await]" => "super", "[", "expression[?Yield, ?Await]", "]"),
+        prod!("superProperty[Yield, Await]" => "super", ".", "identifierName"),
+        prod!("metaProperty" => "new", ".", "target"),
+        prod!("metaProperty" => "import", ".", "meta"),
+        prod!("callExpression[NoBrace, NoDecl, Yield, Await]" => "memberExpression[?NoBrace, ?NoDecl, ?Yield, ?Await, +NoAsync]", "arguments[?Yield, ?Await]"),
+        prod!("callExpression[NoBrace, NoDecl, Yield, Await]" => "coverCallExpressionAndAsyncArrowHead[?Yield, ?Await]"),
+        prod!("coverCallExpressionAndAsyncArrowHead[Yield, Await]" => "async", "arguments[?Yield, ?Await]"),
+        prod!("callExpression[NoBrace, NoDecl, Yield, Await]" => "superCall[?Yield, ?Await]"),
+        prod!("callExpression[NoBrace, NoDecl, Yield, Await]" => "importCall[?Yield, ?Await]"),
+        prod!("callExpression[NoBrace, NoDecl, Yield, Await]" => "callExpression[?NoBrace, ?NoDecl, ?Yield, ?Await]", "arguments[?Yield, ?Await]"),
+        prod!("callExpression[NoBrace, NoDecl, Yield, Await]" => "callExpression[?NoBrace, ?NoDecl, ?Yield, ?Await]", "[", "expression[?Yield, ?Await]", "]"),
+        prod!("callExpression[NoBrace, NoDecl, Yield, Await]" => "callExpression[?NoBrace, ?NoDecl, ?Yield, ?Await]", ".", "identifierName"),
+        prod!("callExpression[NoBrace, NoDecl, Yield, Await]" => "callExpression[?NoBrace, ?NoDecl, ?Yield, ?Await]", ".", "privateidentifier"),
+        prod!("callExpression[NoBrace, NoDecl, Yield, Await]" => "callExpression[?NoBrace, ?NoDecl, ?Yield, ?Await]", "templateLiteral[?Yield, ?Await]"),
+        prod!("superCall[Yield, Await]" => "super", "arguments[?Yield, ?Await]"),
+        prod!("importCall[Yield, Await]" => "import", "(", "assignmentExpression[?Yield, ?Await]", ")"),
+        prod!("optionalExpression[NoBrace, NoDecl, Yield, Await]" => "memberExpression[?NoBrace, ?NoDecl, ?Yield, ?Await]", "optionalChain[?Yield, ?Await]"),
+        prod!("optionalExpression[NoBrace, NoDecl, Yield, Await]" => "callExpression[?NoBrace, ?NoDecl, ?Yield, ?Await]", "optionalChain[?Yield, ?Await]"),
+        prod!("optionalExpression[NoBrace, NoDecl, Yield, Await]" => "optionalExpression[?NoBrace, ?NoDecl, ?Yield, ?Await]", "optionalChain[?Yield, ?Await]"),
+        prod!("optionalChain[Yield, Await]" => "?.", "arguments[?Yield, ?Await]"),
+        prod!("optionalChain[Yield, Await]" => "?.", "[", "expression[?Yield, ?Await]", "]"),
+        prod!("optionalChain[Yield, Await]" => "?.", "identifierName"),
+        prod!("optionalChain[Yield, Await]" => "?.", "privateidentifier"),
+        prod!("optionalChain[Yield, Await]" => "optionalChain[?Yield, ?Await]", "arguments[?Yield, ?Await]"),
+        prod!("optionalChain[Yield, Await]" => "optionalChain[?Yield, ?Await]", "[", "expression[?Yield, ?Await]", "]"),
+        prod!("optionalChain[Yield, Await]" => "optionalChain[?Yield, ?Await]", ".", "identifierName"),
+        prod!("optionalChain[Yield, Await]" => "optionalChain[?Yield, ?Await]", ".", "privateidentifier"),
+        prod!("arguments[Yield, Await]" => "(", ")"),
+        prod!("arguments[Yield, Await]" => "(", "argumentList[?Yield, ?Await]", ")"),
+        prod!("arguments[Yield, Await]" => "(", "argumentList[?Yield, ?Await]", ",", ")"),
+        prod!("argumentList[Yield, Await]" => "assignmentExpression[?Yield, ?Await]"),
+        prod!("argumentList[Yield, Await]" => "spreadElement[?Yield, ?Await]"),
+        prod!("argumentList[Yield, Await]" => "argumentList[?Yield, ?Await]", ",", "assignmentExpression[?Yield, ?Await]"),
+        prod!("argumentList[Yield, Await]" => "argumentList[?Yield, ?Await]", ",", "spreadElement[?Yield, ?Await]"),
+        //primary
+        prod!("primaryExpression[NoBrace, NoDecl, Yield, Await]" => "this"),
+        prod!("primaryExpression[NoBrace, NoDecl, Yield, Await]" => "identifierReference[?Yield, ?Await]"),
+        prod!("primaryExpression[NoBrace, NoDecl, Yield, Await]" => "literal"),
+        prod!("primaryExpression[NoBrace, NoDecl, Yield, Await]" => "arrayLiteral[?Yield, ?Await]"),
+        prod!("primaryExpression[NoBrace, NoDecl, Yield, Await]" => "[~NoBrace]", "objectLiteral[?Yield, ?Await]"),
+        prod!("primaryExpression[NoBrace, NoDecl, Yield, Await]" => "[~NoDecl]", "functionExpression"),
+        prod!("primaryExpression[NoBrace, NoDecl, Yield, Await]" => "[~NoDecl]", "classExpression[?Yield, ?Await]"),
+        prod!("primaryExpression[NoBrace, NoDecl, Yield, Await]" => "templateLiteral[?Yield, ?Await]"),
+        prod!("primaryExpression[NoBrace, NoDecl, Yield, Await]" => "(", "expression[?Yield, ?Await]", ")"),
+        prod!("literal" => "null"),
+        prod!("literal" => "true"),
+        prod!("literal" => "false"),
+        prod!("literal" => "number"),
+        prod!("literal" => "str"),
+        prod!("literal" => "regularexpression"),
+        prod!("identifierReference[Yield, Await]" => "identifier"),
+        prod!("identifierReference[Yield, Await]" => "contextualKeyword"),
+        prod!("identifierReference[Yield, Await]" => "[~Yield]", "yield"),
+        prod!("identifierReference[Yield, Await]" => "[~Await]", "await"),
+        prod!("bindingIdentifier[Yield, Await]" => "identifier"),
+        prod!("bindingIdentifier[Yield, Await]" => "contextualKeyword"),
+        prod!("bindingIdentifier[Yield, Await]" => "async"),
+        prod!("bindingIdentifier[Yield, Await]" => "[~Yield]", "yield"),
+        prod!("bindingIdentifier[Yield, Await]" => "[~Await]", "await"),
+        prod!("identifierName" => "identifier"),
+        prod!("identifierName" => "contextualKeyword"),
+        prod!("identifierName" => "async"),
         //obj
-        prod!("expression" => "objectLiteral"),
-        prod!("objectLiteral" => "{", "propertyList_", "}"),
-        prod!("propertyList_" => "property", ",", "propertyList_"),
-        prod!("propertyList_" => "property"),
-        prod!("propertyList_" => ""),
-        prod!("property" => "identifier", ":", "expression"),
+        prod!("objectLiteral[Yield, Await]" => "{", "}"),
+        prod!("objectLiteral[Yield, Await]" => "{", "propertyDefinitionList[?Yield, ?Await]", "}"),
+        prod!("objectLiteral[Yield, Await]" => "{", "propertyDefinitionList[?Yield, ?Await]", ",", "}"),
+        prod!("propertyDefinitionList[Yield, Await]" => "propertyDefinition[?Yield, ?Await]"),
+        prod!("propertyDefinitionList[Yield, Await]" => "propertyDefinitionList[?Yield, ?Await]", ",", "propertyDefinition[?Yield, ?Await]"),
+        prod!("propertyDefinition[Yield, Await]" => "identifierReference[?Yield, ?Await]"),
+        prod!("propertyDefinition[Yield, Await]" => "async"),                                             //not an identifierReference, that's `memberExpression`'s
+        prod!("propertyDefinition[Yield, Await]" => "coverInitializedName[?Yield, ?Await]"),
+        prod!("propertyDefinition[Yield, Await]" => "propertyName[?Yield, ?Await]", ":", "assignmentExpression[?Yield, ?Await]"),
+        prod!("propertyDefinition[Yield, Await]" => "methodDefinition[?Yield, ?Await]"),
+        prod!("propertyDefinition[Yield, Await]" => "...", "assignmentExpression[?Yield, ?Await]"),
+        prod!("coverInitializedName[Yield, Await]" => "identifierReference[?Yield, ?Await]", "initializer[?Yield, ?Await]"),  //only valid in a pattern
+        prod!("coverInitializedName[Yield, Await]" => "async", "initializer[?Yield, ?Await]"),
         //array
-        prod!("expression" => "arrayLiteral"),
-        prod!("arrayLiteral" => "[", "elementList_", "]"),
-        prod!("elementList_" => "expression", ",", "elementList_"),
-        prod!("elementList_" => "expression"),
-        prod!("elementList_" => ""),
-        //template
-        prod!("templateLiteral" => "`", "templateCharacters", "`"),
-        prod!("templateCharacters" => "templateCharacter", "templateCharacters"),
-        prod!("templateCharacters" => "${", "expression", "}", "templateCharacters"),
-        prod!("templateCharacters" => "templateCharacter"),
-        prod!("templateCharacters" => ""),
-        //array
-        prod!("arrowFunction" => "parameters", "=>", "block"),
-        prod!("parameters" => "(", "parameterList", ")"),
-        prod!("parameterList" => "identifier", "=", "expression", ",", "parameterList"),
-        prod!("parameterList" => "identifier", "=", "expression"),
-        prod!("parameterList" => "identifier", ",", "parameterList"),
-        prod!("parameterList" => "identifier"),
-    )
+        prod!("arrayLiteral[Yield, Await]" => "[", "elision_", "]"),
+        prod!("arrayLiteral[Yield, Await]" => "[", "elementList[?Yield, ?Await]", "]"),
+        prod!("arrayLiteral[Yield, Await]" => "[", "elementList[?Yield, ?Await]", ",", "elision_", "]"),
+        prod!("elementList[Yield, Await]" => "elision_", "assignmentExpression[?Yield, ?Await]"),
+        prod!("elementList[Yield, Await]" => "elision_", "spreadElement[?Yield, ?Await]"),
+        prod!("elementList[Yield, Await]" => "elementList[?Yield, ?Await]", ",", "elision_", "assignmentExpression[?Yield, ?Await]"),
+        prod!("elementList[Yield, Await]" => "elementList[?Yield, ?Await]", ",", "elision_", "spreadElement[?Yield, ?Await]"),
+        prod!("spreadElement[Yield, Await]" => "...", "assignmentExpression[?Yield, ?Await]"),
+    ];
+    prods.extend(RESERVED_WORDS.iter().map(|w| prod!("identifierName" => w)));
+    prods.extend(CONTEXTUAL_KEYWORDS.iter().map(|w| prod!("contextualKeyword" => w)));
+    Grammar::from_prods("program", expand_params("program", prods))
+}
+
+/// Missing semicolons are inserted where the line breaks, `}` or the end of input allow it
+fn js_asi() -> Asi {
+    Asi {
+        semicolon: Term!(";"),
+        terminator: NonTerm!("eos"),
+        do_while_terminator: Some(NonTerm!("doWhileEos")),
+        close_brace: Term!("}"),
+        restricted: ["return", "break", "continue", "throw", "yield"]
+            .iter()
+            .map(|s| Term!(s))
+            .collect(),
+        no_line_break_before: vec![Term!("++"), Term!("--")],
+        never_after_line_break: vec![Term!("=>")],
+    }
+}
+
+/// A terminal of the source, where it is and what the lexer found out about its value
+struct Lexeme {
+    symbol: Symbol,
+    span: Span,
+    data: TokenData,
+}
+
+/// A lexical or syntax error
+struct Diagnostic {
+    span: Span,
+    message: String,
+}
+
+/// What is saved for a source file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Emit {
+    /// Python source, the default
+    Python,
+    /// The Python syntax tree as JSON, in the shape of Python's `ast` module
+    PythonAst,
+    /// The syntax tree as ESTree JSON
+    Estree,
+    /// The parse tree as JSON
+    Cst,
+}
+
+impl Emit {
+    fn from_name(name: &str) -> Option<Emit> {
+        match name {
+            "py" => Some(Emit::Python),
+            "py-ast" => Some(Emit::PythonAst),
+            "estree" => Some(Emit::Estree),
+            "cst" => Some(Emit::Cst),
+            _ => None,
+        }
+    }
+
+    /// The name of the output for the file `name`, Python files are named like modules and
+    /// `index.js` is the `__init__.py` of its package
+    fn output_name(self, name: &str) -> String {
+        let module = match module_name(name) {
+            index if index == "index" => "__init__".to_string(),
+            module => module,
+        };
+        match self {
+            Emit::Python => format!("{}.py", module),
+            Emit::PythonAst => format!("{}.py.json", module),
+            Emit::Estree | Emit::Cst => format!("{}.json", name),
+        }
+    }
+}
+
+/// How Python is written, from the command line
+struct Options {
+    /// `--packages=<file>`: the Python modules of npm packages
+    packages: Packages,
+    /// `--source-map`: a Source Map v3 of each Python file next to it, `.py.map`
+    source_map: bool,
+    /// `--js-comments`: a `# js: file.js:12` comment above the statements of each JavaScript line
+    comments: bool,
+}
+
+/// The module files under `dir` and their outputs under `package`. Directories are packages
+/// named like modules, `node_modules` and hidden ones are left out
+fn package_files(dir: &Path, package: &Path, kind: Emit, out: &mut Vec<(PathBuf, PathBuf)>) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+    let mut paths: Vec<_> = entries.filter_map(|e| e.ok()).map(|e| e.path()).collect();
+    paths.sort();
+    for path in paths {
+        let name = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
+        if name.starts_with('.') || name == "node_modules" {
+            continue;
+        }
+        if path.is_dir() {
+            package_files(&path, &package.join(module_name(name)), kind, out);
+        } else if path
+            .extension()
+            .and_then(|e| e.to_str())
+            .is_some_and(|e| EXTENSIONS.contains(&e))
+        {
+            out.push((path.clone(), package.join(kind.output_name(name))));
+        }
+    }
+}
+
+/// The text `emit` asks for from the parse tree of `filename`, and for Python the span of the
+/// JavaScript each line comes from. Translation errors go in `diagnostics`
+fn emit(
+    emit: Emit,
+    tree: &ASTNode,
+    lexemes: &[Lexeme],
+    filename: &str,
+    source: &str,
+    options: &Options,
+    diagnostics: &mut Vec<Diagnostic>,
+) -> serde_json::Result<(String, Vec<Option<Span>>)> {
+    if emit == Emit::Cst {
+        return Ok((serde_json::to_string_pretty(tree)?, Vec::new()));
+    }
+    let program = lower::lower(tree, lexemes, source);
+    let scopes = scope::analyze(&program, diagnostics);
+    if emit == Emit::Estree {
+        let json = serde_json::to_string_pretty(&estree::program(&program))?;
+        return Ok((json, Vec::new()));
+    }
+    let module = codegen::translate(&program, &scopes, &options.packages, diagnostics);
+    match emit {
+        Emit::PythonAst => Ok((
+            serde_json::to_string_pretty(&python::to_json(&module))?,
+            Vec::new(),
+        )),
+        _ if !options.source_map && !options.comments => Ok((python::unparse(&module), Vec::new())),
+        _ => {
+            let comment = |span: Span| format!("js: {}:{}", filename, span.line_col(source).0);
+            let comment: Option<&dyn Fn(Span) -> String> = match options.comments {
+                true => Some(&comment),
+                false => None,
+            };
+            Ok(python::unparse_located(&module, comment))
+        }
+    }
 }
 
-fn process(parser: &Parser, symbols: Vec<Symbol>, output_path: String) {
-    let result = parser.parse(symbols);
+/// The parse tree of `source`, as the grammar has it, and the lexemes. Lexical and syntax errors
+/// go in `diagnostics`, there is no tree if the parse failed
+fn parse_tree(
+    parser: &Parser,
+    source: &str,
+    diagnostics: &mut Vec<Diagnostic>,
+) -> Option<(ASTNode, Vec<Lexeme>)> {
+    // 由语法分析器决定 '/' 是除号还是正则表达式的开头
+    let terminals = &parser.grammar.terminals;
+    let mut tokens = LexerSource::new(source, |token| token_terminal(terminals, token));
+    let result = parser.parse_source(&mut tokens);
+    // the lexical errors after a syntax error are reported as well
+    if result.is_err() {
+        while tokens.next_symbol(&|_| false).is_some() {}
+    }
+    diagnostics.extend(tokens.errors.iter().map(|e| Diagnostic {
+        span: e.span,
+        message: format!("lexical error: {}", e),
+    }));
+    let lexemes: Vec<_> = tokens
+        .tokens
+        .iter()
+        .map(|token| Lexeme {
+            symbol: token_terminal(terminals, token).unwrap(),
+            span: token.span,
+            data: token.data.clone(),
+        })
+        .collect();
+    // the end marker (or a semicolon inserted before it) is past the last lexeme
+    let end = Span {
+        start: source.len(),
+        end: source.len(),
+    };
     match result {
-        Ok(tree) => {
+        Ok(tree) => return Some((tree, lexemes)),
+        Err(ParseError::Syntax { pos, symbol, .. }) => {
+            let found = match symbol {
+                Symbol::EndMarker => "end of input".to_string(),
+                symbol => format!("'{}'", symbol),
+            };
+            diagnostics.push(Diagnostic {
+                span: lexemes.get(pos).map_or(end, |l| l.span),
+                message: format!("syntax error: unexpected {}", found),
+            });
+        }
+        Err(e) => diagnostics.push(Diagnostic {
+            span: end,
+            message: format!("error parsing: {}", e),
+        }),
+    }
+    None
+}
+
+/// The parse tree of `source` with the covers reparsed as what they turned out to be, and the
+/// lexemes. Errors go in `diagnostics`
+fn parse(
+    parser: &Parser,
+    source: &str,
+    diagnostics: &mut Vec<Diagnostic>,
+) -> Option<(ASTNode, Vec<Lexeme>)> {
+    let (mut tree, lexemes) = parse_tree(parser, source, diagnostics)?;
+    if let Err(e) = cover::reparse(&mut tree) {
+        diagnostics.push(Diagnostic {
+            span: terminal_span(&tree, e.terminal, &lexemes, source),
+            message: format!("syntax error: {}", e.message),
+        });
+    }
+    Some((tree, lexemes))
+}
+
+/// The parser the tests share, its tables take a while to build
+#[cfg(test)]
+fn test_parser() -> &'static Parser {
+    static PARSER: std::sync::OnceLock<Parser> = std::sync::OnceLock::new();
+    PARSER.get_or_init(|| Parser::new(js_grammar()).with_asi(js_asi()))
+}
+
+/// `parse_tree` for tests, which fail on an error
+#[cfg(test)]
+fn test_parse_tree(source: &str) -> (ASTNode, Vec<Lexeme>) {
+    let mut diagnostics = Vec::new();
+    let parsed = parse_tree(test_parser(), source, &mut diagnostics);
+    let messages: Vec<_> = diagnostics.iter().map(|d| &d.message).collect();
+    assert!(messages.is_empty(), "{:?}: {:?}", source, messages);
+    parsed.unwrap()
+}
+
+/// `parse` for tests, which fail on an error
+#[cfg(test)]
+fn test_parse(source: &str) -> (ASTNode, Vec<Lexeme>) {
+    let mut diagnostics = Vec::new();
+    let parsed = parse(test_parser(), source, &mut diagnostics);
+    let messages: Vec<_> = diagnostics.iter().map(|d| &d.message).collect();
+    assert!(messages.is_empty(), "{:?}: {:?}", source, messages);
+    parsed.unwrap()
+}
+
+/// Parse `source` and save what `kind` asks for
+fn process(
+    parser: &Parser,
+    filename: &str,
+    source: &str,
+    output_path: &Path,
+    kind: Emit,
+    options: &Options,
+) {
+    let mut diagnostics = Vec::new();
+    match parse(parser, source, &mut diagnostics) {
+        Some((tree, lexemes)) if diagnostics.is_empty() => {
             println!("Accepted");
             // println!("Parse tree: {:#?}", tree);
 
-            // 序列化解析树并保存为 JSON 文件
-            if let Ok(json) = serde_json::to_string_pretty(&tree) {
-                let mut file = File::create(output_path.clone()).expect("Unable to create file");
-                file.write_all(json.as_bytes()).expect("Unable to write data");
-                println!("Parse tree saved to {}", output_path.clone())
-            } else {
-                eprintln!("Error serializing parse tree to JSON");
+            // 翻译或序列化语法树, 保存到文件
+            let emitted = emit(
+                kind,
+                &tree,
+                &lexemes,
+                filename,
+                source,
+                options,
+                &mut diagnostics,
+            );
+            match emitted {
+                Ok((text, lines)) if diagnostics.is_empty() => {
+                    let mut file = File::create(output_path).expect("Unable to create file");
+                    file.write_all(text.as_bytes()).expect("Unable to write data");
+                    println!("Output saved to {}", output_path.display());
+                    // 源码映射放在 .py 旁边, 名为 .py.map
+                    if options.source_map && kind == Emit::Python {
+                        let name = output_path.file_name().unwrap_or_default();
+                        let map = sourcemap::source_map(
+                            &name.to_string_lossy(),
+                            &text,
+                            &lines,
+                            filename,
+                            source,
+                        );
+                        let map_path = output_path.with_extension("py.map");
+                        std::fs::write(&map_path, map.to_string()).expect("Unable to write data");
+                        println!("Source map saved to {}", map_path.display());
+                    }
+                    return;
+                }
+                // 翻译错误在下面报告
+                Ok(_) => {}
+                Err(_) => {
+                    eprintln!("Error serializing parse tree to JSON");
+                    return;
+                }
             }
         }
-        Err(e) => {
-            eprintln!("Error parsing: {}", e);
+        _ => {}
+    }
+
+    // 按位置合并词法错误和语法错误
+    diagnostics.sort_by_key(|d| d.span);
+    for d in &diagnostics {
+        let (line, column) = d.span.line_col(source);
+        eprintln!("{}:{}:{}: {}", filename, line, column, d.message);
+    }
+}
+
+/// Where the `index`th terminal of `tree` is. The terminals of the tree are the lexemes plus the
+/// semicolons inserted automatically, which take no room
+fn terminal_span(tree: &ASTNode, index: usize, lexemes: &[Lexeme], source: &str) -> Span {
+    fn leaves<'a>(node: &'a ASTNode, out: &mut Vec<&'a str>) {
+        match node {
+            ASTNode::Terminal(t) => out.push(t),
+            ASTNode::NonTerminal(_, children) => children.iter().for_each(|c| leaves(c, out)),
+        }
+    }
+    let mut terminals = Vec::new();
+    leaves(tree, &mut terminals);
+    let mut next = 0;
+    for &t in terminals.iter().take(index) {
+        if lexemes.get(next).is_some_and(|l| l.symbol == Term!(t)) {
+            next += 1;
         }
     }
+    lexemes.get(next).map_or(
+        Span {
+            start: source.len(),
+            end: source.len(),
+        },
+        |l| l.span,
+    )
 }
 
-fn symbolize(source: &str) -> Vec<Symbol> {
-    token_stream(source)
-        .iter()
-        .map(|token| Term!(format!("{:?}", token.typ).to_lowercase()))
-        .filter(|s| s != &Term!("lineterminator"))
-        .collect()
+/// The terminals that are kinds of tokens rather than words, an identifier spelled like one of
+/// them is still an identifier
+const TOKEN_KINDS: &[&str] = &[
+    "identifier",
+    "number",
+    "str",
+    "regularexpression",
+    "privateidentifier",
+    "nosubstitutiontemplate",
+    "templatehead",
+    "templatemiddle",
+    "templatetail",
+];
+
+/// The terminal of a token, none for line terminators and the end. Keywords, contextual keywords
+/// and punctuators are named by their text, other tokens by their type
+fn token_terminal(terminals: &HashSet<Symbol>, token: &Token) -> Option<Symbol> {
+    let name = match format!("{:?}", token.typ).to_lowercase().as_str() {
+        "lineterminator" | "eof" => return None,
+        "str" | "stringliteral" => "str".to_string(),
+        name @ ("number" | "regularexpression" | "privateidentifier") => name.to_string(),
+        name if name.contains("template") => name.to_string(),
+        _ if terminals.contains(&Term!(token.val))
+            && !TOKEN_KINDS.contains(&token.val.as_str()) =>
+        {
+            token.val.clone()
+        }
+        // a keyword of the lexer's that JavaScript doesn't have, as `print`, is a name
+        name if name == token.val => "identifier".to_string(),
+        name => name.to_string(),
+    };
+    Some(Term!(name))
 }
 
 fn main() {
-    let args: Vec<_> = std::env::args().collect();
-    if args.len() != 2 {
-        eprintln!("Usage: {} <filename>", args[0]);
+    let mut args: Vec<_> = std::env::args().collect();
+    // `--emit=<kind>` chooses what to save, Python source by default
+    let kind = match args.iter().position(|a| a.starts_with("--emit=")) {
+        Some(i) => Emit::from_name(&args.remove(i)["--emit=".len()..]),
+        None => Some(Emit::Python),
+    };
+    // `--packages=<file>` 是 npm 包名到 Python 模块名的 JSON 映射
+    let packages = match args.iter().position(|a| a.starts_with("--packages=")) {
+        Some(i) => match Packages::load(Path::new(&args.remove(i)["--packages=".len()..])) {
+            Ok(packages) => packages,
+            Err(e) => {
+                eprintln!("Error reading package map: {}", e);
+                std::process::exit(1);
+            }
+        },
+        None => Packages::default(),
+    };
+    // 源码映射和 `# js:` 注释, 只对 Python 源码有效
+    let mut flag = |name: &str| {
+        let i = args.iter().position(|a| a == name);
+        i.map(|i| args.remove(i)).is_some()
+    };
+    let options = Options {
+        packages,
+        source_map: flag("--source-map"),
+        comments: flag("--js-comments"),
+    };
+    let (Some(kind), 2) = (kind, args.len()) else {
+        eprintln!(
+            "Usage: {} [--emit=py|py-ast|estree|cst] [--packages=<file.json>] [--source-map] \
+             [--js-comments] <file or directory>",
+            args[0]
+        );
         std::process::exit(1);
-    }
+    };
 
-    let filename = &args[1];
-    let source = std::fs::read_to_string(filename);
-    let source = match source {
-        Ok(source) => source,
-        Err(e) => {
-            eprintln!("Error reading file: {}", e);
+    // 输出文件放在 output/ 下, 以不带路径的文件名命名.
+    // 目录翻译成同名的包, 子目录是子包, 每个包都有 __init__.py
+    let input = Path::new(&args[1]);
+    let output = Path::new("output");
+    let mut files = Vec::new();
+    if input.is_dir() {
+        let name = input
+            .canonicalize()
+            .ok()
+            .and_then(|p| p.file_name().map(|n| n.to_string_lossy().into_owned()))
+            .unwrap_or_default();
+        package_files(input, &output.join(module_name(&name)), kind, &mut files);
+        if files.is_empty() {
+            eprintln!("No {} files in {}", EXTENSIONS.join("/"), input.display());
             std::process::exit(1);
         }
-    };
-    let symbols = symbolize(&source);
-    let parser = Parser::new(js_grammar());
-
+    } else {
+        let name = input.file_name().and_then(|n| n.to_str()).unwrap_or("");
+        files.push((input.to_path_buf(), output.join(kind.output_name(name))));
+    }
+    for (_, output_path) in &files {
+        let dir = output_path.parent().unwrap_or(output);
+        std::fs::create_dir_all(dir).expect("Unable to create directory");
+        if kind == Emit::Python {
+            for package in dir.ancestors().take_while(|d| *d != output) {
+                let init = package.join("__init__.py");
+                if !init.exists() {
+                    File::create(init).expect("Unable to create file");
+                }
+            }
+        }
+    }
+    // 翻译出的 Python 要用到运行时模块, 放在 output/ 下
+    if kind == Emit::Python {
+        let runtime = output.join(format!("{}.py", codegen::RUNTIME_MODULE));
+        std::fs::write(runtime, codegen::RUNTIME).expect("Unable to write data");
+    }
 
-    // 将 filename 转换为 Path 并获取不带路径的文件名
-    let without_dir = Path::new(filename)
-        .file_name() // 提取文件名
-        .and_then(|name| name.to_str()) // 转换为 &str
-        .unwrap_or(""); // 如果为空，返回空字符串
-    process(&parser, symbols, format!("output/{}.json", without_dir));
+    let parser = Parser::new(js_grammar()).with_asi(js_asi());
+    for (path, output_path) in &files {
+        let filename = path.to_string_lossy();
+        match std::fs::read_to_string(path) {
+            Ok(source) => process(&parser, &filename, &source, output_path, kind, &options),
+            Err(e) => {
+                eprintln!("Error reading file: {}", e);
+                std::process::exit(1);
+            }
+        }
+    }
 }
 
 #[cfg(test)]
 mod test {
-    // use lr1_rs::*;
-    // use super::*;
-
-    // #[test]
-    // fn test1() {
-    //     let symbols: Vec<_> = ["import", "str", "semicolon", "eof"]
-    //         .iter()
-    //         .map(|s| Term!(s))
-    //         .collect();
-    //     let parser = Parser::new(js_grammar());
-    //     process(&parser, symbols);
-    // }
-    // #[test]
-    // fn test2() {
-    //     let symbols = symbolize("import 'yl';");
-    //     let parser = Parser::new(js_grammar());
-    //     process(&parser, symbols);
-    // }
+    use super::*;
+
+    /// The messages of the errors in `source`, the early errors of scope analysis included
+    fn errors(source: &str) -> Vec<String> {
+        let mut diagnostics = Vec::new();
+        if let Some((tree, lexemes)) = parse(test_parser(), source, &mut diagnostics) {
+            scope::analyze(&lower::lower(&tree, &lexemes, source), &mut diagnostics);
+        }
+        diagnostics.into_iter().map(|d| d.message).collect()
+    }
+
+    fn accepted(sources: &[&str]) {
+        for source in sources {
+            let errors = errors(source);
+            assert!(errors.is_empty(), "{}: {:?}", source, errors);
+        }
+    }
+
+    fn rejected(sources: &[&str]) {
+        for source in sources {
+            assert!(!errors(source).is_empty(), "{} is accepted", source);
+        }
+    }
+
+    fn terminals(source: &str) -> Vec<String> {
+        let (_, lexemes) = test_parse(source);
+        lexemes.iter().map(|l| l.symbol.to_string()).collect()
+    }
+
+    #[test]
+    fn test_identifiers_named_like_terminals() {
+        assert_eq!(
+            terminals("f(number, str, regularexpression, identifier)"),
+            [
+                "identifier",
+                "(",
+                "identifier",
+                ",",
+                "identifier",
+                ",",
+                "identifier",
+                ",",
+                "identifier",
+                ")"
+            ]
+        );
+        assert!(errors("let number = 5\nfunction f(number, str) { return str }").is_empty());
+        assert!(errors("let print = 1, input = 2\nprint(input)").is_empty());
+        // contextual keywords are still their own terminals
+        assert_eq!(terminals("for (x of y);")[3], "of");
+    }
+
+    #[test]
+    fn test_regex_or_division() {
+        let count = |source, name: &str| {
+            let terminals = terminals(source);
+            terminals.iter().filter(|t| *t == name).count()
+        };
+        let source = "const re = /ab+/g; x = a / b / re.lastIndex;\nf(/x/)\nif (/x/.test(s)) a /= 2";
+        assert_eq!(count(source, "regularexpression"), 3);
+        assert_eq!(count(source, "/"), 2);
+        assert_eq!(count(source, "/="), 1);
+        // a line break doesn't end `a` before a division
+        assert_eq!(count("x = a\n/ b / c", "/"), 2);
+        assert_eq!(
+            errors("x = /a"),
+            [
+                "lexical error: unterminated regular expression literal",
+                "syntax error: unexpected end of input"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_do_while_semicolon() {
+        assert!(errors("let i = 0; do i++; while (i < 3) console.log(i)").is_empty());
+        assert!(errors("do { i++ } while (i < 3) i--").is_empty());
+        assert!(!errors("f(i) g(i)").is_empty());
+    }
+
+    #[test]
+    fn test_optional_chaining_and_nullish() {
+        accepted(&[
+            "a?.b",
+            "a?.[k]",
+            "f?.()",
+            "a?.b.c(d)?.[0]",
+            "x ?? y ?? z",
+            "(a || b) ?? c",
+            "a ?? (b && c)",
+            "a.b = 1; a[0] += 1; (a).b++; --a.b",
+        ]);
+        rejected(&[
+            "a ?? b || c",
+            "a || b ?? c",
+            "a && b ?? c",
+            "a?.b = 1",
+            "a?.b += 1",
+            "a?.b++",
+            "--a?.[0]",
+            "new a?.b()",
+            "a?.`t`",
+        ]);
+    }
+
+    #[test]
+    fn test_classes() {
+        accepted(&[
+            "class A {}",
+            "class A extends B.c(d) { constructor() { super() } }",
+            "const A = class extends (x ? B : C) {}",
+            "class A { static x = 1; static { A.y = 2 } static m() {} }",
+            "class A { get a() { return 1 } set a(v) {} static get b() {} }",
+            "class A { [k] = 1; ['m' + n]() {} }",
+            "class A { #p = 1; #m() {} has(o) { return #p in o && this.#m() } }",
+            "class A { async m() { await x } *g() { yield 1 } async *h() {} }",
+            "class A { #p; m() { class B { n() { return this.#p } } } }",
+            "class A { a; b = 2;; }",
+        ]);
+        rejected(&[
+            "class {}",
+            "class A { x: 1 }",
+            "class A extends B, C {}",
+            "class A { static static {} }",
+            "#p in o",
+            "class A { m(o) { return #q in o } }",
+            "class A { #p; } this.#p",
+            "class A { get a(x) {} }",
+        ]);
+    }
+
+    #[test]
+    fn test_generators_and_async() {
+        accepted(&[
+            "function* g() { yield; yield 1; yield* h(); const x = yield }",
+            "const g = function* () { yield [yield] }",
+            "async function f() { await g(); for await (const x of xs) {} }",
+            "const f = async () => await x, g = async x => x, h = async function () {}",
+            "async function* f() { yield await x }",
+            "function f() { var yield = 1, await = 2 }",
+            "let o = { *g() { yield 1 }, async m() { await 1 } }",
+            "async(x)",
+            "async\nfunction f() {}",
+        ]);
+        rejected(&[
+            "function f() { yield 1 }",
+            "function* g() { var yield }",
+            "function f() { await x }",
+            "async function f() { var await }",
+            "for await (const x of xs) {}",
+            "function* g() { function h() { yield 1 } }",
+            "async function f() { () => { await x } }",
+        ]);
+    }
+
+    #[test]
+    fn test_modules() {
+        accepted(&[
+            "import 'm'",
+            "import a from 'm'",
+            "import { a, b as c, default as d, 'x y' as e } from 'm'",
+            "import * as ns from 'm'",
+            "import a, { b } from 'm'; import a2, * as ns from 'm'",
+            "import('m').then(f); const u = import.meta.url",
+            "export default 1 + 2",
+            "export default class {}",
+            "export default function () {}",
+            "export default async function f() {}",
+            "let a, b; export { a, b as c, a as default }",
+            "export * from 'm'; export * as ns from 'm'",
+            "export { a as 'x y' } from 'm'",
+            "export const x = 1; export let y; export function f() {} export class C {}",
+        ]);
+        rejected(&[
+            "import { a as 'b' } from 'm'",
+            "import * from 'm'",
+            "import a, b from 'm'",
+            "import { a } 'm'",
+            "export a",
+            "export * as ns",
+            "export default const x = 1",
+            "import.meta = 1",
+            "{ import a from 'm' }",
+        ]);
+    }
+
+    #[test]
+    fn test_for_heads() {
+        accepted(&[
+            "for (let i = 0, j = 1; i < j; i++) {}",
+            "for (var i = 0; ;) break",
+            "for (;;) {}",
+            "for (i = 0, n = a.length; i < n; i++);",
+            "for (x in o); for (var k in o); for (let k in o); for (const k in o);",
+            "for (const [k, v] of m) {} for (let { a, b = 1 } of xs);",
+            "for (o.p of xs); for ([a, b] of xs); for ({ a } of xs);",
+            "for (let i = ('a' in o) ? 0 : 1; i;);",
+            "async function f() { for await (const x of xs); for await (x of xs); }",
+            "for (async of => x; ;);",
+        ]);
+        rejected(&[
+            "for (let x = 1 of y);",
+            "for (let i = 'a' in o ? 0 : 1; i;);",
+            "for (let x, y of z);",
+            "for (const x of a, b);",
+            "for (let x in o; ;);",
+            "for (async of x);",
+            "for (x + 1 of xs);",
+            "for (const x;;);",
+            "const c;",
+            "let [d];",
+            "for (var { e };;);",
+            "for (i = 0; i < n);",
+        ]);
+    }
+
+    #[test]
+    fn test_object_literals() {
+        accepted(&[
+            "x = { a: 1, 'b': 2, 3: 4, [k]: 5, }",
+            "x = { a, b, async, get, set, static, of }",
+            "x = { async, b }",
+            "({ async } = o); ({ async = 1, b } = o); const { async: f } = o",
+            "x = { f() {}, get g() { return 1 }, set g(v) {}, async h() {}, *i() {}, async *j() {} }",
+            "x = { get() {}, set: 1, async() {}, 'get'() {} }",
+            "x = { ...o, a: 1, ...p }",
+            "x = { __proto__: null, '__proto__'() {} }",
+            "x = [, a, , ...b, c, ,]",
+            "x = []; y = [,]",
+        ]);
+        rejected(&[
+            "x = { a b }",
+            "x = { a = 1 }",
+            "x = { , }",
+            "x = { __proto__: a, __proto__: b }",
+            "x = { get g(a, b) {} }",
+            "({ ...o, } = p)",
+            "x = { async f: 1 }",
+        ]);
+    }
+
+    #[test]
+    fn test_functions() {
+        accepted(&[
+            "function f(a, b = 1, ...rest) {}",
+            "function f([a, , b] = [], { c, d: [e] = [] }, ...[g, h]) {}",
+            "function f(a, b,) {} f(1, 2,); f(...xs, 1, ...ys,)",
+            "const f = function g() {}; (function () {})()",
+            "x => x * 2; (x) => x; () => {}; (a, b = 1, ...c) => a",
+            "const f = () => ({ a: 1 }); const g = ({ a }, [b]) => a + b",
+            "const h = x => y => x + y",
+        ]);
+        rejected(&[
+            "function f(...rest,) {}",
+            "function f(...rest, a) {}",
+            "function f(...rest = []) {}",
+            "function (a) {}",
+            "() => { a: 1, b: 2 }",
+            "x\n=> x",
+            "(a + b) => a",
+            "(...a, b) => a",
+            "f(,)",
+        ]);
+    }
 }
@@ -0,0 +1,146 @@
+//! The methods of strings, arrays and numbers the translation knows: the kind of value they give,
+//! and the Python for a call of them where Python has the same operation. A call that has no
+//! Python form, or whose arguments don't fit it, goes to the runtime's `js_method`, which has them
+//! all.
+
+use crate::infer::Kind;
+
+/// What a call of a method is in Python. The forms check the arguments they need
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Python {
+    /// `receiver.name(arguments)`, the arguments having these kinds
+    Method(&'static str, &'static [Kind]),
+    /// `argument in receiver`, for a string argument
+    In,
+    /// `receiver[start:end]`, for integer literals
+    Slice,
+    /// `receiver.split(separator)`, for a separator that is a string literal other than `""`
+    Split,
+    /// `receiver * count`, for an integer literal
+    Repeat,
+    /// `receiver + argument`, for a string argument
+    Concat,
+    /// `receiver.replace(pattern, replacement)`, once unless `all`, for string arguments, the
+    /// replacement a literal without the `$` patterns
+    Replace { all: bool },
+    /// `receiver.append(argument)`, as a statement, JavaScript's gives the new length
+    Append,
+    /// `[callback(x) for x in receiver]`, for a function of one parameter that is a `lambda`
+    Map,
+    /// `[x for x in receiver if callback(x)]`, as `Map`
+    Filter,
+    /// Only the runtime's
+    Runtime,
+}
+
+pub struct Method {
+    pub name: &'static str,
+    /// `String`, `Array` or `Number`
+    pub receiver: Kind,
+    pub returns: Kind,
+    pub python: Python,
+}
+
+const fn string(name: &'static str, returns: Kind, python: Python) -> Method {
+    Method {
+        name,
+        receiver: Kind::String,
+        returns,
+        python,
+    }
+}
+
+const fn array(name: &'static str, returns: Kind, python: Python) -> Method {
+    Method {
+        name,
+        receiver: Kind::Array,
+        returns,
+        python,
+    }
+}
+
+const fn number(name: &'static str, returns: Kind, python: Python) -> Method {
+    Method {
+        name,
+        receiver: Kind::Number,
+        returns,
+        python,
+    }
+}
+
+const STRING: &[Kind] = &[Kind::String];
+
+pub const METHODS: &[Method] = &[
+    string("at", Kind::Unknown, Python::Runtime),
+    string("charAt", Kind::String, Python::Runtime),
+    string("charCodeAt", Kind::Number, Python::Runtime),
+    string("concat", Kind::String, Python::Concat),
+    string(
+        "endsWith",
+        Kind::Boolean,
+        Python::Method("endswith", STRING),
+    ),
+    string("includes", Kind::Boolean, Python::In),
+    string("indexOf", Kind::Number, Python::Method("find", STRING)),
+    string("lastIndexOf", Kind::Number, Python::Method("rfind", STRING)),
+    string("padEnd", Kind::String, Python::Runtime),
+    string("padStart", Kind::String, Python::Runtime),
+    string("repeat", Kind::String, Python::Repeat),
+    string("replace", Kind::String, Python::Replace { all: false }),
+    string("replaceAll", Kind::String, Python::Replace { all: true }),
+    string("slice", Kind::String, Python::Slice),
+    string("split", Kind::Array, Python::Split),
+    string(
+        "startsWith",
+        Kind::Boolean,
+        Python::Method("startswith", STRING),
+    ),
+    string("substring", Kind::String, Python::Runtime),
+    string("toLowerCase", Kind::String, Python::Method("lower", &[])),
+    string("toString", Kind::String, Python::Runtime),
+    string("toUpperCase", Kind::String, Python::Method("upper", &[])),
+    string("trim", Kind::String, Python::Method("strip", &[])),
+    string("trimEnd", Kind::String, Python::Method("rstrip", &[])),
+    string("trimStart", Kind::String, Python::Method("lstrip", &[])),
+    array("at", Kind::Unknown, Python::Runtime),
+    array("concat", Kind::Array, Python::Runtime),
+    array("every", Kind::Boolean, Python::Runtime),
+    array("fill", Kind::Array, Python::Runtime),
+    array("filter", Kind::Array, Python::Filter),
+    array("find", Kind::Unknown, Python::Runtime),
+    array("findIndex", Kind::Number, Python::Runtime),
+    array("flat", Kind::Array, Python::Runtime),
+    array("flatMap", Kind::Array, Python::Runtime),
+    array("forEach", Kind::Undefined, Python::Runtime),
+    array("includes", Kind::Boolean, Python::In),
+    array("indexOf", Kind::Number, Python::Runtime),
+    array("join", Kind::String, Python::Runtime),
+    array("lastIndexOf", Kind::Number, Python::Runtime),
+    array("map", Kind::Array, Python::Map),
+    array("pop", Kind::Unknown, Python::Runtime),
+    array("push", Kind::Number, Python::Append),
+    array("reduce", Kind::Unknown, Python::Runtime),
+    array("reduceRight", Kind::Unknown, Python::Runtime),
+    array("reverse", Kind::Array, Python::Runtime),
+    array("shift", Kind::Unknown, Python::Runtime),
+    array("slice", Kind::Array, Python::Slice),
+    array("some", Kind::Boolean, Python::Runtime),
+    array("sort", Kind::Array, Python::Runtime),
+    array("splice", Kind::Array, Python::Runtime),
+    array("toString", Kind::String, Python::Runtime),
+    array("unshift", Kind::Number, Python::Runtime),
+    number("toFixed", Kind::String, Python::Runtime),
+    number("toString", Kind::String, Python::Runtime),
+];
+
+/// The method `name` of values of the kind
+pub fn method(receiver: Kind, name: &str) -> Option<&'static Method> {
+    METHODS
+        .iter()
+        .find(|method| method.receiver == receiver && method.name == name)
+}
+
+/// Whether strings, arrays or numbers have a method `name`, which a value of unknown kind may be
+pub fn is_method(name: &str) -> bool {
+    METHODS.iter().any(|method| method.name == name)
+}
@@ -0,0 +1,151 @@
+//! JavaScript modules as Python ones. A relative specifier names a module of the package tree a
+//! directory is translated to, where a directory is a package and its `index.js` the package's
+//! `__init__.py`. A bare specifier names an npm package, which is the Python module the package
+//! map has for it, else a module of its own name.
+
+use std::collections::HashMap;
+use std::path::Path;
+
+/// The extensions of the files that are modules
+pub const EXTENSIONS: &[&str] = &["js", "mjs", "cjs"];
+
+const PYTHON_KEYWORDS: &[&str] = &[
+    "False", "None", "True", "and", "as", "assert", "async", "await", "break", "class", "continue",
+    "def", "del", "elif", "else", "except", "finally", "for", "from", "global", "if", "import",
+    "in", "is", "lambda", "nonlocal", "not", "or", "pass", "raise", "return", "try", "while",
+    "with", "yield",
+];
+
+/// A Python module: `level` dots for a relative one, then the dotted names
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ModulePath {
+    pub level: usize,
+    pub names: Vec<String>,
+}
+
+impl ModulePath {
+    pub fn dotted(&self) -> String {
+        self.names.join(".")
+    }
+}
+
+/// The Python modules of npm packages, read from a JSON object of package names and dotted
+/// module names. A package's entry is also used for the paths in it, `"lodash": "pydash"` makes
+/// `lodash/fp` `pydash.fp`
+#[derive(Debug, Default)]
+pub struct Packages(HashMap<String, String>);
+
+impl Packages {
+    pub fn load(path: &Path) -> Result<Packages, String> {
+        let text = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+        let map = serde_json::from_str(&text).map_err(|e| e.to_string())?;
+        Ok(Packages(map))
+    }
+
+    /// The Python module an `import` of `specifier` is
+    pub fn resolve(&self, specifier: &str) -> ModulePath {
+        let relative_path = specifier.starts_with("./") || specifier.starts_with("../");
+        if relative_path || matches!(specifier, "." | "..") {
+            return relative(specifier);
+        }
+        if let Some(module) = self.0.get(specifier) {
+            return absolute(module.split('.'));
+        }
+        // @scope/name/path or name/path
+        let parts = match specifier.starts_with('@') {
+            true => 2,
+            false => 1,
+        };
+        let mut segments = specifier.splitn(parts + 1, '/');
+        let package: Vec<_> = segments.by_ref().take(parts).collect();
+        let path = segments.next().map(|rest| rest.split('/'));
+        let mut module = match self.0.get(&package.join("/")) {
+            Some(module) => absolute(module.split('.')),
+            None => absolute(package.iter().map(|s| s.trim_start_matches('@'))),
+        };
+        module
+            .names
+            .extend(path.into_iter().flatten().map(module_name));
+        module
+    }
+}
+
+fn absolute<'s>(names: impl Iterator<Item = &'s str>) -> ModulePath {
+    ModulePath {
+        level: 0,
+        names: names.map(module_name).collect(),
+    }
+}
+
+/// `./a/b.js` is `.a.b`, `../a/index.js` is `..a`
+fn relative(specifier: &str) -> ModulePath {
+    let mut module = ModulePath {
+        level: 1,
+        names: Vec::new(),
+    };
+    for segment in specifier.split('/') {
+        match segment {
+            "" | "." => {}
+            ".." if module.names.is_empty() => module.level += 1,
+            ".." => {
+                module.names.pop();
+            }
+            segment => module.names.push(module_name(segment)),
+        }
+    }
+    if module.names.last().is_some_and(|name| name == "index") {
+        module.names.pop();
+    }
+    module
+}
+
+/// The Python name of a module file or directory, without the extension
+pub fn module_name(file_name: &str) -> String {
+    let stem = match file_name.rsplit_once('.') {
+        Some((stem, extension)) if EXTENSIONS.contains(&extension) => stem,
+        _ => file_name,
+    };
+    let mut name: String = stem
+        .chars()
+        .map(|c| match c.is_alphanumeric() || c == '_' {
+            true => c,
+            false => '_',
+        })
+        .collect();
+    if !name.starts_with(|c: char| c.is_alphabetic() || c == '_') {
+        name.insert(0, '_');
+    }
+    if PYTHON_KEYWORDS.contains(&name.as_str()) {
+        name.push('_');
+    }
+    name
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_resolve() {
+        let packages = Packages(HashMap::from([
+            ("lodash".to_string(), "pydash".to_string()),
+            ("node:path".to_string(), "os.path".to_string()),
+        ]));
+        let resolved = |specifier| {
+            let module = packages.resolve(specifier);
+            (module.level, module.dotted())
+        };
+        assert_eq!(resolved("./util.js"), (1, "util".to_string()));
+        assert_eq!(
+            resolved("../lib/my-list/index.mjs"),
+            (2, "lib.my_list".to_string())
+        );
+        assert_eq!(resolved("./a/../b"), (1, "b".to_string()));
+        assert_eq!(resolved("."), (1, String::new()));
+        assert_eq!(resolved("lodash/fp"), (0, "pydash.fp".to_string()));
+        assert_eq!(resolved("node:path"), (0, "os.path".to_string()));
+        assert_eq!(resolved("@scope/pkg/sub"), (0, "scope.pkg.sub".to_string()));
+        assert_eq!(module_name("2d-shapes.js"), "_2d_shapes");
+        assert_eq!(module_name("class.js"), "class_");
+    }
+}
@@ -0,0 +1,1201 @@
+//! The Python syntax tree the translator produces, shaped like the one of Python's `ast` module,
+//! with the two ways out of it: Python source (`unparse`) and `ast`-module JSON (`to_json`), where
+//! each node is an object whose `_type` is the name of its `ast` class.
+
+use js_lexer::token::Span;
+use serde_json::{json, Value};
+
+#[derive(Debug, Clone)]
+pub struct Module {
+    pub body: Vec<Stmt>,
+}
+
+#[derive(Debug, Clone)]
+pub enum Stmt {
+    FunctionDef {
+        name: String,
+        args: Arguments,
+        body: Vec<Stmt>,
+        decorators: Vec<Expr>,
+        is_async: bool,
+    },
+    ClassDef {
+        name: String,
+        bases: Vec<Expr>,
+        body: Vec<Stmt>,
+    },
+    Return(Option<Expr>),
+    Delete(Vec<Expr>),
+    /// `import names`
+    Import(Vec<Alias>),
+    /// `from module import names`, with `level` dots before the module
+    ImportFrom {
+        module: String,
+        names: Vec<Alias>,
+        level: usize,
+    },
+    /// `a = b = value`
+    Assign {
+        targets: Vec<Expr>,
+        value: Expr,
+    },
+    AugAssign {
+        target: Expr,
+        op: Operator,
+        value: Expr,
+    },
+    For {
+        target: Expr,
+        iter: Expr,
+        body: Vec<Stmt>,
+        is_async: bool,
+    },
+    While {
+        test: Expr,
+        body: Vec<Stmt>,
+    },
+    If {
+        test: Expr,
+        body: Vec<Stmt>,
+        /// A single `If` here is printed as `elif`
+        orelse: Vec<Stmt>,
+    },
+    Raise(Option<Expr>),
+    /// `global names`
+    Global(Vec<String>),
+    /// `nonlocal names`
+    Nonlocal(Vec<String>),
+    Try {
+        body: Vec<Stmt>,
+        handlers: Vec<ExceptHandler>,
+        finalbody: Vec<Stmt>,
+    },
+    Expr(Expr),
+    Pass,
+    Break,
+    Continue,
+    /// The statement, translated from the JavaScript at `span`. It is written as the statement,
+    /// the span is for the source map
+    Located {
+        span: Span,
+        stmt: Box<Stmt>,
+    },
+}
+
+/// An imported name, `name as asname`
+#[derive(Debug, Clone)]
+pub struct Alias {
+    pub name: String,
+    pub asname: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct ExceptHandler {
+    pub typ: Option<Expr>,
+    pub name: Option<String>,
+    pub body: Vec<Stmt>,
+}
+
+/// The parameters of a function: positional ones, the last `defaults.len()` of which have a
+/// default, and `*vararg`
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Arguments {
+    pub args: Vec<String>,
+    pub defaults: Vec<Expr>,
+    pub vararg: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[allow(clippy::enum_variant_names)] // the names of `ast`
+pub enum Expr {
+    BoolOp {
+        op: BoolOp,
+        values: Vec<Expr>,
+    },
+    /// `(target := value)`
+    NamedExpr {
+        target: String,
+        value: Box<Expr>,
+    },
+    BinOp {
+        left: Box<Expr>,
+        op: Operator,
+        right: Box<Expr>,
+    },
+    UnaryOp {
+        op: UnaryOp,
+        operand: Box<Expr>,
+    },
+    Lambda {
+        args: Arguments,
+        body: Box<Expr>,
+    },
+    IfExp {
+        test: Box<Expr>,
+        body: Box<Expr>,
+        orelse: Box<Expr>,
+    },
+    /// A `None` key is a `**value`
+    Dict {
+        keys: Vec<Option<Expr>>,
+        values: Vec<Expr>,
+    },
+    Await(Box<Expr>),
+    Yield(Option<Box<Expr>>),
+    YieldFrom(Box<Expr>),
+    Compare {
+        left: Box<Expr>,
+        op: CmpOp,
+        right: Box<Expr>,
+    },
+    Call {
+        func: Box<Expr>,
+        args: Vec<Expr>,
+    },
+    Constant(Constant),
+    Attribute {
+        value: Box<Expr>,
+        attr: String,
+    },
+    Subscript {
+        value: Box<Expr>,
+        slice: Box<Expr>,
+    },
+    Starred(Box<Expr>),
+    Name(String),
+    List(Vec<Expr>),
+    Tuple(Vec<Expr>),
+    /// `lower:upper`, only as a subscript
+    Slice {
+        lower: Option<Box<Expr>>,
+        upper: Option<Box<Expr>>,
+    },
+    /// `[element for target in iter if condition]`
+    ListComp {
+        element: Box<Expr>,
+        target: String,
+        iter: Box<Expr>,
+        condition: Option<Box<Expr>>,
+    },
+    /// `f"..."`, of string constants and the values put between them, which are strings too
+    JoinedStr(Vec<Expr>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Constant {
+    None,
+    Bool(bool),
+    Int(i64),
+    /// Finite, infinities and NaN are `float("inf")` and the like
+    Float(f64),
+    Str(String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BoolOp {
+    And,
+    Or,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Operator {
+    Add,
+    Sub,
+    Mult,
+    Div,
+    Pow,
+    BitOr,
+    BitXor,
+    BitAnd,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UnaryOp {
+    Invert,
+    Not,
+    UAdd,
+    USub,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CmpOp {
+    Eq,
+    NotEq,
+    Lt,
+    LtE,
+    Gt,
+    GtE,
+    Is,
+    IsNot,
+    In,
+}
+
+impl Expr {
+    pub fn name(name: &str) -> Expr {
+        Expr::Name(name.to_string())
+    }
+
+    pub fn str(s: &str) -> Expr {
+        Expr::Constant(Constant::Str(s.to_string()))
+    }
+
+    pub fn none() -> Expr {
+        Expr::Constant(Constant::None)
+    }
+
+    pub fn bool(b: bool) -> Expr {
+        Expr::Constant(Constant::Bool(b))
+    }
+
+    pub fn call(func: Expr, args: Vec<Expr>) -> Expr {
+        Expr::Call {
+            func: Box::new(func),
+            args,
+        }
+    }
+
+    pub fn binary(left: Expr, op: Operator, right: Expr) -> Expr {
+        Expr::BinOp {
+            left: Box::new(left),
+            op,
+            right: Box::new(right),
+        }
+    }
+
+    pub fn compare(left: Expr, op: CmpOp, right: Expr) -> Expr {
+        Expr::Compare {
+            left: Box::new(left),
+            op,
+            right: Box::new(right),
+        }
+    }
+
+    pub fn attribute(value: Expr, attr: &str) -> Expr {
+        Expr::Attribute {
+            value: Box::new(value),
+            attr: attr.to_string(),
+        }
+    }
+
+    pub fn subscript(value: Expr, slice: Expr) -> Expr {
+        Expr::Subscript {
+            value: Box::new(value),
+            slice: Box::new(slice),
+        }
+    }
+
+    pub fn not(operand: Expr) -> Expr {
+        Expr::UnaryOp {
+            op: UnaryOp::Not,
+            operand: Box::new(operand),
+        }
+    }
+}
+
+impl Stmt {
+    /// `name = value`
+    pub fn assign(name: &str, value: Expr) -> Stmt {
+        Stmt::Assign {
+            targets: vec![Expr::name(name)],
+            value,
+        }
+    }
+
+    /// `stmt` from the JavaScript at `span`, unless it already says where it is from
+    pub fn located(span: Span, stmt: Stmt) -> Stmt {
+        match stmt {
+            Stmt::Located { .. } => stmt,
+            stmt => Stmt::Located {
+                span,
+                stmt: Box::new(stmt),
+            },
+        }
+    }
+
+    /// The statement without where it is from
+    pub fn unlocated(&self) -> &Stmt {
+        match self {
+            Stmt::Located { stmt, .. } => stmt.unlocated(),
+            stmt => stmt,
+        }
+    }
+}
+
+impl Operator {
+    fn symbol(self) -> &'static str {
+        match self {
+            Operator::Add => "+",
+            Operator::Sub => "-",
+            Operator::Mult => "*",
+            Operator::Div => "/",
+            Operator::Pow => "**",
+            Operator::BitOr => "|",
+            Operator::BitXor => "^",
+            Operator::BitAnd => "&",
+        }
+    }
+
+    fn precedence(self) -> u8 {
+        match self {
+            Operator::BitOr => BIT_OR,
+            Operator::BitXor => BIT_XOR,
+            Operator::BitAnd => BIT_AND,
+            Operator::Add | Operator::Sub => ARITH,
+            Operator::Pow => POWER,
+            _ => TERM,
+        }
+    }
+}
+
+impl CmpOp {
+    fn symbol(self) -> &'static str {
+        match self {
+            CmpOp::Eq => "==",
+            CmpOp::NotEq => "!=",
+            CmpOp::Lt => "<",
+            CmpOp::LtE => "<=",
+            CmpOp::Gt => ">",
+            CmpOp::GtE => ">=",
+            CmpOp::Is => "is",
+            CmpOp::IsNot => "is not",
+            CmpOp::In => "in",
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------------------------
+// source
+
+// 优先级, 同 Python 的 ast.unparse
+const TUPLE: u8 = 0;
+const YIELD: u8 = 1;
+const TEST: u8 = 2;
+const OR: u8 = 3;
+const AND: u8 = 4;
+const NOT: u8 = 5;
+const CMP: u8 = 6;
+const EXPR: u8 = 7;
+const BIT_OR: u8 = 7;
+const BIT_XOR: u8 = 8;
+const BIT_AND: u8 = 9;
+const ARITH: u8 = 11;
+const TERM: u8 = 12;
+const FACTOR: u8 = 13;
+const POWER: u8 = 14;
+const AWAIT: u8 = 15;
+const ATOM: u8 = 16;
+
+/// Python source for `module`
+pub fn unparse(module: &Module) -> String {
+    unparse_located(module, None).0
+}
+
+/// Python source for `module`, and for each of its lines the span of the JavaScript it comes
+/// from. With `comment`, the statements from another line of the JavaScript than the one before
+/// have a comment above them, what `comment` gives for their span
+pub fn unparse_located(
+    module: &Module,
+    comment: Option<&dyn Fn(Span) -> String>,
+) -> (String, Vec<Option<Span>>) {
+    let mut out = Writer {
+        text: String::new(),
+        lines: Vec::new(),
+        located: Vec::new(),
+        comment,
+        last_comment: None,
+    };
+    block(&module.body, 0, &mut out);
+    (out.text, out.lines)
+}
+
+/// Python source being written
+struct Writer<'a> {
+    text: String,
+    /// The span of the JavaScript each line written comes from
+    lines: Vec<Option<Span>>,
+    /// The spans of the `Located` statements being written, the innermost last
+    located: Vec<Span>,
+    comment: Option<&'a dyn Fn(Span) -> String>,
+    last_comment: Option<String>,
+}
+
+impl Writer<'_> {
+    /// Start writing what comes from `span`
+    fn enter(&mut self, span: Span, indent: usize) {
+        self.located.push(span);
+        if let Some(comment) = self.comment {
+            let text = comment(span);
+            if self.last_comment.as_ref() != Some(&text) {
+                line(&format!("# {}", text), indent, self);
+                self.last_comment = Some(text);
+            }
+        }
+    }
+
+    fn leave(&mut self) {
+        self.located.pop();
+    }
+}
+
+fn block(body: &[Stmt], indent: usize, out: &mut Writer) {
+    if body.is_empty() && indent > 0 {
+        line("pass", indent, out);
+    }
+    for (i, stmt) in body.iter().enumerate() {
+        // a blank line around definitions
+        let is_definition = |s: &Stmt| {
+            matches!(
+                s.unlocated(),
+                Stmt::FunctionDef { .. } | Stmt::ClassDef { .. }
+            )
+        };
+        if i > 0 && (is_definition(stmt) || is_definition(&body[i - 1])) {
+            out.text.push('\n');
+            out.lines.push(None);
+        }
+        statement(stmt, indent, out);
+    }
+}
+
+fn line(text: &str, indent: usize, out: &mut Writer) {
+    out.text.push_str(&"    ".repeat(indent));
+    out.text.push_str(text);
+    out.text.push('\n');
+    out.lines.push(out.located.last().copied());
+}
+
+fn statement(stmt: &Stmt, indent: usize, out: &mut Writer) {
+    match stmt {
+        Stmt::FunctionDef {
+            name,
+            args,
+            body,
+            decorators,
+            is_async,
+        } => {
+            for decorator in decorators {
+                line(&format!("@{}", expr(decorator, EXPR)), indent, out);
+            }
+            let keyword = if *is_async { "async def" } else { "def" };
+            line(
+                &format!("{} {}({}):", keyword, name, arguments(args)),
+                indent,
+                out,
+            );
+            block(body, indent + 1, out);
+        }
+        Stmt::ClassDef { name, bases, body } => {
+            let bases: Vec<_> = bases.iter().map(|b| expr(b, TEST)).collect();
+            let header = match bases.is_empty() {
+                true => format!("class {}:", name),
+                false => format!("class {}({}):", name, bases.join(", ")),
+            };
+            line(&header, indent, out);
+            block(body, indent + 1, out);
+        }
+        Stmt::Return(value) => match value {
+            Some(value) => line(&format!("return {}", expr(value, TUPLE)), indent, out),
+            None => line("return", indent, out),
+        },
+        Stmt::Delete(targets) => {
+            let targets: Vec<_> = targets.iter().map(|t| expr(t, EXPR)).collect();
+            line(&format!("del {}", targets.join(", ")), indent, out);
+        }
+        Stmt::Import(names) => line(&format!("import {}", aliases(names)), indent, out),
+        Stmt::ImportFrom {
+            module,
+            names,
+            level,
+        } => {
+            line(
+                &format!(
+                    "from {}{} import {}",
+                    ".".repeat(*level),
+                    module,
+                    aliases(names)
+                ),
+                indent,
+                out,
+            );
+        }
+        Stmt::Assign { targets, value } => {
+            let mut text = String::new();
+            for target in targets {
+                text.push_str(&expr(target, TUPLE));
+                text.push_str(" = ");
+            }
+            text.push_str(&expr(value, TUPLE));
+            line(&text, indent, out);
+        }
+        Stmt::AugAssign { target, op, value } => line(
+            &format!(
+                "{} {}= {}",
+                expr(target, TUPLE),
+                op.symbol(),
+                expr(value, YIELD)
+            ),
+            indent,
+            out,
+        ),
+        Stmt::For {
+            target,
+            iter,
+            body,
+            is_async,
+        } => {
+            let keyword = if *is_async { "async for" } else { "for" };
+            line(
+                &format!(
+                    "{} {} in {}:",
+                    keyword,
+                    expr(target, TUPLE),
+                    expr(iter, TEST)
+                ),
+                indent,
+                out,
+            );
+            block(body, indent + 1, out);
+        }
+        Stmt::While { test, body } => {
+            line(&format!("while {}:", expr(test, TEST)), indent, out);
+            block(body, indent + 1, out);
+        }
+        Stmt::If { test, body, orelse } => {
+            line(&format!("if {}:", expr(test, TEST)), indent, out);
+            block(body, indent + 1, out);
+            let mut orelse = orelse.as_slice();
+            while let Some((
+                span,
+                Stmt::If {
+                    test,
+                    body,
+                    orelse: next,
+                },
+            )) = elif(orelse)
+            {
+                if let Some(span) = span {
+                    out.enter(span, indent);
+                }
+                line(&format!("elif {}:", expr(test, TEST)), indent, out);
+                block(body, indent + 1, out);
+                if span.is_some() {
+                    out.leave();
+                }
+                orelse = next;
+            }
+            if !orelse.is_empty() {
+                line("else:", indent, out);
+                block(orelse, indent + 1, out);
+            }
+        }
+        Stmt::Raise(exc) => match exc {
+            Some(exc) => line(&format!("raise {}", expr(exc, TEST)), indent, out),
+            None => line("raise", indent, out),
+        },
+        Stmt::Try {
+            body,
+            handlers,
+            finalbody,
+        } => {
+            line("try:", indent, out);
+            block(body, indent + 1, out);
+            for handler in handlers {
+                let mut text = "except".to_string();
+                if let Some(typ) = &handler.typ {
+                    text = format!("{} {}", text, expr(typ, TEST));
+                }
+                if let Some(name) = &handler.name {
+                    text = format!("{} as {}", text, name);
+                }
+                text.push(':');
+                line(&text, indent, out);
+                block(&handler.body, indent + 1, out);
+            }
+            if !finalbody.is_empty() {
+                line("finally:", indent, out);
+                block(finalbody, indent + 1, out);
+            }
+        }
+        Stmt::Global(names) => line(&format!("global {}", names.join(", ")), indent, out),
+        Stmt::Nonlocal(names) => line(&format!("nonlocal {}", names.join(", ")), indent, out),
+        Stmt::Expr(value) => line(&expr(value, YIELD), indent, out),
+        Stmt::Pass => line("pass", indent, out),
+        Stmt::Break => line("break", indent, out),
+        Stmt::Continue => line("continue", indent, out),
+        Stmt::Located { span, stmt } => {
+            out.enter(*span, indent);
+            statement(stmt, indent, out);
+            out.leave();
+        }
+    }
+}
+
+/// The `if` alone in an `else`, which is printed as `elif`, and where it is from
+fn elif(orelse: &[Stmt]) -> Option<(Option<Span>, &Stmt)> {
+    match orelse {
+        [stmt @ Stmt::If { .. }] => Some((None, stmt)),
+        [Stmt::Located { span, stmt }] => {
+            elif(std::slice::from_ref(stmt)).map(|(inner, stmt)| (inner.or(Some(*span)), stmt))
+        }
+        _ => None,
+    }
+}
+
+fn aliases(names: &[Alias]) -> String {
+    let names: Vec<_> = names
+        .iter()
+        .map(|alias| match &alias.asname {
+            Some(asname) => format!("{} as {}", alias.name, asname),
+            None => alias.name.clone(),
+        })
+        .collect();
+    names.join(", ")
+}
+
+fn arguments(args: &Arguments) -> String {
+    let first_default = args.args.len() - args.defaults.len();
+    let mut params: Vec<_> = args
+        .args
+        .iter()
+        .enumerate()
+        .map(|(i, name)| match i.checked_sub(first_default) {
+            Some(d) => format!("{}={}", name, expr(&args.defaults[d], TEST)),
+            None => name.clone(),
+        })
+        .collect();
+    if let Some(vararg) = &args.vararg {
+        params.push(format!("*{}", vararg));
+    }
+    params.join(", ")
+}
+
+/// The source of `e`, parenthesized if its precedence is lower than `min`
+fn expr(e: &Expr, min: u8) -> String {
+    let (text, precedence) = match e {
+        Expr::BoolOp { op, values } => {
+            let (keyword, precedence) = match op {
+                BoolOp::And => (" and ", AND),
+                BoolOp::Or => (" or ", OR),
+            };
+            let values: Vec<_> = values.iter().map(|v| expr(v, precedence + 1)).collect();
+            (values.join(keyword), precedence)
+        }
+        // always parenthesized, it can't stand on its own in most places
+        Expr::NamedExpr { target, value } => {
+            (format!("({} := {})", target, expr(value, TEST)), ATOM)
+        }
+        Expr::BinOp { left, op, right } => {
+            let precedence = op.precedence();
+            // `**` groups to the right, the others to the left
+            let (left_min, right_min) = match op {
+                Operator::Pow => (AWAIT, FACTOR),
+                _ => (precedence, precedence + 1),
+            };
+            let text = format!(
+                "{} {} {}",
+                expr(left, left_min),
+                op.symbol(),
+                expr(right, right_min)
+            );
+            (text, precedence)
+        }
+        Expr::UnaryOp { op, operand } => match op {
+            UnaryOp::Not => (format!("not {}", expr(operand, NOT)), NOT),
+            UnaryOp::Invert => (format!("~{}", expr(operand, FACTOR)), FACTOR),
+            UnaryOp::UAdd => (format!("+{}", expr(operand, FACTOR)), FACTOR),
+            UnaryOp::USub => (format!("-{}", expr(operand, FACTOR)), FACTOR),
+        },
+        Expr::Lambda { args, body } => {
+            let args = arguments(args);
+            let text = match args.is_empty() {
+                true => format!("lambda: {}", expr(body, TEST)),
+                false => format!("lambda {}: {}", args, expr(body, TEST)),
+            };
+            (text, TEST)
+        }
+        Expr::IfExp { test, body, orelse } => {
+            let text = format!(
+                "{} if {} else {}",
+                expr(body, OR),
+                expr(test, OR),
+                expr(orelse, TEST)
+            );
+            (text, TEST)
+        }
+        Expr::Dict { keys, values } => {
+            let items: Vec<_> = keys
+                .iter()
+                .zip(values)
+                .map(|(key, value)| match key {
+                    Some(key) => format!("{}: {}", expr(key, TEST), expr(value, TEST)),
+                    None => format!("**{}", expr(value, EXPR)),
+                })
+                .collect();
+            (format!("{{{}}}", items.join(", ")), ATOM)
+        }
+        Expr::Await(value) => (format!("await {}", expr(value, ATOM)), AWAIT),
+        Expr::Yield(value) => match value {
+            Some(value) => (format!("yield {}", expr(value, TUPLE)), YIELD),
+            None => ("yield".to_string(), YIELD),
+        },
+        Expr::YieldFrom(value) => (format!("yield from {}", expr(value, TEST)), YIELD),
+        Expr::Compare { left, op, right } => {
+            let text = format!(
+                "{} {} {}",
+                expr(left, CMP + 1),
+                op.symbol(),
+                expr(right, CMP + 1)
+            );
+            (text, CMP)
+        }
+        Expr::Call { func, args } => {
+            let args: Vec<_> = args.iter().map(|a| expr(a, TEST)).collect();
+            (format!("{}({})", expr(func, ATOM), args.join(", ")), ATOM)
+        }
+        Expr::Constant(c) => (constant(c), ATOM),
+        Expr::Attribute { value, attr } => {
+            let value = match value.as_ref() {
+                // `1 .real`, not the float `1.`
+                Expr::Constant(Constant::Int(_)) => format!("({})", expr(value, ATOM)),
+                _ => expr(value, ATOM),
+            };
+            (format!("{}.{}", value, attr), ATOM)
+        }
+        Expr::Subscript { value, slice } => {
+            let slice = match slice.as_ref() {
+                // `a[1, 2]` is `a[(1, 2)]`
+                Expr::Tuple(items) if !items.is_empty() => {
+                    let items: Vec<_> = items.iter().map(|i| expr(i, TEST)).collect();
+                    items.join(", ")
+                }
+                slice => expr(slice, TUPLE),
+            };
+            (format!("{}[{}]", expr(value, ATOM), slice), ATOM)
+        }
+        Expr::Starred(value) => (format!("*{}", expr(value, EXPR)), EXPR),
+        Expr::Name(name) => (name.clone(), ATOM),
+        Expr::List(items) => {
+            let items: Vec<_> = items.iter().map(|i| expr(i, TEST)).collect();
+            (format!("[{}]", items.join(", ")), ATOM)
+        }
+        Expr::Tuple(items) => {
+            let items: Vec<_> = items.iter().map(|i| expr(i, TEST)).collect();
+            // parenthesized unless it stands alone, as in `a, b = b, a`
+            match items.len() {
+                0 => ("()".to_string(), ATOM),
+                1 => (format!("{},", items[0]), TUPLE),
+                _ => (items.join(", "), TUPLE),
+            }
+        }
+        Expr::Slice { lower, upper } => {
+            let bound = |b: &Option<Box<Expr>>| b.as_ref().map_or(String::new(), |b| expr(b, TEST));
+            (format!("{}:{}", bound(lower), bound(upper)), ATOM)
+        }
+        Expr::ListComp {
+            element,
+            target,
+            iter,
+            condition,
+        } => {
+            let mut text = format!(
+                "[{} for {} in {}",
+                expr(element, TEST),
+                target,
+                expr(iter, OR)
+            );
+            if let Some(condition) = condition {
+                text = format!("{} if {}", text, expr(condition, OR));
+            }
+            text.push(']');
+            (text, ATOM)
+        }
+        Expr::JoinedStr(values) => (joined_str(values), ATOM),
+    };
+    match precedence < min {
+        true => format!("({})", text),
+        false => text,
+    }
+}
+
+fn constant(c: &Constant) -> String {
+    match c {
+        Constant::None => "None".to_string(),
+        Constant::Bool(true) => "True".to_string(),
+        Constant::Bool(false) => "False".to_string(),
+        Constant::Int(n) => n.to_string(),
+        Constant::Float(f) => format!("{:?}", f),
+        Constant::Str(s) => string(s),
+    }
+}
+
+/// A string literal, in double quotes unless the string has more of them than single ones
+fn string(s: &str) -> String {
+    let quote = match s.matches('"').count() > s.matches('\'').count() {
+        true => '\'',
+        false => '"',
+    };
+    let mut out = String::new();
+    out.push(quote);
+    escape(s, quote, &mut out);
+    out.push(quote);
+    out
+}
+
+/// An f-string. Before Python 3.12 the values in it can't have its quote, a backslash or a `#`,
+/// where that leaves no quote for it, the strings are joined with `"".join`. Of the quotes left
+/// it has the one its text has fewer of
+fn joined_str(values: &[Expr]) -> String {
+    let mut text = String::new();
+    let mut code = Vec::new();
+    for value in values {
+        match value {
+            Expr::Constant(Constant::Str(s)) => text.push_str(s),
+            value => code.push(expr(value, OR)),
+        }
+    }
+    let quote = ['"', '\'']
+        .into_iter()
+        .filter(|&quote| code.iter().all(|c| !c.contains([quote, '\\', '#', '\n'])))
+        .min_by_key(|&quote| text.matches(quote).count());
+    let Some(quote) = quote else {
+        let items: Vec<_> = values.iter().map(|v| expr(v, TEST)).collect();
+        return format!("\"\".join([{}])", items.join(", "));
+    };
+    let mut out = format!("f{}", quote);
+    let mut code = code.into_iter();
+    for value in values {
+        match value {
+            Expr::Constant(Constant::Str(s)) => {
+                escape(&s.replace('{', "{{").replace('}', "}}"), quote, &mut out)
+            }
+            // `{{` would be a brace
+            _ => match code.next().unwrap() {
+                c if c.starts_with('{') => out.push_str(&format!("{{ {}}}", c)),
+                c => out.push_str(&format!("{{{}}}", c)),
+            },
+        }
+    }
+    out.push(quote);
+    out
+}
+
+/// The characters of `s` in a string literal between `quote`s
+fn escape(s: &str, quote: char, out: &mut String) {
+    for c in s.chars() {
+        match c {
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if c == quote => {
+                out.push('\\');
+                out.push(c);
+            }
+            c if (c as u32) < 0x20 || c == '\u{7f}' => {
+                out.push_str(&format!("\\x{:02x}", c as u32))
+            }
+            // lone surrogates don't make it into a `String`, line separators would split the line
+            c if matches!(c, '\u{2028}' | '\u{2029}' | '\u{85}') => {
+                out.push_str(&format!("\\u{:04x}", c as u32))
+            }
+            c => out.push(c),
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------------------------
+// ast JSON
+
+/// `module` in the shape of Python's `ast` nodes
+pub fn to_json(module: &Module) -> Value {
+    json!({
+        "_type": "Module",
+        "body": statements(&module.body),
+        "type_ignores": [],
+    })
+}
+
+fn statements(body: &[Stmt]) -> Value {
+    body.iter().map(stmt_json).collect()
+}
+
+#[derive(Clone, Copy)]
+enum Context {
+    Load,
+    Store,
+    Del,
+}
+
+fn context(ctx: Context) -> Value {
+    let name = match ctx {
+        Context::Load => "Load",
+        Context::Store => "Store",
+        Context::Del => "Del",
+    };
+    json!({ "_type": name })
+}
+
+fn aliases_json(names: &[Alias]) -> Value {
+    names
+        .iter()
+        .map(|alias| json!({ "_type": "alias", "name": alias.name, "asname": alias.asname }))
+        .collect()
+}
+
+fn arguments_json(args: &Arguments) -> Value {
+    let arg = |name: &String| json!({ "_type": "arg", "arg": name, "annotation": null });
+    json!({
+        "_type": "arguments",
+        "posonlyargs": [],
+        "args": args.args.iter().map(arg).collect::<Vec<_>>(),
+        "vararg": args.vararg.as_ref().map(arg),
+        "kwonlyargs": [],
+        "kw_defaults": [],
+        "kwarg": null,
+        "defaults": args.defaults.iter().map(|d| expr_json(d, Context::Load)).collect::<Vec<_>>(),
+    })
+}
+
+fn stmt_json(stmt: &Stmt) -> Value {
+    let load = |e: &Expr| expr_json(e, Context::Load);
+    let store = |e: &Expr| expr_json(e, Context::Store);
+    match stmt {
+        Stmt::FunctionDef {
+            name,
+            args,
+            body,
+            decorators,
+            is_async,
+        } => json!({
+            "_type": if *is_async { "AsyncFunctionDef" } else { "FunctionDef" },
+            "name": name,
+            "args": arguments_json(args),
+            "body": statements(body),
+            "decorator_list": decorators.iter().map(load).collect::<Vec<_>>(),
+            "returns": null,
+        }),
+        Stmt::ClassDef { name, bases, body } => json!({
+            "_type": "ClassDef",
+            "name": name,
+            "bases": bases.iter().map(load).collect::<Vec<_>>(),
+            "keywords": [],
+            "body": statements(body),
+            "decorator_list": [],
+        }),
+        Stmt::Return(value) => json!({ "_type": "Return", "value": value.as_ref().map(load) }),
+        Stmt::Delete(targets) => json!({
+            "_type": "Delete",
+            "targets": targets.iter().map(|t| expr_json(t, Context::Del)).collect::<Vec<_>>(),
+        }),
+        Stmt::Import(names) => json!({ "_type": "Import", "names": aliases_json(names) }),
+        Stmt::ImportFrom {
+            module,
+            names,
+            level,
+        } => json!({
+            "_type": "ImportFrom",
+            "module": (!module.is_empty()).then_some(module),
+            "names": aliases_json(names),
+            "level": level,
+        }),
+        Stmt::Assign { targets, value } => json!({
+            "_type": "Assign",
+            "targets": targets.iter().map(store).collect::<Vec<_>>(),
+            "value": load(value),
+        }),
+        Stmt::AugAssign { target, op, value } => json!({
+            "_type": "AugAssign",
+            "target": store(target),
+            "op": { "_type": format!("{:?}", op) },
+            "value": load(value),
+        }),
+        Stmt::For {
+            target,
+            iter,
+            body,
+            is_async,
+        } => json!({
+            "_type": if *is_async { "AsyncFor" } else { "For" },
+            "target": store(target),
+            "iter": load(iter),
+            "body": statements(body),
+            "orelse": [],
+        }),
+        Stmt::While { test, body } => json!({
+            "_type": "While",
+            "test": load(test),
+            "body": statements(body),
+            "orelse": [],
+        }),
+        Stmt::If { test, body, orelse } => json!({
+            "_type": "If",
+            "test": load(test),
+            "body": statements(body),
+            "orelse": statements(orelse),
+        }),
+        Stmt::Raise(exc) => {
+            json!({ "_type": "Raise", "exc": exc.as_ref().map(load), "cause": null })
+        }
+        Stmt::Try {
+            body,
+            handlers,
+            finalbody,
+        } => {
+            let handlers: Vec<_> = handlers
+                .iter()
+                .map(|h| {
+                    json!({
+                        "_type": "ExceptHandler",
+                        "type": h.typ.as_ref().map(load),
+                        "name": h.name,
+                        "body": statements(&h.body),
+                    })
+                })
+                .collect();
+            json!({
+                "_type": "Try",
+                "body": statements(body),
+                "handlers": handlers,
+                "orelse": [],
+                "finalbody": statements(finalbody),
+            })
+        }
+        Stmt::Global(names) => json!({ "_type": "Global", "names": names }),
+        Stmt::Nonlocal(names) => json!({ "_type": "Nonlocal", "names": names }),
+        Stmt::Expr(value) => json!({ "_type": "Expr", "value": load(value) }),
+        Stmt::Pass => json!({ "_type": "Pass" }),
+        Stmt::Break => json!({ "_type": "Break" }),
+        Stmt::Continue => json!({ "_type": "Continue" }),
+        Stmt::Located { stmt, .. } => stmt_json(stmt),
+    }
+}
+
+fn expr_json(e: &Expr, ctx: Context) -> Value {
+    let load = |e: &Expr| expr_json(e, Context::Load);
+    let all = |items: &[Expr], ctx| items.iter().map(|i| expr_json(i, ctx)).collect::<Vec<_>>();
+    match e {
+        Expr::BoolOp { op, values } => json!({
+            "_type": "BoolOp",
+            "op": { "_type": format!("{:?}", op) },
+            "values": all(values, Context::Load),
+        }),
+        Expr::NamedExpr { target, value } => json!({
+            "_type": "NamedExpr",
+            "target": { "_type": "Name", "id": target, "ctx": context(Context::Store) },
+            "value": load(value),
+        }),
+        Expr::BinOp { left, op, right } => json!({
+            "_type": "BinOp",
+            "left": load(left),
+            "op": { "_type": format!("{:?}", op) },
+            "right": load(right),
+        }),
+        Expr::UnaryOp { op, operand } => json!({
+            "_type": "UnaryOp",
+            "op": { "_type": format!("{:?}", op) },
+            "operand": load(operand),
+        }),
+        Expr::Lambda { args, body } => json!({
+            "_type": "Lambda",
+            "args": arguments_json(args),
+            "body": load(body),
+        }),
+        Expr::IfExp { test, body, orelse } => json!({
+            "_type": "IfExp",
+            "test": load(test),
+            "body": load(body),
+            "orelse": load(orelse),
+        }),
+        Expr::Dict { keys, values } => json!({
+            "_type": "Dict",
+            "keys": keys.iter().map(|k| k.as_ref().map(load)).collect::<Vec<_>>(),
+            "values": all(values, Context::Load),
+        }),
+        Expr::Await(value) => json!({ "_type": "Await", "value": load(value) }),
+        Expr::Yield(value) => json!({ "_type": "Yield", "value": value.as_deref().map(load) }),
+        Expr::YieldFrom(value) => json!({ "_type": "YieldFrom", "value": load(value) }),
+        Expr::Compare { left, op, right } => json!({
+            "_type": "Compare",
+            "left": load(left),
+            "ops": [{ "_type": format!("{:?}", op) }],
+            "comparators": [load(right)],
+        }),
+        Expr::Call { func, args } => json!({
+            "_type": "Call",
+            "func": load(func),
+            "args": all(args, Context::Load),
+            "keywords": [],
+        }),
+        Expr::Constant(c) => {
+            let value = match c {
+                Constant::None => Value::Null,
+                Constant::Bool(b) => json!(b),
+                Constant::Int(n) => json!(n),
+                Constant::Float(f) => json!(f),
+                Constant::Str(s) => json!(s),
+            };
+            json!({ "_type": "Constant", "value": value, "kind": null })
+        }
+        Expr::Attribute { value, attr } => json!({
+            "_type": "Attribute",
+            "value": load(value),
+            "attr": attr,
+            "ctx": context(ctx),
+        }),
+        Expr::Subscript { value, slice } => json!({
+            "_type": "Subscript",
+            "value": load(value),
+            "slice": load(slice),
+            "ctx": context(ctx),
+        }),
+        Expr::Starred(value) => json!({
+            "_type": "Starred",
+            "value": expr_json(value, ctx),
+            "ctx": context(ctx),
+        }),
+        Expr::Name(id) => json!({ "_type": "Name", "id": id, "ctx": context(ctx) }),
+        Expr::List(items) => {
+            json!({ "_type": "List", "elts": all(items, ctx), "ctx": context(ctx) })
+        }
+        Expr::Tuple(items) => {
+            json!({ "_type": "Tuple", "elts": all(items, ctx), "ctx": context(ctx) })
+        }
+        Expr::Slice { lower, upper } => json!({
+            "_type": "Slice",
+            "lower": lower.as_deref().map(load),
+            "upper": upper.as_deref().map(load),
+            "step": null,
+        }),
+        Expr::ListComp {
+            element,
+            target,
+            iter,
+            condition,
+        } => json!({
+            "_type": "ListComp",
+            "elt": load(element),
+            "generators": [{
+                "_type": "comprehension",
+                "target": { "_type": "Name", "id": target, "ctx": context(Context::Store) },
+                "iter": load(iter),
+                "ifs": condition.as_deref().map(load).into_iter().collect::<Vec<_>>(),
+                "is_async": 0,
+            }],
+        }),
+        Expr::JoinedStr(values) => {
+            let values: Vec<_> = values
+                .iter()
+                .map(|value| match value {
+                    Expr::Constant(_) => load(value),
+                    value => json!({
+                        "_type": "FormattedValue",
+                        "value": load(value),
+                        "conversion": -1,
+                        "format_spec": null,
+                    }),
+                })
+                .collect();
+            json!({ "_type": "JoinedStr", "values": values })
+        }
+    }
+}
@@ -0,0 +1,971 @@
+//! Scopes and bindings: which declaration every identifier of a program refers to.
+//!
+//! The scopes form a tree, the program's global scope at the root, then a scope for each
+//! function (parameters and body together), block, `for` head, `switch`, `catch` clause and
+//! class body. Declarations are hoisted the way JavaScript does: `var` and function declarations
+//! of a function body to the function scope, `let`, `const` and `class` to the start of their
+//! block, where they stay uninitialized until the declaration runs. Using such a binding before
+//! that from code that runs right away (not from a nested function) is a temporal dead zone
+//! violation, reported like a redeclaration and an assignment to a constant.
+//!
+//! A binding is captured if a nested function refers to it, it outlives its scope then.
+//!
+//! Private names aren't bindings, but one that no enclosing class body declares is reported.
+
+use std::collections::{BTreeMap, HashMap};
+use std::mem;
+
+use js_lexer::token::Span;
+
+use crate::ast::*;
+use crate::visit::{self, bound_names, Visit};
+use crate::Diagnostic;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ScopeId(usize);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct BindingId(usize);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScopeKind {
+    Global,
+    /// A function or arrow function, or a class's static block
+    Function,
+    /// A block, `for` statement or `switch` body
+    Block,
+    Catch,
+    /// The body of a class, which binds the class's own name
+    Class,
+}
+
+#[derive(Debug, Clone)]
+pub struct Scope {
+    pub kind: ScopeKind,
+    pub parent: Option<ScopeId>,
+    /// The bindings declared in it, by name
+    pub names: BTreeMap<String, BindingId>,
+    pub children: Vec<ScopeId>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BindingKind {
+    Var,
+    Let,
+    Const,
+    Function,
+    Class,
+    Parameter,
+    CatchParameter,
+    Import,
+}
+
+impl BindingKind {
+    /// Whether the binding can't be used before its declaration ran
+    pub fn is_lexical(self) -> bool {
+        matches!(
+            self,
+            BindingKind::Let | BindingKind::Const | BindingKind::Class
+        )
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Binding {
+    pub name: String,
+    pub kind: BindingKind,
+    pub scope: ScopeId,
+    /// Where the binding gets its value, an earlier use is in the temporal dead zone
+    pub initialized: usize,
+    /// Whether a nested function refers to it
+    pub captured: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Access {
+    Read,
+    Write,
+    /// `x += 1`, `x++`
+    ReadWrite,
+}
+
+#[derive(Debug, Clone)]
+pub struct Reference {
+    pub name: String,
+    pub access: Access,
+    /// The innermost function or the global scope it is in
+    pub function: ScopeId,
+    /// `None` for a global the program doesn't declare
+    pub binding: Option<BindingId>,
+}
+
+/// The scopes of a program and what its identifiers refer to
+#[derive(Debug, Clone)]
+pub struct Scopes {
+    scopes: Vec<Scope>,
+    bindings: Vec<Binding>,
+    references: Vec<Reference>,
+    /// The scopes by the span of the node that makes them
+    nodes: HashMap<Span, ScopeId>,
+    /// The bindings by the spans of their declared identifiers
+    declarations: HashMap<Span, BindingId>,
+    /// The references by the spans of their identifiers
+    uses: HashMap<Span, usize>,
+}
+
+impl Scopes {
+    /// The global scope
+    pub fn root(&self) -> ScopeId {
+        ScopeId(0)
+    }
+
+    pub fn scope(&self, id: ScopeId) -> &Scope {
+        &self.scopes[id.0]
+    }
+
+    pub fn binding(&self, id: BindingId) -> &Binding {
+        &self.bindings[id.0]
+    }
+
+    pub fn bindings(&self) -> impl Iterator<Item = (BindingId, &Binding)> {
+        self.bindings
+            .iter()
+            .enumerate()
+            .map(|(i, binding)| (BindingId(i), binding))
+    }
+
+    /// In the order of the source
+    pub fn references(&self) -> &[Reference] {
+        &self.references
+    }
+
+    /// The scope made by the node at `span`: a function, block, `for`, `switch`, `catch` clause
+    /// or class
+    pub fn scope_at(&self, span: Span) -> Option<ScopeId> {
+        self.nodes.get(&span).copied()
+    }
+
+    /// What the identifier at `span` declares or refers to
+    pub fn binding_at(&self, span: Span) -> Option<BindingId> {
+        match self.declarations.get(&span) {
+            Some(&binding) => Some(binding),
+            None => self.reference_at(span).and_then(|r| r.binding),
+        }
+    }
+
+    pub fn reference_at(&self, span: Span) -> Option<&Reference> {
+        self.uses.get(&span).map(|&i| &self.references[i])
+    }
+
+    /// What `name` means in `scope`
+    pub fn lookup(&self, mut scope: ScopeId, name: &str) -> Option<BindingId> {
+        loop {
+            if let Some(&binding) = self.scope(scope).names.get(name) {
+                return Some(binding);
+            }
+            scope = self.scope(scope).parent?;
+        }
+    }
+
+    /// Whether `scope` is `outer` or inside it
+    pub fn encloses(&self, outer: ScopeId, mut scope: ScopeId) -> bool {
+        loop {
+            if scope == outer {
+                return true;
+            }
+            match self.scope(scope).parent {
+                Some(parent) => scope = parent,
+                None => return false,
+            }
+        }
+    }
+
+    /// The innermost function or the global scope `scope` is in
+    pub fn function_of(&self, mut scope: ScopeId) -> ScopeId {
+        while !matches!(
+            self.scope(scope).kind,
+            ScopeKind::Function | ScopeKind::Global
+        ) {
+            scope = self.scope(scope).parent.unwrap();
+        }
+        scope
+    }
+
+    /// The bindings of enclosing functions that the function `function` assigns to, which a
+    /// Python function has to declare `nonlocal` or `global`
+    pub fn outer_writes(&self, function: ScopeId) -> Vec<BindingId> {
+        let mut writes = Vec::new();
+        for reference in &self.references {
+            let Some(binding) = reference.binding else {
+                continue;
+            };
+            if reference.function == function
+                && reference.access != Access::Read
+                && self.function_of(self.binding(binding).scope) != function
+                && !writes.contains(&binding)
+            {
+                writes.push(binding);
+            }
+        }
+        writes
+    }
+}
+
+/// The scopes of `program`, reporting redeclarations, uses in the temporal dead zone,
+/// assignments to constants, missing initializers and undeclared private names
+pub fn analyze(program: &Program, diagnostics: &mut Vec<Diagnostic>) -> Scopes {
+    let mut analyzer = Analyzer {
+        scopes: Scopes {
+            scopes: Vec::new(),
+            bindings: Vec::new(),
+            references: Vec::new(),
+            nodes: HashMap::new(),
+            declarations: HashMap::new(),
+            uses: HashMap::new(),
+        },
+        current: ScopeId(0),
+        private_names: Vec::new(),
+        diagnostics,
+    };
+    analyzer.enter(ScopeKind::Global, program.span);
+    analyzer.hoist_function(&program.body);
+    visit::walk_program(&mut analyzer, program);
+    analyzer.scopes
+}
+
+struct Analyzer<'d> {
+    scopes: Scopes,
+    current: ScopeId,
+    /// The private names each enclosing class body declares, the innermost last
+    private_names: Vec<Vec<String>>,
+    diagnostics: &'d mut Vec<Diagnostic>,
+}
+
+/// The `var` declarations of a function body, not those of nested functions
+#[derive(Default)]
+struct VarNames<'a> {
+    names: Vec<&'a Identifier>,
+}
+
+impl<'a> VarNames<'a> {
+    fn declaration(&mut self, declaration: &'a VariableDeclaration) {
+        if declaration.kind == VariableKind::Var {
+            for declarator in &declaration.declarations {
+                bound_names(&declarator.id, &mut self.names);
+            }
+        }
+    }
+}
+
+impl<'a> Visit<'a> for VarNames<'a> {
+    fn visit_statement(&mut self, statement: &'a Statement) {
+        match statement {
+            Statement::Variable(declaration)
+            | Statement::For {
+                init: Some(ForInit::Variable(declaration)),
+                ..
+            }
+            | Statement::ForIn {
+                left: ForHead::Variable(declaration),
+                ..
+            }
+            | Statement::ForOf {
+                left: ForHead::Variable(declaration),
+                ..
+            } => self.declaration(declaration),
+            _ => {}
+        }
+        visit::walk_statement(self, statement);
+    }
+
+    fn visit_expression(&mut self, _: &'a Expression) {}
+
+    fn visit_pattern(&mut self, _: &'a Pattern) {}
+
+    fn visit_function(&mut self, _: &'a Function) {}
+
+    fn visit_class(&mut self, _: &'a Class) {}
+}
+
+impl Analyzer<'_> {
+    fn error(&mut self, span: Span, message: String) {
+        self.diagnostics.push(Diagnostic { span, message });
+    }
+
+    fn kind(&self, scope: ScopeId) -> ScopeKind {
+        self.scopes.scope(scope).kind
+    }
+
+    /// Make a scope inside the current one the current scope, returning the one before
+    fn enter(&mut self, kind: ScopeKind, span: Span) -> ScopeId {
+        let id = ScopeId(self.scopes.scopes.len());
+        let parent = (!self.scopes.scopes.is_empty()).then_some(self.current);
+        if let Some(parent) = parent {
+            self.scopes.scopes[parent.0].children.push(id);
+        }
+        self.scopes.scopes.push(Scope {
+            kind,
+            parent,
+            names: BTreeMap::new(),
+            children: Vec::new(),
+        });
+        if kind != ScopeKind::Global {
+            self.scopes.nodes.insert(span, id);
+        }
+        mem::replace(&mut self.current, id)
+    }
+
+    fn declare(&mut self, id: &Identifier, kind: BindingKind, initialized: usize) -> BindingId {
+        let scope = self.current;
+        let function_level = matches!(self.kind(scope), ScopeKind::Function | ScopeKind::Global);
+        // `var`s and parameters may be declared again, as function declarations of a function
+        // body, what else has a name of its scope is an error
+        let var_like = |kind| match kind {
+            BindingKind::Var | BindingKind::Parameter => true,
+            BindingKind::Function => function_level,
+            _ => false,
+        };
+        let binding = match self.scopes.scope(scope).names.get(&id.name) {
+            Some(&binding) => {
+                let existing = &mut self.scopes.bindings[binding.0];
+                if var_like(existing.kind) && var_like(kind) {
+                    if kind == BindingKind::Function {
+                        existing.kind = kind;
+                    }
+                } else {
+                    self.redeclared(id);
+                }
+                binding
+            }
+            None => {
+                let binding = BindingId(self.scopes.bindings.len());
+                self.scopes.bindings.push(Binding {
+                    name: id.name.clone(),
+                    kind,
+                    scope,
+                    initialized,
+                    captured: false,
+                });
+                self.scopes.scopes[scope.0]
+                    .names
+                    .insert(id.name.clone(), binding);
+                binding
+            }
+        };
+        self.scopes.declarations.entry(id.span).or_insert(binding);
+        binding
+    }
+
+    fn redeclared(&mut self, id: &Identifier) {
+        let message = format!("syntax error: '{}' has already been declared", id.name);
+        self.error(id.span, message);
+    }
+
+    fn check_private(&mut self, id: &Identifier) {
+        if !self
+            .private_names
+            .iter()
+            .flatten()
+            .any(|name| *name == id.name)
+        {
+            let message = format!(
+                "syntax error: private name '#{}' must be declared in an enclosing class",
+                id.name
+            );
+            self.error(id.span, message);
+        }
+    }
+
+    /// Declare what a function body or the program declares, its `var`s first
+    fn hoist_function(&mut self, statements: &[Statement]) {
+        let mut vars = VarNames::default();
+        for statement in statements {
+            vars.visit_statement(statement);
+        }
+        for id in vars.names {
+            self.declare(id, BindingKind::Var, 0);
+        }
+        self.hoist_lexical(statements);
+    }
+
+    /// Declare the `let`, `const`, class and function declarations of a block
+    fn hoist_lexical(&mut self, statements: &[Statement]) {
+        for statement in statements {
+            self.hoist(statement);
+        }
+    }
+
+    fn hoist(&mut self, statement: &Statement) {
+        match statement {
+            Statement::Variable(declaration) => self.lexical(declaration),
+            Statement::Function(function)
+            | Statement::ExportDefault {
+                declaration: ExportDefault::Function(function),
+                ..
+            } => {
+                if let Some(id) = &function.id {
+                    self.declare(id, BindingKind::Function, 0);
+                }
+            }
+            Statement::Class(class)
+            | Statement::ExportDefault {
+                declaration: ExportDefault::Class(class),
+                ..
+            } => {
+                if let Some(id) = &class.id {
+                    self.declare(id, BindingKind::Class, class.span.end);
+                }
+            }
+            Statement::Import(import) => {
+                for specifier in &import.specifiers {
+                    let local = match specifier {
+                        ImportSpecifier::Default(local)
+                        | ImportSpecifier::Namespace { local, .. }
+                        | ImportSpecifier::Named { local, .. } => local,
+                    };
+                    self.declare(local, BindingKind::Import, 0);
+                }
+            }
+            Statement::ExportNamed(export) => {
+                if let Some(declaration) = &export.declaration {
+                    self.hoist(declaration);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Declare the names of a `let` or `const` declaration in the current scope
+    fn lexical(&mut self, declaration: &VariableDeclaration) {
+        let kind = match declaration.kind {
+            VariableKind::Var => return,
+            VariableKind::Let => BindingKind::Let,
+            VariableKind::Const => BindingKind::Const,
+        };
+        for declarator in &declaration.declarations {
+            let mut names = Vec::new();
+            bound_names(&declarator.id, &mut names);
+            for id in names {
+                self.declare(id, kind, declarator.span.end);
+            }
+        }
+    }
+
+    /// Declare the names of `pattern` in the current scope
+    fn bind(&mut self, pattern: &Pattern, kind: BindingKind) {
+        let mut names = Vec::new();
+        bound_names(pattern, &mut names);
+        for id in names {
+            self.declare(id, kind, 0);
+        }
+    }
+
+    /// A `var` may not be declared where a block scope has a lexical binding of its name
+    fn check_var(&mut self, id: &Identifier) {
+        let mut scope = self.current;
+        while !matches!(self.kind(scope), ScopeKind::Function | ScopeKind::Global) {
+            if let Some(&binding) = self.scopes.scope(scope).names.get(&id.name) {
+                if self.scopes.binding(binding).kind != BindingKind::CatchParameter {
+                    self.redeclared(id);
+                }
+            }
+            scope = self.scopes.scope(scope).parent.unwrap();
+        }
+    }
+
+    /// Whether code in `scope` runs later than the code of the enclosing scope `outer`, in a
+    /// function or class body between them
+    fn is_deferred(&self, mut scope: ScopeId, outer: ScopeId) -> bool {
+        while scope != outer {
+            if matches!(self.kind(scope), ScopeKind::Function | ScopeKind::Class) {
+                return true;
+            }
+            scope = self.scopes.scope(scope).parent.unwrap();
+        }
+        false
+    }
+
+    fn resolve(&mut self, id: &Identifier, access: Access) {
+        let binding = self.scopes.lookup(self.current, &id.name);
+        let function = self.scopes.function_of(self.current);
+        let index = self.scopes.references.len();
+        if let Some(binding) = binding {
+            let declared = self.scopes.binding(binding);
+            let (kind, scope) = (declared.kind, declared.scope);
+            if kind.is_lexical()
+                && id.span.start < declared.initialized
+                && !self.is_deferred(self.current, scope)
+            {
+                let message = format!(
+                    "reference error: cannot access '{}' before its declaration",
+                    id.name
+                );
+                self.error(id.span, message);
+            } else if kind == BindingKind::Const && access != Access::Read {
+                let message = format!("type error: assignment to constant '{}'", id.name);
+                self.error(id.span, message);
+            }
+            if self.scopes.function_of(scope) != function {
+                self.scopes.bindings[binding.0].captured = true;
+            }
+        }
+        self.scopes.references.push(Reference {
+            name: id.name.clone(),
+            access,
+            function,
+            binding,
+        });
+        self.scopes.uses.insert(id.span, index);
+    }
+
+    /// The initializers and computed keys in a pattern that declares names
+    fn binding_pattern(&mut self, pattern: &Pattern) {
+        match pattern {
+            Pattern::Identifier(_) => {}
+            Pattern::Object { properties, .. } => {
+                for property in properties {
+                    match property {
+                        PatternProperty::Property { key, value, .. } => {
+                            visit::walk_property_key(self, key);
+                            self.binding_pattern(value);
+                        }
+                        PatternProperty::Rest { argument, .. } => self.binding_pattern(argument),
+                    }
+                }
+            }
+            Pattern::Array { elements, .. } => {
+                for element in elements.iter().flatten() {
+                    self.binding_pattern(element);
+                }
+            }
+            Pattern::Rest { argument, .. } => self.binding_pattern(argument),
+            Pattern::Assignment { left, right, .. } => {
+                self.binding_pattern(left);
+                self.visit_expression(right);
+            }
+            Pattern::Expression(e) => self.visit_expression(e),
+        }
+    }
+
+    /// The declarators of a declaration whose names are declared already
+    fn declaration(&mut self, declaration: &VariableDeclaration) {
+        for declarator in &declaration.declarations {
+            if declaration.kind == VariableKind::Var {
+                let mut names = Vec::new();
+                bound_names(&declarator.id, &mut names);
+                for id in names {
+                    self.check_var(id);
+                }
+            }
+            self.binding_pattern(&declarator.id);
+            if let Some(init) = &declarator.init {
+                self.visit_expression(init);
+            }
+        }
+    }
+
+    /// Constants and patterns have to be initialized, but in the head of a `for-in` or `for-of`
+    fn initializers(&mut self, declaration: &VariableDeclaration) {
+        for declarator in &declaration.declarations {
+            let message = match (&declarator.id, declaration.kind) {
+                _ if declarator.init.is_some() => continue,
+                (Pattern::Identifier(_), VariableKind::Const) => "const declaration",
+                (Pattern::Identifier(_), _) => continue,
+                _ => "destructuring declaration",
+            };
+            let message = format!("syntax error: missing initializer in {}", message);
+            self.error(declarator.span, message);
+        }
+    }
+
+    fn block(&mut self, block: &Block) {
+        let outer = self.enter(ScopeKind::Block, block.span);
+        self.hoist_lexical(&block.body);
+        for statement in &block.body {
+            self.visit_statement(statement);
+        }
+        self.current = outer;
+    }
+
+    /// A function expression's own name is visible in its body, unless something there has
+    /// that name
+    fn function(&mut self, function: &Function, expression: bool) {
+        let outer = self.enter(ScopeKind::Function, function.span);
+        for param in &function.params {
+            self.bind(param, BindingKind::Parameter);
+        }
+        self.hoist_function(&function.body.body);
+        if let (true, Some(id)) = (expression, &function.id) {
+            if !self.scopes.scope(self.current).names.contains_key(&id.name) {
+                self.declare(id, BindingKind::Function, 0);
+            }
+        }
+        for param in &function.params {
+            self.binding_pattern(param);
+        }
+        for statement in &function.body.body {
+            self.visit_statement(statement);
+        }
+        self.current = outer;
+    }
+
+    /// The class's name is bound in the enclosing scope by its declaration, and in the class
+    /// body. The superclass is evaluated outside
+    fn class(&mut self, class: &Class) {
+        if let Some(super_class) = &class.super_class {
+            self.visit_expression(super_class);
+        }
+        let outer = self.enter(ScopeKind::Class, class.span);
+        if let Some(id) = &class.id {
+            self.declare(id, BindingKind::Class, class.body.span.start);
+        }
+        let private_names = class.body.body.iter().filter_map(|member| match member {
+            ClassMember::Method(MethodDefinition {
+                key: PropertyKey::PrivateName(id),
+                ..
+            })
+            | ClassMember::Field(FieldDefinition {
+                key: PropertyKey::PrivateName(id),
+                ..
+            }) => Some(id.name.clone()),
+            _ => None,
+        });
+        self.private_names.push(private_names.collect());
+        for member in &class.body.body {
+            match member {
+                ClassMember::Method(method) => {
+                    visit::walk_property_key(self, &method.key);
+                    self.function(&method.value, false);
+                }
+                ClassMember::Field(field) => {
+                    visit::walk_property_key(self, &field.key);
+                    if let Some(value) = &field.value {
+                        self.visit_expression(value);
+                    }
+                }
+                ClassMember::StaticBlock(block) => {
+                    let class = self.enter(ScopeKind::Function, block.span);
+                    self.hoist_function(&block.body);
+                    for statement in &block.body {
+                        self.visit_statement(statement);
+                    }
+                    self.current = class;
+                }
+            }
+        }
+        self.private_names.pop();
+        self.current = outer;
+    }
+}
+
+impl<'a> Visit<'a> for Analyzer<'_> {
+    fn visit_statement(&mut self, statement: &'a Statement) {
+        match statement {
+            Statement::Block(block) => self.block(block),
+            Statement::Variable(declaration) => {
+                self.initializers(declaration);
+                self.declaration(declaration);
+            }
+            Statement::For {
+                init,
+                test,
+                update,
+                body,
+                span,
+            } => {
+                let outer = self.enter(ScopeKind::Block, *span);
+                match init {
+                    Some(ForInit::Variable(declaration)) => {
+                        self.lexical(declaration);
+                        self.initializers(declaration);
+                        self.declaration(declaration);
+                    }
+                    Some(ForInit::Expression(e)) => self.visit_expression(e),
+                    None => {}
+                }
+                for e in [test, update].into_iter().flatten() {
+                    self.visit_expression(e);
+                }
+                self.visit_statement(body);
+                self.current = outer;
+            }
+            Statement::ForIn {
+                left,
+                right,
+                body,
+                span,
+            }
+            | Statement::ForOf {
+                left,
+                right,
+                body,
+                span,
+                ..
+            } => {
+                let outer = self.enter(ScopeKind::Block, *span);
+                match left {
+                    ForHead::Variable(declaration) => {
+                        self.lexical(declaration);
+                        self.declaration(declaration);
+                    }
+                    ForHead::Pattern(pattern) => self.visit_pattern(pattern),
+                }
+                self.visit_expression(right);
+                self.visit_statement(body);
+                self.current = outer;
+            }
+            Statement::Switch {
+                discriminant,
+                cases,
+                span,
+            } => {
+                self.visit_expression(discriminant);
+                let outer = self.enter(ScopeKind::Block, *span);
+                for case in cases {
+                    self.hoist_lexical(&case.consequent);
+                }
+                for case in cases {
+                    if let Some(test) = &case.test {
+                        self.visit_expression(test);
+                    }
+                    for statement in &case.consequent {
+                        self.visit_statement(statement);
+                    }
+                }
+                self.current = outer;
+            }
+            Statement::Try {
+                block,
+                handler,
+                finalizer,
+                ..
+            } => {
+                self.block(block);
+                if let Some(handler) = handler {
+                    let outer = self.enter(ScopeKind::Catch, handler.span);
+                    self.bind(&handler.param, BindingKind::CatchParameter);
+                    self.binding_pattern(&handler.param);
+                    self.hoist_lexical(&handler.body.body);
+                    for statement in &handler.body.body {
+                        self.visit_statement(statement);
+                    }
+                    self.current = outer;
+                }
+                if let Some(finalizer) = finalizer {
+                    self.block(finalizer);
+                }
+            }
+            Statement::Function(function) => self.function(function, false),
+            Statement::Class(class) => self.class(class),
+            Statement::ExportNamed(export) => match &export.declaration {
+                Some(declaration) => self.visit_statement(declaration),
+                None if export.source.is_none() => {
+                    for specifier in &export.specifiers {
+                        if let ModuleExportName::Identifier(local) = &specifier.local {
+                            self.resolve(local, Access::Read);
+                        }
+                    }
+                }
+                None => {}
+            },
+            Statement::ExportDefault {
+                declaration: ExportDefault::Function(function),
+                ..
+            } => self.function(function, false),
+            _ => visit::walk_statement(self, statement),
+        }
+    }
+
+    fn visit_expression(&mut self, expression: &'a Expression) {
+        match expression {
+            Expression::Identifier(id) => self.resolve(id, Access::Read),
+            Expression::PrivateIdentifier(id) => self.check_private(id),
+            Expression::Member {
+                object,
+                property: MemberProperty::PrivateName(id),
+                ..
+            } => {
+                self.visit_expression(object);
+                self.check_private(id);
+            }
+            Expression::Function(function) => self.function(function, true),
+            Expression::Assignment {
+                operator,
+                left,
+                right,
+                ..
+            } => {
+                match (operator, left.as_ref()) {
+                    (AssignmentOperator::Assign, pattern) => self.visit_pattern(pattern),
+                    (_, Pattern::Identifier(id)) => self.resolve(id, Access::ReadWrite),
+                    (_, pattern) => self.visit_pattern(pattern),
+                }
+                self.visit_expression(right);
+            }
+            Expression::Update { argument, .. } => match argument.as_ref() {
+                Expression::Identifier(id) => self.resolve(id, Access::ReadWrite),
+                argument => self.visit_expression(argument),
+            },
+            _ => visit::walk_expression(self, expression),
+        }
+    }
+
+    /// Only patterns that are assigned to get here, declarations are taken care of apart
+    fn visit_pattern(&mut self, pattern: &'a Pattern) {
+        match pattern {
+            Pattern::Identifier(id) => self.resolve(id, Access::Write),
+            _ => visit::walk_pattern(self, pattern),
+        }
+    }
+
+    fn visit_function(&mut self, function: &'a Function) {
+        self.function(function, false);
+    }
+
+    fn visit_arrow(&mut self, arrow: &'a ArrowFunction) {
+        let outer = self.enter(ScopeKind::Function, arrow.span);
+        for param in &arrow.params {
+            self.bind(param, BindingKind::Parameter);
+        }
+        if let ArrowBody::Block(block) = &arrow.body {
+            self.hoist_function(&block.body);
+        }
+        for param in &arrow.params {
+            self.binding_pattern(param);
+        }
+        match &arrow.body {
+            ArrowBody::Block(block) => {
+                for statement in &block.body {
+                    self.visit_statement(statement);
+                }
+            }
+            ArrowBody::Expression(e) => self.visit_expression(e),
+        }
+        self.current = outer;
+    }
+
+    fn visit_class(&mut self, class: &'a Class) {
+        self.class(class);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{lower, test_parse};
+
+    #[test]
+    fn test_analyze() {
+        let analyze = |source: &str| {
+            let (tree, lexemes) = test_parse(source);
+            let mut diagnostics = Vec::new();
+            let scopes = analyze(&lower::lower(&tree, &lexemes, source), &mut diagnostics);
+            let messages: Vec<_> = diagnostics.into_iter().map(|d| d.message).collect();
+            (scopes, messages)
+        };
+
+        let (scopes, messages) = analyze(
+            "var a = 1\nfunction f(p) {\n  let b = a\n  { let a = p; g(a) }\n  \
+             return () => b + c\n}\nlet c",
+        );
+        assert!(messages.is_empty(), "{:?}", messages);
+        let resolved: Vec<_> = scopes
+            .references()
+            .iter()
+            .map(|r| {
+                let binding = r.binding.map(|b| scopes.binding(b));
+                let kind = binding.map(|b| (b.kind, scopes.scope(b.scope).kind));
+                (r.name.as_str(), kind)
+            })
+            .collect();
+        assert_eq!(
+            resolved,
+            [
+                ("a", Some((BindingKind::Var, ScopeKind::Global))),
+                ("p", Some((BindingKind::Parameter, ScopeKind::Function))),
+                ("g", None),
+                ("a", Some((BindingKind::Let, ScopeKind::Block))),
+                ("b", Some((BindingKind::Let, ScopeKind::Function))),
+                ("c", Some((BindingKind::Let, ScopeKind::Global))),
+            ]
+        );
+        let captured: Vec<_> = scopes
+            .bindings()
+            .filter(|(_, b)| b.captured)
+            .map(|(_, b)| b.name.as_str())
+            .collect();
+        assert_eq!(captured, ["a", "c", "b"]);
+
+        let (_, messages) = analyze(
+            "let x = 1; var x\n{ y; let y }\nconst k = 1; k = 2\n\
+             try {} catch (e) { var e }\nfunction h() { return z } let z",
+        );
+        assert_eq!(
+            messages,
+            [
+                "syntax error: 'x' has already been declared",
+                "reference error: cannot access 'y' before its declaration",
+                "type error: assignment to constant 'k'",
+            ]
+        );
+
+        let (_, messages) = analyze(
+            "function f(a, b = a) { let a }\n{ function g() {} let g }\n\
+             switch (0) { case 0: let s; case 1: let s }\nclass C extends C {}\n\
+             for (let i = i; ;) {}\nlet t = () => t; var v; var v; function v() {}\n\
+             try {} catch ([p, p]) {}",
+        );
+        assert_eq!(
+            messages,
+            [
+                "syntax error: 'a' has already been declared",
+                "syntax error: 'g' has already been declared",
+                "syntax error: 's' has already been declared",
+                "reference error: cannot access 'C' before its declaration",
+                "reference error: cannot access 'i' before its declaration",
+                "syntax error: 'p' has already been declared",
+            ]
+        );
+
+        // finding the scopes and bindings of the nodes
+        let source = "let x = 1\nfor (let i = 0; i < 2; i++) { let x = i; f(() => x) }";
+        let (scopes, _) = analyze(source);
+        let span = |text: &str, nth| {
+            let start = source.match_indices(text).nth(nth).unwrap().0;
+            Span {
+                start,
+                end: start + text.len(),
+            }
+        };
+        let for_span = Span {
+            start: source.find("for").unwrap(),
+            end: source.len(),
+        };
+        let loop_scope = scopes.scope_at(for_span).unwrap();
+        assert_eq!(scopes.scope(loop_scope).kind, ScopeKind::Block);
+        assert_eq!(scopes.scope(loop_scope).parent, Some(scopes.root()));
+        let body = scopes.scope(loop_scope).children[0];
+        let inner = scopes.lookup(body, "x").unwrap();
+        let outer = scopes.lookup(scopes.root(), "x").unwrap();
+        assert_ne!(inner, outer);
+        assert_eq!(scopes.binding_at(span("x", 1)), Some(inner));
+        assert_eq!(scopes.binding_at(span("x", 2)), Some(inner));
+        assert_eq!(scopes.binding_at(span("x", 0)), Some(outer));
+        assert_eq!(scopes.lookup(body, "i"), scopes.lookup(loop_scope, "i"));
+        assert!(scopes.binding(inner).captured);
+        assert!(!scopes.binding(outer).captured);
+        assert!(!scopes.binding(scopes.lookup(body, "i").unwrap()).captured);
+        assert_eq!(scopes.lookup(body, "f"), None);
+        let call = scopes.reference_at(span("f", 1)).unwrap();
+        assert_eq!((call.binding, call.access), (None, Access::Read));
+        assert_eq!(scopes.function_of(body), scopes.root());
+        let update = scopes.reference_at(span("i", 2)).unwrap();
+        assert_eq!(update.access, Access::ReadWrite);
+
+        let (scopes, _) = analyze("let n = 0\nfunction inc() { n++; let m; m = 1 }");
+        let function = scopes.scope(scopes.root()).children[0];
+        let writes = scopes.outer_writes(function);
+        let names: Vec<_> = writes.iter().map(|&b| &scopes.binding(b).name).collect();
+        assert_eq!(names, ["n"]);
+    }
+}
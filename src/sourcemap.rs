@@ -0,0 +1,92 @@
+//! Source maps from the translated Python back to the JavaScript, in the Source Map v3 format.
+//! A line of Python maps from where its text starts to the start of the JavaScript statement it
+//! was translated from, which is what a traceback needs to name the JavaScript line.
+
+use js_lexer::token::Span;
+use serde_json::{json, Value};
+
+const BASE64: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// The source map of the Python file `file`, whose text is `python` and whose lines come from
+/// the `lines` spans of `source`, the JavaScript file `source_name`
+pub fn source_map(
+    file: &str,
+    python: &str,
+    lines: &[Option<Span>],
+    source_name: &str,
+    source: &str,
+) -> Value {
+    let line_starts: Vec<usize> = std::iter::once(0)
+        .chain(source.match_indices('\n').map(|(i, _)| i + 1))
+        .collect();
+    let mut mappings = String::new();
+    // a segment's fields but the column are relative to the segment before
+    let (mut last_line, mut last_column) = (0, 0);
+    for (i, (text, span)) in python.lines().zip(lines).enumerate() {
+        if i > 0 {
+            mappings.push(';');
+        }
+        let Some(span) = span else {
+            continue;
+        };
+        let start = span.start.min(source.len());
+        let line = line_starts.partition_point(|&s| s <= start) - 1;
+        // columns count UTF-16 code units
+        let column = source[line_starts[line]..start].encode_utf16().count() as i64;
+        let indent = text.len() - text.trim_start().len();
+        vlq(indent as i64, &mut mappings);
+        vlq(0, &mut mappings);
+        vlq(line as i64 - last_line, &mut mappings);
+        vlq(column - last_column, &mut mappings);
+        (last_line, last_column) = (line as i64, column);
+    }
+    json!({
+        "version": 3,
+        "file": file,
+        "sources": [source_name],
+        "sourcesContent": [source],
+        "names": [],
+        "mappings": mappings,
+    })
+}
+
+/// `value` as a base 64 VLQ: five bits a digit, the lowest first, the sign in the lowest bit
+fn vlq(value: i64, out: &mut String) {
+    let mut rest = (value.unsigned_abs() << 1) | u64::from(value < 0);
+    loop {
+        let digit = (rest & 31) as usize;
+        rest >>= 5;
+        let continued = if rest > 0 { 32 } else { 0 };
+        out.push(BASE64[digit | continued] as char);
+        if rest == 0 {
+            break;
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_source_map() {
+        let encoded = |value| {
+            let mut out = String::new();
+            vlq(value, &mut out);
+            out
+        };
+        assert_eq!(encoded(0), "A");
+        assert_eq!(encoded(-1), "D");
+        assert_eq!(encoded(15), "e");
+        assert_eq!(encoded(16), "gB");
+        assert_eq!(encoded(-1000), "x+B");
+
+        let source = "let a = 1;\nif (a) {\n  f(\"é\", a);\n}\n";
+        let span = |start, end| Some(Span { start, end });
+        let python = "a = 1\nif a:\n    f(\"é\", a)\n\nx = 2\n";
+        let lines = [span(0, 10), span(11, 33), span(22, 32), None, span(11, 33)];
+        let map = source_map("m.py", python, &lines, "m.js", source);
+        assert_eq!(map["mappings"], "AAAA;AACA;IACE;;AADF");
+        assert_eq!(map["sources"], json!(["m.js"]));
+    }
+}
@@ -0,0 +1,363 @@
+//! Walking the syntax tree. A `Visit` implementation overrides the methods of the nodes it looks
+//! at and calls their `walk_` function to go on into the children, the default methods do only
+//! that.
+
+use crate::ast::*;
+
+pub trait Visit<'a> {
+    fn visit_statement(&mut self, statement: &'a Statement) {
+        walk_statement(self, statement)
+    }
+
+    fn visit_expression(&mut self, expression: &'a Expression) {
+        walk_expression(self, expression)
+    }
+
+    fn visit_pattern(&mut self, pattern: &'a Pattern) {
+        walk_pattern(self, pattern)
+    }
+
+    fn visit_function(&mut self, function: &'a Function) {
+        walk_function(self, function)
+    }
+
+    fn visit_arrow(&mut self, arrow: &'a ArrowFunction) {
+        walk_arrow(self, arrow)
+    }
+
+    fn visit_class(&mut self, class: &'a Class) {
+        walk_class(self, class)
+    }
+}
+
+pub fn walk_program<'a, V: Visit<'a> + ?Sized>(v: &mut V, program: &'a Program) {
+    for statement in &program.body {
+        v.visit_statement(statement);
+    }
+}
+
+pub fn walk_statement<'a, V: Visit<'a> + ?Sized>(v: &mut V, statement: &'a Statement) {
+    match statement {
+        Statement::Expression { expression, .. } => v.visit_expression(expression),
+        Statement::Block(block) => walk_block(v, block),
+        Statement::Empty { .. }
+        | Statement::Debugger { .. }
+        | Statement::Break { .. }
+        | Statement::Continue { .. }
+        | Statement::Import(_)
+        | Statement::ExportAll { .. } => {}
+        Statement::With { object, body, .. } => {
+            v.visit_expression(object);
+            v.visit_statement(body);
+        }
+        Statement::Return { argument, .. } => {
+            if let Some(argument) = argument {
+                v.visit_expression(argument);
+            }
+        }
+        Statement::Labeled { body, .. } => v.visit_statement(body),
+        Statement::If {
+            test,
+            consequent,
+            alternate,
+            ..
+        } => {
+            v.visit_expression(test);
+            v.visit_statement(consequent);
+            if let Some(alternate) = alternate {
+                v.visit_statement(alternate);
+            }
+        }
+        Statement::Switch {
+            discriminant,
+            cases,
+            ..
+        } => {
+            v.visit_expression(discriminant);
+            for case in cases {
+                if let Some(test) = &case.test {
+                    v.visit_expression(test);
+                }
+                for statement in &case.consequent {
+                    v.visit_statement(statement);
+                }
+            }
+        }
+        Statement::Throw { argument, .. } => v.visit_expression(argument),
+        Statement::Try {
+            block,
+            handler,
+            finalizer,
+            ..
+        } => {
+            walk_block(v, block);
+            if let Some(handler) = handler {
+                v.visit_pattern(&handler.param);
+                walk_block(v, &handler.body);
+            }
+            if let Some(finalizer) = finalizer {
+                walk_block(v, finalizer);
+            }
+        }
+        Statement::While { test, body, .. } | Statement::DoWhile { body, test, .. } => {
+            v.visit_expression(test);
+            v.visit_statement(body);
+        }
+        Statement::For {
+            init,
+            test,
+            update,
+            body,
+            ..
+        } => {
+            match init {
+                Some(ForInit::Variable(declaration)) => walk_declaration(v, declaration),
+                Some(ForInit::Expression(e)) => v.visit_expression(e),
+                None => {}
+            }
+            for e in [test, update].into_iter().flatten() {
+                v.visit_expression(e);
+            }
+            v.visit_statement(body);
+        }
+        Statement::ForIn {
+            left, right, body, ..
+        }
+        | Statement::ForOf {
+            left, right, body, ..
+        } => {
+            match left {
+                ForHead::Variable(declaration) => walk_declaration(v, declaration),
+                ForHead::Pattern(pattern) => v.visit_pattern(pattern),
+            }
+            v.visit_expression(right);
+            v.visit_statement(body);
+        }
+        Statement::Function(function) => v.visit_function(function),
+        Statement::Variable(declaration) => walk_declaration(v, declaration),
+        Statement::Class(class) => v.visit_class(class),
+        Statement::ExportNamed(export) => {
+            if let Some(declaration) = &export.declaration {
+                v.visit_statement(declaration);
+            }
+        }
+        Statement::ExportDefault { declaration, .. } => match declaration {
+            ExportDefault::Function(function) => v.visit_function(function),
+            ExportDefault::Class(class) => v.visit_class(class),
+            ExportDefault::Expression(e) => v.visit_expression(e),
+        },
+    }
+}
+
+pub fn walk_block<'a, V: Visit<'a> + ?Sized>(v: &mut V, block: &'a Block) {
+    for statement in &block.body {
+        v.visit_statement(statement);
+    }
+}
+
+pub fn walk_declaration<'a, V: Visit<'a> + ?Sized>(
+    v: &mut V,
+    declaration: &'a VariableDeclaration,
+) {
+    for declarator in &declaration.declarations {
+        v.visit_pattern(&declarator.id);
+        if let Some(init) = &declarator.init {
+            v.visit_expression(init);
+        }
+    }
+}
+
+pub fn walk_expression<'a, V: Visit<'a> + ?Sized>(v: &mut V, expression: &'a Expression) {
+    match expression {
+        Expression::Identifier(_)
+        | Expression::Literal(_)
+        | Expression::This { .. }
+        | Expression::Super { .. }
+        | Expression::MetaProperty { .. }
+        | Expression::PrivateIdentifier(_) => {}
+        Expression::Template(template) => {
+            for e in &template.expressions {
+                v.visit_expression(e);
+            }
+        }
+        Expression::TaggedTemplate { tag, quasi, .. } => {
+            v.visit_expression(tag);
+            for e in &quasi.expressions {
+                v.visit_expression(e);
+            }
+        }
+        Expression::Array { elements, .. } => {
+            for element in elements.iter().flatten() {
+                walk_argument(v, element);
+            }
+        }
+        Expression::Object { properties, .. } => {
+            for property in properties {
+                match property {
+                    ObjectProperty::Property(property) => {
+                        walk_property_key(v, &property.key);
+                        v.visit_expression(&property.value);
+                    }
+                    ObjectProperty::Spread(spread) => v.visit_expression(&spread.argument),
+                }
+            }
+        }
+        Expression::Function(function) => v.visit_function(function),
+        Expression::Arrow(arrow) => v.visit_arrow(arrow),
+        Expression::Class(class) => v.visit_class(class),
+        Expression::Unary { argument, .. }
+        | Expression::Update { argument, .. }
+        | Expression::Await { argument, .. } => v.visit_expression(argument),
+        Expression::Binary { left, right, .. } | Expression::Logical { left, right, .. } => {
+            v.visit_expression(left);
+            v.visit_expression(right);
+        }
+        Expression::Assignment { left, right, .. } => {
+            v.visit_pattern(left);
+            v.visit_expression(right);
+        }
+        Expression::Conditional {
+            test,
+            consequent,
+            alternate,
+            ..
+        } => {
+            v.visit_expression(test);
+            v.visit_expression(consequent);
+            v.visit_expression(alternate);
+        }
+        Expression::Call {
+            callee, arguments, ..
+        }
+        | Expression::New {
+            callee, arguments, ..
+        } => {
+            v.visit_expression(callee);
+            for argument in arguments {
+                walk_argument(v, argument);
+            }
+        }
+        Expression::Member {
+            object, property, ..
+        } => {
+            v.visit_expression(object);
+            if let MemberProperty::Computed(key) = property {
+                v.visit_expression(key);
+            }
+        }
+        Expression::Chain { expression, .. } => v.visit_expression(expression),
+        Expression::Sequence { expressions, .. } => {
+            for e in expressions {
+                v.visit_expression(e);
+            }
+        }
+        Expression::Yield { argument, .. } => {
+            if let Some(argument) = argument {
+                v.visit_expression(argument);
+            }
+        }
+        Expression::Import { source, .. } => v.visit_expression(source),
+    }
+}
+
+fn walk_argument<'a, V: Visit<'a> + ?Sized>(v: &mut V, argument: &'a ExpressionOrSpread) {
+    match argument {
+        ExpressionOrSpread::Expression(e) => v.visit_expression(e),
+        ExpressionOrSpread::Spread(spread) => v.visit_expression(&spread.argument),
+    }
+}
+
+pub fn walk_property_key<'a, V: Visit<'a> + ?Sized>(v: &mut V, key: &'a PropertyKey) {
+    if let PropertyKey::Computed(e) = key {
+        v.visit_expression(e);
+    }
+}
+
+pub fn walk_pattern<'a, V: Visit<'a> + ?Sized>(v: &mut V, pattern: &'a Pattern) {
+    match pattern {
+        Pattern::Identifier(_) => {}
+        Pattern::Object { properties, .. } => {
+            for property in properties {
+                match property {
+                    PatternProperty::Property { key, value, .. } => {
+                        walk_property_key(v, key);
+                        v.visit_pattern(value);
+                    }
+                    PatternProperty::Rest { argument, .. } => v.visit_pattern(argument),
+                }
+            }
+        }
+        Pattern::Array { elements, .. } => {
+            for element in elements.iter().flatten() {
+                v.visit_pattern(element);
+            }
+        }
+        Pattern::Rest { argument, .. } => v.visit_pattern(argument),
+        Pattern::Assignment { left, right, .. } => {
+            v.visit_pattern(left);
+            v.visit_expression(right);
+        }
+        Pattern::Expression(e) => v.visit_expression(e),
+    }
+}
+
+pub fn walk_function<'a, V: Visit<'a> + ?Sized>(v: &mut V, function: &'a Function) {
+    for param in &function.params {
+        v.visit_pattern(param);
+    }
+    walk_block(v, &function.body);
+}
+
+pub fn walk_arrow<'a, V: Visit<'a> + ?Sized>(v: &mut V, arrow: &'a ArrowFunction) {
+    for param in &arrow.params {
+        v.visit_pattern(param);
+    }
+    match &arrow.body {
+        ArrowBody::Block(block) => walk_block(v, block),
+        ArrowBody::Expression(e) => v.visit_expression(e),
+    }
+}
+
+pub fn walk_class<'a, V: Visit<'a> + ?Sized>(v: &mut V, class: &'a Class) {
+    if let Some(super_class) = &class.super_class {
+        v.visit_expression(super_class);
+    }
+    for member in &class.body.body {
+        match member {
+            ClassMember::Method(method) => {
+                walk_property_key(v, &method.key);
+                v.visit_function(&method.value);
+            }
+            ClassMember::Field(field) => {
+                walk_property_key(v, &field.key);
+                if let Some(value) = &field.value {
+                    v.visit_expression(value);
+                }
+            }
+            ClassMember::StaticBlock(block) => walk_block(v, block),
+        }
+    }
+}
+
+/// The names a pattern binds, in order
+pub fn bound_names<'a>(pattern: &'a Pattern, out: &mut Vec<&'a Identifier>) {
+    match pattern {
+        Pattern::Identifier(id) => out.push(id),
+        Pattern::Object { properties, .. } => {
+            for property in properties {
+                match property {
+                    PatternProperty::Property { value, .. } => bound_names(value, out),
+                    PatternProperty::Rest { argument, .. } => bound_names(argument, out),
+                }
+            }
+        }
+        Pattern::Array { elements, .. } => {
+            for element in elements.iter().flatten() {
+                bound_names(element, out);
+            }
+        }
+        Pattern::Rest { argument, .. } => bound_names(argument, out),
+        Pattern::Assignment { left, .. } => bound_names(left, out),
+        Pattern::Expression(_) => {}
+    }
+}